@@ -0,0 +1,27 @@
+/* C interface of the schema-jenerator library, built with `cargo build --release --features ffi`.
+ * Strings are NUL-terminated UTF-8. */
+
+#ifndef SCHEMA_JENERATOR_H
+#define SCHEMA_JENERATOR_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Generates the schema for the JSON document `json`. `options` is a JSON object such as
+ * {"tier": "comprehensive", "draft": "draft-07"}, or NULL for the defaults. Returns the schema
+ * as JSON text, to be released with sj_free_string, or NULL on failure. */
+char *sj_generate_schema(const char *json, const char *options);
+
+/* Releases a string returned by sj_generate_schema; NULL is ignored. */
+void sj_free_string(char *s);
+
+/* The message of the last failure on the calling thread, or NULL if the last call succeeded.
+ * Owned by the library and valid until the next call on the same thread. */
+const char *sj_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,43 @@
+use serde_json::Value;
+use std::convert::Infallible;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::schema::walk::for_each_schema_post_mut;
+
+/// The `additionalProperties` value given to every generated object schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdditionalProperties {
+    Allow,
+    Deny,
+    /// A file holding the schema fragment to use as the value
+    Schema(PathBuf),
+}
+
+impl FromStr for AdditionalProperties {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "true" => AdditionalProperties::Allow,
+            "false" => AdditionalProperties::Deny,
+            path => AdditionalProperties::Schema(PathBuf::from(path)),
+        })
+    }
+}
+
+/// Sets `additionalProperties` to `value` on every object schema, replacing the tier's choice
+pub fn apply_additional_properties(schema: &mut Value, value: &Value) {
+    // Subschemas first, so a schema given as `value` is not walked into
+    for_each_schema_post_mut(schema, &mut |obj| {
+        let is_object = obj.contains_key("properties")
+            || obj.get("type").is_some_and(|t| match t {
+                Value::String(t) => t == "object",
+                Value::Array(types) => types.iter().any(|t| t == "object"),
+                _ => false,
+            });
+        if is_object {
+            obj.insert("additionalProperties".to_string(), value.clone());
+        }
+    });
+}
@@ -0,0 +1,147 @@
+use regex::Regex;
+use std::path::Path;
+
+use crate::spans::{enclosing_value_span, line_col};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum AnnotationFormat {
+    /// GitHub Actions workflow commands (`::error file=...::message`)
+    Github,
+}
+
+impl AnnotationFormat {
+    pub fn reporter(self) -> Box<dyn Reporter> {
+        match self {
+            AnnotationFormat::Github => Box::new(GithubReporter),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Notice,
+}
+
+/// A problem tied to a location in a file, rendered by a CI-specific `Reporter`
+#[derive(Debug, Clone)]
+pub struct Annotation {
+    pub severity: Severity,
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub title: Option<String>,
+    pub message: String,
+}
+
+impl Annotation {
+    pub fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            file: None,
+            line: None,
+            column: None,
+            title: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, message)
+    }
+
+    pub fn file(mut self, path: &Path) -> Self {
+        self.file = Some(path.display().to_string());
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn at(mut self, line: usize, column: usize) -> Self {
+        self.line = Some(line);
+        self.column = Some(column);
+        self
+    }
+
+    /// Points at the value `pointer` addresses in the JSON `text`, or at its nearest
+    /// enclosing value when the pointer itself does not exist there
+    pub fn at_pointer(self, text: &str, pointer: &str) -> Self {
+        match enclosing_value_span(text, pointer) {
+            Some(span) => {
+                let (line, column) = line_col(text, span.start);
+                self.at(line, column)
+            }
+            None => self,
+        }
+    }
+
+    /// An error for `path`, located at the line and column of JSON syntax errors
+    pub fn from_error(path: &Path, error: &anyhow::Error) -> Self {
+        let message = error.to_string();
+        let annotation = Self::error(message.clone()).file(path);
+
+        let location = Regex::new(r"at line (\d+) column (\d+)")
+            .ok()
+            .and_then(|re| re.captures(&message))
+            .and_then(|caps| Some((caps[1].parse().ok()?, caps[2].parse().ok()?)));
+        match location {
+            Some((line, column)) => annotation.at(line, column),
+            None => annotation,
+        }
+    }
+}
+
+pub trait Reporter {
+    fn render(&self, annotation: &Annotation) -> String;
+}
+
+pub struct GithubReporter;
+
+impl Reporter for GithubReporter {
+    fn render(&self, annotation: &Annotation) -> String {
+        let command = match annotation.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Notice => "notice",
+        };
+
+        let mut properties = Vec::new();
+        if let Some(file) = &annotation.file {
+            properties.push(format!("file={}", escape_property(file)));
+        }
+        if let Some(line) = annotation.line {
+            properties.push(format!("line={}", line));
+        }
+        if let Some(column) = annotation.column {
+            properties.push(format!("col={}", column));
+        }
+        if let Some(title) = &annotation.title {
+            properties.push(format!("title={}", escape_property(title)));
+        }
+
+        let separator = if properties.is_empty() { "" } else { " " };
+        format!(
+            "::{}{}{}::{}",
+            command,
+            separator,
+            properties.join(","),
+            escape_data(&annotation.message)
+        )
+    }
+}
+
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
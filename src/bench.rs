@@ -0,0 +1,145 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::error::AppError;
+use crate::schema::{generate_schema, SchemaOutputTier};
+use crate::validation::validate_schema;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PhaseTiming {
+    pub phase: String,
+    pub seconds: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    pub tier: SchemaOutputTier,
+    pub files: usize,
+    pub bytes: u64,
+    pub seconds: f64,
+    pub megabytes_per_second: f64,
+    pub files_per_second: f64,
+    /// Peak resident set size of the whole process, where the platform reports it
+    pub peak_memory_kb: Option<u64>,
+    pub phases: Vec<PhaseTiming>,
+}
+
+impl BenchReport {
+    pub fn render_text(&self) -> String {
+        let mut out = format!("Tier: {:?}\n", self.tier);
+        out.push_str(&format!(
+            "  Files:        {} ({:.2} MB)\n",
+            self.files,
+            self.bytes as f64 / 1_000_000.0
+        ));
+        out.push_str(&format!("  Total time:   {:.3}s\n", self.seconds));
+        out.push_str(&format!(
+            "  Throughput:   {:.2} MB/s, {:.1} files/s\n",
+            self.megabytes_per_second, self.files_per_second
+        ));
+        match self.peak_memory_kb {
+            Some(kb) => out.push_str(&format!("  Peak memory:  {} KB\n", kb)),
+            None => out.push_str("  Peak memory:  unavailable\n"),
+        }
+        out.push_str("  Phases:\n");
+        for phase in &self.phases {
+            let share = if self.seconds > 0.0 {
+                phase.seconds / self.seconds * 100.0
+            } else {
+                0.0
+            };
+            out.push_str(&format!(
+                "    {:<10} {:>9.3}ms ({:.0}%)\n",
+                phase.phase,
+                phase.seconds * 1000.0,
+                share
+            ));
+        }
+        out
+    }
+}
+
+#[derive(Default)]
+struct Timings {
+    read: Duration,
+    parse: Duration,
+    generate: Duration,
+    validate: Duration,
+    serialize: Duration,
+}
+
+/// Runs the full read → parse → generate → serialize pipeline over every file, without
+/// writing any output, and reports where the time went.
+pub fn run_bench(paths: &[PathBuf], tier: &SchemaOutputTier, validate: bool) -> Result<BenchReport> {
+    let mut timings = Timings::default();
+    let mut bytes = 0u64;
+    let started = Instant::now();
+
+    for path in paths {
+        let phase = Instant::now();
+        let content = fs::read(path).map_err(|_| AppError::FileNotFound(path.display().to_string()))?;
+        timings.read += phase.elapsed();
+        bytes += content.len() as u64;
+
+        let phase = Instant::now();
+        let value: serde_json::Value = serde_json::from_slice(&content)
+            .map_err(|e| AppError::invalid_json_in(path.display().to_string(), &e))?;
+        timings.parse += phase.elapsed();
+
+        let phase = Instant::now();
+        let schema = generate_schema(&value, tier)?;
+        timings.generate += phase.elapsed();
+
+        if validate {
+            let phase = Instant::now();
+            validate_schema(&schema)?.ensure_valid("Schema validation")?;
+            timings.validate += phase.elapsed();
+        }
+
+        let phase = Instant::now();
+        serde_json::to_string(&schema)?;
+        timings.serialize += phase.elapsed();
+    }
+
+    let elapsed = started.elapsed().as_secs_f64();
+    let per_second = |amount: f64| if elapsed > 0.0 { amount / elapsed } else { 0.0 };
+
+    let mut phases = vec![
+        ("read", timings.read),
+        ("parse", timings.parse),
+        ("generate", timings.generate),
+    ];
+    if validate {
+        phases.push(("validate", timings.validate));
+    }
+    phases.push(("serialize", timings.serialize));
+
+    Ok(BenchReport {
+        tier: tier.clone(),
+        files: paths.len(),
+        bytes,
+        seconds: elapsed,
+        megabytes_per_second: per_second(bytes as f64 / 1_000_000.0),
+        files_per_second: per_second(paths.len() as f64),
+        peak_memory_kb: peak_memory_kb(),
+        phases: phases
+            .into_iter()
+            .map(|(phase, duration)| PhaseTiming {
+                phase: phase.to_string(),
+                seconds: duration.as_secs_f64(),
+            })
+            .collect(),
+    })
+}
+
+/// Peak resident set size of the process so far, where the platform reports it
+pub fn peak_memory_kb() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))
+        .and_then(|rest| rest.trim().trim_end_matches("kB").trim().parse().ok())
+}
@@ -0,0 +1,72 @@
+//! What the language bindings share: each takes the input and its options as JSON text and
+//! returns the schema as JSON text.
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::error::AppError;
+use crate::generator::{ExamplePolicy, GeneratorOptions, SchemaGenerator};
+use crate::infer::InferenceFamily;
+use crate::nulls::NullPolicy;
+use crate::required::RequiredMode;
+use crate::schema::{SchemaDraft, SchemaOutputTier};
+
+/// The options object of the language bindings, with the names of the CLI flags in camelCase
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct BindingOptions {
+    pub tier: Option<SchemaOutputTier>,
+    pub draft: Option<SchemaDraft>,
+    pub null_policy: Option<NullPolicy>,
+    pub required_mode: Option<RequiredMode>,
+    pub additional_properties: Option<Value>,
+    pub infer: Vec<InferenceFamily>,
+    pub no_infer: Vec<InferenceFamily>,
+    pub no_examples: bool,
+    pub examples_limit: Option<usize>,
+    pub max_depth: Option<usize>,
+    pub redact: Vec<String>,
+    pub pretty: bool,
+}
+
+impl From<BindingOptions> for GeneratorOptions {
+    fn from(options: BindingOptions) -> Self {
+        let examples = match (options.no_examples, options.examples_limit) {
+            (true, _) => ExamplePolicy::Omit,
+            (false, Some(limit)) => ExamplePolicy::Limit(limit),
+            (false, None) => ExamplePolicy::Keep,
+        };
+        let defaults = GeneratorOptions::default();
+        GeneratorOptions {
+            tier: options.tier.unwrap_or(defaults.tier.clone()),
+            draft: options.draft.unwrap_or(defaults.draft),
+            null_policy: options.null_policy.unwrap_or_default(),
+            required: options.required_mode,
+            additional_properties: options.additional_properties,
+            infer: options.infer,
+            no_infer: options.no_infer,
+            examples,
+            max_depth: options.max_depth,
+            redact: options.redact,
+            ..defaults
+        }
+    }
+}
+
+/// Generates the schema for the JSON document `json`, with `options` a JSON object of
+/// [`BindingOptions`], and returns it serialized
+pub fn generate_schema_json(json: &str, options: Option<&str>) -> Result<String> {
+    let options: BindingOptions = match options {
+        Some(options) => {
+            serde_json::from_str(options).map_err(|e| AppError::InvalidOption(format!("Invalid options: {}", e)))?
+        }
+        None => BindingOptions::default(),
+    };
+    let pretty = options.pretty;
+    let value: Value = serde_json::from_str(json).map_err(|e| AppError::invalid_json(&e))?;
+
+    let schema = SchemaGenerator::new(options.into()).generate(&value)?;
+    let text = if pretty { serde_json::to_string_pretty(&schema)? } else { serde_json::to_string(&schema)? };
+    Ok(text)
+}
@@ -0,0 +1,317 @@
+use anyhow::{Context, Result};
+use jsonschema::{SchemaResolver, SchemaResolverError};
+use reqwest::Url;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::error::AppError;
+use crate::pointer;
+use crate::remote::{self, RemoteOptions};
+use crate::schema::keywords::for_each_subschema_mut;
+use crate::schema::SchemaDraft;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Location {
+    File(PathBuf),
+    Url(String),
+}
+
+impl Location {
+    pub fn from_path(path: &Path) -> Self {
+        Location::File(path.canonicalize().unwrap_or_else(|_| path.to_path_buf()))
+    }
+
+    pub fn parse(location: &str) -> Self {
+        if remote::is_url(location) {
+            Location::Url(location.to_string())
+        } else {
+            Location::from_path(Path::new(location))
+        }
+    }
+
+    pub fn resolve(&self, reference: &str) -> Result<Self> {
+        if remote::is_url(reference) {
+            return Ok(Location::Url(reference.to_string()));
+        }
+
+        match self {
+            Location::Url(base) => {
+                let joined = Url::parse(base)
+                    .and_then(|base| base.join(reference))
+                    .map_err(|e| AppError::UnresolvableRef(format!("{}: {}", reference, e)))?;
+                Ok(Location::Url(joined.to_string()))
+            }
+            Location::File(path) => {
+                let dir = path.parent().unwrap_or_else(|| Path::new("."));
+                Ok(Location::from_path(&dir.join(reference)))
+            }
+        }
+    }
+
+    fn stem(&self) -> String {
+        let name = match self {
+            Location::File(path) => path.file_name().map(|n| n.to_string_lossy().to_string()),
+            Location::Url(url) => url.rsplit('/').next().map(str::to_string),
+        };
+        let name = name.unwrap_or_default();
+        name.split('.').next().unwrap_or("schema").to_string()
+    }
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Location::File(path) => write!(f, "{}", path.display()),
+            Location::Url(url) => f.write_str(url),
+        }
+    }
+}
+
+type RefId = (Location, String);
+
+pub struct Bundler {
+    documents: HashMap<Location, Value>,
+    root: RefId,
+    in_progress: Vec<RefId>,
+    cyclic: HashSet<RefId>,
+    def_names: HashMap<RefId, String>,
+    defs: Map<String, Value>,
+    remote: RemoteOptions,
+}
+
+impl Bundler {
+    pub fn new(root: &Value, location: Location) -> Self {
+        let mut documents = HashMap::new();
+        documents.insert(location.clone(), root.clone());
+        Self {
+            documents,
+            root: (location, String::new()),
+            in_progress: Vec::new(),
+            cyclic: HashSet::new(),
+            def_names: HashMap::new(),
+            defs: Map::new(),
+            remote: RemoteOptions::default(),
+        }
+    }
+
+    /// Fetches remote references with `remote`'s headers and credentials
+    pub fn with_remote(mut self, remote: RemoteOptions) -> Self {
+        self.remote = remote;
+        self
+    }
+
+    pub fn bundle(mut self) -> Result<Value> {
+        let (location, _) = self.root.clone();
+        let mut schema = self.documents[&location].clone();
+        let defs_keyword = match SchemaDraft::detect(&schema) {
+            Some(draft) if !draft.uses_defs() => "definitions",
+            _ => "$defs",
+        };
+
+        if let Value::Object(root) = &mut schema {
+            root.remove("$defs");
+            root.remove("definitions");
+        }
+
+        self.in_progress.push(self.root.clone());
+        self.expand(&mut schema, &location, defs_keyword)?;
+        self.in_progress.pop();
+
+        if !self.defs.is_empty() {
+            if let Value::Object(root) = &mut schema {
+                root.insert(defs_keyword.to_string(), Value::Object(self.defs));
+            }
+        }
+
+        Ok(schema)
+    }
+
+    fn expand(&mut self, node: &mut Value, base: &Location, defs_keyword: &str) -> Result<()> {
+        let Value::Object(obj) = node else { return Ok(()) };
+
+        if let Some(Value::String(reference)) = obj.get("$ref") {
+            let reference = reference.clone();
+            obj.remove("$ref");
+            let mut siblings = Value::Object(std::mem::take(obj));
+            self.expand(&mut siblings, base, defs_keyword)?;
+            let target = self.resolve_ref(&reference, base, defs_keyword)?;
+            let Value::Object(siblings) = siblings else { unreachable!() };
+            *node = attach_siblings(target, siblings);
+            return Ok(());
+        }
+
+        let mut result = Ok(());
+        for_each_subschema_mut(obj, &mut |child| {
+            if result.is_ok() {
+                result = self.expand(child, base, defs_keyword);
+            }
+        });
+        result
+    }
+
+    fn resolve_ref(&mut self, reference: &str, base: &Location, defs_keyword: &str) -> Result<Value> {
+        let (document, fragment) = reference.split_once('#').unwrap_or((reference, ""));
+        let location = if document.is_empty() {
+            base.clone()
+        } else {
+            base.resolve(document)?
+        };
+        let id: RefId = (location.clone(), fragment.to_string());
+
+        if id == self.root {
+            return Ok(serde_json::json!({ "$ref": "#" }));
+        }
+
+        if self.in_progress.contains(&id) {
+            self.cyclic.insert(id.clone());
+            return Ok(self.def_ref(&id, defs_keyword));
+        }
+
+        if self.def_names.get(&id).is_some_and(|name| self.defs.contains_key(name)) {
+            return Ok(self.def_ref(&id, defs_keyword));
+        }
+
+        let mut target = self.load_fragment(&location, fragment, reference)?;
+
+        self.in_progress.push(id.clone());
+        self.expand(&mut target, &location, defs_keyword)?;
+        self.in_progress.pop();
+
+        if self.cyclic.contains(&id) {
+            let reference = self.def_ref(&id, defs_keyword);
+            let name = self.def_names[&id].clone();
+            self.defs.insert(name, target);
+            return Ok(reference);
+        }
+
+        Ok(target)
+    }
+
+    fn def_ref(&mut self, id: &RefId, defs_keyword: &str) -> Value {
+        let name = match self.def_names.get(id) {
+            Some(name) => name.clone(),
+            None => {
+                let base_name = id
+                    .1
+                    .rsplit('/')
+                    .next()
+                    .filter(|token| !token.is_empty())
+                    .map(pointer::unescape_token)
+                    .unwrap_or_else(|| id.0.stem());
+                let taken: HashSet<&String> = self.def_names.values().collect();
+                let mut name = base_name.clone();
+                let mut suffix = 2;
+                while taken.contains(&name) {
+                    name = format!("{}{}", base_name, suffix);
+                    suffix += 1;
+                }
+                self.def_names.insert(id.clone(), name.clone());
+                name
+            }
+        };
+        serde_json::json!({ "$ref": format!("#/{}/{}", defs_keyword, pointer::escape_token(&name)) })
+    }
+
+    fn load_fragment(&mut self, location: &Location, fragment: &str, reference: &str) -> Result<Value> {
+        if !self.documents.contains_key(location) {
+            let document = load_document(location, &self.remote)?;
+            self.documents.insert(location.clone(), document);
+        }
+
+        let document = &self.documents[location];
+        let target = if fragment.is_empty() {
+            Some(document)
+        } else if fragment.starts_with('/') {
+            document.pointer(fragment)
+        } else {
+            find_anchor(document, fragment)
+        };
+
+        target
+            .cloned()
+            .ok_or_else(|| AppError::UnresolvableRef(format!("{} (in {})", reference, location)).into())
+    }
+}
+
+pub fn bundle_schema(schema: &Value, location: Location) -> Result<Value> {
+    Bundler::new(schema, location).bundle()
+}
+
+/// Reads a referenced schema document, fetching URLs with `remote` and its cache
+pub fn load_document(location: &Location, remote: &RemoteOptions) -> Result<Value> {
+    match location {
+        Location::Url(url) => remote::fetch_json_with(url, remote),
+        Location::File(path) => {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read referenced schema: {:?}", path))?;
+            Ok(serde_json::from_str(&content).map_err(|e| AppError::invalid_json_in(path.display().to_string(), &e))?)
+        }
+    }
+}
+
+/// Loads the documents that `$ref`s point at while a schema is compiled for validation, as
+/// [`Bundler`] does: relative references against `base`, URLs with `remote`
+#[derive(Debug, Clone, Default)]
+pub struct RefResolver {
+    /// Where the schema was read from. Without it, relative references resolve only against an
+    /// `$id`.
+    pub base: Option<Location>,
+    pub remote: RemoteOptions,
+}
+
+impl SchemaResolver for RefResolver {
+    fn resolve(&self, _root: &Value, url: &Url, reference: &str) -> Result<Arc<Value>, SchemaResolverError> {
+        let unresolvable = |why: &str| AppError::UnresolvableRef(format!("{} ({})", reference, why));
+        let location = match url.scheme() {
+            "http" | "https" => {
+                let mut url = url.clone();
+                url.set_fragment(None);
+                Location::Url(url.to_string())
+            }
+            "file" => Location::from_path(&url.to_file_path().map_err(|_| unresolvable("not a local path"))?),
+            // The validator's base for a schema without an `$id`, so the path is relative to
+            // the schema itself
+            "json-schema" => match &self.base {
+                Some(base) => base.resolve(url.path().trim_start_matches('/'))?,
+                None => return Err(unresolvable("the schema's location is unknown").into()),
+            },
+            scheme => return Err(unresolvable(&format!("unsupported scheme {}", scheme)).into()),
+        };
+        Ok(Arc::new(load_document(&location, &self.remote)?))
+    }
+}
+
+fn attach_siblings(target: Value, siblings: Map<String, Value>) -> Value {
+    if siblings.is_empty() {
+        return target;
+    }
+
+    match target {
+        Value::Object(mut obj) if siblings.keys().all(|k| !obj.contains_key(k)) && !obj.contains_key("$ref") => {
+            obj.extend(siblings);
+            Value::Object(obj)
+        }
+        other => {
+            let mut wrapper = siblings;
+            wrapper.insert("allOf".to_string(), Value::Array(vec![other]));
+            Value::Object(wrapper)
+        }
+    }
+}
+
+fn find_anchor<'a>(node: &'a Value, anchor: &str) -> Option<&'a Value> {
+    let obj = node.as_object()?;
+    if obj.get("$anchor").and_then(Value::as_str) == Some(anchor) {
+        return Some(node);
+    }
+
+    let mut found = None;
+    crate::schema::keywords::for_each_subschema(obj, &mut |_, child| {
+        if found.is_none() {
+            found = find_anchor(child, anchor);
+        }
+    });
+    found
+}
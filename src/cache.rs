@@ -0,0 +1,92 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::hash::{sha256, to_hex};
+
+pub const DEFAULT_TTL: Duration = Duration::from_secs(3600);
+
+/// On-disk cache of fetched URL inputs and remote `$ref`s, one file per URL
+#[derive(Debug, Clone)]
+pub struct RemoteCache {
+    pub dir: PathBuf,
+    /// How long an entry is used without asking the server again
+    pub ttl: Duration,
+    /// Ignore existing entries and fetch everything again
+    pub refresh: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub url: String,
+    pub etag: Option<String>,
+    /// Seconds since the Unix epoch when the body was last fetched or revalidated
+    pub fetched_at: u64,
+    pub body: String,
+}
+
+impl CacheEntry {
+    fn age(&self) -> Duration {
+        Duration::from_secs(now().saturating_sub(self.fetched_at))
+    }
+}
+
+impl RemoteCache {
+    pub fn new(dir: PathBuf, ttl: Duration) -> Self {
+        Self { dir, ttl, refresh: false }
+    }
+
+    pub fn with_refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    /// `$XDG_CACHE_HOME/schema-jenerator`, falling back to `~/.cache/schema-jenerator`
+    pub fn default_dir() -> PathBuf {
+        let base = std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+            .unwrap_or_else(std::env::temp_dir);
+        base.join("schema-jenerator")
+    }
+
+    /// The entry for `url`, however old, unless `refresh` is set
+    pub fn lookup(&self, url: &str) -> Option<CacheEntry> {
+        if self.refresh {
+            return None;
+        }
+        let content = std::fs::read_to_string(self.entry_path(url)).ok()?;
+        serde_json::from_str::<CacheEntry>(&content).ok().filter(|entry| entry.url == url)
+    }
+
+    pub fn is_fresh(&self, entry: &CacheEntry) -> bool {
+        entry.age() < self.ttl
+    }
+
+    pub fn store(&self, url: &str, etag: Option<String>, body: &str) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create cache directory: {:?}", self.dir))?;
+        let entry = CacheEntry {
+            url: url.to_string(),
+            etag,
+            fetched_at: now(),
+            body: body.to_string(),
+        };
+        let path = self.entry_path(url);
+        std::fs::write(&path, serde_json::to_string(&entry)?)
+            .with_context(|| format!("Failed to write cache entry: {:?}", path))?;
+        Ok(())
+    }
+
+    fn entry_path(&self, url: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", to_hex(&sha256(url.as_bytes()))))
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
@@ -1,19 +1,133 @@
 use clap::{Parser, CommandFactory};
 use clap_complete::{generate, Shell};
-use log::info;
-use std::path::PathBuf;
+use tracing::warn;
+use std::path::{Path, PathBuf};
 use std::fs;
-use std::io;
+use std::time::Duration;
+use std::io::{self, BufRead, Write};
 use anyhow::{Context, Result};
 use glob::glob;
 
-use crate::error::AppError;
-use crate::schema::{generate_schema, SchemaOutputTier};
-use crate::validation::validate_schema;
+use crate::additional::AdditionalProperties;
+use crate::annotate::{Annotation, AnnotationFormat};
+use crate::bench::run_bench;
+use crate::bundle::{Bundler, Location, RefResolver};
+use crate::cache::{self, RemoteCache};
+use crate::compat::check_compatibility;
+use crate::complexity::{measure_complexity, ComplexityLimits};
+use crate::coverage::measure_coverage;
+use crate::editor::{vscode_settings, workspace_root, SchemaMapping};
+use crate::insomnia::parse_export;
+use crate::config::{Config, DEFAULT_JSON_FILE, DEFAULT_TOML_FILE};
+use crate::config_schema::config_schema;
+use crate::convert::convert_schema;
+use crate::diff::diff_schemas;
+use crate::doctor::run_diagnostics;
+use crate::error::{exit_code, AppError};
+use crate::explain::{explain_schema, SourceAnnotation};
+use crate::fake::generate_fake_documents;
+use crate::generator::order_keys;
+use crate::infer::InferenceFamily;
+use crate::limits::ResourceLimits;
+use crate::lint::{fix_schema, lint_schema};
+use crate::logging::{self, LogFormat};
+use crate::hash::{embed_hash, schema_hash};
+use crate::merge::merge_all;
+use crate::metadata::SchemaMetadata;
+use crate::minify::minify_schema;
+use crate::normalize::{canonical_string, normalize_schema};
+use crate::nulls::NullPolicy;
+use crate::openapi::{update_component, SpecFormat};
+use crate::output::{write_output, WriteOptions, WriteOutcome};
+use crate::patch::PatchFormat;
+use crate::pipeline::{
+    load_json, BatchInputs, BatchObserver, BatchReport, FileOutcome, OutputFile, Pipeline, ProcessOptions, ProcessReport,
+    ProcessRequest,
+};
+use crate::progress::Progress;
+use crate::quality::{validate_files, QualityFormat, QualityOptions};
+use crate::remote::{self, RemoteOptions};
+use crate::required::RequiredMode;
+use crate::schema::{SchemaDraft, SchemaOutputTier};
+use crate::split::{split_schema, NameCase, SplitOptions};
+use crate::stats::collect_stats;
+use crate::style::{self, highlight_json, paint, Color};
+use crate::tiers::TierChoice;
+use crate::update::update_schema;
+use crate::validation::Severity;
+use crate::{notice, status};
 
 #[derive(Parser, Debug)]
-#[clap(author, version, about, long_about = None)]
+#[clap(author, version, about, long_about = None, disable_version_flag = true)]
 pub struct Args {
+    /// Print version
+    #[clap(long, action = clap::ArgAction::Version)]
+    version: Option<bool>,
+
+    /// Only print errors
+    #[clap(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Log more detail; repeat for debug (-VV) and trace (-VVV)
+    #[clap(short = 'V', long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Disable colored output (also disabled by the NO_COLOR environment variable)
+    #[clap(long, global = true)]
+    pub no_color: bool,
+
+    /// Log line format on stderr; json lines carry file, phase, duration_ms and result fields
+    #[clap(long, global = true, value_enum, default_value = "text")]
+    pub log_format: LogFormat,
+
+    /// Also report failures as CI annotations on stdout so they show up inline on pull requests
+    #[clap(long, global = true, value_enum)]
+    pub annotate: Option<AnnotationFormat>,
+
+    /// Extra request header for URL inputs and remote $refs, e.g. "Authorization: Bearer ..."; repeatable
+    #[clap(long = "header", global = true, value_name = "NAME: VALUE")]
+    pub headers: Vec<String>,
+
+    /// HTTP basic authentication for URL inputs and remote $refs
+    #[clap(long, global = true, value_name = "USER:PASS")]
+    pub basic: Option<String>,
+
+    /// Environment variable holding a bearer token for URL inputs and remote $refs
+    #[clap(long, global = true, value_name = "VAR")]
+    pub token_env: Option<String>,
+
+    /// Retries for URL inputs and remote $refs after connection errors, timeouts, HTTP 429 and 5xx
+    #[clap(long, global = true, default_value_t = 2)]
+    pub retries: u32,
+
+    /// Milliseconds to wait before the first retry; doubled for each further retry
+    #[clap(long, global = true, value_name = "MS", default_value_t = 500)]
+    pub retry_delay: u64,
+
+    /// Seconds allowed for each attempt to fetch a URL input or remote $ref; 0 disables the limit
+    #[clap(long, global = true, value_name = "SECS", default_value_t = 30)]
+    pub timeout: u64,
+
+    /// Proxy for URL inputs and remote $refs; HTTP_PROXY, HTTPS_PROXY and NO_PROXY are honored without it
+    #[clap(long, global = true, value_name = "URL")]
+    pub proxy: Option<String>,
+
+    /// Fetch URL inputs and remote $refs every time instead of using the on-disk cache
+    #[clap(long, global = true)]
+    pub no_cache: bool,
+
+    /// Ignore cached copies of URL inputs and remote $refs and fetch them again
+    #[clap(long, global = true, conflicts_with = "no_cache")]
+    pub refresh: bool,
+
+    /// Seconds a cached URL input or remote $ref is used before it is revalidated with its ETag
+    #[clap(long, global = true, value_name = "SECS", default_value_t = cache::DEFAULT_TTL.as_secs())]
+    pub cache_ttl: u64,
+
+    /// Cache directory for URL inputs and remote $refs [default: $XDG_CACHE_HOME/schema-jenerator]
+    #[clap(long, global = true, value_name = "DIR")]
+    pub cache_dir: Option<PathBuf>,
+
     #[clap(subcommand)]
     pub command: Option<Commands>,
 
@@ -23,8 +137,138 @@ pub struct Args {
     #[clap(short, long, value_parser)]
     pub output: Option<PathBuf>,
 
-    #[clap(short = 't', long, value_enum, default_value = "standard")]
-    pub tier: SchemaOutputTier,
+    /// Write schemas under this directory, mirroring the inputs' directory structure
+    #[clap(long, conflicts_with = "output")]
+    pub output_dir: Option<PathBuf>,
+
+    /// Output path template, e.g. "{dir}/{stem}.{tier}.schema.json"; variables: stem, name, ext, dir, tier, draft
+    #[clap(long, conflicts_with = "output")]
+    pub output_template: Option<String>,
+
+    /// Target JSON Schema draft; the generated schema is converted when given
+    #[clap(long, value_enum)]
+    pub draft: Option<SchemaDraft>,
+
+    /// Schema tier: basic, standard, comprehensive, expert or a tier defined under [tiers] in the config
+    /// [default: the config's default_tier, or standard]
+    #[clap(short = 't', long, value_name = "TIER")]
+    pub tier: Option<TierChoice>,
+
+    /// How null samples are reflected: widen types, add an anyOf null branch, relax required,
+    /// or keep them literally
+    #[clap(long, value_enum, default_value = "as-is")]
+    pub null_policy: NullPolicy,
+
+    /// Which properties are required, independently of the tier; defaults to the tier's policy
+    #[clap(long, value_enum)]
+    pub required_mode: Option<RequiredMode>,
+
+    /// additionalProperties for every object, independently of the tier: true, false or a
+    /// path to a schema fragment file
+    #[clap(long, value_name = "true|false|PATH")]
+    pub additional_properties: Option<AdditionalProperties>,
+
+    /// Stop recursing this many levels below the root; deeper objects and arrays get a
+    /// permissive schema marked with "x-truncated"
+    #[clap(long, value_name = "DEPTH")]
+    pub max_depth: Option<usize>,
+
+    /// Read at most this many bytes of an input file; larger arrays and NDJSON are inferred
+    /// from the records that fit, marked with "x-sampled", and other documents are rejected
+    #[clap(long, value_name = "BYTES")]
+    pub max_input_size: Option<u64>,
+
+    /// Infer from at most this many values per document or record, sampling arrays and then
+    /// truncating the deepest levels to fit
+    #[clap(long, value_name = "N")]
+    pub max_nodes: Option<usize>,
+
+    /// Milliseconds inference may take before it starts over on a sample of each array, or
+    /// stops reading further streamed records
+    #[clap(long, value_name = "MS")]
+    pub time_limit: Option<u64>,
+
+    /// Generate the schema for the subtree at this JSON Pointer, e.g. "/data/items/0"
+    #[clap(long)]
+    pub pointer: Option<String>,
+
+    /// Only keep input fields whose JSON Pointer matches this glob, e.g. "/data/**"; repeatable
+    #[clap(long)]
+    pub include: Vec<String>,
+
+    /// Leave out input fields whose JSON Pointer matches this glob, e.g. "/metadata/**"; repeatable
+    #[clap(long)]
+    pub exclude: Vec<String>,
+
+    /// Only infer from this many elements of each array, picked by reservoir sampling
+    #[clap(long, value_name = "N")]
+    pub array_sample: Option<usize>,
+
+    /// Seed for --array-sample; a fixed default keeps output reproducible
+    #[clap(long, requires = "array_sample")]
+    pub sample_seed: Option<u64>,
+
+    /// Infer these keyword families even when the tier would not, e.g. "formats,enums,bounds"
+    #[clap(long, value_enum, value_delimiter = ',')]
+    pub infer: Vec<InferenceFamily>,
+
+    /// Never emit these keyword families, e.g. "patterns,examples"
+    #[clap(long, value_enum, value_delimiter = ',')]
+    pub no_infer: Vec<InferenceFamily>,
+
+    /// Never emit examples, which copy raw values from the input, at any tier
+    #[clap(long)]
+    pub no_examples: bool,
+
+    /// Keep at most this many examples per keyword; long example strings are always truncated
+    #[clap(long, value_name = "N")]
+    pub examples_limit: Option<usize>,
+
+    /// Byte-identical output for identical input and options: oneOf/anyOf branches are sorted
+    /// canonically instead of following the order of the data
+    #[clap(long)]
+    pub deterministic: bool,
+
+    /// List properties in the order of the input document instead of alphabetically. Needs a
+    /// build with the preserve-order feature
+    #[clap(long)]
+    pub preserve_order: bool,
+
+    /// Threads for inferring the elements of large arrays and the properties of wide objects;
+    /// 0 uses every core. The schema is the same for any number
+    #[clap(long, value_name = "N", default_value_t = 0)]
+    pub jobs: usize,
+
+    /// Record on each subschema the data pointers it was inferred from and the heuristics that
+    /// fired, as a $comment or an x-source object, for auditing inferred output
+    #[clap(long, value_enum, value_name = "STYLE")]
+    pub source_annotations: Option<SourceAnnotation>,
+
+    /// Embed an x-generated-by block in the schema root: tool version, the options that were set,
+    /// a hash of the input and the sample count. Without a timestamp under --deterministic
+    #[clap(long)]
+    pub provenance: bool,
+
+    /// Name of the root definition, e.g. "User"; used as the title unless --title is given
+    #[clap(long)]
+    pub root_name: Option<String>,
+
+    /// Title of the generated schema
+    #[clap(long)]
+    pub title: Option<String>,
+
+    /// Description of the generated schema
+    #[clap(long)]
+    pub description: Option<String>,
+
+    /// Language of the generated titles and descriptions, e.g. de or de-AT, read from a
+    /// descriptions.<locale>.yaml (.toml, .json) file; messages it lacks stay in English
+    #[clap(long, value_name = "LOCALE")]
+    pub locale: Option<String>,
+
+    /// Directory of the locale files [default: current directory]
+    #[clap(long, value_name = "DIR")]
+    pub locale_dir: Option<PathBuf>,
 
     #[clap(short, long)]
     pub pretty: bool,
@@ -32,11 +276,187 @@ pub struct Args {
     #[clap(short = 'v', long)]
     pub validate: bool,
 
+    /// Validate each input against the schema generated from it and fail when it does not pass,
+    /// e.g. when a tier emits constraints the data itself breaks
+    #[clap(long)]
+    pub verify: bool,
+
+    /// Merge into the existing schema at the output path instead of overwriting it: types widen
+    /// and new properties are added, but titles, descriptions, properties missing from the new
+    /// data and subschemas marked "x-manual": true are kept
+    #[clap(long)]
+    pub update: bool,
+
+    /// Also write what changed from the existing schema at the output path, as an RFC 6902
+    /// JSON Patch (.patch.json) or RFC 7386 merge patch (.merge-patch.json); repeatable
+    #[clap(long, value_enum, value_name = "FORMAT")]
+    pub patch: Vec<PatchFormat>,
+
+    /// Write only the --patch files, leaving the existing schema as it is
+    #[clap(long, requires = "patch")]
+    pub patch_only: bool,
+
+    /// Fail with a diff when the generated schema differs from this golden file
+    #[clap(long, value_name = "GOLDEN")]
+    pub expect: Option<PathBuf>,
+
+    /// Overwrite the --expect golden file with the generated schema instead of comparing
+    #[clap(long, requires = "expect")]
+    pub update_golden: bool,
+
+    /// Set the "$schema" of each input to its generated schema, so editors validate and
+    /// complete it
+    #[clap(long)]
+    pub inject_schema: bool,
+
+    /// Map each input to its generated schema in the json.schemas setting of this VS Code
+    /// settings file, e.g. .vscode/settings.json
+    #[clap(long, value_name = "SETTINGS")]
+    pub vscode_settings: Option<PathBuf>,
+
     #[clap(short = 'b', long)]
     pub batch: bool,
 
+    /// Process the input paths listed one per line in this file, or on stdin for "-", e.g. the
+    /// output of `git diff --name-only`
+    #[clap(long, value_name = "FILE", conflicts_with_all = ["input", "batch"])]
+    pub files_from: Option<PathBuf>,
+
+    /// Config profile to apply, i.e. a [profile.<NAME>] table overriding the top-level settings
+    #[clap(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Config file; without it, .schema-jenerator.toml or schema-jenerator.json is looked up from each input's directory upwards
     #[clap(short = 'c', long)]
     pub config: Option<PathBuf>,
+
+    /// Read NDJSON or a top-level JSON array one record at a time, for inputs too large to
+    /// parse whole; always on for .ndjson and .jsonl files
+    #[clap(long)]
+    pub stream: bool,
+
+    /// Also write a `.min.json` copy without titles, descriptions, examples and x- keywords
+    #[clap(long)]
+    pub minify: bool,
+
+    /// Also write a .map.json source map linking each schema pointer to the input data pointers
+    /// it was derived from
+    #[clap(long)]
+    pub source_map: bool,
+
+    /// Print which rule produced each keyword of the generated schema
+    #[clap(long)]
+    pub explain: bool,
+
+    /// Stop batch processing at the first file that fails
+    #[clap(long, conflicts_with = "keep_going")]
+    pub fail_fast: bool,
+
+    /// Exit successfully even when some batch files failed
+    #[clap(long)]
+    pub keep_going: bool,
+
+    /// Refuse to overwrite existing schema files unless --force is given
+    #[clap(long)]
+    pub no_clobber: bool,
+
+    /// Overwrite existing files even with --no-clobber
+    #[clap(short = 'f', long)]
+    pub force: bool,
+
+    /// Leave existing files untouched, mtime included, when the generated content is identical
+    #[clap(long)]
+    pub skip_unchanged: bool,
+
+    /// Generate and validate, but print schemas and their target paths instead of writing files
+    #[clap(long)]
+    pub dry_run: bool,
+}
+
+impl Args {
+    fn remote_options(&self) -> Result<RemoteOptions> {
+        Ok(RemoteOptions {
+            headers: self
+                .headers
+                .iter()
+                .map(|header| RemoteOptions::parse_header(header))
+                .collect::<Result<_>>()?,
+            basic_auth: self.basic.as_deref().map(RemoteOptions::parse_basic_auth),
+            bearer_token: self.token_env.as_deref().map(RemoteOptions::token_from_env).transpose()?,
+            retries: self.retries,
+            retry_delay: Duration::from_millis(self.retry_delay),
+            timeout: (self.timeout > 0).then(|| Duration::from_secs(self.timeout)),
+            proxy: self.proxy.clone(),
+            cache: (!self.no_cache).then(|| {
+                let dir = self.cache_dir.clone().unwrap_or_else(RemoteCache::default_dir);
+                RemoteCache::new(dir, Duration::from_secs(self.cache_ttl)).with_refresh(self.refresh)
+            }),
+        })
+    }
+
+    fn process_options(&self) -> Result<ProcessOptions> {
+        Ok(ProcessOptions {
+            config: self.config.clone(),
+            profile: self.profile.clone(),
+            tier: self.tier.clone(),
+            draft: self.draft,
+            pretty: self.pretty,
+            validate: self.validate,
+            verify: self.verify,
+            update: self.update,
+            patch: self.patch.clone(),
+            patch_only: self.patch_only,
+            expect: self.expect.clone(),
+            update_golden: self.update_golden,
+            inject_schema: self.inject_schema,
+            output: self.output.clone(),
+            output_dir: self.output_dir.clone(),
+            output_template: self.output_template.clone(),
+            pointer: self.pointer.clone(),
+            include: self.include.clone(),
+            exclude: self.exclude.clone(),
+            array_sample: self.array_sample,
+            sample_seed: self.sample_seed,
+            null_policy: self.null_policy,
+            required_mode: self.required_mode,
+            additional_properties: self.additional_properties.clone(),
+            max_depth: self.max_depth,
+            limits: ResourceLimits {
+                max_input_size: self.max_input_size,
+                max_nodes: self.max_nodes,
+                max_depth: None,
+                time_limit: self.time_limit,
+            },
+            infer: self.infer.clone(),
+            no_infer: self.no_infer.clone(),
+            no_examples: self.no_examples,
+            examples_limit: self.examples_limit,
+            deterministic: self.deterministic,
+            preserve_order: self.preserve_order,
+            jobs: self.jobs,
+            source_annotations: self.source_annotations,
+            provenance: self.provenance,
+            metadata: SchemaMetadata {
+                root_name: self.root_name.clone(),
+                title: self.title.clone(),
+                description: self.description.clone(),
+            },
+            locale: self.locale.clone(),
+            locale_dir: self.locale_dir.clone(),
+            hooks: Vec::new(),
+            stream: self.stream,
+            minify: self.minify,
+            source_map: self.source_map,
+            explain: self.explain,
+            write: WriteOptions {
+                no_clobber: self.no_clobber && !self.force,
+                skip_unchanged: self.skip_unchanged,
+            },
+            dry_run: self.dry_run,
+            fail_fast: self.fail_fast,
+            remote: self.remote_options()?,
+        })
+    }
 }
 
 #[derive(Parser, Debug)]
@@ -45,30 +465,436 @@ pub enum Commands {
         #[clap(value_enum)]
         shell: Shell,
     },
+    /// Compare two schema files and report what changed
+    Diff {
+        old: PathBuf,
+        new: PathBuf,
+
+        #[clap(short, long, value_enum, default_value = "text")]
+        format: ReportFormat,
+    },
+    /// Classify schema changes as compatible or breaking, failing on breaking changes
+    Compat {
+        old: PathBuf,
+        new: PathBuf,
+
+        #[clap(short, long, value_enum, default_value = "text")]
+        format: ReportFormat,
+    },
+    /// Merge several schemas into one that accepts everything each of them accepts
+    Merge {
+        #[clap(required = true, num_args = 2..)]
+        inputs: Vec<PathBuf>,
+
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+
+        #[clap(short, long)]
+        pretty: bool,
+    },
+    /// Convert a schema between JSON Schema drafts
+    Convert {
+        input: PathBuf,
+
+        /// Source draft; detected from `$schema` when omitted
+        #[clap(long, value_enum)]
+        from: Option<SchemaDraft>,
+
+        #[clap(long, value_enum)]
+        to: SchemaDraft,
+
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+
+        #[clap(short, long)]
+        pretty: bool,
+    },
+    /// Inline local and remote `$ref`s into a single self-contained schema
+    Bundle {
+        input: PathBuf,
+
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+
+        #[clap(short, long)]
+        pretty: bool,
+    },
+    /// Split `$defs` into one file per definition plus an index file
+    Split {
+        input: PathBuf,
+
+        #[clap(long)]
+        out_dir: PathBuf,
+
+        /// File name template for definitions; `{name}` is replaced by the definition name
+        #[clap(long, default_value = "{name}.schema.json")]
+        file_template: String,
+
+        #[clap(long, value_enum, default_value = "original")]
+        case: NameCase,
+
+        #[clap(short, long)]
+        pretty: bool,
+    },
+    /// Generate synthetic JSON documents that satisfy a schema
+    Fake {
+        #[clap(short, long)]
+        schema: PathBuf,
+
+        #[clap(short = 'n', long, default_value_t = 1)]
+        count: usize,
+
+        /// Seed for reproducible output
+        #[clap(long)]
+        seed: Option<u64>,
+
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+
+        #[clap(short, long)]
+        pretty: bool,
+
+        /// Emit one document per line instead of a JSON array
+        #[clap(long)]
+        ndjson: bool,
+    },
+    /// Profile the structure of a JSON document without generating a schema
+    Stats {
+        input: PathBuf,
+
+        #[clap(short, long, value_enum, default_value = "text")]
+        format: ReportFormat,
+
+        /// Number of entries to show in the nullable-path and subtree rankings
+        #[clap(long, default_value_t = 5)]
+        top: usize,
+    },
+    /// Regenerate a schema from data and fail if it differs from the committed one
+    Check {
+        #[clap(short, long)]
+        schema: PathBuf,
+
+        /// Data files or glob patterns to regenerate from
+        #[clap(required = true)]
+        data: Vec<String>,
+
+        /// Schema tier, as for generating [default: the config's default_tier, or standard]
+        #[clap(short = 't', long, value_name = "TIER")]
+        tier: Option<TierChoice>,
+
+        #[clap(short, long, value_enum, default_value = "text")]
+        format: ReportFormat,
+    },
+    /// Rewrite a schema into canonical, byte-stable form
+    Normalize {
+        input: PathBuf,
+
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Measure the size and complexity of a schema and fail above the given thresholds
+    Complexity {
+        input: PathBuf,
+
+        #[clap(short, long, value_enum, default_value = "text")]
+        format: ReportFormat,
+
+        /// Most schemas, the root and every subschema
+        #[clap(long)]
+        max_nodes: Option<usize>,
+
+        /// Most subschema levels below the root
+        #[clap(long)]
+        max_depth: Option<usize>,
+
+        /// Most branches in a single oneOf or anyOf
+        #[clap(long)]
+        max_branches: Option<usize>,
+
+        #[clap(long)]
+        max_refs: Option<usize>,
+
+        /// Most values in a single enum
+        #[clap(long)]
+        max_enum: Option<usize>,
+
+        #[clap(long)]
+        max_score: Option<usize>,
+    },
+    /// Check a schema for redundant, contradictory and untidy keywords
+    Lint {
+        input: PathBuf,
+
+        /// Apply the safe fixes to the schema and report what is left
+        #[clap(long)]
+        fix: bool,
+
+        /// Write the fixed schema here instead of over the input
+        #[clap(short, long, requires = "fix")]
+        output: Option<PathBuf>,
+
+        #[clap(short, long, value_enum, default_value = "text")]
+        format: ReportFormat,
+    },
+    /// Strip annotations (title, description, examples, x-*) for runtime validators
+    Minify {
+        input: PathBuf,
+
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Print a stable fingerprint of a schema's canonical form
+    Hash {
+        input: PathBuf,
+
+        /// Output the schema with the fingerprint embedded as `x-schema-hash` instead
+        #[clap(long)]
+        embed: bool,
+
+        #[clap(short, long, requires = "embed")]
+        output: Option<PathBuf>,
+
+        #[clap(short, long, requires = "embed")]
+        pretty: bool,
+    },
+    /// Write a commented default config file into the current directory
+    Init {
+        #[clap(long, conflicts_with = "json")]
+        toml: bool,
+
+        #[clap(long)]
+        json: bool,
+
+        /// Ask for tier, draft and output directory instead of using defaults
+        #[clap(short, long)]
+        interactive: bool,
+
+        /// Overwrite an existing config file
+        #[clap(short, long)]
+        force: bool,
+    },
+    /// Preview how regenerating from data would change an existing schema before overwriting it
+    Review {
+        /// Data files or glob patterns to regenerate from
+        #[clap(required = true)]
+        data: Vec<String>,
+
+        /// Schema file to compare against and overwrite on confirmation
+        #[clap(long)]
+        against: PathBuf,
+
+        /// Schema tier, as for generating [default: the config's default_tier, or standard]
+        #[clap(short = 't', long, value_name = "TIER")]
+        tier: Option<TierChoice>,
+
+        #[clap(short, long)]
+        pretty: bool,
+
+        /// Write without asking for confirmation
+        #[clap(short, long)]
+        yes: bool,
+
+        /// Merge into the existing schema as the generator's --update does, instead of replacing it
+        #[clap(long)]
+        update: bool,
+    },
+    /// Show which rule or heuristic produced each keyword of a generated schema
+    Explain {
+        schema: PathBuf,
+
+        /// Sample files or glob patterns the schema was generated from, to count support per keyword
+        #[clap(short, long)]
+        data: Vec<String>,
+
+        #[clap(short, long, value_enum, default_value = "text")]
+        format: ReportFormat,
+    },
+    /// Measure generation throughput, peak memory and per-phase timing over a corpus
+    Bench {
+        /// Input files or glob patterns, e.g. 'corpus/**/*.json'
+        #[clap(required = true)]
+        inputs: Vec<String>,
+
+        /// Tier to benchmark; repeat to compare several tiers
+        #[clap(short = 't', long = "tier", value_enum, default_value = "standard")]
+        tiers: Vec<SchemaOutputTier>,
+
+        /// Include meta-schema validation of each generated schema
+        #[clap(long)]
+        validate: bool,
+
+        #[clap(short, long, value_enum, default_value = "text")]
+        format: ReportFormat,
+    },
+    /// Validate data files against a schema on parallel workers and summarize what failed
+    Validate {
+        #[clap(short, long)]
+        schema: PathBuf,
+
+        /// Data files or glob patterns to validate, e.g. 'exports/**/*.json'
+        #[clap(required = true)]
+        data: Vec<String>,
+
+        /// Files validated at once; 0 means one per core
+        #[clap(short = 'j', long, default_value_t = 0)]
+        jobs: usize,
+
+        /// Number of entries to show in the keyword and field rankings
+        #[clap(long, default_value_t = 10)]
+        top: usize,
+
+        /// Stop after this many errors; the files after the one reaching it are not validated
+        #[clap(long)]
+        max_errors: Option<usize>,
+
+        /// Exit with an error on warnings too, not only on errors
+        #[clap(long, value_enum, default_value = "error")]
+        fail_at: Severity,
+
+        /// Report violations of this keyword as warnings that do not fail validation
+        /// (repeatable), e.g. additionalProperties
+        #[clap(long = "warn-keyword", value_name = "KEYWORD")]
+        warn_keywords: Vec<String>,
+
+        #[clap(short = 'f', long, alias = "format", value_enum, default_value = "text")]
+        report_format: QualityFormat,
+
+        /// Write the report to this file and print the text summary instead
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Report the schema branches and properties a dataset never reaches, and the data fields
+    /// the schema does not describe
+    Coverage {
+        #[clap(short, long)]
+        schema: PathBuf,
+
+        /// Data files or glob patterns, e.g. 'exports/**/*.json'
+        #[clap(required = true)]
+        data: Vec<String>,
+
+        #[clap(short, long, value_enum, default_value = "text")]
+        format: ReportFormat,
+    },
+    /// Check the config file, output directory and input patterns for common problems
+    Doctor {
+        /// Input glob patterns to check for matching files
+        patterns: Vec<String>,
+
+        /// Config file to check instead of the defaults in the current directory
+        #[clap(short, long)]
+        config: Option<PathBuf>,
+
+        #[clap(short, long, value_enum, default_value = "text")]
+        format: ReportFormat,
+    },
+    /// Generate a schema for the JSON body of each request in an Insomnia export
+    Insomnia {
+        /// Export written by Insomnia's "Export Data", in its v4 JSON format
+        input: PathBuf,
+
+        /// Directory for the `<request-name>.request.schema.json` files
+        #[clap(long)]
+        out_dir: PathBuf,
+
+        /// Schema tier, as for generating [default: the config's default_tier, or standard]
+        #[clap(short = 't', long, value_name = "TIER")]
+        tier: Option<TierChoice>,
+
+        #[clap(short, long)]
+        pretty: bool,
+    },
+    /// Work with component schemas inside an OpenAPI document
+    Openapi {
+        #[clap(subcommand)]
+        action: OpenapiCommand,
+    },
+    /// Inspect the config file format
+    Config {
+        #[clap(subcommand)]
+        action: ConfigCommand,
+    },
+}
+
+#[derive(Parser, Debug)]
+pub enum ConfigCommand {
+    /// Print a JSON Schema for .schema-jenerator.toml and schema-jenerator.json, e.g. for editor completion
+    Schema,
+}
+
+#[derive(Parser, Debug)]
+pub enum OpenapiCommand {
+    /// Regenerate `components.schemas.<NAME>` from samples, leaving the rest of the spec untouched
+    Update {
+        /// OpenAPI document (.json, .yaml or .yml), rewritten in place
+        spec: PathBuf,
+
+        #[clap(long)]
+        component: String,
+
+        /// Sample files or glob patterns to generate the component from
+        #[clap(long, required = true, num_args = 1..)]
+        from: Vec<String>,
+
+        /// Schema tier, as for generating [default: the config's default_tier, or standard]
+        #[clap(short = 't', long, value_name = "TIER")]
+        tier: Option<TierChoice>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportFormat {
+    Text,
+    Json,
 }
 
 pub fn run() -> Result<()> {
-    env_logger::init();
-    let args = Args::parse();
+    // Clap exits with 2 on bad arguments, which is taken by a missing input
+    let mut args = Args::try_parse().unwrap_or_else(|e| {
+        if !e.use_stderr() {
+            e.exit();
+        }
+        let _ = e.print();
+        std::process::exit(exit_code::USAGE);
+    });
+    style::init(args.no_color);
+    logging::init(args.quiet, args.verbose, args.log_format, args.no_color);
 
-    if let Some(command) = args.command {
-        return handle_command(command);
+    if let Some(command) = args.command.take() {
+        return handle_command(command, &args);
+    }
+
+    if let Some(source) = &args.files_from {
+        return process_files_from(source, &args);
     }
 
     let input = args.input.as_ref().ok_or_else(|| {
-        AppError::SchemaGeneration("Input file is required for schema generation".to_string())
+        AppError::InvalidOption("Input file is required for schema generation".to_string())
     })?;
 
     if args.batch {
         process_batch(input, &args)?;
     } else {
-        process_single_file(input, &args)?;
+        let pipeline = Pipeline::new(args.process_options()?);
+        match pipeline.process(&ProcessRequest::new(input)) {
+            Ok(report) => {
+                print_report(&report, &Progress::hidden());
+                if let Some(settings) = &args.vscode_settings {
+                    write_vscode_settings(settings, std::slice::from_ref(&report), args.dry_run)?;
+                }
+            }
+            Err(e) => {
+                annotate(args.annotate, Annotation::from_error(input, &e));
+                return Err(e);
+            }
+        }
     }
 
     Ok(())
 }
 
-fn handle_command(command: Commands) -> Result<()> {
+fn handle_command(command: Commands, args: &Args) -> Result<()> {
+    let annotations = args.annotate;
     match command {
         Commands::Completion { shell } => {
             let mut app = Args::command();
@@ -76,80 +902,674 @@ fn handle_command(command: Commands) -> Result<()> {
             generate(shell, &mut app, app_name, &mut io::stdout());
             Ok(())
         }
-    }
-}
+        Commands::Diff { old, new, format } => {
+            let diff = diff_schemas(&load_json(&old)?, &load_json(&new)?);
+            match format {
+                ReportFormat::Text => print!("{}", diff.render_text()),
+                ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&diff)?),
+            }
+            Ok(())
+        }
+        Commands::Compat { old, new, format } => {
+            let report = check_compatibility(&load_json(&old)?, &load_json(&new)?);
+            match format {
+                ReportFormat::Text => print!("{}", report.render_text()),
+                ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            }
+            if annotations.is_some() {
+                let text = fs::read_to_string(&new)?;
+                for classified in report.breaking_changes() {
+                    let message = format!("{} ({})", classified.change.describe(), classified.reason);
+                    let annotation = Annotation::error(message)
+                        .file(&new)
+                        .title("Breaking change")
+                        .at_pointer(&text, &classified.change.pointer);
+                    annotate(annotations, annotation);
+                }
+            }
+            if report.is_breaking() {
+                return Err(AppError::BreakingChanges(report.breaking_changes().count()).into());
+            }
+            Ok(())
+        }
+        Commands::Merge { inputs, output, pretty } => {
+            let schemas = inputs
+                .iter()
+                .map(|path| load_json(path))
+                .collect::<Result<Vec<_>>>()?;
+            let merged = merge_all(&schemas).ok_or_else(|| {
+                AppError::InvalidOption("No schemas to merge".to_string())
+            })?;
+            write_json_output(&merged, output.as_deref(), pretty)?;
+            if let Some(path) = output {
+                status!("Schema merged successfully: {:?}", path);
+            }
+            Ok(())
+        }
+        Commands::Convert { input, from, to, output, pretty } => {
+            let schema = load_json(&input)?;
+            let from = from.or_else(|| SchemaDraft::detect(&schema)).ok_or_else(|| {
+                AppError::InvalidOption("Unable to detect the source draft from $schema; pass --from".to_string())
+            })?;
+            let conversion = convert_schema(&schema, from, to);
+            for warning in &conversion.warnings {
+                warn!("{}", warning);
+            }
+            write_json_output(&conversion.schema, output.as_deref(), pretty)?;
+            if let Some(path) = output {
+                status!("Schema converted from {} to {}: {:?}", from, to, path);
+            }
+            Ok(())
+        }
+        Commands::Bundle { input, output, pretty } => {
+            let schema = load_json(&input)?;
+            let bundled = Bundler::new(&schema, Location::from_path(&input))
+                .with_remote(args.remote_options()?)
+                .bundle()?;
+            write_json_output(&bundled, output.as_deref(), pretty)?;
+            if let Some(path) = output {
+                status!("Schema bundled successfully: {:?}", path);
+            }
+            Ok(())
+        }
+        Commands::Split { input, out_dir, file_template, case, pretty } => {
+            let schema = load_json(&input)?;
+            let root_file = input
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| SplitOptions::default().root_file);
+            let options = SplitOptions { root_file, file_template, case };
+            let split = split_schema(&schema, &options);
+
+            fs::create_dir_all(&out_dir)
+                .with_context(|| format!("Failed to create output directory: {:?}", out_dir))?;
+            write_json_output(&split.root, Some(&out_dir.join(&options.root_file)), pretty)?;
+            for (file_name, def) in &split.files {
+                write_json_output(def, Some(&out_dir.join(file_name)), pretty)?;
+            }
+            write_json_output(&split.index, Some(&out_dir.join("index.json")), true)?;
 
-fn process_batch(input_pattern: &PathBuf, args: &Args) -> Result<()> {
-    let pattern = input_pattern.to_string_lossy();
-    let mut processed = 0;
-    let mut errors = Vec::new();
+            status!(
+                "Split {} definition(s) into {:?}",
+                split.files.len(),
+                out_dir
+            );
+            Ok(())
+        }
+        Commands::Fake { schema, count, seed, output, pretty, ndjson } => {
+            let schema = load_json(&schema)?;
+            let mut documents = generate_fake_documents(&schema, count, seed);
 
-    for entry in glob(&pattern)
-        .map_err(|e| AppError::SchemaGeneration(format!("Invalid glob pattern: {}", e)))?
-    {
-        match entry {
-            Ok(path) => {
-                info!("Processing file: {:?}", path);
-                match process_single_file(&path, args) {
-                    Ok(_) => processed += 1,
-                    Err(e) => errors.push(format!("{:?}: {}", path, e)),
+            if ndjson {
+                let mut content = String::new();
+                for document in &documents {
+                    content.push_str(&serde_json::to_string(document)?);
+                    content.push('\n');
+                }
+                match &output {
+                    Some(path) => fs::write(path, content)
+                        .with_context(|| format!("Failed to write documents to file: {:?}", path))?,
+                    None => print!("{}", content),
                 }
+            } else {
+                let value = if count == 1 {
+                    documents.remove(0)
+                } else {
+                    serde_json::Value::Array(documents)
+                };
+                write_json_output(&value, output.as_deref(), pretty)?;
             }
-            Err(e) => errors.push(format!("Glob error: {}", e)),
+            Ok(())
         }
-    }
+        Commands::Stats { input, format, top } => {
+            let stats = collect_stats(&load_json(&input)?, top);
+            match format {
+                ReportFormat::Text => print!("{}", stats.render_text()),
+                ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&stats)?),
+            }
+            Ok(())
+        }
+        Commands::Init { toml: _, json, interactive, force } => run_init(json, interactive, force),
+        Commands::Normalize { input, output } => {
+            let normalized = canonical_string(&normalize_schema(&load_json(&input)?));
+            match output {
+                Some(path) => {
+                    fs::write(&path, normalized)
+                        .with_context(|| format!("Failed to write schema to file: {:?}", path))?;
+                    status!("Schema normalized successfully: {:?}", path);
+                }
+                None => print!("{}", normalized),
+            }
+            Ok(())
+        }
+        Commands::Complexity { input, format, max_nodes, max_depth, max_branches, max_refs, max_enum, max_score } => {
+            let report = measure_complexity(&load_json(&input)?);
+            let limits = ComplexityLimits { max_nodes, max_depth, max_branches, max_refs, max_enum, max_score };
+            let violations = report.violations(&limits);
 
-    println!("Processed {} files successfully", processed);
-    if !errors.is_empty() {
-        println!("Errors encountered:");
-        for error in errors {
-            println!("  {}", error);
+            match format {
+                ReportFormat::Text => print!("{}", report.render_text()),
+                ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            }
+            for violation in &violations {
+                warn!("{}", violation);
+            }
+            if !violations.is_empty() {
+                return Err(AppError::ComplexityExceeded(violations.len()).into());
+            }
+            Ok(())
+        }
+        Commands::Lint { input, fix, output, format } => {
+            let mut schema = load_json(&input)?;
+            let report = if fix { fix_schema(&mut schema) } else { lint_schema(&schema) };
+
+            match format {
+                ReportFormat::Text => print!("{}", report.render_text()),
+                ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            }
+            if report.fixed() > 0 || output.is_some() {
+                let path = output.unwrap_or(input);
+                write_json_output(&schema, Some(&path), true)?;
+                status!("Fixed {} issue(s): {:?}", report.fixed(), path);
+            }
+            if report.remaining() > 0 {
+                return Err(AppError::LintFailed(report.remaining()).into());
+            }
+            Ok(())
+        }
+        Commands::Minify { input, output } => {
+            let minified = minify_schema(&load_json(&input)?);
+            write_json_output(&minified, output.as_deref(), false)?;
+            if let Some(path) = output {
+                status!("Schema minified successfully: {:?}", path);
+            }
+            Ok(())
+        }
+        Commands::Hash { input, embed, output, pretty } => {
+            let schema = load_json(&input)?;
+            if !embed {
+                println!("{}", schema_hash(&schema));
+                return Ok(());
+            }
+
+            write_json_output(&embed_hash(&schema), output.as_deref(), pretty)?;
+            if let Some(path) = output {
+                status!("Schema hash embedded successfully: {:?}", path);
+            }
+            Ok(())
+        }
+        Commands::Check { schema, data, tier, format } => {
+            let committed = load_json(&schema)?;
+            let regenerated = regenerating_pipeline(args, tier)?.generate_merged(&expand_inputs(&data)?)?;
+            let diff = diff_schemas(&committed, &regenerated);
+
+            match format {
+                ReportFormat::Text => print!("{}", diff.render_text()),
+                ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&diff)?),
+            }
+            if annotations.is_some() {
+                let text = fs::read_to_string(&schema)?;
+                for change in &diff.changes {
+                    let annotation = Annotation::error(change.describe())
+                        .file(&schema)
+                        .title("Schema drift")
+                        .at_pointer(&text, &change.pointer);
+                    annotate(annotations, annotation);
+                }
+            }
+            if !diff.is_empty() {
+                return Err(AppError::SchemaDrift(diff.changes.len()).into());
+            }
+            Ok(())
+        }
+        Commands::Review { data, against, tier, pretty, yes, update } => {
+            run_review(&regenerating_pipeline(args, tier)?, &data, &against, pretty, yes, update)
+        }
+        Commands::Explain { schema, data, format } => {
+            let samples = if data.is_empty() {
+                Vec::new()
+            } else {
+                expand_inputs(&data)?
+                    .iter()
+                    .map(|path| load_json(path))
+                    .collect::<Result<Vec<_>>>()?
+            };
+            let report = explain_schema(&load_json(&schema)?, &samples);
+
+            match format {
+                ReportFormat::Text => print!("{}", report.render_text()),
+                ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            }
+            Ok(())
+        }
+        Commands::Bench { inputs, tiers, validate, format } => {
+            let paths = expand_inputs(&inputs)?;
+            let reports = tiers
+                .iter()
+                .map(|tier| run_bench(&paths, tier, validate))
+                .collect::<Result<Vec<_>>>()?;
+
+            match format {
+                ReportFormat::Text => {
+                    let rendered: Vec<String> = reports.iter().map(|r| r.render_text()).collect();
+                    print!("{}", rendered.join("\n"));
+                }
+                ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&reports)?),
+            }
+            Ok(())
+        }
+        Commands::Validate { schema, data, jobs, top, max_errors, fail_at, warn_keywords, report_format, output } => {
+            let refs = RefResolver { base: Some(Location::from_path(&schema)), remote: args.remote_options()? };
+            let options = QualityOptions { jobs, top, max_errors, warn_keywords, refs };
+            let report = validate_files(&load_json(&schema)?, &expand_inputs(&data)?, &options)?;
+
+            let rendered = report.render(report_format, &schema)?;
+            match &output {
+                Some(path) => {
+                    fs::write(path, rendered).with_context(|| format!("Failed to write report to file: {:?}", path))?;
+                    print!("{}", report.render_text());
+                }
+                None => print!("{}", rendered),
+            }
+            if !report.is_valid() {
+                return Err(AppError::ValidationFailed(format!(
+                    "{} of {} file(s) failed validation",
+                    report.failed, report.files
+                ))
+                .into());
+            }
+            if report.fails_at(fail_at) {
+                return Err(AppError::ValidationFailed(format!(
+                    "{} warning(s) with --fail-at warning",
+                    report.warnings
+                ))
+                .into());
+            }
+            Ok(())
+        }
+        Commands::Coverage { schema, data, format } => {
+            let documents = expand_inputs(&data)?.iter().map(|path| load_json(path)).collect::<Result<Vec<_>>>()?;
+            let report = measure_coverage(&load_json(&schema)?, &documents);
+            match format {
+                ReportFormat::Text => print!("{}", report.render_text()),
+                ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            }
+            Ok(())
+        }
+        Commands::Doctor { patterns, config, format } => {
+            let report = run_diagnostics(config.as_deref(), &patterns);
+
+            match format {
+                ReportFormat::Text => print!("{}", report.render_text()),
+                ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            }
+            if report.errors() > 0 {
+                return Err(AppError::DiagnosticsFailed(report.errors()).into());
+            }
+            Ok(())
+        }
+        Commands::Insomnia { input, out_dir, tier, pretty } => {
+            let export = parse_export(&load_json(&input)?)?;
+            for message in &export.skipped {
+                warn!("Skipping {}", message);
+            }
+            if export.requests.is_empty() {
+                notice!("No requests with a JSON body in {:?}", input);
+                return Ok(());
+            }
+
+            fs::create_dir_all(&out_dir)
+                .with_context(|| format!("Failed to create output directory: {:?}", out_dir))?;
+            let options = regenerating_pipeline(args, tier)?.options().clone();
+            for request in &export.requests {
+                let description = format!("Request body of {} {}", request.method, request.url);
+                let metadata = SchemaMetadata {
+                    title: Some(request.name.clone()),
+                    description: Some(description.trim_end().to_string()),
+                    ..options.metadata.clone()
+                };
+                let pipeline = Pipeline::new(ProcessOptions { metadata, ..options.clone() });
+                let schema = pipeline.generate_value(&input, request.body.clone())?;
+                let path = out_dir.join(format!("{}.request.schema.json", request.file_stem));
+                write_json_output(&schema, Some(&path), pretty)?;
+                status!("Schema generated successfully: {:?}", path);
+            }
+            Ok(())
+        }
+        Commands::Openapi { action: OpenapiCommand::Update { spec, component, from, tier } } => {
+            let text = fs::read_to_string(&spec)
+                .map_err(|_| AppError::FileNotFound(spec.display().to_string()))?;
+            let schema = regenerating_pipeline(args, tier)?.generate_merged(&expand_inputs(&from)?)?;
+            let updated = update_component(&text, SpecFormat::from_path(&spec), &component, &schema)?;
+            fs::write(&spec, updated)?;
+            status!("Component {} updated in {:?}", component, spec);
+            Ok(())
+        }
+        Commands::Config { action: ConfigCommand::Schema } => {
+            println!("{}", serde_json::to_string_pretty(&config_schema())?);
+            Ok(())
         }
     }
+}
 
-    Ok(())
+fn expand_inputs(patterns: &[String]) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for pattern in patterns {
+        let mut matched = false;
+        for entry in glob(pattern)
+            .map_err(|e| AppError::InvalidPattern { pattern: pattern.clone(), message: format!("Invalid glob pattern: {}", e) })?
+        {
+            paths.push(entry.map_err(|e| AppError::IoError(e.into_error()))?);
+            matched = true;
+        }
+        if !matched {
+            return Err(AppError::FileNotFound(pattern.clone()).into());
+        }
+    }
+    Ok(paths)
 }
 
-fn process_single_file(input: &PathBuf, args: &Args) -> Result<()> {
-    info!("Processing input file: {:?}", input);
+// A pipeline for the subcommands that regenerate a schema from data, with the same config
+// and options as a plain run, and their own --tier over the global one
+fn regenerating_pipeline(args: &Args, tier: Option<TierChoice>) -> Result<Pipeline> {
+    let options = args.process_options()?;
+    Ok(Pipeline::new(ProcessOptions { tier: tier.or(options.tier.clone()), ..options }))
+}
 
-    if !input.exists() {
-        return Err(AppError::FileNotFound(input.display().to_string()).into());
+fn run_init(json: bool, interactive: bool, force: bool) -> Result<()> {
+    let path = PathBuf::from(if json { DEFAULT_JSON_FILE } else { DEFAULT_TOML_FILE });
+    if path.exists() && !force {
+        return Err(AppError::OutputExists(path.display().to_string()).into());
     }
 
-    let json_content = fs::read_to_string(input)
-        .with_context(|| format!("Failed to read input file: {:?}", input))?;
+    let mut config = Config::default();
+    if interactive {
+        let stdin = io::stdin();
+        let mut lines = stdin.lock().lines();
 
-    let json_value: serde_json::Value = serde_json::from_str(&json_content)
-        .map_err(|e| AppError::InvalidJson(e.to_string()))?;
+        let tier = prompt(&mut lines, "Tier (basic, standard, comprehensive, expert)", "standard")?;
+        config.default_tier = <SchemaOutputTier as clap::ValueEnum>::from_str(&tier, true)
+            .map_err(|_| AppError::InvalidOption(format!("Unknown tier: {}", tier)))?;
 
-    let schema = generate_schema(&json_value, &args.tier)?;
+        let draft = prompt(&mut lines, "Draft (draft-04, draft-06, draft-07, 2019-09, 2020-12)", "2020-12")?;
+        config.draft = <SchemaDraft as clap::ValueEnum>::from_str(&draft, true)
+            .map_err(|_| AppError::InvalidOption(format!("Unknown draft: {}", draft)))?;
 
-    if args.validate {
-        validate_schema(&schema)?;
+        let output_directory = prompt(&mut lines, "Output directory (blank to write next to inputs)", "")?;
+        if !output_directory.is_empty() {
+            config.output_directory = Some(PathBuf::from(output_directory));
+        }
     }
 
-    let output_path = match &args.output {
-        Some(path) => path.clone(),
-        None => {
-            let mut path = input.clone();
-            let stem = path.file_stem().unwrap_or_default();
-            let new_name = format!("{}.schema.json", stem.to_string_lossy());
-            path.set_file_name(new_name);
-            path
+    config.save_commented(&path)?;
+    status!("Config written to {:?}", path);
+    Ok(())
+}
+
+fn run_review(
+    pipeline: &Pipeline,
+    data: &[String],
+    against: &Path,
+    pretty: bool,
+    yes: bool,
+    update: bool,
+) -> Result<()> {
+    let mut regenerated = pipeline.generate_merged(&expand_inputs(data)?)?;
+
+    if against.exists() {
+        let existing = load_json(against)?;
+        if update {
+            regenerated = update_schema(&existing, &regenerated);
+            order_keys(&mut regenerated, pipeline.options().preserve_order);
+        }
+        let diff = diff_schemas(&existing, &regenerated);
+        if diff.is_empty() {
+            print!("{}", diff.render_text());
+            return Ok(());
+        }
+
+        if style::enabled() {
+            print!("{}", diff.render_colored());
+        } else {
+            print!("{}", diff.render_text());
         }
+
+        if !yes {
+            let stdin = io::stdin();
+            let mut lines = stdin.lock().lines();
+            let question = format!("Overwrite {}? (y/N)", against.display());
+            let answer = prompt(&mut lines, &question, "")?;
+            if !matches!(answer.to_lowercase().as_str(), "y" | "yes") {
+                notice!("Aborted, {:?} left unchanged", against);
+                return Ok(());
+            }
+        }
+    } else {
+        notice!("{:?} does not exist yet, writing a new schema", against);
+    }
+
+    write_json_output(&regenerated, Some(against), pretty)?;
+    status!("Schema generated successfully: {:?}", against);
+    Ok(())
+}
+
+fn prompt(lines: &mut impl Iterator<Item = io::Result<String>>, question: &str, default: &str) -> Result<String> {
+    if default.is_empty() {
+        print!("{}: ", question);
+    } else {
+        print!("{} [{}]: ", question, default);
+    }
+    io::stdout().flush()?;
+
+    let answer = lines.next().transpose()?.unwrap_or_default();
+    let answer = answer.trim();
+    Ok(if answer.is_empty() { default.to_string() } else { answer.to_string() })
+}
+
+fn write_json_output(value: &serde_json::Value, output: Option<&Path>, pretty: bool) -> Result<()> {
+    let content = if pretty {
+        serde_json::to_string_pretty(value)?
+    } else {
+        serde_json::to_string(value)?
     };
 
-    let schema_json = if args.pretty {
-        serde_json::to_string_pretty(&schema)?
+    match output {
+        Some(path) => fs::write(path, content)
+            .with_context(|| format!("Failed to write schema to file: {:?}", path))?,
+        None => println!("{}", content),
+    }
+
+    Ok(())
+}
+
+fn process_batch(input_pattern: &Path, args: &Args) -> Result<()> {
+    let pipeline = Pipeline::new(args.process_options()?);
+    let inputs = pipeline.glob_inputs(input_pattern)?;
+    process_files(&pipeline, inputs, args)
+}
+
+// Input paths listed one per line in `source`, or on stdin for `-`
+fn process_files_from(source: &Path, args: &Args) -> Result<()> {
+    let list = if source == Path::new("-") {
+        io::read_to_string(io::stdin()).context("Failed to read the file list from stdin")?
     } else {
-        serde_json::to_string(&schema)?
+        fs::read_to_string(source).with_context(|| format!("Failed to read file list: {:?}", source))?
     };
 
-    fs::write(&output_path, schema_json)
-        .with_context(|| format!("Failed to write schema to file: {:?}", output_path))?;
+    let pipeline = Pipeline::new(args.process_options()?);
+    let inputs = pipeline.listed_inputs(&list)?;
+    process_files(&pipeline, inputs, args)
+}
+
+fn process_files(pipeline: &Pipeline, inputs: BatchInputs, args: &Args) -> Result<()> {
+    let mut observer = BatchProgress {
+        progress: Progress::hidden(),
+        annotate: args.annotate,
+    };
+    let report = pipeline.process_batch(inputs, &mut observer);
+    observer.progress.finish();
+
+    let processed = report.reports.len();
+    status!("Processed {} files successfully", processed);
+    if !report.errors.is_empty() {
+        println!("{}", paint("Errors encountered:", Color::Red));
+        for error in &report.errors {
+            println!("  {}", paint(error, Color::Red));
+        }
+    }
+    print_batch_summary(processed, report.errors.len(), report.not_run(), report.ignored, report.elapsed);
+    print_memory_summary(&report);
+    if let Some(settings) = &args.vscode_settings {
+        write_vscode_settings(settings, &report.reports, args.dry_run)?;
+    }
+
+    if !report.errors.is_empty() && !args.keep_going {
+        return Err(AppError::BatchFailed(report.errors.len()).into());
+    }
+
+    Ok(())
+}
+
+/// Draws the progress bar and prints what each file produced as a batch goes
+struct BatchProgress {
+    progress: Progress,
+    annotate: Option<AnnotationFormat>,
+}
+
+impl BatchObserver for BatchProgress {
+    fn begin(&mut self, total: usize) {
+        self.progress = Progress::new(total);
+    }
+
+    fn started(&mut self, input: &Path) {
+        self.progress.start(input);
+    }
+
+    fn finished(&mut self, input: &Path, result: &Result<ProcessReport>, elapsed: Duration) {
+        if let Ok(report) = result {
+            print_report(report, &self.progress);
+        }
+        self.progress.finish_item(input, elapsed, result.is_ok());
+        if let (Err(e), Some(format)) = (result, self.annotate) {
+            self.progress.println(&format.reporter().render(&Annotation::from_error(input, e)));
+        }
+    }
+}
+
+fn print_report(report: &ProcessReport, progress: &Progress) {
+    let output = &report.output;
+    match output.outcome {
+        FileOutcome::DryRun => {
+            progress.notice(format_args!("Would write {:?}:", output.path));
+            progress.println(&highlight_json(&output.content));
+        }
+        FileOutcome::Written => progress.status(format_args!("Schema generated successfully: {:?}", output.path)),
+        FileOutcome::Unchanged => progress.notice(format_args!("Schema unchanged: {:?}", output.path)),
+        FileOutcome::Kept => progress.notice(format_args!("Schema left as it is: {:?}", output.path)),
+    }
+    if report.validation.is_some() {
+        progress.status(format_args!("Schema validation passed"));
+    }
+    if report.verification.is_some() {
+        progress.status(format_args!("Input matches its schema"));
+    }
+
+    if let Some(minified) = &report.minified {
+        print_companion(minified, "Minified schema", progress);
+    }
+    if let Some(source_map) = &report.source_map {
+        print_companion(source_map, "Source map", progress);
+    }
+    for patch in &report.patches {
+        print_companion(patch, "Patch", progress);
+    }
+    if let Some(golden) = &report.golden {
+        print_companion(golden, "Golden file", progress);
+    }
+    if let Some(injected) = &report.injected {
+        match injected.outcome {
+            FileOutcome::DryRun => progress.notice(format_args!("Would add $schema to {:?}", injected.path)),
+            FileOutcome::Written => progress.status(format_args!("$schema added to {:?}", injected.path)),
+            FileOutcome::Unchanged | FileOutcome::Kept => {
+                progress.notice(format_args!("$schema already set in {:?}", injected.path))
+            }
+        }
+    }
 
-    println!("Schema generated successfully: {:?}", output_path);
+    if let Some(explanation) = &report.explanation {
+        progress.println(explanation.render_text().trim_end());
+    }
+}
+
+// Local inputs only, since a URL has no place in the workspace
+fn write_vscode_settings(settings: &Path, reports: &[ProcessReport], dry_run: bool) -> Result<()> {
+    let workspace = workspace_root(settings);
+    let mappings: Vec<SchemaMapping> = reports
+        .iter()
+        .filter(|report| report.input.to_str().is_none_or(|location| !remote::is_url(location)))
+        .map(|report| SchemaMapping::new(&workspace, &report.input, &report.output.path))
+        .collect();
+    let existing = if settings.exists() { Some(load_json(settings)?) } else { None };
+    let content = serde_json::to_string_pretty(&vscode_settings(existing.as_ref(), &mappings))?;
 
+    if dry_run {
+        notice!("Would write {:?}:", settings);
+        println!("{}", highlight_json(&content));
+        return Ok(());
+    }
+    match write_output(settings, &content, WriteOptions { no_clobber: false, skip_unchanged: true })? {
+        WriteOutcome::Written => status!("VS Code settings updated: {:?}", settings),
+        WriteOutcome::Unchanged => notice!("VS Code settings unchanged: {:?}", settings),
+    }
     Ok(())
-}
\ No newline at end of file
+}
+
+fn print_companion(file: &OutputFile, what: &str, progress: &Progress) {
+    match file.outcome {
+        FileOutcome::DryRun => {
+            progress.notice(format_args!("Would write {:?}:", file.path));
+            progress.println(&highlight_json(&file.content));
+        }
+        FileOutcome::Written => progress.status(format_args!("{} generated successfully: {:?}", what, file.path)),
+        FileOutcome::Unchanged | FileOutcome::Kept => progress.notice(format_args!("{} unchanged: {:?}", what, file.path)),
+    }
+}
+
+fn annotate(format: Option<AnnotationFormat>, annotation: Annotation) {
+    if let Some(format) = format {
+        println!("{}", format.reporter().render(&annotation));
+    }
+}
+
+fn print_batch_summary(succeeded: usize, failed: usize, not_run: usize, ignored: usize, elapsed: Duration) {
+    let mut parts = vec![paint(&format!("{} succeeded", succeeded), Color::Green)];
+    if failed > 0 {
+        parts.push(paint(&format!("{} failed", failed), Color::Red));
+    }
+    if not_run > 0 {
+        parts.push(paint(&format!("{} not run", not_run), Color::Yellow));
+    }
+    if ignored > 0 {
+        parts.push(format!("{} ignored", ignored));
+    }
+    logging::print_line(format_args!("Summary: {} in {:.1?}", parts.join(", "), elapsed));
+}
+
+fn print_memory_summary(report: &BatchReport) {
+    let mut parts = Vec::new();
+    if let Some(kb) = report.peak_memory_kb {
+        parts.push(format!("peak {} KB", kb));
+    }
+    let interned = report.interned;
+    if interned.lookups > 0 {
+        parts.push(format!(
+            "{} distinct strings shared by {} lookups, {} KB not copied",
+            interned.strings,
+            interned.lookups,
+            interned.bytes_saved / 1024
+        ));
+    }
+    if !parts.is_empty() {
+        logging::print_line(format_args!("Memory: {}", parts.join(", ")));
+    }
+}
@@ -0,0 +1,216 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+use crate::diff::{diff_schemas, ChangeKind, SchemaChange};
+use crate::pointer;
+use crate::schema::keywords::is_annotation;
+
+const LOWER_BOUNDS: &[&str] = &[
+    "minimum",
+    "exclusiveMinimum",
+    "minLength",
+    "minItems",
+    "minProperties",
+    "minContains",
+];
+
+const UPPER_BOUNDS: &[&str] = &[
+    "maximum",
+    "exclusiveMaximum",
+    "maxLength",
+    "maxItems",
+    "maxProperties",
+    "maxContains",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Compatibility {
+    Compatible,
+    Breaking,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClassifiedChange {
+    #[serde(flatten)]
+    pub change: SchemaChange,
+    pub compatibility: Compatibility,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CompatReport {
+    pub changes: Vec<ClassifiedChange>,
+}
+
+impl CompatReport {
+    pub fn breaking_changes(&self) -> impl Iterator<Item = &ClassifiedChange> {
+        self.changes
+            .iter()
+            .filter(|c| c.compatibility == Compatibility::Breaking)
+    }
+
+    pub fn is_breaking(&self) -> bool {
+        self.breaking_changes().next().is_some()
+    }
+
+    pub fn render_text(&self) -> String {
+        let mut out = String::new();
+        for classified in &self.changes {
+            let label = match classified.compatibility {
+                Compatibility::Compatible => "compatible",
+                Compatibility::Breaking => "BREAKING",
+            };
+            out.push_str(&format!(
+                "[{}] {} ({})\n",
+                label,
+                classified.change.describe(),
+                classified.reason
+            ));
+        }
+
+        let breaking = self.breaking_changes().count();
+        out.push_str(&format!(
+            "{} change(s), {} breaking, {} compatible\n",
+            self.changes.len(),
+            breaking,
+            self.changes.len() - breaking
+        ));
+        out
+    }
+}
+
+pub fn check_compatibility(old: &Value, new: &Value) -> CompatReport {
+    let changes = diff_schemas(old, new)
+        .changes
+        .into_iter()
+        .map(|change| {
+            let (compatibility, reason) = classify(&change);
+            ClassifiedChange {
+                change,
+                compatibility,
+                reason: reason.to_string(),
+            }
+        })
+        .collect();
+
+    CompatReport { changes }
+}
+
+fn classify(change: &SchemaChange) -> (Compatibility, &'static str) {
+    use Compatibility::*;
+
+    let keyword = change.keyword.as_deref().unwrap_or("");
+    let is_annotation = is_annotation(keyword) || matches!(keyword, "$schema" | "$id");
+
+    match change.kind {
+        ChangeKind::PropertyAdded => (Compatible, "new optional property"),
+        ChangeKind::PropertyRemoved => {
+            if is_definition(&change.pointer) {
+                (Breaking, "definition removed")
+            } else {
+                (Breaking, "property removed")
+            }
+        }
+        ChangeKind::RequiredAdded => (Breaking, "new required field"),
+        ChangeKind::RequiredRemoved => (Compatible, "field became optional"),
+        ChangeKind::TypeChanged => {
+            let widened = match (type_set(change.old.as_ref()), type_set(change.new.as_ref())) {
+                (_, None) => true,
+                (None, Some(_)) => false,
+                (Some(old), Some(new)) => new.is_superset(&old),
+            };
+            if widened {
+                (Compatible, "type widened")
+            } else {
+                (Breaking, "type narrowed")
+            }
+        }
+        ChangeKind::KeywordAdded if is_annotation => (Compatible, "annotation added"),
+        ChangeKind::KeywordAdded => match (keyword, change.new.as_ref()) {
+            ("additionalProperties", Some(Value::Bool(true))) => {
+                (Compatible, "additional properties allowed")
+            }
+            ("uniqueItems", Some(Value::Bool(false))) => (Compatible, "no-op constraint added"),
+            _ => (Breaking, "constraint added"),
+        },
+        ChangeKind::KeywordRemoved if is_annotation => (Compatible, "annotation removed"),
+        ChangeKind::KeywordRemoved => (Compatible, "constraint removed"),
+        ChangeKind::KeywordChanged if is_annotation => (Compatible, "annotation changed"),
+        ChangeKind::KeywordChanged => classify_keyword_change(keyword, change),
+        ChangeKind::SchemaReplaced => match change.new.as_ref() {
+            Some(Value::Bool(true)) => (Compatible, "schema relaxed to accept anything"),
+            _ => (Breaking, "schema replaced"),
+        },
+    }
+}
+
+fn classify_keyword_change(keyword: &str, change: &SchemaChange) -> (Compatibility, &'static str) {
+    use Compatibility::*;
+
+    let old = change.old.as_ref();
+    let new = change.new.as_ref();
+    let old_num = old.and_then(Value::as_f64);
+    let new_num = new.and_then(Value::as_f64);
+
+    if LOWER_BOUNDS.contains(&keyword) {
+        return match (old_num, new_num) {
+            (Some(o), Some(n)) if n <= o => (Compatible, "lower bound relaxed"),
+            _ => (Breaking, "lower bound tightened"),
+        };
+    }
+
+    if UPPER_BOUNDS.contains(&keyword) {
+        return match (old_num, new_num) {
+            (Some(o), Some(n)) if n >= o => (Compatible, "upper bound relaxed"),
+            _ => (Breaking, "upper bound tightened"),
+        };
+    }
+
+    match keyword {
+        "enum" => {
+            let old_values = old.and_then(Value::as_array).cloned().unwrap_or_default();
+            let new_values = new.and_then(Value::as_array).cloned().unwrap_or_default();
+            if old_values.iter().all(|v| new_values.contains(v)) {
+                (Compatible, "enum widened")
+            } else {
+                (Breaking, "enum values removed")
+            }
+        }
+        "additionalProperties" => match new {
+            Some(Value::Bool(true)) => (Compatible, "additional properties allowed"),
+            _ => (Breaking, "additional properties restricted"),
+        },
+        "uniqueItems" => match new {
+            Some(Value::Bool(false)) => (Compatible, "uniqueness no longer enforced"),
+            _ => (Breaking, "uniqueness now enforced"),
+        },
+        _ => (Breaking, "constraint changed"),
+    }
+}
+
+// The types a `type` keyword allows; `None` when it is absent, which allows every type
+fn type_set(value: Option<&Value>) -> Option<BTreeSet<String>> {
+    let mut types: BTreeSet<String> = match value? {
+        Value::String(t) => std::iter::once(t.clone()).collect(),
+        Value::Array(ts) => ts
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => BTreeSet::new(),
+    };
+
+    // "number" accepts every integer, so it subsumes "integer" when comparing
+    if types.contains("number") {
+        types.insert("integer".to_string());
+    }
+    Some(types)
+}
+
+fn is_definition(path: &str) -> bool {
+    let parent = path.rsplit_once('/').map(|(p, _)| p).unwrap_or("");
+    let last = parent.rsplit('/').next().map(pointer::unescape_token);
+    matches!(last.as_deref(), Some("$defs") | Some("definitions"))
+}
@@ -0,0 +1,132 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::pointer;
+use crate::schema::keywords::for_each_subschema;
+
+const REF_KEYWORDS: &[&str] = &["$ref", "$dynamicRef", "$recursiveRef"];
+
+/// Size and shape of a schema, to catch pathological ones, e.g. a `oneOf` with thousands of
+/// branches, before they are published
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ComplexityReport {
+    /// Schemas, the root and every subschema
+    pub nodes: usize,
+    /// Subschema levels below the root
+    pub max_depth: usize,
+    pub deepest: String,
+    /// Branches over every `oneOf` and `anyOf`
+    pub branches: usize,
+    /// Branches of the largest `oneOf` or `anyOf`
+    pub max_branches: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub widest: Option<String>,
+    /// `$ref`, `$dynamicRef` and `$recursiveRef` keywords
+    pub refs: usize,
+    pub enums: usize,
+    /// Values over every `enum`
+    pub enum_values: usize,
+    /// Values of the largest `enum`
+    pub max_enum: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub largest_enum: Option<String>,
+    /// See [`ComplexityReport::score`]
+    pub score: usize,
+}
+
+/// Thresholds for [`ComplexityReport::violations`]; `None` leaves a metric unchecked
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ComplexityLimits {
+    pub max_nodes: Option<usize>,
+    pub max_depth: Option<usize>,
+    pub max_branches: Option<usize>,
+    pub max_refs: Option<usize>,
+    pub max_enum: Option<usize>,
+    pub max_score: Option<usize>,
+}
+
+impl ComplexityReport {
+    /// One point per node, two per `oneOf`/`anyOf` branch since validators may try each of
+    /// them, one per `$ref` and one per ten `enum` values
+    pub fn score(&self) -> usize {
+        self.nodes + 2 * self.branches + self.refs + self.enum_values.div_ceil(10)
+    }
+
+    /// A message for each metric above its threshold in `limits`
+    pub fn violations(&self, limits: &ComplexityLimits) -> Vec<String> {
+        let checks = [
+            ("nodes", self.nodes, limits.max_nodes, "--max-nodes"),
+            ("depth", self.max_depth, limits.max_depth, "--max-depth"),
+            ("largest oneOf/anyOf", self.max_branches, limits.max_branches, "--max-branches"),
+            ("$refs", self.refs, limits.max_refs, "--max-refs"),
+            ("largest enum", self.max_enum, limits.max_enum, "--max-enum"),
+            ("score", self.score, limits.max_score, "--max-score"),
+        ];
+        checks
+            .into_iter()
+            .filter_map(|(metric, value, limit, flag)| {
+                let limit = limit.filter(|limit| value > *limit)?;
+                Some(format!("{} {} is over {} {}", metric, value, flag, limit))
+            })
+            .collect()
+    }
+
+    pub fn render_text(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&format!("Nodes:              {}\n", self.nodes));
+        out.push_str(&format!("Max depth:          {} ({})\n", self.max_depth, pointer::display(&self.deepest)));
+        out.push_str(&format!("oneOf/anyOf:        {} branch(es)", self.branches));
+        if let Some(widest) = &self.widest {
+            out.push_str(&format!(", at most {} ({})", self.max_branches, pointer::display(widest)));
+        }
+        out.push('\n');
+        out.push_str(&format!("$refs:              {}\n", self.refs));
+        out.push_str(&format!("Enums:              {} with {} value(s)", self.enums, self.enum_values));
+        if let Some(largest) = &self.largest_enum {
+            out.push_str(&format!(", at most {} ({})", self.max_enum, pointer::display(largest)));
+        }
+        out.push('\n');
+        out.push_str(&format!("Complexity score:   {}\n", self.score));
+        out
+    }
+}
+
+pub fn measure_complexity(schema: &Value) -> ComplexityReport {
+    let mut report = ComplexityReport::default();
+    measure(schema, "", 0, &mut report);
+    report.score = report.score();
+    report
+}
+
+fn measure(schema: &Value, at: &str, depth: usize, report: &mut ComplexityReport) {
+    report.nodes += 1;
+    if depth > report.max_depth {
+        report.max_depth = depth;
+        report.deepest = at.to_string();
+    }
+    let Value::Object(obj) = schema else { return };
+
+    report.refs += REF_KEYWORDS.iter().filter(|keyword| obj.contains_key(**keyword)).count();
+    for keyword in ["oneOf", "anyOf"] {
+        if let Some(Value::Array(branches)) = obj.get(keyword) {
+            report.branches += branches.len();
+            if branches.len() > report.max_branches {
+                report.max_branches = branches.len();
+                report.widest = Some(pointer::join(at, keyword));
+            }
+        }
+    }
+    if let Some(Value::Array(values)) = obj.get("enum") {
+        report.enums += 1;
+        report.enum_values += values.len();
+        if values.len() > report.max_enum {
+            report.max_enum = values.len();
+            report.largest_enum = Some(pointer::join(at, "enum"));
+        }
+    }
+
+    for_each_subschema(obj, &mut |path, child| {
+        let child_at = path.iter().fold(at.to_string(), |pointer, token| pointer::join(&pointer, token));
+        measure(child, &child_at, depth + 1, report);
+    });
+}
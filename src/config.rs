@@ -1,17 +1,88 @@
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
 use serde::{Deserialize, Serialize};
 use anyhow::{Context, Result};
+use glob::{MatchOptions, Pattern};
 
-use crate::schema::SchemaOutputTier;
+use crate::schema::{SchemaDraft, SchemaOutputTier};
+use crate::config_schema::config_problems;
+use crate::constraints::ConstraintPolicy;
 use crate::error::AppError;
+use crate::infer::InferenceFamily;
+use crate::limits::ResourceLimits;
+use crate::overrides::FieldOverride;
+use crate::tiers::{CustomTier, TierChoice};
+
+pub const DEFAULT_TOML_FILE: &str = ".schema-jenerator.toml";
+pub const DEFAULT_JSON_FILE: &str = "schema-jenerator.json";
+
+/// Settings can also be given as `SCHEMA_JENERATOR_<KEY>` environment variables, which take
+/// precedence over config files
+pub const ENV_PREFIX: &str = "SCHEMA_JENERATOR_";
+
+const ENV_KEYS: &[&str] = &[
+    "default_tier",
+    "pretty_output",
+    "validate_schema",
+    "output_directory",
+    "file_extensions",
+    "draft",
+    "examples",
+    "locale",
+];
+
+const FIELD_DOCS: &[(&str, &str)] = &[
+    ("default_tier", "Schema tier used when --tier is not given: Basic, Standard, Comprehensive, Expert or the name of a [tiers] entry"),
+    ("pretty_output", "Pretty-print generated schemas"),
+    ("validate_schema", "Validate generated schemas against the JSON Schema meta-schema"),
+    ("output_directory", "Directory generated schemas are written to (defaults to next to each input)"),
+    ("file_extensions", "File extensions picked up when processing directories in batch mode"),
+    ("draft", "JSON Schema draft: draft-04, draft-06, draft-07, 2019-09 or 2020-12"),
+    ("examples", "Emit examples copied from the input data; set to false to keep values out of schemas"),
+    ("locale", "Language of generated titles and descriptions, e.g. \"de\", read from descriptions.<locale>.yaml; English when unset"),
+    ("infer", "Keyword families inferred even when the tier would not: formats, patterns, enums, bounds, examples"),
+    ("no_infer", "Keyword families never emitted"),
+    ("exclude", "Fields dropped from the input before inference, as data path globs like \"/debug\" or \"**/internal_*\""),
+    ("redact", "Fields whose examples, enum, const and default values are stripped, as data path globs"),
+    ("constraints", "Per-type keyword policies, e.g. [constraints.string] maxLength = { padding = 20 }; each keyword is \"tier\", \"omit\", \"observed\" or { padding = <percent> }"),
+    ("tiers", "Custom tiers selected with --tier <NAME>, e.g. [tiers.api]: base, required, additional_properties, infer, no_infer, examples"),
+    ("rules", "[[rules]] sections applying settings to inputs matching a pattern relative to the config file, e.g. pattern = \"events/**\""),
+    ("profile", "Named profiles selected with --profile, e.g. [profile.ci], each overriding any of the settings above"),
+    ("limits", "Caps for untrusted input, e.g. [limits] max_input_size = 10485760: max_input_size (bytes), max_nodes, max_depth, time_limit (milliseconds); inputs over a cap are inferred from a sample"),
+    ("fields", "Per-field overrides keyed by data path (\"/user/id\", \"/items/*/sku\") or property name pattern (\"*_at\"): type, format, description, required, title"),
+];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     pub default_tier: SchemaOutputTier,
     pub pretty_output: bool,
     pub validate_schema: bool,
     pub output_directory: Option<PathBuf>,
     pub file_extensions: Vec<String>,
+    pub draft: SchemaDraft,
+    pub examples: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub infer: Vec<InferenceFamily>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub no_infer: Vec<InferenceFamily>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub redact: Vec<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub constraints: ConstraintPolicy,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub tiers: BTreeMap<String, CustomTier>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, FieldOverride>,
+    #[serde(skip_serializing_if = "ResourceLimits::is_empty")]
+    pub limits: ResourceLimits,
+    /// The `[tiers]` entry named by `default_tier`, whose base `default_tier` then holds
+    #[serde(skip)]
+    pub custom_default_tier: Option<String>,
 }
 
 impl Default for Config {
@@ -22,34 +93,148 @@ impl Default for Config {
             validate_schema: false,
             output_directory: None,
             file_extensions: vec!["json".to_string()],
+            draft: SchemaDraft::default(),
+            examples: true,
+            locale: None,
+            infer: Vec::new(),
+            no_infer: Vec::new(),
+            exclude: Vec::new(),
+            redact: Vec::new(),
+            constraints: ConstraintPolicy::new(),
+            tiers: BTreeMap::new(),
+            fields: BTreeMap::new(),
+            limits: ResourceLimits::default(),
+            custom_default_tier: None,
         }
     }
 }
 
 impl Config {
-    pub fn load_from_file(path: &PathBuf) -> Result<Self> {
+    pub fn load_from_file(path: &Path) -> Result<Self> {
         if !path.exists() {
             return Ok(Self::default());
         }
 
-        let content = std::fs::read_to_string(path)
-            .with_context(|| format!("Failed to read config file: {:?}", path))?;
+        // Checked against the config schema like every other config that is loaded
+        let raw = load_raw(path, &[])?;
+        Self::from_value(raw).map_err(|message| invalid_config(path, message).into())
+    }
 
-        let config: Config = if path.extension().and_then(|ext| ext.to_str()) == Some("toml") {
-            toml::from_str(&content)
-                .map_err(|e| AppError::SchemaGeneration(format!("Invalid TOML config: {}", e)))?
-        } else {
-            serde_json::from_str(&content)
-                .map_err(|e| AppError::SchemaGeneration(format!("Invalid JSON config: {}", e)))?
-        };
+    /// Loads `path` like `load_from_file`, with the `[profile.<name>]` table merged over the
+    /// top-level settings when a profile is given, and then the `[[rules]]` matching `target`.
+    /// Unlike `load_from_file`, a missing `path` is an error, since it was asked for by name.
+    pub fn load_with_profile(path: &Path, target: Option<&Path>, profile: Option<&str>) -> Result<Self> {
+        if !path.exists() {
+            return Err(invalid_config(path, format!("Config file {:?} not found", path)).into());
+        }
+        let raw = load_raw(path, &[])?;
+        Self::from_raw(raw, target, profile)
+    }
 
+    /// Merges the config files found in `dir` and its ancestors, nearer files overriding
+    /// farther ones key by key, then applies `profile` and the rules matching `target`.
+    /// Rules from all files are kept, farther ones first.
+    pub fn discover(dir: &Path, target: Option<&Path>, profile: Option<&str>) -> Result<Self> {
+        let mut merged = serde_json::Value::Object(serde_json::Map::new());
+        let mut rules = Vec::new();
+        let mut tiers = Vec::new();
+        for path in discover_config_files(&absolute(dir)) {
+            let mut raw = load_raw(&path, &tiers)?;
+            if let Some(defined) = raw.get("tiers").and_then(serde_json::Value::as_object) {
+                tiers.extend(defined.keys().cloned());
+            }
+            if let Some(serde_json::Value::Array(file_rules)) = raw.as_object_mut().and_then(|obj| obj.remove("rules")) {
+                rules.extend(file_rules);
+            }
+            merge_raw(&mut merged, raw);
+        }
+        if let serde_json::Value::Object(obj) = &mut merged {
+            obj.insert("rules".to_string(), serde_json::Value::Array(rules));
+        }
+        Self::from_raw(merged, target, profile)
+    }
+
+    fn from_raw(mut raw: serde_json::Value, target: Option<&Path>, profile: Option<&str>) -> Result<Self> {
+        let profiles = raw.as_object_mut().and_then(|obj| obj.remove("profile"));
+        let rules = raw.as_object_mut().and_then(|obj| obj.remove("rules"));
+        if let Some(name) = profile {
+            let overlay = profiles.as_ref().and_then(|p| p.get(name)).cloned().ok_or_else(|| {
+                let defined: Vec<&String> = profiles
+                    .as_ref()
+                    .and_then(|p| p.as_object())
+                    .map(|p| p.keys().collect())
+                    .unwrap_or_default();
+                AppError::InvalidConfig {
+                    file: None,
+                    message: format!("Profile {:?} is not defined; config profiles: {:?}", name, defined),
+                }
+            })?;
+            merge_raw(&mut raw, overlay);
+        }
+        if let (Some(target), Some(serde_json::Value::Array(rules))) = (target, rules) {
+            let target = absolute(target);
+            for rule in rules {
+                if let Some(overlay) = matching_rule(rule, &target)? {
+                    merge_raw(&mut raw, overlay);
+                }
+            }
+        }
+        merge_raw(&mut raw, env_overlay());
+        Self::from_value(raw).map_err(|message| AppError::InvalidConfig { file: None, message }.into())
+    }
+
+    // `default_tier` may also name a `[tiers]` entry, which the field cannot hold: it gets
+    // that tier's base, and the name is kept for `resolve_tier`
+    fn from_value(mut raw: serde_json::Value) -> std::result::Result<Self, String> {
+        let custom = match raw.get("default_tier").and_then(serde_json::Value::as_str).map(str::parse) {
+            Some(Ok(TierChoice::Custom(name))) => Some(name),
+            _ => None,
+        };
+        if let (Some(_), Some(obj)) = (&custom, raw.as_object_mut()) {
+            obj.remove("default_tier");
+        }
+        let mut config: Config = serde_json::from_value(raw).map_err(|e| format!("Invalid config: {}", e))?;
+        if let Some(name) = custom {
+            let tier = config.tiers.get(&name).ok_or_else(|| {
+                format!(
+                    "default_tier {:?} is not one of basic, standard, comprehensive, expert or a config tier: {:?}",
+                    name,
+                    config.tiers.keys().collect::<Vec<_>>()
+                )
+            })?;
+            config.default_tier = tier.base.clone().unwrap_or(SchemaOutputTier::Standard);
+            config.custom_default_tier = Some(name);
+        }
         Ok(config)
     }
 
+    /// The built-in tier to generate with and the options of the chosen custom tier, which
+    /// are all unset for a built-in one
+    pub fn resolve_tier(&self, choice: Option<&TierChoice>) -> Result<(SchemaOutputTier, CustomTier)> {
+        match choice {
+            None => match &self.custom_default_tier {
+                Some(name) => self.resolve_tier(Some(&TierChoice::Custom(name.clone()))),
+                None => Ok((self.default_tier.clone(), CustomTier::default())),
+            },
+            Some(TierChoice::Builtin(tier)) => Ok((tier.clone(), CustomTier::default())),
+            Some(TierChoice::Custom(name)) => {
+                let custom = self.tiers.get(name).ok_or_else(|| {
+                    AppError::InvalidOption(format!(
+                        "Unknown tier {:?}; expected basic, standard, comprehensive, expert or a config tier: {:?}",
+                        name,
+                        self.tiers.keys().collect::<Vec<_>>()
+                    ))
+                })?;
+                let base = custom.base.clone().unwrap_or(SchemaOutputTier::Standard);
+                Ok((base, custom.clone()))
+            }
+        }
+    }
+
     pub fn save_to_file(&self, path: &PathBuf) -> Result<()> {
         let content = if path.extension().and_then(|ext| ext.to_str()) == Some("toml") {
             toml::to_string_pretty(self)
-                .map_err(|e| AppError::SchemaGeneration(format!("Failed to serialize config to TOML: {}", e)))?
+                .map_err(|e| invalid_config(path, format!("Failed to serialize config to TOML: {}", e)))?
         } else {
             serde_json::to_string_pretty(self)?
         };
@@ -60,9 +245,46 @@ impl Config {
         Ok(())
     }
 
+    pub fn save_commented(&self, path: &PathBuf) -> Result<()> {
+        self.save_to_file(path)?;
+
+        if path.extension().and_then(|ext| ext.to_str()) != Some("toml") {
+            return Ok(());
+        }
+
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {:?}", path))?;
+        let mut commented = String::from("# schema-jenerator configuration\n");
+        let mut seen = Vec::new();
+
+        for line in content.lines() {
+            let key = line.split('=').next().unwrap_or("").trim();
+            if let Some(doc) = field_doc(key) {
+                commented.push_str(&format!("\n# {}\n", doc));
+                seen.push(key.to_string());
+            }
+            commented.push_str(line);
+            commented.push('\n');
+        }
+
+        // TOML has no null, so unset optional fields are documented as commented-out examples
+        if !seen.iter().any(|key| key == "output_directory") {
+            commented.push_str(&format!(
+                "\n# {}\n# output_directory = \"schemas\"\n",
+                field_doc("output_directory").unwrap_or_default()
+            ));
+        }
+
+        std::fs::write(path, commented)
+            .with_context(|| format!("Failed to write config file: {:?}", path))?;
+
+        Ok(())
+    }
+
     pub fn merge_with_args(&mut self, tier: Option<SchemaOutputTier>, pretty: bool, validate: bool) {
         if let Some(t) = tier {
             self.default_tier = t;
+            self.custom_default_tier = None;
         }
         if pretty {
             self.pretty_output = true;
@@ -71,4 +293,146 @@ impl Config {
             self.validate_schema = true;
         }
     }
-}
\ No newline at end of file
+}
+
+/// Config files in `dir` and its ancestors, farthest first. A directory with both files
+/// contributes only the TOML one.
+pub fn discover_config_files(dir: &Path) -> Vec<PathBuf> {
+    let mut found: Vec<PathBuf> = dir
+        .ancestors()
+        .filter_map(|dir| {
+            [DEFAULT_TOML_FILE, DEFAULT_JSON_FILE]
+                .iter()
+                .map(|name| dir.join(name))
+                .find(|path| path.is_file())
+        })
+        .collect();
+    found.reverse();
+    found
+}
+
+fn load_raw(path: &Path, inherited_tiers: &[String]) -> Result<serde_json::Value> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {:?}", path))?;
+
+    let mut raw = if path.extension().and_then(|ext| ext.to_str()) == Some("toml") {
+        let table: toml::Value = toml::from_str(&content)
+            .map_err(|e| invalid_config(path, format!("Invalid TOML config {:?}: {}", path, e)))?;
+        serde_json::to_value(table)?
+    } else {
+        serde_json::from_str(&content)
+            .map_err(|e| invalid_config(path, format!("Invalid JSON config {:?}: {}", path, e)))?
+    };
+
+    let problems = config_problems(&raw, inherited_tiers);
+    if !problems.errors.is_empty() {
+        let message = format!("Invalid config {}: {}", path.display(), problems.errors.join("; "));
+        return Err(invalid_config(path, message).into());
+    }
+
+    anchor_rules(&mut raw, absolute(path).parent().unwrap_or(Path::new("")));
+    Ok(raw)
+}
+
+/// The settings given as environment variables; lists are comma-separated
+fn env_overlay() -> serde_json::Value {
+    let settings = ENV_KEYS
+        .iter()
+        .filter_map(|key| {
+            let value = std::env::var(format!("{}{}", ENV_PREFIX, key.to_uppercase())).ok()?;
+            let value = match (*key, value.to_lowercase().as_str()) {
+                ("file_extensions", _) => value.split(',').map(|ext| serde_json::Value::from(ext.trim())).collect(),
+                (_, "true" | "1" | "yes") => serde_json::Value::Bool(true),
+                (_, "false" | "0" | "no") => serde_json::Value::Bool(false),
+                _ => serde_json::Value::String(value),
+            };
+            Some((key.to_string(), value))
+        })
+        .collect();
+    serde_json::Value::Object(settings)
+}
+
+/// Makes the rule patterns in `raw` relative to `dir`, the directory of their config file
+fn anchor_rules(raw: &mut serde_json::Value, dir: &Path) {
+    let Some(serde_json::Value::Array(rules)) = raw.get_mut("rules") else { return };
+
+    for rule in rules {
+        if let Some(serde_json::Value::String(pattern)) = rule.get_mut("pattern") {
+            *pattern = format!("{}/{}", Pattern::escape(&dir.to_string_lossy()), pattern);
+        }
+    }
+}
+
+/// The settings of `rule` when its pattern matches `target`. Rules may say `tier` for
+/// `default_tier`.
+fn matching_rule(rule: serde_json::Value, target: &Path) -> Result<Option<serde_json::Value>> {
+    let serde_json::Value::Object(mut rule) = rule else {
+        return Err(AppError::InvalidConfig { file: None, message: "Config rules must be tables".to_string() }.into());
+    };
+    let pattern = match rule.remove("pattern") {
+        Some(serde_json::Value::String(pattern)) => pattern,
+        _ => return Err(AppError::InvalidConfig { file: None, message: "Config rule without a pattern".to_string() }.into()),
+    };
+    let pattern = Pattern::new(&pattern)
+        .map_err(|e| AppError::InvalidPattern { message: format!("Invalid rule pattern {}: {}", pattern, e), pattern })?;
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::new()
+    };
+    if !pattern.matches_path_with(target, options) {
+        return Ok(None);
+    }
+
+    if let Some(tier) = rule.remove("tier") {
+        rule.insert("default_tier".to_string(), tier);
+    }
+    Ok(Some(serde_json::Value::Object(rule)))
+}
+
+/// `path` made absolute against the current directory, without `.` components
+pub(crate) fn absolute(path: &Path) -> PathBuf {
+    let path = std::env::current_dir()
+        .map(|cwd| cwd.join(path))
+        .unwrap_or_else(|_| path.to_path_buf());
+    path.components().filter(|c| !matches!(c, Component::CurDir)).collect()
+}
+
+/// Tables are merged recursively; any other value in `overlay` replaces the one in `base`
+fn merge_raw(base: &mut serde_json::Value, overlay: serde_json::Value) {
+    match (base, overlay) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_raw(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Top-level keys in a raw config document that `Config` does not know about
+pub fn unknown_fields(raw: &serde_json::Value) -> Vec<String> {
+    raw.as_object()
+        .map(|obj| {
+            obj.keys()
+                .filter(|key| field_doc(key).is_none())
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub(crate) fn field_doc(name: &str) -> Option<&'static str> {
+    FIELD_DOCS
+        .iter()
+        .find(|(field, _)| *field == name)
+        .map(|(_, doc)| *doc)
+}
+
+fn invalid_config(path: &Path, message: String) -> AppError {
+    AppError::InvalidConfig { file: Some(path.display().to_string()), message }
+}
@@ -0,0 +1,255 @@
+use jsonschema::error::ValidationErrorKind;
+use jsonschema::JSONSchema;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeSet;
+
+use crate::config::field_doc;
+use crate::constraints::POLICY_KEYWORDS;
+use crate::limits::{MAX_INPUT_SIZE, MAX_NODES, TIME_LIMIT};
+use crate::schema::SchemaDraft;
+use crate::tiers::TierChoice;
+
+const TIERS: &[&str] = &[
+    "Basic", "Standard", "Comprehensive", "Expert", "basic", "standard", "comprehensive", "expert",
+];
+const INFERENCE_FAMILIES: &[&str] = &["formats", "patterns", "enums", "bounds", "examples"];
+const REQUIRED_MODES: &[&str] = &["none", "non-null", "all", "observed"];
+
+/// JSON Schema for `.schema-jenerator.toml` and `schema-jenerator.json`
+pub fn config_schema() -> Value {
+    let settings = settings_properties();
+
+    let mut root = settings.clone();
+    root.insert(
+        "profile".to_string(),
+        documented("profile", json!({ "type": "object", "additionalProperties": { "$ref": "#/$defs/settings" } })),
+    );
+    root.insert(
+        "rules".to_string(),
+        documented("rules", json!({ "type": "array", "items": { "$ref": "#/$defs/rule" } })),
+    );
+
+    let mut rule = settings.clone();
+    rule.insert(
+        "pattern".to_string(),
+        json!({ "type": "string", "description": "Glob over input paths, relative to the config file" }),
+    );
+    rule.insert(
+        "tier".to_string(),
+        json!({ "type": "string", "examples": TIERS, "description": "Same as default_tier" }),
+    );
+
+    json!({
+        "$schema": SchemaDraft::Draft202012.uri(),
+        "title": "schema-jenerator configuration",
+        "type": "object",
+        "properties": root,
+        "additionalProperties": false,
+        "$defs": {
+            "settings": { "type": "object", "properties": settings, "additionalProperties": false },
+            "rule": { "type": "object", "properties": rule, "required": ["pattern"], "additionalProperties": false },
+            "policy": {
+                "oneOf": [
+                    { "enum": ["tier", "omit", "observed"] },
+                    {
+                        "type": "object",
+                        "properties": { "padding": { "type": "number", "minimum": 0 } },
+                        "required": ["padding"],
+                        "additionalProperties": false
+                    }
+                ]
+            },
+            "inference": { "type": "array", "items": { "enum": INFERENCE_FAMILIES } },
+            "tier": {
+                "type": "object",
+                "properties": {
+                    "base": { "enum": TIERS },
+                    "required": { "enum": REQUIRED_MODES },
+                    "additional_properties": { "type": "boolean" },
+                    "infer": { "$ref": "#/$defs/inference" },
+                    "no_infer": { "$ref": "#/$defs/inference" },
+                    "examples": { "type": "boolean" }
+                },
+                "additionalProperties": false
+            },
+            "field": {
+                "type": "object",
+                "properties": {
+                    "type": { "enum": ["string", "number", "integer", "boolean", "object", "array", "null"] },
+                    "format": { "type": "string" },
+                    "description": { "type": "string" },
+                    "required": { "type": "boolean" },
+                    "title": { "type": "string" }
+                },
+                "additionalProperties": false
+            }
+        }
+    })
+}
+
+fn settings_properties() -> Map<String, Value> {
+    let drafts: Vec<&str> = SchemaDraft::ALL.iter().map(SchemaDraft::name).collect();
+    let strings = json!({ "type": "array", "items": { "type": "string" } });
+
+    [
+        // A built-in tier or one defined under `tiers`, which `config_problems` checks
+        ("default_tier", json!({ "type": "string", "examples": TIERS })),
+        ("pretty_output", json!({ "type": "boolean" })),
+        ("validate_schema", json!({ "type": "boolean" })),
+        ("output_directory", json!({ "type": ["string", "null"] })),
+        ("file_extensions", strings.clone()),
+        ("draft", json!({ "enum": drafts })),
+        ("examples", json!({ "type": "boolean" })),
+        ("locale", json!({ "type": "string" })),
+        ("infer", json!({ "$ref": "#/$defs/inference" })),
+        ("no_infer", json!({ "$ref": "#/$defs/inference" })),
+        ("exclude", strings.clone()),
+        ("redact", strings),
+        ("constraints", constraints_schema()),
+        ("tiers", json!({ "type": "object", "additionalProperties": { "$ref": "#/$defs/tier" } })),
+        ("fields", json!({ "type": "object", "additionalProperties": { "$ref": "#/$defs/field" } })),
+        ("limits", limits_schema()),
+    ]
+    .into_iter()
+    .map(|(name, schema)| (name.to_string(), documented(name, schema)))
+    .collect()
+}
+
+fn constraints_schema() -> Value {
+    let types: Map<String, Value> = POLICY_KEYWORDS
+        .iter()
+        .map(|(schema_type, keywords)| {
+            let keywords: Map<String, Value> = keywords
+                .iter()
+                .map(|keyword| (keyword.to_string(), json!({ "$ref": "#/$defs/policy" })))
+                .collect();
+            let schema = json!({ "type": "object", "properties": keywords, "additionalProperties": false });
+            (schema_type.to_string(), schema)
+        })
+        .collect();
+    json!({ "type": "object", "properties": types, "additionalProperties": false })
+}
+
+fn limits_schema() -> Value {
+    let cap = json!({ "type": "integer", "minimum": 0 });
+    let caps: Map<String, Value> = [MAX_INPUT_SIZE, MAX_NODES, "max_depth", TIME_LIMIT]
+        .iter()
+        .map(|name| (name.to_string(), cap.clone()))
+        .collect();
+    json!({ "type": "object", "properties": caps, "additionalProperties": false })
+}
+
+fn documented(name: &str, mut schema: Value) -> Value {
+    if let (Some(doc), Value::Object(obj)) = (field_doc(name), &mut schema) {
+        obj.insert("description".to_string(), Value::String(doc.to_string()));
+    }
+    schema
+}
+
+/// What is wrong with a raw config document
+#[derive(Debug, Default)]
+pub struct ConfigProblems {
+    /// Every problem found, e.g. ``unknown key `colour` `` or
+    /// `tiers.api.base: "Hyper" is not one of [...]`
+    pub errors: Vec<String>,
+}
+
+/// The problems of `raw`, a config file that may also select tiers defined by the farther
+/// config files it is merged over, named in `inherited_tiers`
+pub fn config_problems(raw: &Value, inherited_tiers: &[String]) -> ConfigProblems {
+    let mut problems = ConfigProblems::default();
+    let schema = config_schema();
+    let compiled = JSONSchema::compile(&schema).expect("config schema is valid");
+    check_tier_names(raw, inherited_tiers, &mut problems);
+    let Err(errors) = compiled.validate(raw) else { return problems };
+
+    for error in errors {
+        let path = error.instance_path.clone().into_vec();
+        match &error.kind {
+            ValidationErrorKind::AdditionalProperties { unexpected } if path.is_empty() => {
+                let known: Vec<&str> = schema["properties"]
+                    .as_object()
+                    .map(|properties| properties.keys().map(String::as_str).collect())
+                    .unwrap_or_default();
+                for key in unexpected {
+                    problems.errors.push(match closest(key, &known) {
+                        Some(suggestion) => format!("unknown key `{}`, did you mean `{}`?", key, suggestion),
+                        None => format!("unknown key `{}`", key),
+                    });
+                }
+            }
+            ValidationErrorKind::AdditionalProperties { unexpected } => {
+                for key in unexpected {
+                    problems.errors.push(format!("{}: unknown key `{}`", path.join("."), key));
+                }
+            }
+            _ if path.is_empty() => problems.errors.push(error.to_string()),
+            _ => problems.errors.push(format!("{}: {}", path.join("."), error)),
+        }
+    }
+    problems
+}
+
+// Tier names must be built in or defined under `tiers`, at the top level, in the same
+// profile or rule, or in a farther config file
+fn check_tier_names(raw: &Value, inherited: &[String], problems: &mut ConfigProblems) {
+    let defined_in = |settings: &Value| -> Vec<String> {
+        settings
+            .get("tiers")
+            .and_then(Value::as_object)
+            .map(|tiers| tiers.keys().cloned().collect())
+            .unwrap_or_default()
+    };
+    let mut places = vec![(String::new(), raw, &["default_tier"][..])];
+    if let Some(profiles) = raw.get("profile").and_then(Value::as_object) {
+        places.extend(profiles.iter().map(|(name, profile)| (format!("profile.{}.", name), profile, &["default_tier"][..])));
+    }
+    if let Some(rules) = raw.get("rules").and_then(Value::as_array) {
+        places.extend(rules.iter().enumerate().map(|(i, rule)| (format!("rules.{}.", i), rule, &["default_tier", "tier"][..])));
+    }
+
+    for (prefix, settings, keys) in places {
+        for key in keys {
+            let Some(Value::String(name)) = settings.get(*key) else { continue };
+            if matches!(name.parse(), Ok(TierChoice::Builtin(_))) {
+                continue;
+            }
+            let defined: BTreeSet<String> =
+                inherited.iter().cloned().chain(defined_in(raw)).chain(defined_in(settings)).collect();
+            if !defined.contains(name) {
+                problems.errors.push(format!(
+                    "{}{}: {:?} is not one of basic, standard, comprehensive, expert or a config tier: {:?}",
+                    prefix,
+                    key,
+                    name,
+                    defined.iter().collect::<Vec<_>>()
+                ));
+            }
+        }
+    }
+}
+
+// The known name a misspelled key is most likely meant to be, if any is close enough
+fn closest<'a>(key: &str, known: &[&'a str]) -> Option<&'a str> {
+    let limit = (key.chars().count() / 3).max(1);
+    known
+        .iter()
+        .map(|name| (edit_distance(key, name), *name))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
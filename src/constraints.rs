@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+use crate::samples::walk_with_samples;
+
+/// Keywords a policy can be given for, by the JSON type of the schema they appear in
+pub const POLICY_KEYWORDS: &[(&str, &[&str])] = &[
+    ("string", &["minLength", "maxLength"]),
+    ("integer", &["minimum", "maximum", "multipleOf"]),
+    ("number", &["minimum", "maximum"]),
+    ("array", &["minItems", "maxItems", "uniqueItems"]),
+    ("object", &["minProperties", "maxProperties"]),
+];
+
+/// How one keyword is emitted
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KeywordPolicy {
+    Mode(PolicyMode),
+    /// The observed bound widened by this percentage of itself, e.g. 20 for maxLength 12 → 15
+    Padded { padding: f64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyMode {
+    /// Whatever the tier emits
+    Tier,
+    /// Never emitted
+    Omit,
+    /// The smallest or largest value seen in the samples
+    Observed,
+}
+
+/// Policies by JSON type, then by keyword, from the `[constraints]` table of the config
+pub type ConstraintPolicy = BTreeMap<String, BTreeMap<String, KeywordPolicy>>;
+
+/// Applies `policy` to every schema in `schema`, using `data` as the samples it was generated from
+pub fn apply_constraints(schema: &mut Value, data: &Value, policy: &ConstraintPolicy) {
+    if policy.is_empty() {
+        return;
+    }
+
+    walk_with_samples(schema, &[data], &mut |obj, samples| {
+        let Some(keywords) = schema_type(obj).and_then(|t| policy.get(t)) else { return };
+
+        for (keyword, keyword_policy) in keywords {
+            match keyword_policy {
+                KeywordPolicy::Mode(PolicyMode::Tier) => {}
+                KeywordPolicy::Mode(PolicyMode::Omit) => {
+                    obj.remove(keyword);
+                }
+                KeywordPolicy::Mode(PolicyMode::Observed) => set_observed(obj, keyword, samples, 0.0),
+                KeywordPolicy::Padded { padding } => set_observed(obj, keyword, samples, *padding),
+            }
+        }
+    });
+}
+
+fn schema_type(obj: &Map<String, Value>) -> Option<&str> {
+    match obj.get("type")? {
+        Value::String(t) => Some(t),
+        Value::Array(types) => types.iter().filter_map(Value::as_str).find(|t| *t != "null"),
+        _ => None,
+    }
+}
+
+fn set_observed(obj: &mut Map<String, Value>, keyword: &str, samples: &[&Value], padding: f64) {
+    let values: Vec<f64> = samples
+        .iter()
+        .filter_map(|sample| match (keyword, sample) {
+            ("minLength" | "maxLength", Value::String(s)) => Some(s.chars().count() as f64),
+            ("minimum" | "maximum", Value::Number(n)) => n.as_f64(),
+            ("minItems" | "maxItems", Value::Array(items)) => Some(items.len() as f64),
+            ("minProperties" | "maxProperties", Value::Object(properties)) => Some(properties.len() as f64),
+            _ => None,
+        })
+        .collect();
+
+    let observed = match keyword {
+        "uniqueItems" => {
+            let unique = samples
+                .iter()
+                .filter_map(|s| s.as_array())
+                .all(|items| items.iter().enumerate().all(|(i, item)| !items[..i].contains(item)));
+            obj.insert(keyword.to_string(), Value::Bool(unique));
+            return;
+        }
+        "multipleOf" => return,
+        _ if values.is_empty() => return,
+        _ if keyword.starts_with("min") => {
+            let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+            min - min.abs() * padding / 100.0
+        }
+        _ => {
+            let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            max + max.abs() * padding / 100.0
+        }
+    };
+
+    // Counts and integer bounds are rounded outwards, so padding never tightens them
+    let value = match keyword {
+        "minimum" | "maximum" if schema_type(obj) == Some("number") => Value::from(observed),
+        "minimum" => Value::from(observed.floor() as i64),
+        _ if keyword.starts_with("min") => Value::from(observed.floor().max(0.0) as u64),
+        _ => Value::from(observed.ceil() as i64),
+    };
+    obj.insert(keyword.to_string(), value);
+}
@@ -0,0 +1,179 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::schema::walk::{for_each_schema_mut, for_each_schema_post_mut};
+use crate::schema::SchemaDraft;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Conversion {
+    pub schema: Value,
+    pub warnings: Vec<String>,
+}
+
+pub fn convert_schema(schema: &Value, from: SchemaDraft, to: SchemaDraft) -> Conversion {
+    let mut converted = schema.clone();
+    let warnings = convert_schema_in_place(&mut converted, from, to);
+    Conversion {
+        schema: converted,
+        warnings,
+    }
+}
+
+/// Like [`convert_schema`], converting `schema` where it is and returning the warnings
+pub fn convert_schema_in_place(schema: &mut Value, from: SchemaDraft, to: SchemaDraft) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    if from != to {
+        for_each_schema_post_mut(schema, &mut |obj| upgrade(obj, from));
+        for_each_schema_post_mut(schema, &mut |obj| downgrade(obj, to, &mut warnings));
+        rewrite_refs(schema, to);
+    }
+
+    if let Value::Object(root) = schema {
+        if root.contains_key("$schema") || from != to {
+            root.insert("$schema".to_string(), Value::String(to.uri().to_string()));
+        }
+    }
+
+    warnings.sort();
+    warnings.dedup();
+    warnings
+}
+
+fn upgrade(obj: &mut Map<String, Value>, from: SchemaDraft) {
+    if from == SchemaDraft::Draft04 {
+        rename(obj, "id", "$id");
+        for (bound, exclusive) in [("maximum", "exclusiveMaximum"), ("minimum", "exclusiveMinimum")] {
+            if obj.get(exclusive) == Some(&Value::Bool(true)) {
+                if let Some(limit) = obj.remove(bound) {
+                    obj.insert(exclusive.to_string(), limit);
+                }
+            } else if obj.get(exclusive).is_some_and(Value::is_boolean) {
+                obj.remove(exclusive);
+            }
+        }
+    }
+
+    if from < SchemaDraft::Draft201909 {
+        rename(obj, "definitions", "$defs");
+        if let Some(Value::Object(dependencies)) = obj.remove("dependencies") {
+            let mut required = Map::new();
+            let mut schemas = Map::new();
+            for (name, dependency) in dependencies {
+                if dependency.is_array() {
+                    required.insert(name, dependency);
+                } else {
+                    schemas.insert(name, dependency);
+                }
+            }
+            if !required.is_empty() {
+                obj.insert("dependentRequired".to_string(), Value::Object(required));
+            }
+            if !schemas.is_empty() {
+                obj.insert("dependentSchemas".to_string(), Value::Object(schemas));
+            }
+        }
+    }
+
+    if from < SchemaDraft::Draft202012 && obj.get("items").is_some_and(Value::is_array) {
+        let tuple = obj.remove("items").unwrap();
+        obj.insert("prefixItems".to_string(), tuple);
+        if let Some(rest) = obj.remove("additionalItems") {
+            obj.insert("items".to_string(), rest);
+        }
+    } else if from < SchemaDraft::Draft202012 {
+        // additionalItems is ignored unless items is an array, so it carries no meaning here
+        obj.remove("additionalItems");
+    }
+}
+
+fn downgrade(obj: &mut Map<String, Value>, to: SchemaDraft, warnings: &mut Vec<String>) {
+    if to < SchemaDraft::Draft202012 {
+        if let Some(tuple) = obj.remove("prefixItems") {
+            if let Some(rest) = obj.remove("items") {
+                obj.insert("additionalItems".to_string(), rest);
+            }
+            obj.insert("items".to_string(), tuple);
+        }
+        unsupported(obj, &["$dynamicRef", "$dynamicAnchor"], to, warnings);
+    }
+
+    if to < SchemaDraft::Draft201909 {
+        rename(obj, "$defs", "definitions");
+        let mut dependencies = Map::new();
+        for keyword in ["dependentRequired", "dependentSchemas"] {
+            if let Some(Value::Object(entries)) = obj.remove(keyword) {
+                dependencies.extend(entries);
+            }
+        }
+        if !dependencies.is_empty() {
+            obj.insert("dependencies".to_string(), Value::Object(dependencies));
+        }
+        unsupported(
+            obj,
+            &[
+                "unevaluatedProperties",
+                "unevaluatedItems",
+                "$anchor",
+                "$recursiveRef",
+                "$recursiveAnchor",
+                "minContains",
+                "maxContains",
+            ],
+            to,
+            warnings,
+        );
+    }
+
+    if to < SchemaDraft::Draft07 {
+        unsupported(obj, &["if", "then", "else"], to, warnings);
+    }
+
+    if to < SchemaDraft::Draft06 {
+        rename(obj, "$id", "id");
+        if let Some(value) = obj.remove("const") {
+            obj.insert("enum".to_string(), Value::Array(vec![value]));
+        }
+        for (bound, exclusive) in [("maximum", "exclusiveMaximum"), ("minimum", "exclusiveMinimum")] {
+            if let Some(limit) = obj.get(exclusive).filter(|v| v.is_number()).cloned() {
+                obj.insert(bound.to_string(), limit);
+                obj.insert(exclusive.to_string(), Value::Bool(true));
+            }
+        }
+        unsupported(obj, &["contains", "propertyNames"], to, warnings);
+    }
+}
+
+fn rewrite_refs(node: &mut Value, to: SchemaDraft) {
+    let (from_prefix, to_prefix) = if to.uses_defs() {
+        ("#/definitions/", "#/$defs/")
+    } else {
+        ("#/$defs/", "#/definitions/")
+    };
+
+    let mut rewrite = |obj: &mut Map<String, Value>| {
+        if let Some(Value::String(reference)) = obj.get_mut("$ref") {
+            if let Some(rest) = reference.strip_prefix(from_prefix) {
+                *reference = format!("{}{}", to_prefix, rest);
+            }
+        }
+    };
+    match node {
+        Value::Array(items) => items.iter_mut().for_each(|item| for_each_schema_mut(item, &mut rewrite)),
+        node => for_each_schema_mut(node, &mut rewrite),
+    }
+}
+
+fn rename(obj: &mut Map<String, Value>, from: &str, to: &str) {
+    if let Some(value) = obj.remove(from) {
+        obj.insert(to.to_string(), value);
+    }
+}
+
+fn unsupported(obj: &mut Map<String, Value>, keywords: &[&str], to: SchemaDraft, warnings: &mut Vec<String>) {
+    for keyword in keywords {
+        if obj.remove(*keyword).is_some() {
+            warnings.push(format!("'{}' is not supported by {} and was dropped", keyword, to));
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use regex::Regex;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::pointer;
+use crate::validation::Validator;
+
+/// The parts of a schema a dataset never reaches, and the parts of the dataset the schema does
+/// not describe
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CoverageReport {
+    pub documents: usize,
+    /// `oneOf`/`anyOf` branches no value matched
+    pub unused_branches: Vec<String>,
+    /// Properties that are not required and that no object had
+    pub unseen_properties: Vec<String>,
+    /// Object members that no `properties` or `patternProperties` describe
+    pub uncovered_fields: Vec<UncoveredField>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UncoveredField {
+    /// Path of the member in the data, with `*` for array elements, e.g. `/users/*/nickname`
+    pub path: String,
+    /// JSON Pointer of the object schema that does not describe it
+    pub schema: String,
+    pub occurrences: usize,
+    /// Whether `additionalProperties: false` rejects it
+    pub rejected: bool,
+}
+
+impl CoverageReport {
+    pub fn is_complete(&self) -> bool {
+        self.unused_branches.is_empty() && self.unseen_properties.is_empty() && self.uncovered_fields.is_empty()
+    }
+
+    pub fn render_text(&self) -> String {
+        let mut out = format!("Documents:          {}\n", self.documents);
+        if self.is_complete() {
+            out.push_str("\nThe dataset covers the whole schema, and the schema the whole dataset\n");
+            return out;
+        }
+
+        if !self.unused_branches.is_empty() {
+            out.push_str("\nUnused branches:\n");
+            for branch in &self.unused_branches {
+                out.push_str(&format!("  {}\n", branch));
+            }
+        }
+        if !self.unseen_properties.is_empty() {
+            out.push_str("\nOptional properties never seen:\n");
+            for property in &self.unseen_properties {
+                out.push_str(&format!("  {}\n", property));
+            }
+        }
+        if !self.uncovered_fields.is_empty() {
+            out.push_str("\nFields not in the schema:\n");
+            for field in &self.uncovered_fields {
+                out.push_str(&format!(
+                    "  {} in {} occurrence(s), at {}{}\n",
+                    field.path,
+                    field.occurrences,
+                    pointer::display(&field.schema),
+                    if field.rejected { ", rejected by additionalProperties: false" } else { "" }
+                ));
+            }
+        }
+        out
+    }
+}
+
+/// Follows every document of `documents` through `schema`. A value goes down each `oneOf` or
+/// `anyOf` branch it is valid against, which local `$ref`s to `$defs` and `definitions` of the
+/// root are resolved for; what is under a branch or property nothing reached is not reported
+/// again.
+pub fn measure_coverage(schema: &Value, documents: &[Value]) -> CoverageReport {
+    let mut walker = Walker { root: schema, following: Vec::new(), branches: HashMap::new(), report: Default::default() };
+    let values: Vec<&Value> = documents.iter().collect();
+    walker.cover(schema, "", "", &values);
+
+    let mut report = walker.report;
+    report.documents = documents.len();
+    report.unused_branches.sort();
+    report.unused_branches.dedup();
+    report.unseen_properties.sort();
+    report.unseen_properties.dedup();
+    report.uncovered_fields.sort_by(|a, b| a.path.cmp(&b.path).then(a.schema.cmp(&b.schema)));
+    report
+}
+
+struct Walker<'a> {
+    root: &'a Value,
+    /// `$ref`s being followed, so recursive schemas end
+    following: Vec<&'a str>,
+    /// A validator for each branch, `None` when the branch does not compile on its own
+    branches: HashMap<String, Option<Validator>>,
+    report: CoverageReport,
+}
+
+impl<'a> Walker<'a> {
+    fn cover(&mut self, schema: &'a Value, at: &str, data_at: &str, values: &[&Value]) {
+        let Value::Object(obj) = schema else { return };
+        if values.is_empty() {
+            return;
+        }
+
+        if let Some(Value::String(reference)) = obj.get("$ref") {
+            let target = reference.strip_prefix('#').and_then(|target| self.root.pointer(target));
+            if let Some(target) = target.filter(|_| !self.following.contains(&reference.as_str())) {
+                self.following.push(reference);
+                self.cover(target, reference.trim_start_matches('#'), data_at, values);
+                self.following.pop();
+            }
+        }
+
+        for keyword in ["oneOf", "anyOf"] {
+            let Some(Value::Array(branches)) = obj.get(keyword) else { continue };
+            let mut matched: Vec<Vec<&Value>> = vec![Vec::new(); branches.len()];
+            for value in values {
+                for (index, branch) in branches.iter().enumerate() {
+                    if self.accepts(branch, &pointer::join(&pointer::join(at, keyword), &index.to_string()), value) {
+                        matched[index].push(value);
+                        if keyword == "oneOf" {
+                            break;
+                        }
+                    }
+                }
+            }
+            for (index, (branch, values)) in branches.iter().zip(&matched).enumerate() {
+                let branch_at = pointer::join(&pointer::join(at, keyword), &index.to_string());
+                if values.is_empty() {
+                    self.report.unused_branches.push(branch_at);
+                } else {
+                    self.cover(branch, &branch_at, data_at, values);
+                }
+            }
+        }
+        if let Some(Value::Array(schemas)) = obj.get("allOf") {
+            for (index, subschema) in schemas.iter().enumerate() {
+                self.cover(subschema, &pointer::join(&pointer::join(at, "allOf"), &index.to_string()), data_at, values);
+            }
+        }
+
+        let objects: Vec<&Map<String, Value>> = values.iter().filter_map(|value| value.as_object()).collect();
+        if !objects.is_empty() {
+            self.cover_members(obj, at, data_at, &objects);
+        }
+
+        let arrays: Vec<&Vec<Value>> = values.iter().filter_map(|value| value.as_array()).collect();
+        if !arrays.is_empty() {
+            let item_path = format!("{}/*", data_at);
+            let prefix = match obj.get("prefixItems") {
+                Some(Value::Array(prefix)) => Some(("prefixItems", prefix)),
+                _ => match obj.get("items") {
+                    Some(Value::Array(prefix)) => Some(("items", prefix)),
+                    _ => None,
+                },
+            };
+            let prefix_len = prefix.map_or(0, |(_, prefix)| prefix.len());
+            if let Some((keyword, prefix)) = prefix {
+                for (index, item) in prefix.iter().enumerate() {
+                    let elements: Vec<&Value> = arrays.iter().filter_map(|array| array.get(index)).collect();
+                    self.cover(item, &pointer::join(&pointer::join(at, keyword), &index.to_string()), &item_path, &elements);
+                }
+            }
+            let rest = match obj.get("items") {
+                Some(items @ Value::Object(_)) => Some(("items", items)),
+                _ => obj.get("additionalItems").map(|items| ("additionalItems", items)),
+            };
+            if let Some((keyword, items)) = rest {
+                let elements: Vec<&Value> = arrays.iter().flat_map(|array| array.iter().skip(prefix_len)).collect();
+                self.cover(items, &pointer::join(at, keyword), &item_path, &elements);
+            }
+        }
+    }
+
+    fn cover_members(&mut self, obj: &'a Map<String, Value>, at: &str, data_at: &str, objects: &[&Map<String, Value>]) {
+        let properties = obj.get("properties").and_then(Value::as_object);
+        let required: Vec<&str> =
+            obj.get("required").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str).collect();
+        let patterns: Vec<(&String, Option<Regex>, &Value)> = obj
+            .get("patternProperties")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+            .map(|(pattern, schema)| (pattern, Regex::new(pattern).ok(), schema))
+            .collect();
+
+        for (name, property) in properties.into_iter().flatten() {
+            let property_at = pointer::join(&pointer::join(at, "properties"), name);
+            let children: Vec<&Value> = objects.iter().filter_map(|object| object.get(name)).collect();
+            if children.is_empty() && !required.contains(&name.as_str()) {
+                self.report.unseen_properties.push(property_at);
+            } else {
+                self.cover(property, &property_at, &pointer::join(data_at, name), &children);
+            }
+        }
+
+        let mut extra: BTreeMap<&String, Vec<&Value>> = BTreeMap::new();
+        for (name, value) in objects.iter().flat_map(|object| object.iter()) {
+            if properties.is_some_and(|properties| properties.contains_key(name)) {
+                continue;
+            }
+            let mut described = false;
+            for (pattern, regex, schema) in &patterns {
+                if regex.as_ref().is_some_and(|regex| regex.is_match(name)) {
+                    described = true;
+                    let pattern_at = pointer::join(&pointer::join(at, "patternProperties"), pattern);
+                    self.cover(schema, &pattern_at, &pointer::join(data_at, name), &[value]);
+                }
+            }
+            if !described {
+                extra.entry(name).or_default().push(value);
+            }
+        }
+
+        for (name, values) in extra {
+            match obj.get("additionalProperties") {
+                Some(additional @ Value::Object(_)) => {
+                    self.cover(additional, &pointer::join(at, "additionalProperties"), &pointer::join(data_at, name), &values);
+                }
+                additional => self.report.uncovered_fields.push(UncoveredField {
+                    path: pointer::join(data_at, name),
+                    schema: at.to_string(),
+                    occurrences: values.len(),
+                    rejected: additional == Some(&Value::Bool(false)),
+                }),
+            }
+        }
+    }
+
+    fn accepts(&mut self, branch: &Value, at: &str, value: &Value) -> bool {
+        let root = self.root;
+        let validator = self.branches.entry(at.to_string()).or_insert_with(|| {
+            let mut standalone = branch.clone();
+            if let (Value::Object(standalone), Value::Object(root)) = (&mut standalone, root) {
+                for keyword in ["$schema", "$defs", "definitions"] {
+                    if let (Some(value), false) = (root.get(keyword), standalone.contains_key(keyword)) {
+                        standalone.insert(keyword.to_string(), value.clone());
+                    }
+                }
+            }
+            Validator::new(&standalone).ok()
+        });
+        // A branch that cannot be checked on its own is counted as used, so it is not
+        // reported without evidence
+        validator.as_ref().is_none_or(|validator| validator.validate(value).is_valid())
+    }
+}
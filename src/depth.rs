@@ -0,0 +1,60 @@
+use serde_json::{Map, Value};
+
+pub const TRUNCATED_KEYWORD: &str = "x-truncated";
+
+/// Copies `value`, emptying every object and array nested `max_depth` levels deep so that
+/// schema generation never recurses past that depth
+pub fn truncate_input(value: &Value, max_depth: usize) -> Value {
+    match value {
+        Value::Object(_) if max_depth == 0 => Value::Object(Map::new()),
+        Value::Array(_) if max_depth == 0 => Value::Array(Vec::new()),
+        Value::Object(obj) => Value::Object(
+            obj.iter()
+                .map(|(key, child)| (key.clone(), truncate_input(child, max_depth - 1)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(|item| truncate_input(item, max_depth - 1)).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Replaces the subschemas generated from containers emptied by `truncate_input` with a
+/// permissive schema carrying an `x-truncated` marker
+pub fn mark_truncated(schema: &mut Value, data: &Value, max_depth: usize) {
+    if max_depth == 0 {
+        let kind = match data {
+            Value::Object(obj) if !obj.is_empty() => "object",
+            Value::Array(items) if !items.is_empty() => "array",
+            _ => return,
+        };
+        *schema = serde_json::json!({ "type": kind, TRUNCATED_KEYWORD: true });
+        return;
+    }
+
+    match data {
+        Value::Object(obj) => {
+            for (key, child) in obj {
+                if let Some(property) = schema.get_mut("properties").and_then(|p| p.get_mut(key)) {
+                    mark_truncated(property, child, max_depth - 1);
+                }
+            }
+        }
+        Value::Array(items) => match schema.get_mut("items") {
+            Some(Value::Object(item_schema)) if item_schema.contains_key("oneOf") => {
+                if let Some(Value::Array(branches)) = item_schema.get_mut("oneOf") {
+                    for (branch, item) in branches.iter_mut().zip(items) {
+                        mark_truncated(branch, item, max_depth - 1);
+                    }
+                }
+            }
+            // Homogeneous arrays are generated from their first element
+            Some(item_schema) => {
+                if let Some(first) = items.first() {
+                    mark_truncated(item_schema, first, max_depth - 1);
+                }
+            }
+            None => {}
+        },
+        _ => {}
+    }
+}
@@ -0,0 +1,299 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+
+use crate::pointer;
+use crate::style::{self, Color};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    PropertyAdded,
+    PropertyRemoved,
+    RequiredAdded,
+    RequiredRemoved,
+    TypeChanged,
+    KeywordAdded,
+    KeywordRemoved,
+    KeywordChanged,
+    SchemaReplaced,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SchemaChange {
+    pub pointer: String,
+    pub kind: ChangeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keyword: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SchemaDiff {
+    pub changes: Vec<SchemaChange>,
+}
+
+impl SchemaDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn render_text(&self) -> String {
+        self.render(false)
+    }
+
+    /// Like `render_text`, with additions in green, removals in red and changes in yellow
+    pub fn render_colored(&self) -> String {
+        self.render(true)
+    }
+
+    fn render(&self, color: bool) -> String {
+        if self.changes.is_empty() {
+            return "No differences found\n".to_string();
+        }
+
+        let mut out = String::new();
+        for change in &self.changes {
+            let line = change.describe();
+            let tint = match line.chars().next() {
+                Some('+') => Color::Green,
+                Some('-') => Color::Red,
+                _ => Color::Yellow,
+            };
+            out.push_str(&style::paint_if(color, &line, tint));
+            out.push('\n');
+        }
+        out.push_str(&format!("{} change(s)\n", self.changes.len()));
+        out
+    }
+}
+
+impl SchemaChange {
+    fn new(pointer: &str, kind: ChangeKind) -> Self {
+        Self {
+            pointer: pointer.to_string(),
+            kind,
+            keyword: None,
+            old: None,
+            new: None,
+        }
+    }
+
+    fn keyword(mut self, keyword: &str) -> Self {
+        self.keyword = Some(keyword.to_string());
+        self
+    }
+
+    fn values(mut self, old: Option<&Value>, new: Option<&Value>) -> Self {
+        self.old = old.cloned();
+        self.new = new.cloned();
+        self
+    }
+
+    pub fn describe(&self) -> String {
+        let location = pointer::display(&self.pointer);
+        let keyword = self.keyword.as_deref().unwrap_or("");
+        match self.kind {
+            ChangeKind::PropertyAdded => format!("+ {}: property added", location),
+            ChangeKind::PropertyRemoved => format!("- {}: property removed", location),
+            ChangeKind::RequiredAdded => format!("+ {}: now required", location),
+            ChangeKind::RequiredRemoved => format!("- {}: no longer required", location),
+            ChangeKind::TypeChanged => format!(
+                "~ {}: type changed from {} to {}",
+                location,
+                render_value(self.old.as_ref()),
+                render_value(self.new.as_ref())
+            ),
+            ChangeKind::KeywordAdded => format!(
+                "+ {}: {} added ({})",
+                location,
+                keyword,
+                render_value(self.new.as_ref())
+            ),
+            ChangeKind::KeywordRemoved => format!(
+                "- {}: {} removed (was {})",
+                location,
+                keyword,
+                render_value(self.old.as_ref())
+            ),
+            ChangeKind::KeywordChanged => format!(
+                "~ {}: {} changed from {} to {}",
+                location,
+                keyword,
+                render_value(self.old.as_ref()),
+                render_value(self.new.as_ref())
+            ),
+            ChangeKind::SchemaReplaced => format!(
+                "~ {}: schema replaced ({} -> {})",
+                location,
+                render_value(self.old.as_ref()),
+                render_value(self.new.as_ref())
+            ),
+        }
+    }
+}
+
+fn render_value(value: Option<&Value>) -> String {
+    match value {
+        Some(v) => v.to_string(),
+        None => "nothing".to_string(),
+    }
+}
+
+pub fn diff_schemas(old: &Value, new: &Value) -> SchemaDiff {
+    let mut diff = SchemaDiff::default();
+    diff_node("", old, new, &mut diff.changes);
+    diff
+}
+
+fn diff_node(path: &str, old: &Value, new: &Value, changes: &mut Vec<SchemaChange>) {
+    let (old_obj, new_obj) = match (old, new) {
+        (Value::Object(o), Value::Object(n)) => (o, n),
+        _ => {
+            if old != new {
+                changes.push(
+                    SchemaChange::new(path, ChangeKind::SchemaReplaced).values(Some(old), Some(new)),
+                );
+            }
+            return;
+        }
+    };
+
+    let keys: BTreeSet<&String> = old_obj.keys().chain(new_obj.keys()).collect();
+
+    for key in keys {
+        let old_value = old_obj.get(key.as_str());
+        let new_value = new_obj.get(key.as_str());
+
+        match key.as_str() {
+            "properties" | "patternProperties" | "$defs" | "definitions" => {
+                diff_schema_map(path, key, old_value, new_value, changes);
+            }
+            "required" => diff_required(path, old_value, new_value, changes),
+            "type" if old_value != new_value => changes.push(
+                SchemaChange::new(path, ChangeKind::TypeChanged).values(old_value, new_value),
+            ),
+            "items" | "additionalProperties" | "not" | "contains" | "propertyNames"
+                if matches!(
+                    (old_value, new_value),
+                    (Some(Value::Object(_)), Some(Value::Object(_)))
+                ) =>
+            {
+                diff_node(
+                    &pointer::join(path, key),
+                    old_value.unwrap(),
+                    new_value.unwrap(),
+                    changes,
+                );
+            }
+            "oneOf" | "anyOf" | "allOf" | "prefixItems" => {
+                diff_schema_list(path, key, old_value, new_value, changes);
+            }
+            _ => diff_keyword(path, key, old_value, new_value, changes),
+        }
+    }
+}
+
+fn diff_keyword(
+    path: &str,
+    keyword: &str,
+    old: Option<&Value>,
+    new: Option<&Value>,
+    changes: &mut Vec<SchemaChange>,
+) {
+    let kind = match (old, new) {
+        (None, Some(_)) => ChangeKind::KeywordAdded,
+        (Some(_), None) => ChangeKind::KeywordRemoved,
+        (Some(o), Some(n)) if o != n => ChangeKind::KeywordChanged,
+        _ => return,
+    };
+    changes.push(SchemaChange::new(path, kind).keyword(keyword).values(old, new));
+}
+
+fn diff_schema_map(
+    path: &str,
+    keyword: &str,
+    old: Option<&Value>,
+    new: Option<&Value>,
+    changes: &mut Vec<SchemaChange>,
+) {
+    let empty = Map::new();
+    let (old_map, new_map) = match (old, new) {
+        (Some(Value::Object(o)), Some(Value::Object(n))) => (o, n),
+        (Some(Value::Object(o)), None) => (o, &empty),
+        (None, Some(Value::Object(n))) => (&empty, n),
+        _ => return diff_keyword(path, keyword, old, new, changes),
+    };
+
+    let base = pointer::join(path, keyword);
+    let names: BTreeSet<&String> = old_map.keys().chain(new_map.keys()).collect();
+
+    for name in names {
+        let child = pointer::join(&base, name);
+        match (old_map.get(name.as_str()), new_map.get(name.as_str())) {
+            (Some(o), Some(n)) => diff_node(&child, o, n, changes),
+            (Some(o), None) => changes.push(
+                SchemaChange::new(&child, ChangeKind::PropertyRemoved).values(Some(o), None),
+            ),
+            (None, Some(n)) => changes.push(
+                SchemaChange::new(&child, ChangeKind::PropertyAdded).values(None, Some(n)),
+            ),
+            (None, None) => {}
+        }
+    }
+}
+
+fn diff_schema_list(
+    path: &str,
+    keyword: &str,
+    old: Option<&Value>,
+    new: Option<&Value>,
+    changes: &mut Vec<SchemaChange>,
+) {
+    match (old, new) {
+        (Some(Value::Array(o)), Some(Value::Array(n))) if o.len() == n.len() => {
+            let base = pointer::join(path, keyword);
+            for (index, (old_item, new_item)) in o.iter().zip(n.iter()).enumerate() {
+                diff_node(&pointer::join(&base, &index.to_string()), old_item, new_item, changes);
+            }
+        }
+        _ => diff_keyword(path, keyword, old, new, changes),
+    }
+}
+
+fn diff_required(
+    path: &str,
+    old: Option<&Value>,
+    new: Option<&Value>,
+    changes: &mut Vec<SchemaChange>,
+) {
+    let old_required = required_names(old);
+    let new_required = required_names(new);
+    let properties = pointer::join(path, "properties");
+
+    for name in new_required.difference(&old_required) {
+        changes.push(SchemaChange::new(&pointer::join(&properties, name), ChangeKind::RequiredAdded));
+    }
+    for name in old_required.difference(&new_required) {
+        changes.push(SchemaChange::new(
+            &pointer::join(&properties, name),
+            ChangeKind::RequiredRemoved,
+        ));
+    }
+}
+
+pub fn required_names(value: Option<&Value>) -> BTreeSet<String> {
+    value
+        .and_then(Value::as_array)
+        .map(|names| {
+            names
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
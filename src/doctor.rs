@@ -0,0 +1,229 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, DEFAULT_JSON_FILE, DEFAULT_TOML_FILE};
+use crate::config_schema::config_problems;
+
+const STRING_DETECTORS: &[&str] = &[
+    "format: email (contains '@' and '.')",
+    "format: uri (starts with \"http\")",
+    "pattern: digits, dashes and spaces",
+];
+
+const FEATURES: &[&str] = &["remote $ref fetching over http (built without TLS)"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DoctorReport {
+    pub version: String,
+    pub checks: Vec<Check>,
+    pub features: Vec<String>,
+    pub detectors: Vec<String>,
+}
+
+impl DoctorReport {
+    fn push(&mut self, name: &str, status: CheckStatus, detail: impl Into<String>) {
+        self.checks.push(Check {
+            name: name.to_string(),
+            status,
+            detail: detail.into(),
+        });
+    }
+
+    pub fn errors(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|c| c.status == CheckStatus::Error)
+            .count()
+    }
+
+    pub fn render_text(&self) -> String {
+        let mut out = format!("schema-jenerator {}\n\n", self.version);
+        for check in &self.checks {
+            let label = match check.status {
+                CheckStatus::Ok => "ok",
+                CheckStatus::Warning => "warn",
+                CheckStatus::Error => "error",
+            };
+            out.push_str(&format!("[{}] {}: {}\n", label, check.name, check.detail));
+        }
+
+        out.push_str("\nFeatures:\n");
+        for feature in &self.features {
+            out.push_str(&format!("  {}\n", feature));
+        }
+
+        out.push_str("\nDetectors:\n");
+        for detector in &self.detectors {
+            out.push_str(&format!("  {}\n", detector));
+        }
+
+        let warnings = self
+            .checks
+            .iter()
+            .filter(|c| c.status == CheckStatus::Warning)
+            .count();
+        out.push_str(&format!(
+            "\n{} check(s), {} warning(s), {} error(s)\n",
+            self.checks.len(),
+            warnings,
+            self.errors()
+        ));
+        out
+    }
+}
+
+/// Inspects the config file, output directory and input patterns the CLI would use.
+///
+/// Without an explicit `config_path` the default config files in the current directory
+/// are checked.
+pub fn run_diagnostics(config_path: Option<&Path>, patterns: &[String]) -> DoctorReport {
+    let mut report = DoctorReport {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        features: FEATURES.iter().map(|f| f.to_string()).collect(),
+        detectors: STRING_DETECTORS.iter().map(|d| d.to_string()).collect(),
+        ..Default::default()
+    };
+
+    let config = check_config(&mut report, config_path);
+    check_output_directory(&mut report, config.output_directory.as_deref());
+    check_patterns(&mut report, patterns);
+
+    report
+}
+
+fn check_config(report: &mut DoctorReport, explicit: Option<&Path>) -> Config {
+    let path = match explicit {
+        Some(path) => path.to_path_buf(),
+        None => match [DEFAULT_TOML_FILE, DEFAULT_JSON_FILE]
+            .iter()
+            .map(PathBuf::from)
+            .find(|p| p.exists())
+        {
+            Some(path) => path,
+            None => {
+                report.push("config", CheckStatus::Ok, "no config file found, using defaults");
+                return Config::default();
+            }
+        },
+    };
+
+    if !path.exists() {
+        report.push("config", CheckStatus::Error, format!("{} does not exist", path.display()));
+        return Config::default();
+    }
+
+    let raw = fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| parse_raw(&path, &content));
+    match raw {
+        Ok(raw) => {
+            let errors = config_problems(&raw, &[]).errors;
+            for error in &errors {
+                report.push("config", CheckStatus::Error, format!("{}: {}", path.display(), error));
+            }
+            if !errors.is_empty() {
+                return Config::default();
+            }
+        }
+        Err(e) => {
+            report.push("config", CheckStatus::Error, format!("{}: {}", path.display(), e));
+            return Config::default();
+        }
+    }
+
+    match Config::load_from_file(&path) {
+        Ok(config) => {
+            report.push("config", CheckStatus::Ok, format!("{} is valid", path.display()));
+            config
+        }
+        Err(e) => {
+            report.push("config", CheckStatus::Error, format!("{}: {}", path.display(), e));
+            Config::default()
+        }
+    }
+}
+
+fn parse_raw(path: &Path, content: &str) -> std::result::Result<Value, String> {
+    if path.extension().and_then(|ext| ext.to_str()) == Some("toml") {
+        let table: toml::Value = toml::from_str(content).map_err(|e| e.to_string())?;
+        serde_json::to_value(table).map_err(|e| e.to_string())
+    } else {
+        serde_json::from_str(content).map_err(|e| e.to_string())
+    }
+}
+
+fn check_output_directory(report: &mut DoctorReport, output_directory: Option<&Path>) {
+    let dir = output_directory.unwrap_or(Path::new("."));
+
+    if !dir.exists() {
+        report.push(
+            "output directory",
+            CheckStatus::Warning,
+            format!("{} does not exist yet", dir.display()),
+        );
+        return;
+    }
+    if !dir.is_dir() {
+        report.push(
+            "output directory",
+            CheckStatus::Error,
+            format!("{} is not a directory", dir.display()),
+        );
+        return;
+    }
+
+    // Permission bits don't account for ACLs or read-only mounts, so probe with a real write
+    let probe = dir.join(format!(".schema-jenerator-doctor-{}", std::process::id()));
+    match fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = fs::remove_file(&probe);
+            report.push(
+                "output directory",
+                CheckStatus::Ok,
+                format!("{} is writable", dir.display()),
+            );
+        }
+        Err(e) => report.push(
+            "output directory",
+            CheckStatus::Error,
+            format!("{} is not writable: {}", dir.display(), e),
+        ),
+    }
+}
+
+fn check_patterns(report: &mut DoctorReport, patterns: &[String]) {
+    for pattern in patterns {
+        match glob::glob(pattern) {
+            Ok(paths) => {
+                let count = paths.filter_map(|entry| entry.ok()).count();
+                if count == 0 {
+                    report.push("pattern", CheckStatus::Error, format!("{} matches no files", pattern));
+                } else {
+                    report.push(
+                        "pattern",
+                        CheckStatus::Ok,
+                        format!("{} matches {} file(s)", pattern, count),
+                    );
+                }
+            }
+            Err(e) => report.push("pattern", CheckStatus::Error, format!("{}: {}", pattern, e)),
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use serde_json::{json, Map, Value};
+use std::path::{Component, Path, PathBuf};
+
+use crate::config::absolute;
+use crate::spans::{line_indent, value_span};
+
+/// Files matched to a schema in the `json.schemas` setting of VS Code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaMapping {
+    /// Patterns relative to the workspace, e.g. `/data/users.json`
+    pub file_match: Vec<String>,
+    /// The schema relative to the workspace, e.g. `./data/users.schema.json`
+    pub url: String,
+}
+
+impl SchemaMapping {
+    /// Maps `input` to `schema`, both taken relative to `workspace`
+    pub fn new(workspace: &Path, input: &Path, schema: &Path) -> Self {
+        Self {
+            file_match: vec![format!("/{}", relative_path(workspace, input))],
+            url: format!("./{}", relative_path(workspace, schema)),
+        }
+    }
+}
+
+/// The reference `document` needs in `$schema` to point at `schema`, relative to its directory
+pub fn schema_reference(document: &Path, schema: &Path) -> String {
+    let dir = absolute(document).parent().map(Path::to_path_buf).unwrap_or_default();
+    let reference = relative_path(&dir, schema);
+    if reference.starts_with("..") {
+        reference
+    } else {
+        format!("./{}", reference)
+    }
+}
+
+/// `text` with its top-level `$schema` set to `reference`, replacing the one there or adding
+/// one as the first member, so the rest of the document keeps its formatting. `None` when the
+/// document is not an object.
+pub fn inject_schema_reference(text: &str, reference: &str) -> Option<String> {
+    let rendered = Value::String(reference.to_string()).to_string();
+    if let Some(span) = value_span(text, "/$schema") {
+        return Some(format!("{}{}{}", &text[..span.start], rendered, &text[span.end..]));
+    }
+
+    let root = value_span(text, "")?;
+    if !text[root.clone()].starts_with('{') {
+        return None;
+    }
+    let open = root.start + 1;
+    let body = &text[open..root.end - 1];
+    let member = format!("\"$schema\": {}", rendered);
+    let (at, inserted) = match body.find(|c: char| !c.is_whitespace()) {
+        None => (open, member),
+        // Laid out one member per line: the new one goes on its own line, indented like the next
+        Some(first) if body[..first].contains('\n') => (open, format!("\n{}{},", line_indent(text, open + first), member)),
+        Some(first) => (open + first, format!("{}, ", member)),
+    };
+    Some(format!("{}{}{}", &text[..at], inserted, &text[at..]))
+}
+
+/// The workspace a `settings.json` belongs to: the parent of its `.vscode` directory, or else
+/// its own directory
+pub fn workspace_root(settings: &Path) -> PathBuf {
+    let dir = absolute(settings).parent().map(Path::to_path_buf).unwrap_or_default();
+    match dir.file_name() {
+        Some(name) if name == ".vscode" => dir.parent().map(Path::to_path_buf).unwrap_or(dir),
+        _ => dir,
+    }
+}
+
+/// `existing` VS Code settings with `mappings` in `json.schemas`. An entry for the same schema
+/// is replaced, and every other setting and entry is kept.
+pub fn vscode_settings(existing: Option<&Value>, mappings: &[SchemaMapping]) -> Value {
+    let mut settings = match existing {
+        Some(Value::Object(settings)) => settings.clone(),
+        _ => Map::new(),
+    };
+    let schemas = settings.entry("json.schemas").or_insert_with(|| Value::Array(Vec::new()));
+    if !schemas.is_array() {
+        *schemas = Value::Array(Vec::new());
+    }
+    if let Value::Array(schemas) = schemas {
+        for mapping in mappings {
+            let entry = json!({ "fileMatch": mapping.file_match, "url": mapping.url });
+            match schemas.iter_mut().find(|schema| schema.get("url").and_then(Value::as_str) == Some(&mapping.url)) {
+                Some(existing) => *existing = entry,
+                None => schemas.push(entry),
+            }
+        }
+    }
+    Value::Object(settings)
+}
+
+// `to` relative to the directory `from`, with forward slashes
+fn relative_path(from: &Path, to: &Path) -> String {
+    let from = normalize(&absolute(from));
+    let to = normalize(&absolute(to));
+    let common = from.components().zip(to.components()).take_while(|(a, b)| a == b).count();
+
+    let ups = from.components().count() - common;
+    let mut parts: Vec<String> = vec!["..".to_string(); ups];
+    parts.extend(to.components().skip(common).map(|c| c.as_os_str().to_string_lossy().to_string()));
+    parts.join("/")
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
@@ -1,20 +1,266 @@
 
+use serde::Serialize;
 use thiserror::Error;
 
+/// Process exit codes, so wrapping scripts can branch on the kind of failure
+pub mod exit_code {
+    /// Any failure without a more specific code
+    pub const FAILURE: i32 = 1;
+    pub const INPUT_NOT_FOUND: i32 = 2;
+    pub const INVALID_JSON: i32 = 3;
+    pub const VALIDATION_FAILED: i32 = 4;
+    /// Some files in a batch failed
+    pub const PARTIAL_FAILURE: i32 = 5;
+    /// Invalid command-line arguments, as sysexits' `EX_USAGE`
+    pub const USAGE: i32 = 64;
+}
+
+/// Every failure the library reports. Each variant has a stable [`AppError::code`], and
+/// carries the file, JSON Pointer and line/column it concerns where there is one, so callers
+/// can branch on it instead of parsing messages.
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error("File not found: {0}")]
     FileNotFound(String),
 
-    #[error("Invalid JSON: {0}")]
-    InvalidJson(String),
+    #[error("Invalid JSON{}: {message}", in_file(file))]
+    InvalidJson {
+        file: Option<String>,
+        /// 1-based, where the parser stopped
+        line: Option<usize>,
+        column: Option<usize>,
+        message: String,
+    },
 
     #[error("Schema generation failed: {0}")]
     SchemaGeneration(String),
 
+    /// A schema, given or bundled, that cannot be compiled for validation
+    #[error("Invalid schema: {0}")]
+    InvalidSchema(String),
+
+    /// An OpenAPI document without the parts an update needs
+    #[error("Invalid OpenAPI spec: {0}")]
+    InvalidSpec(String),
+
+    /// A config file or profile that cannot be used
+    #[error("{message}")]
+    InvalidConfig { file: Option<String>, message: String },
+
+    /// A flag or option value, e.g. an unknown tier or a malformed output template
+    #[error("{0}")]
+    InvalidOption(String),
+
+    /// A glob, path filter or field selector that does not parse
+    #[error("{message}")]
+    InvalidPattern { pattern: String, message: String },
+
+    #[error("JSON pointer {pointer:?} does not exist in {file:?}")]
+    PointerNotFound { pointer: String, file: String },
+
+    #[error("{0}")]
+    ValidationFailed(String),
+
+    #[error("Breaking schema changes detected: {0} breaking change(s)")]
+    BreakingChanges(usize),
+
+    #[error("Schema drift detected: {0} difference(s) from the committed schema")]
+    SchemaDrift(usize),
+
+    /// The generated schema differs from the `--expect` golden file; `diff` lists the changes
+    #[error("Generated schema differs from {golden} in {changes} place(s):\n{diff}")]
+    GoldenMismatch { golden: String, changes: usize, diff: String },
+
+    #[error("Batch processing failed for {0} file(s)")]
+    BatchFailed(usize),
+
+    #[error("Doctor found {0} problem(s)")]
+    DiagnosticsFailed(usize),
+
+    #[error("Lint found {0} issue(s)")]
+    LintFailed(usize),
+
+    #[error("Schema exceeds {0} complexity threshold(s)")]
+    ComplexityExceeded(usize),
+
+    #[error("Refusing to overwrite existing file {0} (pass --force to overwrite)")]
+    OutputExists(String),
+
+    #[error("Remote fetch failed: {0}")]
+    RemoteFetch(String),
+
+    #[error("Unresolvable reference: {0}")]
+    UnresolvableRef(String),
+
+    /// A resource cap that could not be met by sampling, named as in the `[limits]` table
+    #[error("{message}")]
+    LimitExceeded { limit: &'static str, message: String },
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
     #[error("JSON error: {0}")]
     JsonError(#[from] serde_json::Error),
+
+    /// Work that panicked or was cancelled, which is a bug
+    #[error("Internal error: {0}")]
+    Internal(String),
+}
+
+fn in_file(file: &Option<String>) -> String {
+    file.as_ref().map(|file| format!(" in {}", file)).unwrap_or_default()
+}
+
+impl AppError {
+    /// Invalid JSON at the position `error` reports
+    pub fn invalid_json(error: &serde_json::Error) -> Self {
+        Self::json_syntax(None, error)
+    }
+
+    /// Invalid JSON read from `file`
+    pub fn invalid_json_in(file: impl Into<String>, error: &serde_json::Error) -> Self {
+        Self::json_syntax(Some(file.into()), error)
+    }
+
+    fn json_syntax(file: Option<String>, error: &serde_json::Error) -> Self {
+        // serde_json reports line 0 for errors that are not about a position in the text
+        let positioned = error.line() > 0;
+        AppError::InvalidJson {
+            file,
+            line: positioned.then(|| error.line()),
+            column: positioned.then(|| error.column()),
+            message: error.to_string(),
+        }
+    }
+
+    /// A machine-readable name for the kind of failure, stable across releases
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::FileNotFound(_) => "file-not-found",
+            AppError::InvalidJson { .. } => "invalid-json",
+            AppError::SchemaGeneration(_) => "schema-generation",
+            AppError::InvalidSchema(_) => "invalid-schema",
+            AppError::InvalidSpec(_) => "invalid-spec",
+            AppError::InvalidConfig { .. } => "invalid-config",
+            AppError::InvalidOption(_) => "invalid-option",
+            AppError::InvalidPattern { .. } => "invalid-pattern",
+            AppError::PointerNotFound { .. } => "pointer-not-found",
+            AppError::ValidationFailed(_) => "validation-failed",
+            AppError::BreakingChanges(_) => "breaking-changes",
+            AppError::SchemaDrift(_) => "schema-drift",
+            AppError::GoldenMismatch { .. } => "golden-mismatch",
+            AppError::BatchFailed(_) => "batch-failed",
+            AppError::DiagnosticsFailed(_) => "diagnostics-failed",
+            AppError::LintFailed(_) => "lint-failed",
+            AppError::ComplexityExceeded(_) => "complexity-exceeded",
+            AppError::OutputExists(_) => "output-exists",
+            AppError::RemoteFetch(_) => "remote-fetch",
+            AppError::UnresolvableRef(_) => "unresolvable-ref",
+            AppError::LimitExceeded { .. } => "limit-exceeded",
+            AppError::IoError(_) => "io",
+            AppError::JsonError(e) if e.is_syntax() || e.is_eof() => "invalid-json",
+            AppError::JsonError(_) => "json",
+            AppError::Internal(_) => "internal",
+        }
+    }
+
+    /// The file the failure concerns
+    pub fn file(&self) -> Option<&str> {
+        match self {
+            AppError::FileNotFound(file)
+            | AppError::OutputExists(file)
+            | AppError::PointerNotFound { file, .. }
+            | AppError::GoldenMismatch { golden: file, .. } => Some(file),
+            AppError::InvalidJson { file, .. } | AppError::InvalidConfig { file, .. } => file.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// JSON Pointer of the value the failure concerns
+    pub fn pointer(&self) -> Option<&str> {
+        match self {
+            AppError::PointerNotFound { pointer, .. } => Some(pointer),
+            _ => None,
+        }
+    }
+
+    /// 1-based line and column the failure concerns
+    pub fn position(&self) -> Option<(usize, usize)> {
+        match self {
+            AppError::InvalidJson { line: Some(line), column: Some(column), .. } => Some((*line, *column)),
+            AppError::JsonError(e) if e.line() > 0 => Some((e.line(), e.column())),
+            _ => None,
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            AppError::FileNotFound(_) => exit_code::INPUT_NOT_FOUND,
+            AppError::InvalidJson { .. } => exit_code::INVALID_JSON,
+            AppError::JsonError(e) if e.is_syntax() || e.is_eof() => exit_code::INVALID_JSON,
+            AppError::ValidationFailed(_) => exit_code::VALIDATION_FAILED,
+            AppError::BatchFailed(_) => exit_code::PARTIAL_FAILURE,
+            _ => exit_code::FAILURE,
+        }
+    }
+}
+
+/// The serializable form of an error, e.g. for `--log-format json`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorReport {
+    pub code: &'static str,
+    /// The message with its context chain, as printed by the CLI
+    pub message: String,
+    pub exit_code: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pointer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+}
+
+impl From<&AppError> for ErrorReport {
+    fn from(error: &AppError) -> Self {
+        ErrorReport {
+            code: error.code(),
+            message: error.to_string(),
+            exit_code: error.exit_code(),
+            file: error.file().map(str::to_string),
+            pointer: error.pointer().map(str::to_string),
+            line: error.position().map(|(line, _)| line),
+            column: error.position().map(|(_, column)| column),
+        }
+    }
+}
+
+impl From<&anyhow::Error> for ErrorReport {
+    /// Describes the first `AppError` in the chain, with the whole chain as the message
+    fn from(error: &anyhow::Error) -> Self {
+        let cause = error.chain().find_map(|cause| cause.downcast_ref::<AppError>());
+        let mut report = match cause {
+            Some(cause) => ErrorReport::from(cause),
+            None => ErrorReport {
+                code: "failure",
+                message: String::new(),
+                exit_code: exit_code::FAILURE,
+                file: None,
+                pointer: None,
+                line: None,
+                column: None,
+            },
+        };
+        report.message = format!("{:#}", error);
+        report
+    }
+}
+
+/// The exit code for an error returned by the CLI, taken from the first `AppError` in its chain
+pub fn exit_code_for(error: &anyhow::Error) -> i32 {
+    error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<AppError>())
+        .map_or(exit_code::FAILURE, AppError::exit_code)
 }
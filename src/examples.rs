@@ -0,0 +1,47 @@
+use serde_json::Value;
+
+use crate::schema::walk::for_each_schema_mut;
+
+/// Removes every `examples` keyword, so no raw input values end up in the schema
+pub fn strip_examples(schema: &mut Value) {
+    for_each_schema_mut(schema, &mut |obj| {
+        obj.remove("examples");
+    });
+}
+
+/// Example strings longer than this many characters are cut short
+pub const MAX_EXAMPLE_LENGTH: usize = 100;
+
+/// Marker appended to truncated example strings
+pub const ELLIPSIS: &str = "…";
+
+/// Keeps at most `limit` entries of each `examples` list and truncates long example strings
+pub fn limit_examples(schema: &mut Value, limit: Option<usize>) {
+    for_each_schema_mut(schema, &mut |obj| {
+        let Some(Value::Array(examples)) = obj.get_mut("examples") else { return };
+        if let Some(limit) = limit {
+            examples.truncate(limit);
+        }
+        for example in examples.iter_mut() {
+            truncate_strings(example);
+        }
+        if examples.is_empty() {
+            obj.remove("examples");
+        }
+    });
+}
+
+fn truncate_strings(value: &mut Value) {
+    let mut stack = vec![value];
+    while let Some(value) = stack.pop() {
+        match value {
+            Value::String(s) if s.chars().count() > MAX_EXAMPLE_LENGTH => {
+                let cut: String = s.chars().take(MAX_EXAMPLE_LENGTH).collect();
+                *s = format!("{}{}", cut, ELLIPSIS);
+            }
+            Value::Array(items) => stack.extend(items.iter_mut()),
+            Value::Object(obj) => stack.extend(obj.values_mut()),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,376 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+
+use crate::pointer;
+use crate::schema::keywords::for_each_subschema;
+
+// Keywords that only hold subschemas; their children are explained individually
+const STRUCTURAL_KEYWORDS: &[&str] = &["properties", "items", "$defs", "definitions"];
+
+/// Data pointers listed per subschema by [`annotate_sources`]; the rest are only counted
+const MAX_SOURCE_POINTERS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleKind {
+    /// Taken directly from the sample data
+    Observed,
+    /// Derived from the data by a guess that may not hold for unseen documents
+    Heuristic,
+    /// Emitted by the output tier regardless of the data
+    TierDefault,
+}
+
+impl RuleKind {
+    fn label(self) -> &'static str {
+        match self {
+            RuleKind::Observed => "observed",
+            RuleKind::Heuristic => "heuristic",
+            RuleKind::TierDefault => "tier default",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Explanation {
+    pub pointer: String,
+    pub keyword: String,
+    pub value: Value,
+    pub kind: RuleKind,
+    pub rule: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub samples: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExplainReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_samples: Option<usize>,
+    pub explanations: Vec<Explanation>,
+}
+
+impl ExplainReport {
+    pub fn render_text(&self) -> String {
+        let mut out = String::new();
+        let mut current = None;
+        for explanation in &self.explanations {
+            if current != Some(&explanation.pointer) {
+                out.push_str(&format!("{}\n", pointer::display(&explanation.pointer)));
+                current = Some(&explanation.pointer);
+            }
+            let samples = match (explanation.samples, self.total_samples) {
+                (Some(seen), Some(total)) => format!(" ({}/{} samples)", seen, total),
+                _ => String::new(),
+            };
+            out.push_str(&format!(
+                "  {}: {} [{}] {}{}\n",
+                explanation.keyword,
+                explanation.value,
+                explanation.kind.label(),
+                explanation.rule,
+                samples
+            ));
+        }
+
+        let heuristic = self
+            .explanations
+            .iter()
+            .filter(|e| e.kind == RuleKind::Heuristic)
+            .count();
+        out.push_str(&format!(
+            "{} keyword(s) explained, {} heuristic\n",
+            self.explanations.len(),
+            heuristic
+        ));
+        out
+    }
+}
+
+/// Attributes every keyword of a generated schema to the generator rule that emits it.
+///
+/// When `samples` is non-empty each explanation also records in how many of them the
+/// keyword's location was present.
+pub fn explain_schema(schema: &Value, samples: &[Value]) -> ExplainReport {
+    let instances: Vec<Instance> = samples
+        .iter()
+        .enumerate()
+        .map(|(sample, value)| Instance { sample, pointer: String::new(), value })
+        .collect();
+    let mut report = ExplainReport {
+        total_samples: (!samples.is_empty()).then_some(samples.len()),
+        explanations: Vec::new(),
+    };
+    explain_node(schema, "", &instances, !samples.is_empty(), &mut report.explanations);
+    report
+}
+
+/// A location in the sample data that a subschema describes
+#[derive(Debug, Clone)]
+struct Instance<'a> {
+    sample: usize,
+    /// JSON Pointer of the value within its sample
+    pointer: String,
+    value: &'a Value,
+}
+
+impl<'a> Instance<'a> {
+    fn child(&self, pointer: String, value: &'a Value) -> Self {
+        Instance { sample: self.sample, pointer, value }
+    }
+}
+
+/// Where a subschema was inferred from
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SourceTrace {
+    /// JSON Pointer of the subschema
+    pub pointer: String,
+    /// JSON Pointers of the values in the sample data it describes
+    pub sources: Vec<String>,
+    /// `keyword: rule` of each of its keywords a heuristic produced
+    pub heuristics: Vec<String>,
+}
+
+/// How [`annotate_sources`] records a subschema's origin
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum SourceAnnotation {
+    /// A `$comment` sentence
+    Comment,
+    /// An `x-source` object with `pointers`, `occurrences` and `heuristics`
+    #[cfg_attr(feature = "cli", value(name = "x-source"))]
+    XSource,
+}
+
+/// Traces every subschema of `schema`, generated from `document`, back to the values it
+/// describes. Subschemas no value reaches, such as `$defs` entries, are left out.
+pub fn trace_sources(schema: &Value, document: &Value) -> Vec<SourceTrace> {
+    let root = Instance { sample: 0, pointer: String::new(), value: document };
+    let mut traces = Vec::new();
+    trace_node(schema, "", &[root], &mut traces);
+    traces
+}
+
+/// Records on each subschema the data pointers it was inferred from and the heuristics that
+/// fired for it, for auditing inferred output
+pub fn annotate_sources(schema: &mut Value, document: &Value, annotation: SourceAnnotation) {
+    for trace in trace_sources(schema, document) {
+        let Some(Value::Object(obj)) = schema.pointer_mut(&trace.pointer) else { continue };
+        let listed = &trace.sources[..trace.sources.len().min(MAX_SOURCE_POINTERS)];
+        match annotation {
+            SourceAnnotation::Comment => {
+                let mut comment = format!(
+                    "Inferred from {}",
+                    listed.iter().map(|p| pointer::display(p)).collect::<Vec<_>>().join(", ")
+                );
+                if trace.sources.len() > listed.len() {
+                    comment.push_str(&format!(" and {} more", trace.sources.len() - listed.len()));
+                }
+                if !trace.heuristics.is_empty() {
+                    comment.push_str(&format!("; heuristics: {}", trace.heuristics.join("; ")));
+                }
+                obj.insert("$comment".to_string(), Value::String(comment));
+            }
+            SourceAnnotation::XSource => {
+                let source = json!({
+                    "pointers": listed,
+                    "occurrences": trace.sources.len(),
+                    "heuristics": trace.heuristics,
+                });
+                obj.insert("x-source".to_string(), source);
+            }
+        }
+    }
+}
+
+fn trace_node(schema: &Value, path: &str, instances: &[Instance], out: &mut Vec<SourceTrace>) {
+    let Value::Object(obj) = schema else { return };
+    if instances.is_empty() {
+        return;
+    }
+
+    let heuristics = obj
+        .iter()
+        .filter(|(keyword, _)| !STRUCTURAL_KEYWORDS.contains(&keyword.as_str()))
+        .filter_map(|(keyword, value)| match classify(keyword, value, obj, instances) {
+            (RuleKind::Heuristic, rule) => Some(format!("{}: {}", keyword, rule)),
+            _ => None,
+        })
+        .collect();
+    out.push(SourceTrace {
+        pointer: path.to_string(),
+        sources: instances.iter().map(|instance| instance.pointer.clone()).collect(),
+        heuristics,
+    });
+
+    for_each_subschema(obj, &mut |tokens, child| {
+        let child_path = tokens.iter().fold(path.to_string(), |p, t| pointer::join(&p, t));
+        let child_instances = descend(&tokens, child, instances);
+        trace_node(child, &child_path, &child_instances, out);
+    });
+}
+
+fn explain_node(
+    schema: &Value,
+    path: &str,
+    instances: &[Instance],
+    with_samples: bool,
+    out: &mut Vec<Explanation>,
+) {
+    let Value::Object(obj) = schema else { return };
+
+    let seen = with_samples.then(|| {
+        instances
+            .iter()
+            .map(|instance| instance.sample)
+            .collect::<BTreeSet<_>>()
+            .len()
+    });
+
+    for (keyword, value) in obj {
+        if STRUCTURAL_KEYWORDS.contains(&keyword.as_str()) {
+            continue;
+        }
+        let (kind, rule) = classify(keyword, value, obj, instances);
+        out.push(Explanation {
+            pointer: path.to_string(),
+            keyword: keyword.clone(),
+            value: value.clone(),
+            kind,
+            rule,
+            samples: seen,
+        });
+    }
+
+    for_each_subschema(obj, &mut |tokens, child| {
+        let child_path = tokens.iter().fold(path.to_string(), |p, t| pointer::join(&p, t));
+        let child_instances = descend(&tokens, child, instances);
+        explain_node(child, &child_path, &child_instances, with_samples, out);
+    });
+}
+
+fn descend<'a>(tokens: &[String], child: &Value, instances: &[Instance<'a>]) -> Vec<Instance<'a>> {
+    match tokens.first().map(String::as_str) {
+        Some("properties") => instances
+            .iter()
+            .filter_map(|instance| {
+                let value = instance.value.get(&tokens[1])?;
+                Some(instance.child(pointer::join(&instance.pointer, &tokens[1]), value))
+            })
+            .collect(),
+        Some("items") => instances
+            .iter()
+            .flat_map(|instance| {
+                let items = instance.value.as_array().into_iter().flatten().enumerate();
+                items.map(move |(index, item)| instance.child(format!("{}/{}", instance.pointer, index), item))
+            })
+            .collect(),
+        // A branch only describes the instances whose JSON type it accepts
+        Some("oneOf") | Some("anyOf") => instances
+            .iter()
+            .filter(|instance| accepts_type(child, instance.value))
+            .cloned()
+            .collect(),
+        Some("allOf") => instances.to_vec(),
+        _ => Vec::new(),
+    }
+}
+
+fn accepts_type(schema: &Value, value: &Value) -> bool {
+    let name = json_type(value);
+    match schema.get("type") {
+        Some(Value::String(t)) => t == name || (t == "number" && name == "integer"),
+        Some(Value::Array(ts)) => ts
+            .iter()
+            .any(|t| t == name || (t == "number" && name == "integer")),
+        _ => true,
+    }
+}
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Object(_) => "object",
+        Value::Array(_) => "array",
+        Value::String(_) => "string",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::Bool(_) => "boolean",
+        Value::Null => "null",
+    }
+}
+
+fn classify(
+    keyword: &str,
+    value: &Value,
+    obj: &serde_json::Map<String, Value>,
+    instances: &[Instance],
+) -> (RuleKind, String) {
+    use RuleKind::*;
+
+    let rule = |kind, text: &str| (kind, text.to_string());
+    match (keyword, value) {
+        ("$schema", _) => rule(TierDefault, "dialect declared by the comprehensive and expert tiers"),
+        ("type", Value::Array(_)) => rule(Observed, "union of the JSON types seen across samples"),
+        ("type", _) => rule(Observed, "JSON type of the sample value"),
+        ("required", _) => rule(Observed, "properties present in every sample"),
+        ("additionalProperties", Value::Bool(true)) => {
+            rule(TierDefault, "standard tier leaves objects open")
+        }
+        ("additionalProperties", Value::Bool(false)) => {
+            rule(TierDefault, "comprehensive and expert tiers close objects to unseen keys")
+        }
+        ("minProperties", _) => rule(TierDefault, "objects are assumed to be non-empty"),
+        ("minItems", v) if v == 0 => rule(TierDefault, "standard tier lower bound"),
+        ("minItems", _) => rule(Heuristic, "arrays are assumed to be non-empty"),
+        ("maxItems", _) => rule(Heuristic, "twice the observed array length"),
+        ("uniqueItems", _) => rule(TierDefault, "expert tier assumes array items are distinct"),
+        ("minLength", _) => rule(TierDefault, "strings may be empty"),
+        ("maxLength", _) => rule(Heuristic, "twice the observed string length"),
+        ("format", Value::String(f)) if f == "email" => {
+            rule(Heuristic, "format detector: value contains '@' and '.'")
+        }
+        ("format", Value::String(f)) if f == "uri" => {
+            rule(Heuristic, "format detector: value starts with \"http\"")
+        }
+        ("format", _) => rule(Heuristic, "format detector"),
+        ("pattern", _) => rule(Heuristic, "pattern detector: only digits, dashes and spaces"),
+        ("minimum", _) | ("maximum", _) => classify_bound(keyword, value, obj, instances),
+        ("multipleOf", _) => rule(TierDefault, "expert tier marks integers as whole multiples of 1"),
+        ("examples", _) => rule(Observed, "sample values"),
+        ("title", _) | ("description", _) => rule(TierDefault, "expert tier placeholder annotation"),
+        ("oneOf", _) => rule(Observed, "heterogeneous array: one branch per item type"),
+        ("anyOf", _) => rule(Observed, "samples disagreed on the shape at this location"),
+        _ => rule(Observed, "not produced by the generator; kept as written"),
+    }
+}
+
+fn classify_bound(
+    keyword: &str,
+    value: &Value,
+    obj: &serde_json::Map<String, Value>,
+    instances: &[Instance],
+) -> (RuleKind, String) {
+    let bound = value.as_f64();
+    let observed = instances.iter().filter_map(|instance| instance.value.as_f64());
+    let extreme = if keyword == "minimum" {
+        observed.fold(None, |acc: Option<f64>, v| Some(acc.map_or(v, |a| a.min(v))))
+    } else {
+        observed.fold(None, |acc: Option<f64>, v| Some(acc.map_or(v, |a| a.max(v))))
+    };
+
+    if extreme.is_some() && extreme == bound {
+        let which = if keyword == "minimum" { "smallest" } else { "largest" };
+        return (RuleKind::Observed, format!("{} value seen in samples", which));
+    }
+
+    // The comprehensive and expert tiers pad bounds by 1000 on either side of the sample
+    if obj.contains_key("minimum") && obj.contains_key("maximum") {
+        let direction = if keyword == "minimum" { "minus" } else { "plus" };
+        return (
+            RuleKind::Heuristic,
+            format!("observed value {} a fixed margin of 1000", direction),
+        );
+    }
+
+    (RuleKind::Observed, "sample value used as the lower bound".to_string())
+}
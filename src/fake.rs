@@ -0,0 +1,340 @@
+use serde_json::{Map, Number, Value};
+
+const MAX_DEPTH: usize = 8;
+const DEFAULT_MAX_ITEMS: usize = 3;
+const DEFAULT_MAX_LENGTH: usize = 12;
+const WORDS: &[&str] = &[
+    "alpha", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel", "india", "juliet",
+];
+
+pub struct FakeDataGenerator<'a> {
+    root: &'a Value,
+    rng: fastrand::Rng,
+}
+
+impl<'a> FakeDataGenerator<'a> {
+    pub fn new(root: &'a Value, seed: Option<u64>) -> Self {
+        let rng = match seed {
+            Some(seed) => fastrand::Rng::with_seed(seed),
+            None => fastrand::Rng::new(),
+        };
+        Self { root, rng }
+    }
+
+    pub fn generate(&mut self) -> Value {
+        self.generate_node(self.root, 0)
+    }
+
+    fn generate_node(&mut self, schema: &Value, depth: usize) -> Value {
+        let obj = match schema {
+            Value::Object(obj) => obj,
+            Value::Bool(true) => return Value::String(self.word()),
+            _ => return Value::Null,
+        };
+
+        if let Some(reference) = obj.get("$ref").and_then(Value::as_str) {
+            if depth < MAX_DEPTH {
+                if let Some(target) = self.resolve_ref(reference) {
+                    return self.generate_node(target, depth + 1);
+                }
+            }
+            return Value::Null;
+        }
+
+        if let Some(value) = obj.get("const") {
+            return value.clone();
+        }
+
+        if let Some(Value::Array(options)) = obj.get("enum") {
+            if !options.is_empty() {
+                return options[self.rng.usize(..options.len())].clone();
+            }
+        }
+
+        for keyword in ["oneOf", "anyOf"] {
+            if let Some(Value::Array(branches)) = obj.get(keyword) {
+                if !branches.is_empty() {
+                    let branch = &branches[self.rng.usize(..branches.len())];
+                    return self.generate_node(branch, depth + 1);
+                }
+            }
+        }
+
+        if let Some(Value::Array(branches)) = obj.get("allOf") {
+            let mut combined = obj.clone();
+            combined.remove("allOf");
+            for branch in branches.iter().filter_map(Value::as_object) {
+                for (key, value) in branch {
+                    match (combined.get_mut(key), value) {
+                        (Some(Value::Object(existing)), Value::Object(extra)) => {
+                            existing.extend(extra.clone());
+                        }
+                        (None, _) => {
+                            combined.insert(key.clone(), value.clone());
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            return self.generate_node(&Value::Object(combined), depth + 1);
+        }
+
+        match self.pick_type(obj).as_str() {
+            "object" => self.generate_object(obj, depth),
+            "array" => self.generate_array(obj, depth),
+            "integer" => self.generate_number(obj, true),
+            "number" => self.generate_number(obj, false),
+            "boolean" => Value::Bool(self.rng.bool()),
+            "null" => Value::Null,
+            _ => self.generate_string(obj),
+        }
+    }
+
+    fn pick_type(&mut self, obj: &Map<String, Value>) -> String {
+        match obj.get("type") {
+            Some(Value::String(t)) => t.clone(),
+            Some(Value::Array(types)) if !types.is_empty() => {
+                // Prefer concrete values over null so optional-looking fields still get data
+                let non_null: Vec<&Value> = types.iter().filter(|t| *t != "null").collect();
+                let pool = if non_null.is_empty() { types.iter().collect() } else { non_null };
+                pool[self.rng.usize(..pool.len())]
+                    .as_str()
+                    .unwrap_or("string")
+                    .to_string()
+            }
+            _ if obj.contains_key("properties") => "object".to_string(),
+            _ if obj.contains_key("items") || obj.contains_key("prefixItems") => "array".to_string(),
+            _ if obj.contains_key("minimum") || obj.contains_key("maximum") => "number".to_string(),
+            _ => "string".to_string(),
+        }
+    }
+
+    fn generate_object(&mut self, obj: &Map<String, Value>, depth: usize) -> Value {
+        let mut result = Map::new();
+        let required: Vec<&str> = obj
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|names| names.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        let min_properties = obj.get("minProperties").and_then(Value::as_u64).unwrap_or(0) as usize;
+
+        if let Some(Value::Object(properties)) = obj.get("properties") {
+            for (name, property) in properties {
+                let include = required.contains(&name.as_str())
+                    || result.len() < min_properties
+                    || (depth < MAX_DEPTH && self.rng.bool());
+                if include {
+                    let value = self.generate_node(property, depth + 1);
+                    result.insert(name.clone(), value);
+                }
+            }
+        }
+
+        for name in required {
+            if !result.contains_key(name) {
+                result.insert(name.to_string(), Value::String(self.word()));
+            }
+        }
+
+        Value::Object(result)
+    }
+
+    fn generate_array(&mut self, obj: &Map<String, Value>, depth: usize) -> Value {
+        let min_items = obj.get("minItems").and_then(Value::as_u64).unwrap_or(0) as usize;
+        let max_items = obj
+            .get("maxItems")
+            .and_then(Value::as_u64)
+            .map(|max| max as usize)
+            .unwrap_or(min_items.max(1) + DEFAULT_MAX_ITEMS - 1)
+            .max(min_items);
+        let max_items = if depth >= MAX_DEPTH { min_items } else { max_items };
+        let length = self.rng.usize(min_items..=max_items);
+        let unique = obj.get("uniqueItems") == Some(&Value::Bool(true));
+
+        let prefix: &[Value] = match obj.get("prefixItems").or_else(|| obj.get("items").filter(|i| i.is_array())) {
+            Some(Value::Array(prefix)) => prefix,
+            _ => &[],
+        };
+        let items = obj
+            .get("items")
+            .filter(|items| !items.is_array() && **items != Value::Bool(false));
+
+        let mut result: Vec<Value> = Vec::new();
+        for index in 0..length {
+            let schema = match (prefix.get(index), items) {
+                (Some(schema), _) => schema,
+                (None, Some(schema)) => schema,
+                (None, None) if prefix.is_empty() => {
+                    result.push(Value::String(self.word()));
+                    continue;
+                }
+                (None, None) => break,
+            };
+
+            let mut value = self.generate_node(schema, depth + 1);
+            let mut attempts = 0;
+            while unique && result.contains(&value) && attempts < 10 {
+                value = self.generate_node(schema, depth + 1);
+                attempts += 1;
+            }
+            if unique && result.contains(&value) {
+                break;
+            }
+            result.push(value);
+        }
+
+        Value::Array(result)
+    }
+
+    fn generate_number(&mut self, obj: &Map<String, Value>, integer: bool) -> Value {
+        let mut low = obj.get("minimum").and_then(Value::as_f64).unwrap_or(0.0);
+        let mut high = obj
+            .get("maximum")
+            .and_then(Value::as_f64)
+            .unwrap_or(low.max(0.0) + 1000.0);
+
+        // Non-integer values are rounded to two decimals, so step inside exclusive bounds by that much
+        let step_inside = if integer { 1.0 } else { 0.01 };
+        if let Some(exclusive) = obj.get("exclusiveMinimum").and_then(Value::as_f64) {
+            low = low.max(exclusive + step_inside);
+        }
+        if let Some(exclusive) = obj.get("exclusiveMaximum").and_then(Value::as_f64) {
+            high = high.min(exclusive - step_inside);
+        }
+        if high < low {
+            high = low;
+        }
+
+        if let Some(step) = obj.get("multipleOf").and_then(Value::as_f64).filter(|s| *s > 0.0) {
+            let first = (low / step).ceil() as i64;
+            let last = (high / step).floor() as i64;
+            let n = if last >= first { self.rng.i64(first..=last) } else { first };
+            let value = n as f64 * step;
+            return if integer || value.fract() == 0.0 && step.fract() == 0.0 {
+                Value::Number((value as i64).into())
+            } else {
+                Number::from_f64(value).map(Value::Number).unwrap_or(Value::Null)
+            };
+        }
+
+        if integer {
+            let (low, high) = (low.ceil() as i64, high.floor() as i64);
+            Value::Number(self.rng.i64(low..=high.max(low)).into())
+        } else {
+            let value = low + self.rng.f64() * (high - low);
+            let value = (value * 100.0).round() / 100.0;
+            Number::from_f64(value.clamp(low, high)).map(Value::Number).unwrap_or(Value::Null)
+        }
+    }
+
+    fn generate_string(&mut self, obj: &Map<String, Value>) -> Value {
+        let min_length = obj.get("minLength").and_then(Value::as_u64).unwrap_or(0) as usize;
+        let max_length = obj
+            .get("maxLength")
+            .and_then(Value::as_u64)
+            .map(|max| max as usize)
+            .unwrap_or(min_length.max(DEFAULT_MAX_LENGTH))
+            .max(min_length);
+
+        if let Some(format) = obj.get("format").and_then(Value::as_str) {
+            if let Some(value) = self.formatted(format) {
+                return Value::String(value);
+            }
+        }
+
+        if let Some(pattern) = obj.get("pattern").and_then(Value::as_str) {
+            if let Some(value) = self.matching_pattern(pattern, min_length, max_length) {
+                return Value::String(value);
+            }
+        }
+
+        let upper = max_length.min(min_length.max(DEFAULT_MAX_LENGTH));
+        let target = self.rng.usize(min_length.max(1).min(upper)..=upper);
+        let mut value = String::new();
+        while value.chars().count() < target {
+            if !value.is_empty() {
+                value.push(' ');
+            }
+            value.push_str(&self.word());
+        }
+        Value::String(value.chars().take(target).collect())
+    }
+
+    fn formatted(&mut self, format: &str) -> Option<String> {
+        let word = self.word();
+        let value = match format {
+            "email" => format!("{}{}@example.com", word, self.rng.u16(..1000)),
+            "uri" | "url" | "iri" => format!("https://example.com/{}", word),
+            "hostname" | "idn-hostname" => format!("{}.example.com", word),
+            "date" => format!(
+                "20{:02}-{:02}-{:02}",
+                self.rng.u8(..30),
+                self.rng.u8(1..=12),
+                self.rng.u8(1..=28)
+            ),
+            "time" => format!(
+                "{:02}:{:02}:{:02}Z",
+                self.rng.u8(..24),
+                self.rng.u8(..60),
+                self.rng.u8(..60)
+            ),
+            "date-time" => format!(
+                "20{:02}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+                self.rng.u8(..30),
+                self.rng.u8(1..=12),
+                self.rng.u8(1..=28),
+                self.rng.u8(..24),
+                self.rng.u8(..60),
+                self.rng.u8(..60)
+            ),
+            "uuid" => format!(
+                "{:08x}-{:04x}-4{:03x}-a{:03x}-{:012x}",
+                self.rng.u32(..),
+                self.rng.u16(..),
+                self.rng.u16(..0x1000),
+                self.rng.u16(..0x1000),
+                self.rng.u64(..0x1_0000_0000_0000)
+            ),
+            "ipv4" => format!(
+                "{}.{}.{}.{}",
+                self.rng.u8(1..=254),
+                self.rng.u8(..),
+                self.rng.u8(..),
+                self.rng.u8(1..=254)
+            ),
+            "ipv6" => format!("2001:db8::{:x}", self.rng.u16(1..)),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    fn matching_pattern(&mut self, pattern: &str, min_length: usize, max_length: usize) -> Option<String> {
+        let regex = regex::Regex::new(pattern).ok()?;
+        let length = self.rng.usize(min_length.max(1)..=max_length.max(min_length.max(1)).min(16));
+        let candidates = [
+            (0..length).map(|_| self.rng.digit(10)).collect::<String>(),
+            (0..length).map(|_| self.rng.lowercase()).collect::<String>(),
+            (0..length).map(|_| self.rng.alphanumeric()).collect::<String>(),
+            (0..length).map(|_| self.rng.uppercase()).collect::<String>(),
+        ];
+        candidates.into_iter().find(|candidate| regex.is_match(candidate))
+    }
+
+    fn resolve_ref(&self, reference: &str) -> Option<&'a Value> {
+        let fragment = reference.strip_prefix('#')?;
+        if fragment.is_empty() {
+            Some(self.root)
+        } else {
+            self.root.pointer(fragment)
+        }
+    }
+
+    fn word(&mut self) -> String {
+        WORDS[self.rng.usize(..WORDS.len())].to_string()
+    }
+}
+
+pub fn generate_fake_documents(schema: &Value, count: usize, seed: Option<u64>) -> Vec<Value> {
+    let mut generator = FakeDataGenerator::new(schema, seed);
+    (0..count).map(|_| generator.generate()).collect()
+}
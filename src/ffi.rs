@@ -0,0 +1,84 @@
+//! C functions for linking the generator from other languages, declared in
+//! `include/schema_jenerator.h`. Strings cross the boundary as NUL-terminated UTF-8.
+//!
+//! A function that fails returns a null pointer and keeps a message for
+//! [`sj_last_error`] on the calling thread.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+use anyhow::Result;
+
+use crate::bindings::generate_schema_json;
+use crate::error::AppError;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Generates the schema for the JSON document `json`, with `options` a JSON object as for the
+/// other bindings or null for the defaults. Returns the schema as JSON text, to be released
+/// with [`sj_free_string`], or null on failure.
+///
+/// # Safety
+///
+/// `json` must point to a NUL-terminated string, as must `options` unless it is null.
+#[no_mangle]
+pub unsafe extern "C" fn sj_generate_schema(json: *const c_char, options: *const c_char) -> *mut c_char {
+    let result = catch_unwind(AssertUnwindSafe(|| -> Result<String> {
+        let json = read_str(json, "json")?;
+        let options = if options.is_null() { None } else { Some(read_str(options, "options")?) };
+        generate_schema_json(json, options)
+    }));
+    let result = match result {
+        Ok(result) => result,
+        Err(_) => Err(AppError::Internal("The generator panicked".to_string()).into()),
+    };
+
+    match result.and_then(|schema| Ok(CString::new(schema)?)) {
+        Ok(schema) => {
+            set_last_error(None);
+            schema.into_raw()
+        }
+        Err(e) => {
+            set_last_error(Some(format!("{:#}", e)));
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Releases a string returned by [`sj_generate_schema`]; null is ignored
+///
+/// # Safety
+///
+/// `s` must be null or a pointer returned by this library and not yet released.
+#[no_mangle]
+pub unsafe extern "C" fn sj_free_string(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// The message of the last failure on this thread, or null if the last call succeeded. The
+/// pointer is owned by the library and valid until the next call on the same thread.
+#[no_mangle]
+pub extern "C" fn sj_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| error.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+unsafe fn read_str<'a>(s: *const c_char, name: &str) -> Result<&'a str> {
+    if s.is_null() {
+        return Err(AppError::InvalidOption(format!("{} is null", name)).into());
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map_err(|e| AppError::InvalidOption(format!("{} is not UTF-8: {}", name, e)).into())
+}
+
+fn set_last_error(message: Option<String>) {
+    // Input quoted in a message may contain NUL bytes
+    let message = message.map(|m| CString::new(m.replace('\0', "\u{FFFD}")).expect("NUL bytes were replaced"));
+    LAST_ERROR.with(|error| *error.borrow_mut() = message);
+}
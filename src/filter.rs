@@ -0,0 +1,146 @@
+use anyhow::Result;
+use glob::Pattern;
+use serde_json::{Map, Value};
+
+use crate::error::AppError;
+use crate::pointer;
+
+/// A glob over JSON Pointer segments; `*` matches one segment and `**` any number of them,
+/// including none, so `/metadata/**` covers `/metadata` itself. A glob may also start with
+/// `**` instead of `/`, e.g. `**/internal_*`.
+#[derive(Debug, Clone)]
+pub(crate) struct PointerGlob(Vec<Segment>);
+
+#[derive(Debug, Clone)]
+pub(crate) enum Segment {
+    AnyDepth,
+    Token(Pattern),
+}
+
+impl PointerGlob {
+    pub(crate) fn parse(glob: &str) -> Result<Self> {
+        let segments = glob
+            .strip_prefix('/')
+            .or_else(|| glob.starts_with("**").then_some(glob))
+            .ok_or_else(|| AppError::InvalidPattern {
+                pattern: glob.to_string(),
+                message: format!("Path filter must start with '/' or '**': {}", glob),
+            })?
+            .split('/')
+            .map(|segment| match segment {
+                "**" => Ok(Segment::AnyDepth),
+                _ => Pattern::new(&pointer::unescape_token(segment))
+                    .map(Segment::Token)
+                    .map_err(|e| AppError::InvalidPattern {
+                        pattern: glob.to_string(),
+                        message: format!("Invalid path filter {}: {}", glob, e),
+                    }),
+            })
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(Self(segments))
+    }
+
+    pub(crate) fn matches(&self, path: &[String]) -> bool {
+        matches(&self.0, path)
+    }
+
+    /// Whether some path below `path` could match
+    fn may_match_below(&self, path: &[String]) -> bool {
+        may_match_below(&self.0, path)
+    }
+}
+
+fn matches(segments: &[Segment], path: &[String]) -> bool {
+    match segments.split_first() {
+        None => path.is_empty(),
+        Some((Segment::AnyDepth, rest)) => (0..=path.len()).any(|skip| matches(rest, &path[skip..])),
+        Some((Segment::Token(pattern), rest)) => match path.split_first() {
+            Some((token, path)) => pattern.matches(token) && matches(rest, path),
+            None => false,
+        },
+    }
+}
+
+fn may_match_below(segments: &[Segment], path: &[String]) -> bool {
+    match (segments.split_first(), path.split_first()) {
+        (Some((Segment::AnyDepth, _)), _) => true,
+        (Some(_), None) => true,
+        (Some((Segment::Token(pattern), rest)), Some((token, path))) => {
+            pattern.matches(token) && may_match_below(rest, path)
+        }
+        (None, _) => false,
+    }
+}
+
+/// `--include` and `--exclude` filters over the JSON Pointers of the input document
+#[derive(Debug, Clone, Default)]
+pub struct PathFilter {
+    include: Vec<PointerGlob>,
+    exclude: Vec<PointerGlob>,
+}
+
+impl PathFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self> {
+        Ok(Self {
+            include: include.iter().map(|g| PointerGlob::parse(g)).collect::<Result<_>>()?,
+            exclude: exclude.iter().map(|g| PointerGlob::parse(g)).collect::<Result<_>>()?,
+        })
+    }
+
+    /// Copies `value` without the excluded subtrees and, when include filters are given,
+    /// without anything outside the included ones
+    pub fn apply(&self, value: &Value) -> Value {
+        let mut path = Vec::new();
+        self.filter(value, &mut path, self.include.is_empty())
+            .unwrap_or_else(|| empty_like(value))
+    }
+
+    fn filter(&self, value: &Value, path: &mut Vec<String>, included: bool) -> Option<Value> {
+        if !path.is_empty() && self.exclude.iter().any(|glob| glob.matches(path)) {
+            return None;
+        }
+        let included = included || self.include.iter().any(|glob| glob.matches(path));
+        if !included && !self.include.iter().any(|glob| glob.may_match_below(path)) {
+            return None;
+        }
+
+        let filtered = match value {
+            Value::Object(obj) => {
+                let mut kept = Map::new();
+                for (key, child) in obj {
+                    path.push(key.clone());
+                    if let Some(child) = self.filter(child, path, included) {
+                        kept.insert(key.clone(), child);
+                    }
+                    path.pop();
+                }
+                if !included && kept.is_empty() {
+                    return None;
+                }
+                Value::Object(kept)
+            }
+            Value::Array(items) => {
+                let mut kept = Vec::new();
+                for (index, item) in items.iter().enumerate() {
+                    path.push(index.to_string());
+                    kept.extend(self.filter(item, path, included));
+                    path.pop();
+                }
+                if !included && kept.is_empty() {
+                    return None;
+                }
+                Value::Array(kept)
+            }
+            _ if included => value.clone(),
+            _ => return None,
+        };
+        Some(filtered)
+    }
+}
+
+fn empty_like(value: &Value) -> Value {
+    match value {
+        Value::Array(_) => Value::Array(Vec::new()),
+        _ => Value::Object(Map::new()),
+    }
+}
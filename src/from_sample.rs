@@ -0,0 +1,89 @@
+//! Schemas for Rust types, inferred from a sample value, and a cross-check against schemas
+//! derived from the type definitions themselves, e.g. by schemars.
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::diff::{diff_schemas, SchemaDiff};
+use crate::generator::{GeneratorOptions, SchemaGenerator};
+
+// Local `$ref`s nested deeper than this are left unresolved, which stops recursive types
+const MAX_REF_DEPTH: usize = 32;
+
+/// Serializes `sample` and generates the schema for it, as for JSON input
+///
+/// ```
+/// use schema_jenerator::from_sample::generate_schema_for;
+///
+/// #[derive(serde::Serialize)]
+/// struct User {
+///     id: u64,
+///     tags: Vec<String>,
+/// }
+///
+/// let user = User { id: 7, tags: vec!["admin".to_string()] };
+/// let schema = generate_schema_for(&user, Default::default()).unwrap();
+/// assert_eq!(schema["properties"]["id"]["type"], "integer");
+/// ```
+pub fn generate_schema_for<T: Serialize + ?Sized>(sample: &T, options: GeneratorOptions) -> Result<Value> {
+    SchemaGenerator::new(options).generate_for(sample)
+}
+
+impl SchemaGenerator {
+    /// Like [`SchemaGenerator::generate`], for any serializable value
+    pub fn generate_for<T: Serialize + ?Sized>(&self, sample: &T) -> Result<Value> {
+        self.generate(&serde_json::to_value(sample)?)
+    }
+}
+
+/// Where an inferred schema and one derived from the type definition disagree on structure:
+/// types, properties, `required` and array items. Everything else, such as bounds, formats
+/// and annotations, is ignored, as is any `$schema`. Local `$ref`s of `derived`, e.g. to its
+/// `definitions`, are resolved first.
+///
+/// The diff goes from `derived` to `inferred`, so a `PropertyRemoved` is a field of the type
+/// the samples never had.
+pub fn cross_check(inferred: &Value, derived: &Value) -> SchemaDiff {
+    diff_schemas(&structure(derived, derived, 0), &structure(inferred, inferred, 0))
+}
+
+fn structure(node: &Value, root: &Value, depth: usize) -> Value {
+    let Value::Object(obj) = node else { return node.clone() };
+
+    if let Some(Value::String(reference)) = obj.get("$ref") {
+        let target = reference.strip_prefix('#').and_then(|pointer| root.pointer(pointer));
+        if let (Some(target), true) = (target, depth < MAX_REF_DEPTH) {
+            return structure(target, root, depth + 1);
+        }
+    }
+    // schemars wraps a documented field's `$ref` as `allOf: [{"$ref": ...}]`
+    if let Some(Value::Array(branches)) = obj.get("allOf") {
+        if branches.len() == 1 && !obj.contains_key("type") {
+            return structure(&branches[0], root, depth);
+        }
+    }
+
+    let mut kept = Map::new();
+    for (keyword, value) in obj {
+        let value = match (keyword.as_str(), value) {
+            ("type", _) | ("required", _) => value.clone(),
+            ("properties", Value::Object(properties)) => Value::Object(
+                properties
+                    .iter()
+                    .map(|(name, schema)| (name.clone(), structure(schema, root, depth)))
+                    .collect(),
+            ),
+            ("items", _) => match value {
+                Value::Array(items) => Value::Array(items.iter().map(|s| structure(s, root, depth)).collect()),
+                _ => structure(value, root, depth),
+            },
+            ("anyOf" | "oneOf" | "allOf", Value::Array(branches)) => {
+                Value::Array(branches.iter().map(|s| structure(s, root, depth)).collect())
+            }
+            _ => continue,
+        };
+        kept.insert(keyword.clone(), value);
+    }
+    Value::Object(kept)
+}
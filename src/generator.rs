@@ -0,0 +1,523 @@
+use anyhow::Result;
+use tracing::{field, info_span, warn, Span};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::additional::apply_additional_properties;
+use crate::constraints::{apply_constraints, ConstraintPolicy};
+use crate::convert::{convert_schema, convert_schema_in_place};
+use crate::depth::{mark_truncated, truncate_input};
+use crate::error::AppError;
+use crate::examples::{limit_examples, strip_examples};
+use crate::infer::{apply_inference, InferenceFamily};
+use crate::limits;
+use crate::locale::{localize, Locale};
+use crate::merge::merge_schemas;
+use crate::metadata::{apply_metadata, SchemaMetadata};
+use crate::normalize::{sort_branches, sort_keys_in_place, sort_keywords, sort_required};
+use crate::nulls::{apply_null_policy, NullPolicy};
+use crate::overrides::{apply_field_overrides, FieldOverride};
+use crate::parallel;
+use crate::redact::redact_fields;
+use crate::required::{apply_required_mode, RequiredMode};
+use crate::samples::walk_with_pointers;
+use crate::shape::{content_hash, identical};
+use crate::schema::{generate_schema_with, Schema, SchemaDraft, SchemaOutputTier, SchemaWalk, TierPolicy};
+
+/// What happens to the `examples` the tier emits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExamplePolicy {
+    /// Keep them, with long strings truncated
+    #[default]
+    Keep,
+    /// Keep at most this many per keyword
+    Limit(usize),
+    /// Never emit them
+    Omit,
+}
+
+/// What a [`NodeHook`] is told about the schema object it is called with
+#[derive(Debug)]
+pub struct NodeContext<'a> {
+    /// JSON Pointer of the schema object in the generated schema
+    pub pointer: &'a str,
+    /// The input values the schema object describes, e.g. every element for array items
+    pub samples: &'a [&'a Value],
+}
+
+type NodeHookFn = dyn Fn(&NodeContext, &mut Map<String, Value>) + Send + Sync;
+
+/// A callback run on every object of a generated schema, which may modify it
+#[derive(Clone)]
+pub struct NodeHook(Arc<NodeHookFn>);
+
+impl NodeHook {
+    pub fn new(hook: impl Fn(&NodeContext, &mut Map<String, Value>) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+}
+
+impl fmt::Debug for NodeHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("NodeHook")
+    }
+}
+
+/// Everything that controls how a schema is generated from a JSON value. Each option left at
+/// its default keeps what the tier emits.
+#[derive(Debug, Clone)]
+pub struct GeneratorOptions {
+    pub tier: SchemaOutputTier,
+    /// Used instead of `tier` when set
+    pub policy: Option<Arc<dyn TierPolicy>>,
+    /// Draft the schema is written for
+    pub draft: SchemaDraft,
+    pub null_policy: NullPolicy,
+    /// Which properties are required, instead of the tier's choice
+    pub required: Option<RequiredMode>,
+    /// `additionalProperties` for every object, instead of the tier's choice
+    pub additional_properties: Option<Value>,
+    /// Keyword families (format and pattern detectors, enums, bounds, examples) to infer
+    /// even when the tier would not
+    pub infer: Vec<InferenceFamily>,
+    /// Keyword families never emitted; wins over `infer`
+    pub no_infer: Vec<InferenceFamily>,
+    pub examples: ExamplePolicy,
+    /// Depth below the root where objects and arrays get a permissive schema
+    pub max_depth: Option<usize>,
+    /// Values inferred from at most; larger inputs are sampled down to fit, see
+    /// [`fit_nodes`](crate::limits::fit_nodes)
+    pub max_nodes: Option<usize>,
+    /// Time inference may take before it starts over on a sample of each array
+    pub time_limit: Option<Duration>,
+    pub constraints: ConstraintPolicy,
+    /// Overrides by property name glob or data path glob, as in the `[fields]` config table
+    pub fields: BTreeMap<String, FieldOverride>,
+    /// Data path globs whose examples, enums, consts and defaults are removed
+    pub redact: Vec<String>,
+    pub metadata: SchemaMetadata,
+    /// Translations of the generated titles and descriptions
+    pub locale: Option<Locale>,
+    /// Byte-identical output for identical input and options, whatever order array elements
+    /// or observations come in: `oneOf` and `anyOf` branches are sorted canonically
+    pub deterministic: bool,
+    /// Emit `properties` in the order of the input document rather than sorted. Needs the
+    /// `preserve-order` feature, which keeps the order of JSON objects in the first place.
+    pub preserve_order: bool,
+    /// Threads for inferring the elements of large arrays and the properties of wide objects;
+    /// 0 means one per core. The schema is the same for any number.
+    pub jobs: usize,
+    /// Run in order on every schema object, subschemas before their parent, after all other
+    /// options and before the schema is converted to `draft`
+    pub hooks: Vec<NodeHook>,
+}
+
+impl Default for GeneratorOptions {
+    fn default() -> Self {
+        Self {
+            tier: SchemaOutputTier::Standard,
+            policy: None,
+            draft: SchemaDraft::default(),
+            null_policy: NullPolicy::AsIs,
+            required: None,
+            additional_properties: None,
+            infer: Vec::new(),
+            no_infer: Vec::new(),
+            examples: ExamplePolicy::default(),
+            max_depth: None,
+            max_nodes: None,
+            time_limit: None,
+            constraints: ConstraintPolicy::new(),
+            fields: BTreeMap::new(),
+            redact: Vec::new(),
+            metadata: SchemaMetadata::default(),
+            locale: None,
+            deterministic: false,
+            preserve_order: false,
+            jobs: 1,
+            hooks: Vec::new(),
+        }
+    }
+}
+
+/// Generates schemas from JSON values with a fixed set of [`GeneratorOptions`]
+///
+/// ```
+/// use schema_jenerator::{SchemaGenerator, SchemaOutputTier};
+/// use schema_jenerator::required::RequiredMode;
+///
+/// let generator = SchemaGenerator::builder()
+///     .tier(SchemaOutputTier::Comprehensive)
+///     .required(RequiredMode::Observed)
+///     .build();
+/// let schema = generator.generate(&serde_json::json!({ "id": 1 })).unwrap();
+/// assert_eq!(schema["required"], serde_json::json!(["id"]));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SchemaGenerator {
+    options: GeneratorOptions,
+}
+
+impl SchemaGenerator {
+    pub fn new(options: GeneratorOptions) -> Self {
+        Self { options }
+    }
+
+    pub fn builder() -> SchemaGeneratorBuilder {
+        SchemaGeneratorBuilder::default()
+    }
+
+    pub fn options(&self) -> &GeneratorOptions {
+        &self.options
+    }
+
+    /// The schema for `value`. Over `max_nodes` or `time_limit`, it is inferred from a sample
+    /// and marked with [`x-sampled`](crate::limits::SAMPLED_KEYWORD).
+    pub fn generate(&self, value: &Value) -> Result<Value> {
+        let span = info_span!("phase", phase = "infer", properties = field::Empty);
+        let _infer = span.enter();
+        let options = &self.options;
+        if options.preserve_order && !cfg!(feature = "preserve-order") {
+            return Err(AppError::InvalidOption(
+                "Preserving key order needs schema-jenerator built with the preserve-order feature".to_string(),
+            )
+            .into());
+        }
+
+        let mut sampled = None;
+        let fitted = options.max_nodes.and_then(|max_nodes| limits::fit_nodes(value, max_nodes));
+        let (value, max_depth) = match &fitted {
+            Some(fitted) => {
+                warn!("Input has more than {} values; inferring from a sample", options.max_nodes.unwrap_or_default());
+                if fitted.sampled {
+                    sampled = Some(limits::MAX_NODES);
+                }
+                let max_depth = [options.max_depth, fitted.max_depth].into_iter().flatten().min();
+                (&fitted.value, max_depth)
+            }
+            None => (value, options.max_depth),
+        };
+
+        let deadline = options.time_limit.map(|limit| Instant::now() + limit);
+        let mut schema = match limits::with_deadline(deadline, || self.generate_within(value, max_depth, &span)) {
+            Err(e) if limits::is_time_limit(&e) => {
+                warn!("Inference ran out of time; starting over on a sample of each array");
+                sampled = Some(limits::TIME_LIMIT);
+                self.generate_within(&limits::time_limit_sample(value), max_depth, &span)?
+            }
+            result => result?,
+        };
+        if let Some(limit) = sampled {
+            limits::mark_sampled(&mut schema, limit);
+        }
+        Ok(schema)
+    }
+
+    fn generate_within(&self, value: &Value, max_depth: Option<usize>, span: &Span) -> Result<Value> {
+        let options = &self.options;
+        let policy = options.policy.as_deref().unwrap_or(&options.tier);
+        let mut schema = parallel::with_jobs(options.jobs, || match max_depth {
+            Some(max_depth) => {
+                let mut schema = generate_schema_with(&truncate_input(value, max_depth), policy)?;
+                mark_truncated(&mut schema, value, max_depth);
+                Ok::<_, anyhow::Error>(schema)
+            }
+            None => generate_schema_with(value, policy),
+        })?;
+        limits::check_deadline()?;
+        apply_null_policy(&mut schema, value, options.null_policy, policy)?;
+        if let Some(mode) = options.required {
+            apply_required_mode(&mut schema, value, mode);
+        }
+        if let Some(additional) = &options.additional_properties {
+            apply_additional_properties(&mut schema, additional);
+        }
+        if !options.infer.is_empty() || !options.no_infer.is_empty() {
+            parallel::with_jobs(options.jobs, || {
+                apply_inference(&mut schema, value, &options.infer, &options.no_infer)
+            });
+            limits::check_deadline()?;
+        }
+        apply_constraints(&mut schema, value, &options.constraints);
+        apply_field_overrides(&mut schema, &options.fields)?;
+        redact_fields(&mut schema, &options.redact)?;
+        match options.examples {
+            ExamplePolicy::Keep => limit_examples(&mut schema, None),
+            ExamplePolicy::Limit(limit) => limit_examples(&mut schema, Some(limit)),
+            ExamplePolicy::Omit => strip_examples(&mut schema),
+        }
+        if let Some(locale) = &options.locale {
+            localize(&mut schema, locale);
+        }
+        apply_metadata(&mut schema, &options.metadata);
+        if !options.hooks.is_empty() {
+            walk_with_pointers(&mut schema, &[value], &mut |pointer, obj, samples| {
+                let context = NodeContext { pointer, samples };
+                for NodeHook(hook) in &options.hooks {
+                    hook(&context, obj);
+                }
+            });
+        }
+
+        if options.deterministic {
+            sort_branches(&mut schema);
+        }
+        if !span.is_disabled() {
+            span.record("properties", count_properties(&schema));
+        }
+
+        for warning in convert_schema_in_place(&mut schema, SchemaDraft::default(), options.draft) {
+            warn!("{}", warning);
+        }
+        order_keys(&mut schema, options.preserve_order);
+        Ok(schema)
+    }
+
+    /// Like [`SchemaGenerator::generate`], as a typed [`Schema`]
+    pub fn generate_typed(&self, value: &Value) -> Result<Schema> {
+        Ok(Schema::try_from(self.generate(value)?)?)
+    }
+}
+
+/// Accumulates a schema over any number of values observed over time, e.g. messages from a
+/// queue, and emits the schema for everything seen so far on demand.
+///
+/// Each value's schema is merged into the accumulated one as [`merge_schemas`] does: the
+/// union of properties, the intersection of `required`, widened types and bounds.
+#[derive(Debug, Clone)]
+pub struct SchemaBuilder {
+    generator: SchemaGenerator,
+    /// Draft of the finalized schema; observations are merged in the default draft
+    draft: SchemaDraft,
+    merged: Option<Value>,
+    observations: usize,
+    /// Schemas generated for the values fed so far, by content hash, with
+    /// [`SchemaBuilder::with_value_cache`]
+    cache: Option<HashMap<u64, Vec<(Value, Value)>>>,
+    cached: usize,
+}
+
+/// Values a [`SchemaBuilder::with_value_cache`] keeps the schema of at most
+pub const MAX_CACHED_VALUES: usize = 1024;
+
+impl SchemaBuilder {
+    pub fn new(options: GeneratorOptions) -> Self {
+        let draft = options.draft;
+        let generator = SchemaGenerator::new(GeneratorOptions {
+            draft: SchemaDraft::default(),
+            ..options
+        });
+        Self { generator, draft, merged: None, observations: 0, cache: None, cached: 0 }
+    }
+
+    /// Reuses the schema generated for a value identical to one fed before, for up to
+    /// [`MAX_CACHED_VALUES`] distinct values, so repeated records skip inference. Every value
+    /// is still merged, so the result is the same as without the cache.
+    pub fn with_value_cache(mut self) -> Self {
+        self.cache = Some(HashMap::new());
+        self
+    }
+
+    /// Adds `value` to the observations
+    pub fn feed(&mut self, value: &Value) -> Result<()> {
+        let schema = self.generate(value)?;
+        let mut merged = match self.merged.take() {
+            Some(merged) => merge_schemas(&merged, &schema),
+            None => schema,
+        };
+        // Examples accumulate across observations, so the limit is applied to the merged lists
+        if let ExamplePolicy::Limit(limit) = self.generator.options.examples {
+            limit_examples(&mut merged, Some(limit));
+        }
+        self.merged = Some(merged);
+        self.observations += 1;
+        Ok(())
+    }
+
+    fn generate(&mut self, value: &Value) -> Result<Value> {
+        let Some(cache) = &mut self.cache else { return self.generator.generate(value) };
+        let entries = cache.entry(content_hash(value)).or_default();
+        if let Some((_, schema)) = entries.iter().find(|(cached, _)| identical(cached, value)) {
+            return Ok(schema.clone());
+        }
+        let schema = self.generator.generate(value)?;
+        if self.cached < MAX_CACHED_VALUES {
+            entries.push((value.clone(), schema.clone()));
+            self.cached += 1;
+        }
+        Ok(schema)
+    }
+
+    /// How many values have been fed
+    pub fn observations(&self) -> usize {
+        self.observations
+    }
+
+    /// The schema for every value fed so far, or `None` before the first one. Feeding can go
+    /// on afterwards.
+    pub fn finalize(&self) -> Option<Value> {
+        let mut merged = self.merged.clone()?;
+        // Merging appends branches in the order observations arrive
+        if self.generator.options.deterministic {
+            sort_branches(&mut merged);
+        }
+        let mut conversion = convert_schema(&merged, SchemaDraft::default(), self.draft);
+        for warning in &conversion.warnings {
+            warn!("{}", warning);
+        }
+        order_keys(&mut conversion.schema, self.generator.options.preserve_order);
+        Some(conversion.schema)
+    }
+}
+
+impl From<SchemaGenerator> for SchemaBuilder {
+    fn from(generator: SchemaGenerator) -> Self {
+        Self::new(generator.options)
+    }
+}
+
+/// Builds a [`SchemaGenerator`]; every option not set keeps its [`GeneratorOptions`] default
+#[derive(Debug, Clone, Default)]
+pub struct SchemaGeneratorBuilder {
+    options: GeneratorOptions,
+}
+
+impl SchemaGeneratorBuilder {
+    pub fn tier(mut self, tier: SchemaOutputTier) -> Self {
+        self.options.tier = tier;
+        self
+    }
+
+    /// Generates with `policy` instead of a built-in tier
+    pub fn policy(mut self, policy: impl TierPolicy + 'static) -> Self {
+        self.options.policy = Some(Arc::new(policy));
+        self
+    }
+
+    pub fn draft(mut self, draft: SchemaDraft) -> Self {
+        self.options.draft = draft;
+        self
+    }
+
+    pub fn null_policy(mut self, policy: NullPolicy) -> Self {
+        self.options.null_policy = policy;
+        self
+    }
+
+    pub fn required(mut self, mode: RequiredMode) -> Self {
+        self.options.required = Some(mode);
+        self
+    }
+
+    pub fn additional_properties(mut self, value: Value) -> Self {
+        self.options.additional_properties = Some(value);
+        self
+    }
+
+    pub fn infer(mut self, families: impl IntoIterator<Item = InferenceFamily>) -> Self {
+        self.options.infer.extend(families);
+        self
+    }
+
+    pub fn no_infer(mut self, families: impl IntoIterator<Item = InferenceFamily>) -> Self {
+        self.options.no_infer.extend(families);
+        self
+    }
+
+    pub fn examples(mut self, policy: ExamplePolicy) -> Self {
+        self.options.examples = policy;
+        self
+    }
+
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.options.max_depth = Some(depth);
+        self
+    }
+
+    pub fn max_nodes(mut self, nodes: usize) -> Self {
+        self.options.max_nodes = Some(nodes);
+        self
+    }
+
+    pub fn time_limit(mut self, limit: Duration) -> Self {
+        self.options.time_limit = Some(limit);
+        self
+    }
+
+    pub fn constraints(mut self, policy: ConstraintPolicy) -> Self {
+        self.options.constraints = policy;
+        self
+    }
+
+    pub fn field(mut self, selector: impl Into<String>, field: FieldOverride) -> Self {
+        self.options.fields.insert(selector.into(), field);
+        self
+    }
+
+    pub fn redact(mut self, pattern: impl Into<String>) -> Self {
+        self.options.redact.push(pattern.into());
+        self
+    }
+
+    pub fn metadata(mut self, metadata: SchemaMetadata) -> Self {
+        self.options.metadata = metadata;
+        self
+    }
+
+    pub fn deterministic(mut self, deterministic: bool) -> Self {
+        self.options.deterministic = deterministic;
+        self
+    }
+
+    pub fn locale(mut self, locale: Locale) -> Self {
+        self.options.locale = Some(locale);
+        self
+    }
+
+    pub fn preserve_order(mut self, preserve_order: bool) -> Self {
+        self.options.preserve_order = preserve_order;
+        self
+    }
+
+    /// Threads for large arrays and wide objects; 0 means one per core
+    pub fn jobs(mut self, jobs: usize) -> Self {
+        self.options.jobs = jobs;
+        self
+    }
+
+    /// Registers a callback run on every schema object after generation
+    pub fn hook(mut self, hook: impl Fn(&NodeContext, &mut Map<String, Value>) + Send + Sync + 'static) -> Self {
+        self.options.hooks.push(NodeHook::new(hook));
+        self
+    }
+
+    pub fn build(self) -> SchemaGenerator {
+        SchemaGenerator::new(self.options)
+    }
+}
+
+// With the `preserve-order` feature the generator's maps keep insertion order, which is only
+// wanted for the properties themselves and the `required` lists that follow them. Anything
+// that adds to a generated schema before writing it goes through this again.
+pub(crate) fn order_keys(schema: &mut Value, preserve_order: bool) {
+    if preserve_order {
+        sort_keywords(schema);
+    } else if cfg!(feature = "preserve-order") {
+        sort_keys_in_place(schema);
+        sort_required(schema);
+    }
+}
+
+/// How many properties are described throughout `schema`
+fn count_properties(schema: &Value) -> usize {
+    let mut count = 0;
+    schema.walk(|_, node| {
+        if let Some(Value::Object(properties)) = node.get("properties") {
+            count += properties.len();
+        }
+    });
+    count
+}
@@ -0,0 +1,116 @@
+use glob::{MatchOptions, Pattern};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const IGNORE_FILE: &str = ".schemaignore";
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+#[derive(Debug, Clone)]
+struct Rule {
+    pattern: Pattern,
+    negated: bool,
+    dir_only: bool,
+}
+
+/// Paths excluded by a `.schemaignore` file, using gitignore syntax
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    root: PathBuf,
+    rules: Vec<Rule>,
+}
+
+impl IgnoreRules {
+    /// Loads the nearest `.schemaignore` in `dir` or one of its ancestors
+    pub fn discover(dir: &Path) -> Self {
+        dir.ancestors()
+            .find_map(|candidate| {
+                let file = candidate.join(IGNORE_FILE);
+                let content = fs::read_to_string(&file).ok()?;
+                Some(Self::parse(candidate, &content))
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn parse(root: &Path, content: &str) -> Self {
+        let rules = content
+            .lines()
+            .filter_map(|line| {
+                let line = line.trim_end();
+                if line.is_empty() || line.starts_with('#') {
+                    return None;
+                }
+
+                let (negated, line) = match line.strip_prefix('!') {
+                    Some(rest) => (true, rest),
+                    None => (false, line.strip_prefix('\\').unwrap_or(line)),
+                };
+                let (dir_only, line) = match line.strip_suffix('/') {
+                    Some(rest) => (true, rest),
+                    None => (false, line),
+                };
+
+                // Like gitignore, a pattern containing a slash is relative to the ignore file;
+                // otherwise it matches at any depth
+                let anchored = line.contains('/');
+                let line = line.trim_start_matches('/');
+                let glob = if anchored || line.starts_with("**") {
+                    line.to_string()
+                } else {
+                    format!("**/{}", line)
+                };
+
+                Pattern::new(&glob).ok().map(|pattern| Rule {
+                    pattern,
+                    negated,
+                    dir_only,
+                })
+            })
+            .collect();
+
+        Self {
+            root: root.to_path_buf(),
+            rules,
+        }
+    }
+
+    /// Whether `path`, or a directory containing it, is ignored
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+
+        // Check from the outermost directory inwards; once a directory is excluded,
+        // nothing inside it can be re-included
+        let mut prefix = PathBuf::new();
+        let components: Vec<_> = relative.components().collect();
+        for (index, component) in components.iter().enumerate() {
+            prefix.push(component);
+            let is_dir = index + 1 < components.len() || path.is_dir();
+            if self.matches(&prefix, is_dir) {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn matches(&self, relative: &Path, is_dir: bool) -> bool {
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            if rule.pattern.matches_path_with(relative, MATCH_OPTIONS) {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+}
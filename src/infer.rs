@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+
+use crate::parallel;
+use crate::samples::walk_with_samples;
+use crate::schema::types::{detect_string_format, detect_string_pattern};
+
+const BOUND_KEYWORDS: &[&str] = &[
+    "minimum",
+    "maximum",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+    "multipleOf",
+    "minLength",
+    "maxLength",
+    "minItems",
+    "maxItems",
+    "minProperties",
+    "maxProperties",
+];
+
+/// Enums are only inferred for strings with at most this many distinct values
+const MAX_ENUM_VALUES: usize = 10;
+
+/// A family of keywords inferred from the input data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum InferenceFamily {
+    /// `format` from string detectors
+    Formats,
+    /// `pattern` from string detectors
+    Patterns,
+    /// `enum` for strings that repeat a few distinct values
+    Enums,
+    /// Length, size and range limits
+    Bounds,
+    /// `examples` copied from the input
+    Examples,
+}
+
+impl InferenceFamily {
+    fn keywords(self) -> &'static [&'static str] {
+        match self {
+            InferenceFamily::Formats => &["format"],
+            InferenceFamily::Patterns => &["pattern"],
+            InferenceFamily::Enums => &["enum"],
+            InferenceFamily::Bounds => BOUND_KEYWORDS,
+            InferenceFamily::Examples => &["examples"],
+        }
+    }
+}
+
+/// Adds the `enable`d families the tier did not emit, inferred from `data`, then removes
+/// every keyword of the `disable`d families. Disabling wins when a family is in both.
+pub fn apply_inference(schema: &mut Value, data: &Value, enable: &[InferenceFamily], disable: &[InferenceFamily]) {
+    walk_with_samples(schema, &[data], &mut |obj, samples| {
+        for family in enable {
+            if !disable.contains(family) {
+                infer(obj, samples, *family);
+            }
+        }
+        for family in disable {
+            for keyword in family.keywords() {
+                obj.remove(*keyword);
+            }
+        }
+    });
+}
+
+fn infer(obj: &mut Map<String, Value>, samples: &[&Value], family: InferenceFamily) {
+    if samples.is_empty() {
+        return;
+    }
+    let schema_type = obj.get("type").and_then(Value::as_str).unwrap_or_default().to_string();
+    let strings: Vec<&str> = samples.iter().filter_map(|s| s.as_str()).collect();
+
+    match (family, schema_type.as_str()) {
+        (InferenceFamily::Formats, "string") if !obj.contains_key("format") => {
+            if let Some(format) = common(&strings, detect_string_format) {
+                obj.insert("format".to_string(), Value::String(format.to_string()));
+            }
+        }
+        (InferenceFamily::Patterns, "string") if !obj.contains_key("pattern") && !obj.contains_key("format") => {
+            if let Some(pattern) = common(&strings, detect_string_pattern) {
+                obj.insert("pattern".to_string(), Value::String(pattern.to_string()));
+            }
+        }
+        (InferenceFamily::Enums, "string") if !obj.contains_key("enum") => {
+            let distinct: BTreeSet<&str> = strings.iter().copied().collect();
+            if distinct.len() <= MAX_ENUM_VALUES && distinct.len() < strings.len() {
+                obj.insert("enum".to_string(), distinct.into_iter().map(Value::from).collect());
+            }
+        }
+        (InferenceFamily::Bounds, "string") => {
+            let lengths = strings.iter().map(|s| s.chars().count() as f64);
+            insert_range(obj, "minLength", "maxLength", lengths);
+        }
+        (InferenceFamily::Bounds, "integer" | "number") => {
+            let numbers = samples.iter().filter_map(|s| s.as_f64());
+            insert_range(obj, "minimum", "maximum", numbers);
+        }
+        (InferenceFamily::Bounds, "array") => {
+            let sizes = samples.iter().filter_map(|s| s.as_array()).map(|a| a.len() as f64);
+            insert_range(obj, "minItems", "maxItems", sizes);
+        }
+        (InferenceFamily::Examples, "string" | "integer" | "number" | "boolean") if !obj.contains_key("examples") => {
+            let mut examples: Vec<Value> = Vec::new();
+            for sample in samples {
+                if !examples.contains(sample) {
+                    examples.push((*sample).clone());
+                }
+            }
+            obj.insert("examples".to_string(), Value::Array(examples));
+        }
+        _ => {}
+    }
+}
+
+// The detector's result when every sample produces the same one
+fn common(strings: &[&str], detect: fn(&str) -> Option<&'static str>) -> Option<&'static str> {
+    let first = detect(strings.first()?)?;
+    parallel::par_chunks(strings, |chunk| chunk.iter().all(|s| detect(s) == Some(first)))
+        .into_iter()
+        .all(|same| same)
+        .then_some(first)
+}
+
+fn insert_range(obj: &mut Map<String, Value>, min_keyword: &str, max_keyword: &str, values: impl Iterator<Item = f64>) {
+    let values: Vec<f64> = values.collect();
+    if values.is_empty() {
+        return;
+    }
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let as_json = |n: f64| {
+        if n.fract() == 0.0 {
+            Value::from(n as i64)
+        } else {
+            Value::from(n)
+        }
+    };
+    obj.entry(min_keyword).or_insert_with(|| as_json(min));
+    obj.entry(max_keyword).or_insert_with(|| as_json(max));
+}
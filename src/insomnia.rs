@@ -0,0 +1,103 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+use crate::error::AppError;
+
+/// A request of an Insomnia export with a JSON body
+#[derive(Debug, Clone, PartialEq)]
+pub struct InsomniaRequest {
+    pub name: String,
+    pub method: String,
+    pub url: String,
+    /// Names of the request groups it is in, outermost first
+    pub folders: Vec<String>,
+    pub body: Value,
+    /// Kebab-case file stem from the name, unique within the export, e.g. `create-user`
+    pub file_stem: String,
+}
+
+/// What [`parse_export`] found
+#[derive(Debug, Clone, Default)]
+pub struct InsomniaExport {
+    pub requests: Vec<InsomniaRequest>,
+    /// One message per request whose JSON body could not be parsed, e.g. because of template
+    /// tags such as `{{ _.userId }}`
+    pub skipped: Vec<String>,
+}
+
+/// Reads the requests with a JSON body out of an Insomnia v4 export, the `resources` document
+/// written by Insomnia's "Export Data". Exports do not include responses, so only request
+/// bodies are found.
+pub fn parse_export(export: &Value) -> Result<InsomniaExport> {
+    let resources = match (export.get("_type").and_then(Value::as_str), export.get("resources")) {
+        (Some("export"), Some(Value::Array(resources))) => resources,
+        _ => {
+            return Err(AppError::InvalidOption(
+                "Not an Insomnia export: expected \"_type\": \"export\" with a resources array".to_string(),
+            )
+            .into())
+        }
+    };
+    let field = |resource: &Value, key: &str| resource.get(key).and_then(Value::as_str).unwrap_or_default().to_string();
+    let by_id = |id: &str| resources.iter().find(|resource| resource.get("_id").and_then(Value::as_str) == Some(id));
+
+    let mut parsed = InsomniaExport::default();
+    let mut stems = BTreeSet::new();
+    for resource in resources.iter().filter(|resource| resource.get("_type").and_then(Value::as_str) == Some("request")) {
+        let Some(body) = resource.get("body") else { continue };
+        let is_json = body.get("mimeType").and_then(Value::as_str).is_some_and(|mime| mime.contains("json"));
+        let Some(text) = body.get("text").and_then(Value::as_str).filter(|text| is_json && !text.trim().is_empty()) else {
+            continue;
+        };
+        let name = field(resource, "name");
+        let body = match serde_json::from_str(text) {
+            Ok(body) => body,
+            Err(e) => {
+                parsed.skipped.push(format!("{}: body is not valid JSON: {}", name, e));
+                continue;
+            }
+        };
+
+        let mut folders = Vec::new();
+        let mut parent = resource.get("parentId").and_then(Value::as_str).and_then(by_id);
+        while let Some(group) = parent.filter(|group| group.get("_type").and_then(Value::as_str) == Some("request_group")) {
+            folders.insert(0, field(group, "name"));
+            parent = group.get("parentId").and_then(Value::as_str).and_then(by_id);
+        }
+
+        let base = match file_stem(&name) {
+            stem if stem.is_empty() => "request".to_string(),
+            stem => stem,
+        };
+        let mut stem = base.clone();
+        let mut suffix = 2;
+        while !stems.insert(stem.clone()) {
+            stem = format!("{}-{}", base, suffix);
+            suffix += 1;
+        }
+
+        parsed.requests.push(InsomniaRequest {
+            name,
+            method: field(resource, "method"),
+            url: field(resource, "url"),
+            folders,
+            body,
+            file_stem: stem,
+        });
+    }
+    Ok(parsed)
+}
+
+// Lowercase letters and digits, with a dash for every run of anything else
+fn file_stem(name: &str) -> String {
+    let mut stem = String::new();
+    for c in name.chars() {
+        if c.is_alphanumeric() {
+            stem.extend(c.to_lowercase());
+        } else if !stem.is_empty() && !stem.ends_with('-') {
+            stem.push('-');
+        }
+    }
+    stem.trim_end_matches('-').to_string()
+}
@@ -0,0 +1,211 @@
+//! Interned strings for the records of a merge over many samples. `serde_json::Value` owns
+//! every key and string, so a merge over ten million records allocates ten million copies
+//! of `"timestamp"`; records parsed into an [`InternedValue`] share one copy of each property
+//! name and short string instead.
+
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
+
+use crate::shape::Shape;
+
+/// Strings up to this many bytes are interned; longer ones are rarely repeated
+pub const SHORT_STRING_MAX: usize = 32;
+
+/// Distinct strings an [`Interner`] keeps at most, so a column of unique ids cannot grow it
+/// without bound; strings past the cap are allocated as usual
+pub const MAX_INTERNED: usize = 1 << 16;
+
+/// How much an [`Interner`] shared
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct InternStats {
+    /// Distinct strings interned
+    pub strings: usize,
+    /// Property names and short strings looked up
+    pub lookups: usize,
+    /// Bytes of string data not allocated again because an interned copy was shared
+    pub bytes_saved: u64,
+}
+
+impl InternStats {
+    /// Adds the counts of `other`, e.g. for the inputs of a batch
+    pub fn add(&mut self, other: InternStats) {
+        self.strings += other.strings;
+        self.lookups += other.lookups;
+        self.bytes_saved += other.bytes_saved;
+    }
+}
+
+/// A table of shared strings
+#[derive(Debug, Default)]
+pub struct Interner {
+    table: HashSet<Arc<str>>,
+    stats: InternStats,
+}
+
+impl Interner {
+    /// The shared copy of `s`, added to the table if there is room
+    pub fn intern(&mut self, s: &str) -> Arc<str> {
+        self.stats.lookups += 1;
+        if let Some(interned) = self.table.get(s) {
+            self.stats.bytes_saved += s.len() as u64;
+            return Arc::clone(interned);
+        }
+        let interned: Arc<str> = Arc::from(s);
+        if self.table.len() < MAX_INTERNED {
+            self.table.insert(Arc::clone(&interned));
+            self.stats.strings += 1;
+        }
+        interned
+    }
+
+    pub fn stats(&self) -> InternStats {
+        self.stats
+    }
+
+    /// Deserializes one JSON value, sharing its property names and short strings
+    pub fn seed(&mut self) -> InternSeed<'_> {
+        InternSeed { interner: self, key: false }
+    }
+}
+
+/// A JSON value whose property names and short strings are shared through an [`Interner`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum InternedValue {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(Arc<str>),
+    Array(Vec<InternedValue>),
+    /// In document order; a repeated key keeps its last value, as in `Value`
+    Object(Vec<(Arc<str>, InternedValue)>),
+}
+
+impl InternedValue {
+    /// Like [`shape_hash`](crate::shape::shape_hash), with properties in document order, so
+    /// records listing the same properties in another order count as another shape
+    pub fn shape(&self) -> Shape {
+        let mut hasher = DefaultHasher::new();
+        self.hash_shape(&mut hasher);
+        hasher.finish()
+    }
+
+    fn hash_shape(&self, hasher: &mut DefaultHasher) {
+        match self {
+            InternedValue::Null => 0u8.hash(hasher),
+            InternedValue::Bool(_) => 1u8.hash(hasher),
+            InternedValue::Number(n) if n.is_f64() => 2u8.hash(hasher),
+            InternedValue::Number(_) => 3u8.hash(hasher),
+            InternedValue::String(_) => 4u8.hash(hasher),
+            InternedValue::Array(items) => {
+                5u8.hash(hasher);
+                let mut seen = HashSet::new();
+                for shape in items.iter().map(InternedValue::shape) {
+                    if seen.insert(shape) {
+                        shape.hash(hasher);
+                    }
+                }
+            }
+            InternedValue::Object(entries) => {
+                6u8.hash(hasher);
+                entries.len().hash(hasher);
+                for (key, value) in entries {
+                    key.hash(hasher);
+                    value.hash_shape(hasher);
+                }
+            }
+        }
+    }
+
+    pub fn into_value(self) -> Value {
+        match self {
+            InternedValue::Null => Value::Null,
+            InternedValue::Bool(b) => Value::Bool(b),
+            InternedValue::Number(n) => Value::Number(n),
+            InternedValue::String(s) => Value::String(s.to_string()),
+            InternedValue::Array(items) => Value::Array(items.into_iter().map(InternedValue::into_value).collect()),
+            InternedValue::Object(entries) => {
+                let mut obj = Map::new();
+                for (key, value) in entries {
+                    obj.insert(key.to_string(), value.into_value());
+                }
+                Value::Object(obj)
+            }
+        }
+    }
+}
+
+/// See [`Interner::seed`]
+pub struct InternSeed<'a> {
+    interner: &'a mut Interner,
+    /// Property names are always interned, whatever their length
+    key: bool,
+}
+
+impl<'de> DeserializeSeed<'de> for InternSeed<'_> {
+    type Value = InternedValue;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<InternedValue, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for InternSeed<'_> {
+    type Value = InternedValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_bool<E>(self, b: bool) -> Result<InternedValue, E> {
+        Ok(InternedValue::Bool(b))
+    }
+
+    fn visit_i64<E>(self, n: i64) -> Result<InternedValue, E> {
+        Ok(InternedValue::Number(n.into()))
+    }
+
+    fn visit_u64<E>(self, n: u64) -> Result<InternedValue, E> {
+        Ok(InternedValue::Number(n.into()))
+    }
+
+    fn visit_f64<E>(self, n: f64) -> Result<InternedValue, E> {
+        Ok(Number::from_f64(n).map_or(InternedValue::Null, InternedValue::Number))
+    }
+
+    fn visit_str<E>(self, s: &str) -> Result<InternedValue, E> {
+        if self.key || s.len() <= SHORT_STRING_MAX {
+            Ok(InternedValue::String(self.interner.intern(s)))
+        } else {
+            Ok(InternedValue::String(Arc::from(s)))
+        }
+    }
+
+    fn visit_unit<E>(self) -> Result<InternedValue, E> {
+        Ok(InternedValue::Null)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<InternedValue, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element_seed(InternSeed { interner: &mut *self.interner, key: false })? {
+            items.push(item);
+        }
+        Ok(InternedValue::Array(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<InternedValue, A::Error> {
+        let mut entries = Vec::new();
+        while let Some(key) = map.next_key_seed(InternSeed { interner: &mut *self.interner, key: true })? {
+            let InternedValue::String(key) = key else {
+                return Err(de::Error::custom("property names must be strings"));
+            };
+            let value = map.next_value_seed(InternSeed { interner: &mut *self.interner, key: false })?;
+            entries.push((key, value));
+        }
+        Ok(InternedValue::Object(entries))
+    }
+}
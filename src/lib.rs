@@ -1,10 +1,97 @@
+pub mod additional;
+pub mod annotate;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod bench;
+pub mod bindings;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod bundle;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cache;
+#[cfg(all(feature = "cli", not(target_arch = "wasm32")))]
 pub mod cli;
+pub mod compat;
+pub mod complexity;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod config;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod config_schema;
+pub mod constraints;
+pub mod convert;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod coverage;
+pub mod depth;
+pub mod diff;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod doctor;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod editor;
 pub mod error;
+pub mod examples;
+pub mod explain;
+pub mod fake;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod filter;
+pub mod from_sample;
+pub mod generator;
+pub mod hash;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ignore;
+pub mod infer;
+pub mod insomnia;
+pub mod intern;
+pub mod limits;
+pub mod lint;
+pub mod locale;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod logging;
+pub mod merge;
+pub mod metadata;
+pub mod minify;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod mmap;
+#[cfg(all(feature = "async", not(target_arch = "wasm32")))]
+pub mod nonblocking;
+pub mod normalize;
+pub mod nulls;
+pub mod openapi;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod output;
+pub mod overrides;
+pub mod parallel;
+pub mod patch;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod pipeline;
+pub mod pointer;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod progress;
+pub mod provenance;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod quality;
+pub mod redact;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod remote;
+pub mod required;
+pub mod samples;
+pub mod sampling;
 pub mod schema;
+pub mod shape;
+pub mod source_map;
+pub mod spans;
+pub mod split;
+pub mod stats;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod streaming;
+pub mod style;
+pub mod tiers;
+pub mod update;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod validation;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 pub use error::AppError;
-pub use schema::{generate_schema, SchemaOutputTier};
+pub use generator::{GeneratorOptions, SchemaBuilder, SchemaGenerator};
+pub use schema::SchemaOutputTier;
 
 pub type Result<T> = std::result::Result<T, AppError>;
\ No newline at end of file
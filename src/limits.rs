@@ -0,0 +1,199 @@
+//! Caps on what one input may cost, for running on untrusted input. Instead of failing or
+//! running unbounded, an input over a cap is inferred from a sample: arrays keep fewer
+//! elements, deep subtrees become `x-truncated`, and the streamed records past a cap are not
+//! read. A schema inferred from a sample says which cap caused it in `x-sampled` at the root.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+use crate::error::AppError;
+use crate::sampling::sample_arrays;
+
+pub const SAMPLED_KEYWORD: &str = "x-sampled";
+
+/// Names of the caps, as in the `[limits]` config table and `x-sampled`
+pub const MAX_INPUT_SIZE: &str = "max_input_size";
+pub const MAX_NODES: &str = "max_nodes";
+pub const TIME_LIMIT: &str = "time_limit";
+
+/// Elements kept of each array when inference runs out of time and starts over on a sample
+pub const TIME_LIMIT_SAMPLE: usize = 16;
+
+// Nodes the schema builder goes through between looks at the clock
+const DEADLINE_CHECK_INTERVAL: usize = 1024;
+
+/// The `[limits]` config table. Every cap is off when unset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ResourceLimits {
+    /// Bytes of an input file read. Larger top-level arrays and NDJSON are inferred from the
+    /// records that fit; other documents are rejected.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_input_size: Option<u64>,
+    /// JSON values, scalars included, inferred from per document or record. Arrays are
+    /// sampled down to fit, then levels below those that fit are truncated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_nodes: Option<usize>,
+    /// Depth below the root where objects and arrays are truncated, as with `--max-depth`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<usize>,
+    /// Milliseconds of inference per input, after which it starts over on a sample
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_limit: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// Each cap of `self`, or else of `fallback`
+    pub fn or(self, fallback: ResourceLimits) -> ResourceLimits {
+        ResourceLimits {
+            max_input_size: self.max_input_size.or(fallback.max_input_size),
+            max_nodes: self.max_nodes.or(fallback.max_nodes),
+            max_depth: self.max_depth.or(fallback.max_depth),
+            time_limit: self.time_limit.or(fallback.time_limit),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == ResourceLimits::default()
+    }
+
+    pub fn time_limit(&self) -> Option<Duration> {
+        self.time_limit.map(Duration::from_millis)
+    }
+}
+
+/// Marks `schema` as inferred from a sample because of the cap called `limit`
+pub fn mark_sampled(schema: &mut Value, limit: &str) {
+    if let Some(obj) = schema.as_object_mut() {
+        obj.entry(SAMPLED_KEYWORD).or_insert_with(|| Value::String(limit.to_string()));
+    }
+}
+
+/// A value cut down to a node budget by [`fit_nodes`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fitted {
+    pub value: Value,
+    /// Whether arrays were sampled
+    pub sampled: bool,
+    /// Depth to truncate at as well, when sampling every array to one element was not enough
+    pub max_depth: Option<usize>,
+}
+
+/// Samples the arrays of `value`, halving the elements kept each round, until it has at most
+/// `max_nodes` nodes. If one element per array is still too many, the deepest levels go.
+/// `None` when `value` fits as it is.
+pub fn fit_nodes(value: &Value, max_nodes: usize) -> Option<Fitted> {
+    if count_nodes(value) <= max_nodes {
+        return None;
+    }
+
+    let mut limit = longest_array(value);
+    let mut sampled = None;
+    while limit > 1 {
+        limit /= 2;
+        let candidate = sample_arrays(value, limit, None);
+        let fits = count_nodes(&candidate) <= max_nodes;
+        sampled = Some(candidate);
+        if fits {
+            return sampled.map(|value| Fitted { value, sampled: true, max_depth: None });
+        }
+    }
+
+    let was_sampled = sampled.is_some();
+    let value = sampled.unwrap_or_else(|| value.clone());
+    let mut total = 0;
+    let levels = nodes_per_level(&value).into_iter().take_while(|nodes| {
+        total += nodes;
+        total <= max_nodes
+    });
+    let max_depth = levels.count().saturating_sub(1);
+    Some(Fitted { value, sampled: was_sampled, max_depth: Some(max_depth) })
+}
+
+/// Every value in `value`, itself included
+pub fn count_nodes(value: &Value) -> usize {
+    nodes_per_level(value).into_iter().sum()
+}
+
+// Values at each depth, the root's first
+fn nodes_per_level(value: &Value) -> Vec<usize> {
+    let mut levels = Vec::new();
+    let mut level = vec![value];
+    while !level.is_empty() {
+        levels.push(level.len());
+        level = level.into_iter().flat_map(children).collect();
+    }
+    levels
+}
+
+fn longest_array(value: &Value) -> usize {
+    let mut longest = 0;
+    let mut stack = vec![value];
+    while let Some(value) = stack.pop() {
+        if let Value::Array(items) = value {
+            longest = longest.max(items.len());
+        }
+        stack.extend(children(value));
+    }
+    longest
+}
+
+fn children(value: &Value) -> Box<dyn Iterator<Item = &Value> + '_> {
+    match value {
+        Value::Object(obj) => Box::new(obj.values()),
+        Value::Array(items) => Box::new(items.iter()),
+        _ => Box::new(std::iter::empty()),
+    }
+}
+
+thread_local! {
+    static DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// Runs `f` with inference stopping at `deadline` with a [`TIME_LIMIT`] error
+pub fn with_deadline<R>(deadline: Option<Instant>, f: impl FnOnce() -> R) -> R {
+    let previous = DEADLINE.with(|cell| cell.replace(deadline));
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
+    DEADLINE.with(|cell| cell.set(previous));
+    result.unwrap_or_else(|payload| std::panic::resume_unwind(payload))
+}
+
+/// The deadline of the current [`with_deadline`]
+pub fn deadline() -> Option<Instant> {
+    DEADLINE.with(Cell::get)
+}
+
+/// Fails once the current deadline has passed
+pub fn check_deadline() -> Result<(), AppError> {
+    match deadline() {
+        Some(deadline) if Instant::now() >= deadline => Err(AppError::LimitExceeded {
+            limit: TIME_LIMIT,
+            message: "Inference ran out of time".to_string(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// [`check_deadline`] on every 1024th call, for per-node loops
+pub fn check_deadline_every(counter: &mut usize) -> Result<(), AppError> {
+    *counter += 1;
+    if counter.is_multiple_of(DEADLINE_CHECK_INTERVAL) {
+        check_deadline()
+    } else {
+        Ok(())
+    }
+}
+
+/// Whether `error` is the [`TIME_LIMIT`] running out
+pub fn is_time_limit(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .any(|cause| matches!(cause.downcast_ref::<AppError>(), Some(AppError::LimitExceeded { limit: TIME_LIMIT, .. })))
+}
+
+/// The [`TIME_LIMIT_SAMPLE`] inference starts over on after running out of time
+pub fn time_limit_sample(value: &Value) -> Value {
+    sample_arrays(value, TIME_LIMIT_SAMPLE, None)
+}
@@ -0,0 +1,297 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::normalize::redundant_keywords;
+use crate::pointer;
+use crate::schema::keywords::for_each_subschema_entry_mut;
+
+/// The keywords that only apply to one type, so that they imply it when `type` is missing
+const TYPE_KEYWORDS: &[(&str, &[&str])] = &[
+    (
+        "object",
+        &[
+            "properties",
+            "patternProperties",
+            "additionalProperties",
+            "required",
+            "minProperties",
+            "maxProperties",
+            "propertyNames",
+            "dependentRequired",
+            "dependentSchemas",
+        ],
+    ),
+    (
+        "array",
+        &["items", "prefixItems", "additionalItems", "minItems", "maxItems", "uniqueItems", "contains"],
+    ),
+    ("string", &["minLength", "maxLength", "pattern"]),
+    ("number", &["minimum", "maximum", "exclusiveMinimum", "exclusiveMaximum", "multipleOf"]),
+];
+
+/// Keywords that leave the type to something else, so a missing `type` is deliberate
+const TYPE_DEFERRING: &[&str] =
+    &["$ref", "$dynamicRef", "$recursiveRef", "allOf", "anyOf", "oneOf", "not", "if", "enum", "const"];
+
+const BOUNDS: &[(&str, &str)] = &[
+    ("minimum", "maximum"),
+    ("minLength", "maxLength"),
+    ("minItems", "maxItems"),
+    ("minProperties", "maxProperties"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LintRule {
+    /// A keyword that accepts everything it could, e.g. `minLength: 0`
+    RedundantKeyword,
+    DuplicateRequired,
+    UnsortedRequired,
+    DuplicateEnum,
+    /// No `type`, though the other keywords only apply to one
+    MissingType,
+    /// An `enum` without values, which accepts nothing
+    EmptyEnum,
+    /// A lower bound above its upper bound, e.g. `minimum` above `maximum`
+    ContradictoryBounds,
+    /// A required property that `additionalProperties: false` forbids
+    UnsatisfiableRequired,
+    /// A keyword for another type than the schema's, which has no effect
+    MisplacedKeyword,
+}
+
+impl LintRule {
+    pub fn name(self) -> &'static str {
+        match self {
+            LintRule::RedundantKeyword => "redundant-keyword",
+            LintRule::DuplicateRequired => "duplicate-required",
+            LintRule::UnsortedRequired => "unsorted-required",
+            LintRule::DuplicateEnum => "duplicate-enum",
+            LintRule::MissingType => "missing-type",
+            LintRule::EmptyEnum => "empty-enum",
+            LintRule::ContradictoryBounds => "contradictory-bounds",
+            LintRule::UnsatisfiableRequired => "unsatisfiable-required",
+            LintRule::MisplacedKeyword => "misplaced-keyword",
+        }
+    }
+
+    /// Whether [`fix_schema`] applies it. Every fix keeps what the schema accepts, except
+    /// adding a missing `type`, which only rejects values the other keywords ignored.
+    pub fn fixable(self) -> bool {
+        matches!(
+            self,
+            LintRule::RedundantKeyword
+                | LintRule::DuplicateRequired
+                | LintRule::UnsortedRequired
+                | LintRule::DuplicateEnum
+                | LintRule::MissingType
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LintIssue {
+    /// JSON Pointer of the schema object the issue is in
+    pub pointer: String,
+    pub rule: LintRule,
+    pub message: String,
+    pub fixable: bool,
+    /// Whether [`fix_schema`] applied the fix
+    pub fixed: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LintReport {
+    pub issues: Vec<LintIssue>,
+}
+
+impl LintReport {
+    /// Issues that were not fixed
+    pub fn remaining(&self) -> usize {
+        self.issues.iter().filter(|issue| !issue.fixed).count()
+    }
+
+    pub fn fixed(&self) -> usize {
+        self.issues.len() - self.remaining()
+    }
+
+    pub fn render_text(&self) -> String {
+        if self.issues.is_empty() {
+            return "No issues found\n".to_string();
+        }
+
+        let mut out = String::new();
+        for issue in &self.issues {
+            let state = match (issue.fixed, issue.fixable) {
+                (true, _) => ", fixed",
+                (false, true) => ", fixable",
+                (false, false) => "",
+            };
+            out.push_str(&format!(
+                "{}: {} ({}{})\n",
+                pointer::display(&issue.pointer),
+                issue.message,
+                issue.rule.name(),
+                state
+            ));
+        }
+
+        let fixable = self.issues.iter().filter(|issue| issue.fixable && !issue.fixed).count();
+        out.push_str(&format!("\n{} issue(s)", self.issues.len()));
+        if self.fixed() > 0 {
+            out.push_str(&format!(", {} fixed", self.fixed()));
+        }
+        if fixable > 0 {
+            out.push_str(&format!(", {} fixable with --fix", fixable));
+        }
+        out.push('\n');
+        out
+    }
+}
+
+/// Finds redundant, contradictory and untidy keywords in `schema` and its subschemas
+pub fn lint_schema(schema: &Value) -> LintReport {
+    let mut copy = schema.clone();
+    let mut report = LintReport::default();
+    lint_node(&mut copy, "", false, &mut report.issues);
+    report
+}
+
+/// Like [`lint_schema`], applying the fixes of the [fixable](LintRule::fixable) issues to
+/// `schema`
+pub fn fix_schema(schema: &mut Value) -> LintReport {
+    let mut report = LintReport::default();
+    lint_node(schema, "", true, &mut report.issues);
+    report
+}
+
+fn lint_node(node: &mut Value, at: &str, fix: bool, issues: &mut Vec<LintIssue>) {
+    let Value::Object(obj) = node else { return };
+
+    let mut issue = |rule: LintRule, message: String| {
+        issues.push(LintIssue {
+            pointer: at.to_string(),
+            rule,
+            message,
+            fixable: rule.fixable(),
+            fixed: fix && rule.fixable(),
+        });
+    };
+
+    for keyword in redundant_keywords(obj) {
+        issue(LintRule::RedundantKeyword, format!("{} {} is redundant", keyword, obj[keyword]));
+        if fix {
+            obj.remove(keyword);
+        }
+    }
+
+    if let Some(Value::Array(required)) = obj.get_mut("required") {
+        let duplicates = duplicates(required);
+        for value in &duplicates {
+            issue(LintRule::DuplicateRequired, format!("{} is required more than once", value));
+        }
+        if fix && !duplicates.is_empty() {
+            dedup_in_order(required);
+        }
+        if !required.windows(2).all(|pair| pair[0].as_str() <= pair[1].as_str()) {
+            issue(LintRule::UnsortedRequired, "required is not sorted".to_string());
+            if fix {
+                required.sort_by(|a, b| a.as_str().cmp(&b.as_str()));
+            }
+        }
+    }
+
+    if obj.get("additionalProperties") == Some(&Value::Bool(false)) && !obj.contains_key("patternProperties") {
+        let properties = obj.get("properties").and_then(Value::as_object);
+        let required = obj.get("required").and_then(Value::as_array).into_iter().flatten();
+        for name in required.filter_map(Value::as_str) {
+            if !properties.is_some_and(|properties| properties.contains_key(name)) {
+                issue(
+                    LintRule::UnsatisfiableRequired,
+                    format!("\"{}\" is required but not allowed by additionalProperties: false", name),
+                );
+            }
+        }
+    }
+
+    if let Some(Value::Array(values)) = obj.get_mut("enum") {
+        if values.is_empty() {
+            issue(LintRule::EmptyEnum, "enum has no values, so nothing is valid".to_string());
+        }
+        let duplicates = duplicates(values);
+        for value in &duplicates {
+            issue(LintRule::DuplicateEnum, format!("{} is in enum more than once", value));
+        }
+        if fix && !duplicates.is_empty() {
+            dedup_in_order(values);
+        }
+    }
+
+    for (lower, upper) in BOUNDS {
+        let bound = |keyword: &str| obj.get(keyword).and_then(Value::as_f64);
+        if let (Some(min), Some(max)) = (bound(lower), bound(upper)) {
+            if min > max {
+                issue(
+                    LintRule::ContradictoryBounds,
+                    format!("{} {} is greater than {} {}", lower, obj[*lower], upper, obj[*upper]),
+                );
+            }
+        }
+    }
+
+    match obj.get("type").and_then(Value::as_str) {
+        Some(declared) => {
+            let declared = if declared == "integer" { "number" } else { declared };
+            for (kind, keywords) in TYPE_KEYWORDS.iter().filter(|(kind, _)| *kind != declared) {
+                for keyword in keywords.iter().filter(|keyword| obj.contains_key(**keyword)) {
+                    issue(
+                        LintRule::MisplacedKeyword,
+                        format!("{} only applies to {} values and has no effect with type {}", keyword, kind, obj["type"]),
+                    );
+                }
+            }
+        }
+        None if !obj.contains_key("type") && !TYPE_DEFERRING.iter().any(|keyword| obj.contains_key(*keyword)) => {
+            let implied: Vec<(&str, &str)> = TYPE_KEYWORDS
+                .iter()
+                .filter_map(|(kind, keywords)| {
+                    let keyword = keywords.iter().find(|keyword| obj.contains_key(**keyword))?;
+                    Some((*kind, *keyword))
+                })
+                .collect();
+            if let [(kind, keyword)] = implied[..] {
+                issue(LintRule::MissingType, format!("type is missing; {} implies \"{}\"", keyword, kind));
+                if fix {
+                    obj.insert("type".to_string(), Value::String(kind.to_string()));
+                }
+            }
+        }
+        None => {}
+    }
+
+    for_each_subschema_entry_mut(obj, &mut |path, child| {
+        let tokens: String = path.iter().map(|token| format!("/{}", pointer::escape_token(token))).collect();
+        lint_node(child, &format!("{}{}", at, tokens), fix, issues);
+    });
+}
+
+// Each value listed again after its first occurrence, once
+fn duplicates(values: &[Value]) -> Vec<Value> {
+    let mut found: Vec<Value> = Vec::new();
+    for (index, value) in values.iter().enumerate() {
+        if values[..index].contains(value) && !found.contains(value) {
+            found.push(value.clone());
+        }
+    }
+    found
+}
+
+fn dedup_in_order(values: &mut Vec<Value>) {
+    let mut kept: Vec<Value> = Vec::with_capacity(values.len());
+    for value in values.drain(..) {
+        if !kept.contains(&value) {
+            kept.push(value);
+        }
+    }
+    *values = kept;
+}
@@ -0,0 +1,200 @@
+//! Translations of the titles and descriptions the generators emit. A locale file maps message
+//! ids, e.g. `object.title`, to text; ids it leaves out stay in English.
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::error::AppError;
+use crate::schema::SchemaWalk;
+
+pub const OBJECT_TITLE: &str = "Generated Object Schema";
+pub const OBJECT_DESCRIPTION: &str = "Auto-generated schema from JSON data";
+pub const ARRAY_TITLE: &str = "Generated Array Schema";
+pub const ARRAY_DESCRIPTION: &str = "Auto-generated array schema from JSON data";
+pub const STRING_TITLE: &str = "Generated String Schema";
+pub const INTEGER_TITLE: &str = "Generated Integer Schema";
+pub const NUMBER_TITLE: &str = "Generated Number Schema";
+pub const BOOLEAN_TITLE: &str = "Generated Boolean Schema";
+pub const BOOLEAN_DESCRIPTION: &str = "Boolean value from JSON data";
+
+/// Every text the generators emit, by message id
+pub const MESSAGES: &[(&str, &str)] = &[
+    ("object.title", OBJECT_TITLE),
+    ("object.description", OBJECT_DESCRIPTION),
+    ("array.title", ARRAY_TITLE),
+    ("array.description", ARRAY_DESCRIPTION),
+    ("string.title", STRING_TITLE),
+    ("integer.title", INTEGER_TITLE),
+    ("number.title", NUMBER_TITLE),
+    ("boolean.title", BOOLEAN_TITLE),
+    ("boolean.description", BOOLEAN_DESCRIPTION),
+];
+
+/// Locale files are named `descriptions.<locale>.<ext>`, with one of these extensions
+pub const LOCALE_EXTENSIONS: &[&str] = &["yaml", "yml", "toml", "json"];
+
+/// The translated messages of one locale
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Locale {
+    pub name: String,
+    pub messages: BTreeMap<String, String>,
+}
+
+impl Locale {
+    /// Loads `descriptions.<name>.<ext>` from `dir`. A regional locale such as `de-AT` falls
+    /// back to `descriptions.de.*` when it has no file of its own.
+    pub fn load(dir: &Path, name: &str) -> Result<Self> {
+        let language = name.split(['-', '_']).next().unwrap_or(name);
+        let candidates = [name, language];
+        let path = candidates
+            .iter()
+            .flat_map(|locale| LOCALE_EXTENSIONS.iter().map(move |ext| dir.join(format!("descriptions.{}.{}", locale, ext))))
+            .find(|path| path.is_file())
+            .ok_or_else(|| {
+                AppError::InvalidOption(format!(
+                    "No locale file for {:?} in {:?}; expected descriptions.{}.yaml, .toml or .json",
+                    name, dir, name
+                ))
+            })?;
+        Self::from_file(&path, name)
+    }
+
+    pub fn from_file(path: &Path, name: &str) -> Result<Self> {
+        let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read locale file: {:?}", path))?;
+        let invalid = |message: String| AppError::InvalidConfig {
+            file: Some(path.display().to_string()),
+            message: format!("Invalid locale file {:?}: {}", path, message),
+        };
+        let tree: Value = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&text).map_err(|e| invalid(e.to_string()))?,
+            Some("json") => serde_json::from_str(&text).map_err(|e| invalid(e.to_string()))?,
+            _ => parse_yaml(&text).map_err(invalid)?,
+        };
+
+        let mut messages = BTreeMap::new();
+        flatten(&tree, "", &mut messages);
+        if let Some(unknown) = messages.keys().find(|id| english(id).is_none()) {
+            let known: Vec<&str> = MESSAGES.iter().map(|(id, _)| *id).collect();
+            return Err(invalid(format!("unknown message {:?}; expected one of {}", unknown, known.join(", "))).into());
+        }
+        Ok(Self { name: name.to_string(), messages })
+    }
+
+    /// The translation of message `id`, or its English text
+    pub fn message<'a>(&'a self, id: &str) -> Option<&'a str> {
+        self.messages.get(id).map(String::as_str).or_else(|| english(id))
+    }
+}
+
+pub fn english(id: &str) -> Option<&'static str> {
+    MESSAGES.iter().find(|(message, _)| *message == id).map(|(_, text)| *text)
+}
+
+/// Replaces every generated title and description in `schema` with its translation. Texts
+/// that did not come from the generators, e.g. from `--title`, are left alone.
+pub fn localize(schema: &mut Value, locale: &Locale) {
+    let translations: BTreeMap<&str, &str> = MESSAGES
+        .iter()
+        .filter_map(|(id, text)| Some((*text, locale.messages.get(*id)?.as_str())))
+        .collect();
+    if translations.is_empty() {
+        return;
+    }
+
+    schema.transform(|_, node| {
+        let Value::Object(obj) = node else { return };
+        for keyword in ["title", "description"] {
+            if let Some(Value::String(text)) = obj.get_mut(keyword) {
+                if let Some(translated) = translations.get(text.as_str()) {
+                    *text = translated.to_string();
+                }
+            }
+        }
+    });
+}
+
+fn flatten(value: &Value, prefix: &str, out: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Object(obj) => {
+            for (key, child) in obj {
+                let id = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten(child, &id, out);
+            }
+        }
+        Value::String(text) => {
+            out.insert(prefix.to_string(), text.clone());
+        }
+        other => {
+            out.insert(prefix.to_string(), other.to_string());
+        }
+    }
+}
+
+// The subset of YAML locale files need: nested mappings of plain, single- or double-quoted
+// scalars, and comments
+fn parse_yaml(text: &str) -> std::result::Result<Value, String> {
+    let mut root = serde_json::Map::new();
+    // Indentation and key of each mapping the current line may be nested in
+    let mut parents: Vec<(usize, String)> = Vec::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let content = strip_comment(line);
+        if content.trim().is_empty() || content.trim() == "---" {
+            continue;
+        }
+        let indent = content.len() - content.trim_start().len();
+        let (key, value) = content
+            .trim()
+            .split_once(':')
+            .ok_or_else(|| format!("line {}: expected `key: value`", number + 1))?;
+        let key = unquote(key.trim()).map_err(|e| format!("line {}: {}", number + 1, e))?;
+
+        while parents.last().is_some_and(|(parent_indent, _)| *parent_indent >= indent) {
+            parents.pop();
+        }
+        let mut map = &mut root;
+        for (_, parent) in &parents {
+            map = match map.entry(parent.clone()).or_insert_with(|| Value::Object(Default::default())) {
+                Value::Object(child) => child,
+                _ => return Err(format!("line {}: {:?} is not a mapping", number + 1, parent)),
+            };
+        }
+
+        let value = value.trim();
+        if value.is_empty() {
+            map.insert(key.clone(), Value::Object(Default::default()));
+            parents.push((indent, key));
+        } else {
+            let value = unquote(value).map_err(|e| format!("line {}: {}", number + 1, e))?;
+            map.insert(key, Value::String(value));
+        }
+    }
+    Ok(Value::Object(root))
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    for (index, c) in line.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(open)) if c == open => quote = None,
+            ('#', None) if index == 0 || line[..index].ends_with([' ', '\t']) => return &line[..index],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn unquote(scalar: &str) -> std::result::Result<String, String> {
+    if let Some(inner) = scalar.strip_prefix('"') {
+        let inner = inner.strip_suffix('"').ok_or("unterminated double quote")?;
+        return serde_json::from_str(&format!("\"{}\"", inner)).map_err(|e| e.to_string());
+    }
+    if let Some(inner) = scalar.strip_prefix('\'') {
+        let inner = inner.strip_suffix('\'').ok_or("unterminated single quote")?;
+        return Ok(inner.replace("''", "'"));
+    }
+    Ok(scalar.to_string())
+}
@@ -0,0 +1,448 @@
+use serde_json::{Map, Value};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::span::{Attributes, Id, Record};
+use tracing::{info, Event, Level, Metadata, Subscriber};
+
+use crate::style::{self, Color};
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+static JSON: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// Prints a human-oriented status line on stdout, unless `--quiet` is active.
+///
+/// With `--log-format json` the line becomes an info-level log record instead so that
+/// stdout only carries data.
+#[macro_export]
+macro_rules! status {
+    ($($arg:tt)*) => {
+        $crate::logging::print_status(format_args!($($arg)*))
+    };
+}
+
+/// Like `status!`, for lines reporting that nothing was done
+#[macro_export]
+macro_rules! notice {
+    ($($arg:tt)*) => {
+        $crate::logging::print_notice(format_args!($($arg)*))
+    };
+}
+
+/// Installs the global tracing subscriber, which also receives the `log` records of
+/// dependencies.
+///
+/// `-q` limits logging to errors, each `-V` raises the level from the default of warn
+/// (info for JSON logs). Without either flag `RUST_LOG` still applies, as a level or a list of
+/// `target=level` directives.
+///
+/// Every span is reported when it closes, with its fields, those of the spans around it and
+/// its `duration_ms`. The processing phases of a file are spans named `phase`, so JSON logs
+/// carry one record per file and phase.
+pub fn init(quiet: bool, verbose: u8, format: LogFormat, no_color: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+    JSON.store(format == LogFormat::Json, Ordering::Relaxed);
+
+    let default_level = match format {
+        LogFormat::Text => LevelFilter::WARN,
+        LogFormat::Json => LevelFilter::INFO,
+    };
+    let level = if quiet {
+        LevelFilter::ERROR
+    } else {
+        match verbose {
+            0 => default_level,
+            1 => LevelFilter::INFO,
+            2 => LevelFilter::DEBUG,
+            _ => LevelFilter::TRACE,
+        }
+    };
+    let filter = match std::env::var("RUST_LOG") {
+        Ok(spec) if !quiet && verbose == 0 => Filter::parse(&spec, level),
+        _ => Filter { default: level, targets: Vec::new() },
+    };
+
+    let writer = Arc::new(Writer {
+        filter,
+        json: format == LogFormat::Json,
+        color: !no_color && style::stderr_enabled(),
+    });
+    if WRITER.set(writer.clone()).is_err() {
+        return;
+    }
+    let max_level = writer.filter.max();
+    let subscriber = Logger { writer, next_id: AtomicU64::new(1), spans: Mutex::new(HashMap::new()) };
+    if tracing::subscriber::set_global_default(subscriber).is_ok() && log::set_logger(&LogBridge).is_ok() {
+        log::set_max_level(match max_level.into_level() {
+            None => log::LevelFilter::Off,
+            Some(Level::ERROR) => log::LevelFilter::Error,
+            Some(Level::WARN) => log::LevelFilter::Warn,
+            Some(Level::INFO) => log::LevelFilter::Info,
+            Some(Level::DEBUG) => log::LevelFilter::Debug,
+            Some(Level::TRACE) => log::LevelFilter::Trace,
+        });
+    }
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+pub fn is_json() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
+
+pub fn print_status(args: fmt::Arguments) {
+    print_colored(args, Some(Color::Green));
+}
+
+pub fn print_notice(args: fmt::Arguments) {
+    print_colored(args, Some(Color::Yellow));
+}
+
+/// A status line that carries its own coloring
+pub fn print_line(args: fmt::Arguments) {
+    print_colored(args, None);
+}
+
+fn print_colored(args: fmt::Arguments, color: Option<Color>) {
+    if is_quiet() {
+        return;
+    }
+    if is_json() {
+        info!("{}", args);
+        return;
+    }
+    match color {
+        Some(color) => println!("{}", style::paint(&args.to_string(), color)),
+        None => println!("{}", args),
+    }
+}
+
+static WRITER: OnceLock<Arc<Writer>> = OnceLock::new();
+
+thread_local! {
+    /// The spans entered on this thread, innermost last
+    static CURRENT: RefCell<Vec<Id>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Which levels are logged, by target prefix
+#[derive(Debug, Clone)]
+struct Filter {
+    default: LevelFilter,
+    targets: Vec<(String, LevelFilter)>,
+}
+
+impl Filter {
+    /// Parses `RUST_LOG`, e.g. `info` or `warn,schema_jenerator=debug`; unreadable directives
+    /// are skipped
+    fn parse(spec: &str, default: LevelFilter) -> Self {
+        let mut filter = Filter { default, targets: Vec::new() };
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((target, level)) => {
+                    if let Ok(level) = LevelFilter::from_str(level.trim()) {
+                        filter.targets.push((target.trim().to_string(), level));
+                    }
+                }
+                None => {
+                    if let Ok(level) = LevelFilter::from_str(directive) {
+                        filter.default = level;
+                    }
+                }
+            }
+        }
+        filter
+    }
+
+    fn level_for(&self, target: &str) -> LevelFilter {
+        self.targets
+            .iter()
+            .filter(|(prefix, _)| {
+                target == prefix || target.strip_prefix(prefix.as_str()).is_some_and(|rest| rest.starts_with("::"))
+            })
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    fn enabled(&self, level: Level, target: &str) -> bool {
+        level <= self.level_for(target)
+    }
+
+    fn max(&self) -> LevelFilter {
+        self.targets.iter().map(|(_, level)| *level).fold(self.default, LevelFilter::max)
+    }
+}
+
+/// Formats records as text or JSON lines on stderr
+struct Writer {
+    filter: Filter,
+    json: bool,
+    color: bool,
+}
+
+impl Writer {
+    fn write(&self, level: Level, target: &str, message: &str, fields: Map<String, Value>) {
+        let line = if self.json {
+            let mut line = Map::new();
+            line.insert("timestamp".to_string(), Value::String(timestamp()));
+            line.insert("level".to_string(), Value::String(level.as_str().to_lowercase()));
+            line.insert("target".to_string(), Value::String(target.to_string()));
+            line.insert("message".to_string(), Value::String(message.to_string()));
+            line.extend(fields);
+            Value::Object(line).to_string()
+        } else {
+            let label = format!("{:<5}", level.as_str());
+            let label = match level {
+                Level::ERROR if self.color => style::paint_if(true, &label, Color::Red),
+                Level::WARN if self.color => style::paint_if(true, &label, Color::Yellow),
+                _ => label,
+            };
+            format!("[{} {} {}] {}", timestamp(), label, target, message)
+        };
+        let _ = writeln!(std::io::stderr().lock(), "{}", line);
+    }
+}
+
+struct SpanData {
+    name: &'static str,
+    level: Level,
+    target: &'static str,
+    fields: Map<String, Value>,
+    parent: Option<Id>,
+    started: Instant,
+    refs: usize,
+}
+
+struct Logger {
+    writer: Arc<Writer>,
+    next_id: AtomicU64,
+    spans: Mutex<HashMap<Id, SpanData>>,
+}
+
+impl Logger {
+    /// The fields of `span` and every span around it, inner ones winning
+    fn context(&self, spans: &HashMap<Id, SpanData>, span: Option<&Id>) -> Map<String, Value> {
+        let mut chain = Vec::new();
+        let mut next = span;
+        while let Some(data) = next.and_then(|id| spans.get(id)) {
+            chain.push(&data.fields);
+            next = data.parent.as_ref();
+        }
+        let mut fields = Map::new();
+        for span_fields in chain.into_iter().rev() {
+            fields.extend(span_fields.clone());
+        }
+        fields
+    }
+}
+
+fn current_span() -> Option<Id> {
+    CURRENT.with(|current| current.borrow().last().cloned())
+}
+
+impl Subscriber for Logger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.writer.filter.enabled(*metadata.level(), metadata.target())
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(self.writer.filter.max())
+    }
+
+    fn new_span(&self, attributes: &Attributes<'_>) -> Id {
+        let id = Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed));
+        let mut fields = Map::new();
+        attributes.record(&mut JsonFields(&mut fields));
+        let parent = if attributes.is_contextual() { current_span() } else { attributes.parent().cloned() };
+        let metadata = attributes.metadata();
+        let data = SpanData {
+            name: metadata.name(),
+            level: *metadata.level(),
+            target: metadata.target(),
+            fields,
+            parent,
+            started: Instant::now(),
+            refs: 1,
+        };
+        self.spans.lock().unwrap().insert(id.clone(), data);
+        id
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        if let Some(data) = self.spans.lock().unwrap().get_mut(span) {
+            values.record(&mut JsonFields(&mut data.fields));
+        }
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut own = Map::new();
+        event.record(&mut JsonFields(&mut own));
+        let message = match own.remove("message") {
+            Some(Value::String(message)) => message,
+            Some(other) => other.to_string(),
+            None => String::new(),
+        };
+        let parent = if event.is_contextual() { current_span() } else { event.parent().cloned() };
+        let mut fields = self.context(&self.spans.lock().unwrap(), parent.as_ref());
+        fields.extend(own);
+        let metadata = event.metadata();
+        self.writer.write(*metadata.level(), metadata.target(), &message, fields);
+    }
+
+    fn enter(&self, span: &Id) {
+        CURRENT.with(|current| current.borrow_mut().push(span.clone()));
+    }
+
+    fn exit(&self, span: &Id) {
+        CURRENT.with(|current| {
+            let mut current = current.borrow_mut();
+            if let Some(position) = current.iter().rposition(|id| id == span) {
+                current.remove(position);
+            }
+        });
+    }
+
+    fn clone_span(&self, span: &Id) -> Id {
+        if let Some(data) = self.spans.lock().unwrap().get_mut(span) {
+            data.refs += 1;
+        }
+        span.clone()
+    }
+
+    fn try_close(&self, span: Id) -> bool {
+        let mut spans = self.spans.lock().unwrap();
+        let Some(data) = spans.get_mut(&span) else { return false };
+        data.refs -= 1;
+        if data.refs > 0 {
+            return false;
+        }
+
+        let mut fields = self.context(&spans, Some(&span));
+        let data = spans.remove(&span).expect("the span was just looked up");
+        drop(spans);
+        let phase = match fields.get("phase") {
+            Some(Value::String(phase)) => phase.clone(),
+            _ => data.name.to_string(),
+        };
+        let duration_ms = data.started.elapsed().as_micros() as f64 / 1000.0;
+        let mut message = String::new();
+        if let Some(Value::String(file)) = fields.get("file") {
+            message.push_str(&format!("{}: ", file));
+        }
+        message.push_str(&phase);
+        if let Some(Value::String(result)) = fields.get("result") {
+            message.push_str(&format!(" {}", result));
+        }
+        message.push_str(&format!(" in {:.1}ms", duration_ms));
+        fields.insert("phase".to_string(), Value::String(phase));
+        fields.insert("duration_ms".to_string(), Value::from(duration_ms));
+        self.writer.write(data.level, data.target, &message, fields);
+        true
+    }
+}
+
+/// Receives the `log` records of dependencies
+struct LogBridge;
+
+impl log::Log for LogBridge {
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        WRITER.get().is_some_and(|writer| writer.filter.enabled(tracing_level(metadata.level()), metadata.target()))
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if let Some(writer) = WRITER.get().filter(|_| self.enabled(record.metadata())) {
+            let message = record.args().to_string();
+            writer.write(tracing_level(record.level()), record.target(), &message, Map::new());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+fn tracing_level(level: log::Level) -> Level {
+    match level {
+        log::Level::Error => Level::ERROR,
+        log::Level::Warn => Level::WARN,
+        log::Level::Info => Level::INFO,
+        log::Level::Debug => Level::DEBUG,
+        log::Level::Trace => Level::TRACE,
+    }
+}
+
+struct JsonFields<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonFields<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), Value::Bool(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), Value::String(value.to_string()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name().to_string(), Value::String(format!("{:?}", value)));
+    }
+}
+
+/// The current time as RFC 3339 in UTC, with milliseconds
+pub(crate) fn timestamp() -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = now.as_secs();
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    let time = seconds % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60,
+        now.subsec_millis()
+    )
+}
+
+// Days since 1970-01-01 to a proleptic Gregorian date, after Howard Hinnant's algorithm
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
@@ -1,6 +1,16 @@
-use anyhow::Result;
 use schema_jenerator::cli;
+use schema_jenerator::error::{exit_code_for, ErrorReport};
+use schema_jenerator::logging;
+use schema_jenerator::style::{paint_stderr, Color};
 
-fn main() -> Result<()> {
-    cli::run()
+fn main() {
+    if let Err(error) = cli::run() {
+        if logging::is_json() {
+            let report = serde_json::to_value(ErrorReport::from(&error)).expect("an error report serializes");
+            eprintln!("{}", serde_json::json!({ "level": "error", "error": report }));
+        } else {
+            eprintln!("{} {:?}", paint_stderr("Error:", Color::Red), error);
+        }
+        std::process::exit(exit_code_for(&error));
+    }
 }
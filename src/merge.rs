@@ -0,0 +1,249 @@
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+
+use crate::diff::required_names;
+
+const COMPOSITE_KEYWORDS: &[&str] = &["$ref", "oneOf", "anyOf", "allOf", "not", "if"];
+
+const LOWER_BOUNDS: &[&str] = &[
+    "minimum",
+    "exclusiveMinimum",
+    "minLength",
+    "minItems",
+    "minProperties",
+];
+
+const UPPER_BOUNDS: &[&str] = &[
+    "maximum",
+    "exclusiveMaximum",
+    "maxLength",
+    "maxItems",
+    "maxProperties",
+];
+
+const FIRST_WINS: &[&str] = &[
+    "$schema",
+    "$id",
+    "$comment",
+    "title",
+    "description",
+    "default",
+];
+
+pub fn merge_all(schemas: &[Value]) -> Option<Value> {
+    let mut iter = schemas.iter();
+    let first = iter.next()?.clone();
+    Some(iter.fold(first, |acc, schema| merge_schemas(&acc, schema)))
+}
+
+pub fn merge_schemas(a: &Value, b: &Value) -> Value {
+    if a == b {
+        return a.clone();
+    }
+
+    let (a_obj, b_obj) = match (a, b) {
+        (Value::Bool(true), _) | (_, Value::Bool(true)) => return Value::Bool(true),
+        (Value::Bool(false), other) | (other, Value::Bool(false)) => return other.clone(),
+        (Value::Object(a_obj), Value::Object(b_obj)) => (a_obj, b_obj),
+        _ => return any_of(a, b),
+    };
+
+    if has_composite(a_obj) || has_composite(b_obj) {
+        return any_of(a, b);
+    }
+
+    let (a_types, b_types) = match (type_set(a_obj), type_set(b_obj)) {
+        (Some(a_types), Some(b_types)) => (a_types, b_types),
+        // A schema without "type" accepts every type, which absorbs the other side
+        _ => return Value::Object(Map::new()),
+    };
+
+    let mut merged = Map::new();
+    let keys: Vec<&String> = a_obj
+        .keys()
+        .chain(b_obj.keys().filter(|k| !a_obj.contains_key(k.as_str())))
+        .collect();
+
+    for key in keys {
+        let a_value = a_obj.get(key.as_str());
+        let b_value = b_obj.get(key.as_str());
+
+        if key == "type" {
+            merged.insert(key.clone(), type_value(&a_types, &b_types));
+            continue;
+        }
+
+        if let Some(applies_to) = keyword_type(key) {
+            let a_applies = includes_type(&a_types, applies_to);
+            let b_applies = includes_type(&b_types, applies_to);
+            if a_applies && !b_applies {
+                if let Some(v) = a_value {
+                    merged.insert(key.clone(), v.clone());
+                }
+                continue;
+            }
+            if b_applies && !a_applies {
+                if let Some(v) = b_value {
+                    merged.insert(key.clone(), v.clone());
+                }
+                continue;
+            }
+        }
+
+        if let Some(value) = merge_keyword(key, a_value, b_value) {
+            merged.insert(key.clone(), value);
+        }
+    }
+
+    Value::Object(merged)
+}
+
+fn merge_keyword(key: &str, a: Option<&Value>, b: Option<&Value>) -> Option<Value> {
+    if FIRST_WINS.contains(&key) || key.starts_with("x-") {
+        return a.or(b).cloned();
+    }
+
+    match key {
+        "properties" | "patternProperties" | "$defs" | "definitions" => {
+            let empty = Map::new();
+            let a_map = a.and_then(Value::as_object).unwrap_or(&empty);
+            let b_map = b.and_then(Value::as_object).unwrap_or(&empty);
+            let mut merged = a_map.clone();
+            for (name, b_schema) in b_map {
+                let value = match a_map.get(name) {
+                    Some(a_schema) => merge_schemas(a_schema, b_schema),
+                    None => b_schema.clone(),
+                };
+                merged.insert(name.clone(), value);
+            }
+            Some(Value::Object(merged))
+        }
+        "required" => {
+            let a_required = required_names(a);
+            let b_required = required_names(b);
+            let common: Vec<Value> = a_required
+                .intersection(&b_required)
+                .map(|name| Value::String(name.clone()))
+                .collect();
+            if common.is_empty() {
+                None
+            } else {
+                Some(Value::Array(common))
+            }
+        }
+        "examples" | "enum" => {
+            let (a_values, b_values) = (a?.as_array()?, b?.as_array()?);
+            let mut merged = a_values.clone();
+            for value in b_values {
+                if !merged.contains(value) {
+                    merged.push(value.clone());
+                }
+            }
+            Some(Value::Array(merged))
+        }
+        "additionalProperties" | "items" | "contains" | "propertyNames" => {
+            let (a, b) = (a?, b?);
+            Some(merge_schemas(a, b))
+        }
+        "uniqueItems" => match (a?, b?) {
+            (Value::Bool(true), Value::Bool(true)) => Some(Value::Bool(true)),
+            _ => None,
+        },
+        _ if LOWER_BOUNDS.contains(&key) => pick_bound(a?, b?, |x, y| x <= y),
+        _ if UPPER_BOUNDS.contains(&key) => pick_bound(a?, b?, |x, y| x >= y),
+        _ => {
+            let (a, b) = (a?, b?);
+            if a == b {
+                Some(a.clone())
+            } else {
+                None
+            }
+        }
+    }
+}
+
+fn pick_bound(a: &Value, b: &Value, prefer_a: impl Fn(f64, f64) -> bool) -> Option<Value> {
+    let (x, y) = (a.as_f64()?, b.as_f64()?);
+    if prefer_a(x, y) {
+        Some(a.clone())
+    } else {
+        Some(b.clone())
+    }
+}
+
+fn has_composite(obj: &Map<String, Value>) -> bool {
+    COMPOSITE_KEYWORDS.iter().any(|k| obj.contains_key(*k))
+}
+
+fn any_of(a: &Value, b: &Value) -> Value {
+    let mut branches = Vec::new();
+    for schema in [a, b] {
+        let nested = schema
+            .as_object()
+            .filter(|obj| obj.len() == 1)
+            .and_then(|obj| obj.get("anyOf"))
+            .and_then(Value::as_array);
+        match nested {
+            Some(items) => branches.extend(items.iter().cloned()),
+            None => branches.push(schema.clone()),
+        }
+    }
+
+    let mut unique: Vec<Value> = Vec::new();
+    for branch in branches {
+        if !unique.contains(&branch) {
+            unique.push(branch);
+        }
+    }
+
+    serde_json::json!({ "anyOf": unique })
+}
+
+fn type_set(obj: &Map<String, Value>) -> Option<BTreeSet<String>> {
+    match obj.get("type")? {
+        Value::String(t) => Some(std::iter::once(t.clone()).collect()),
+        Value::Array(ts) => Some(
+            ts.iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+fn type_value(a: &BTreeSet<String>, b: &BTreeSet<String>) -> Value {
+    let mut types: BTreeSet<String> = a.union(b).cloned().collect();
+    if types.contains("number") {
+        types.remove("integer");
+    }
+
+    if types.len() == 1 {
+        Value::String(types.into_iter().next().unwrap())
+    } else {
+        Value::Array(types.into_iter().map(Value::String).collect())
+    }
+}
+
+fn includes_type(types: &BTreeSet<String>, applies_to: &str) -> bool {
+    if applies_to == "number" {
+        types.contains("number") || types.contains("integer")
+    } else {
+        types.contains(applies_to)
+    }
+}
+
+fn keyword_type(key: &str) -> Option<&'static str> {
+    match key {
+        "minLength" | "maxLength" | "pattern" | "format" => Some("string"),
+        "minimum" | "maximum" | "exclusiveMinimum" | "exclusiveMaximum" | "multipleOf" => {
+            Some("number")
+        }
+        "items" | "prefixItems" | "minItems" | "maxItems" | "uniqueItems" | "contains" => {
+            Some("array")
+        }
+        "properties" | "patternProperties" | "required" | "additionalProperties"
+        | "minProperties" | "maxProperties" | "propertyNames" => Some("object"),
+        _ => None,
+    }
+}
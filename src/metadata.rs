@@ -0,0 +1,22 @@
+use serde_json::Value;
+
+/// Names and documentation for the root of a generated schema
+#[derive(Debug, Clone, Default)]
+pub struct SchemaMetadata {
+    /// Name of the root definition, e.g. `User`; also the default title
+    pub root_name: Option<String>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Sets the root `title` and `description`, replacing the generic ones emitted by the generators
+pub fn apply_metadata(schema: &mut Value, metadata: &SchemaMetadata) {
+    let Value::Object(obj) = schema else { return };
+
+    if let Some(title) = metadata.title.as_ref().or(metadata.root_name.as_ref()) {
+        obj.insert("title".to_string(), Value::String(title.clone()));
+    }
+    if let Some(description) = &metadata.description {
+        obj.insert("description".to_string(), Value::String(description.clone()));
+    }
+}
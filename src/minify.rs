@@ -0,0 +1,29 @@
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use crate::schema::keywords::{for_each_subschema_mut, is_annotation};
+
+pub fn minify_schema(schema: &Value) -> Value {
+    let mut minified = schema.clone();
+    strip_annotations(&mut minified);
+    minified
+}
+
+fn strip_annotations(node: &mut Value) {
+    let Value::Object(obj) = node else { return };
+
+    obj.retain(|key, _| !is_annotation(key));
+    for_each_subschema_mut(obj, &mut strip_annotations);
+}
+
+pub fn minified_path(path: &Path) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let minified = match file_name.strip_suffix(".json") {
+        Some(stem) => format!("{}.min.json", stem),
+        None => format!("{}.min", file_name),
+    };
+    path.with_file_name(minified)
+}
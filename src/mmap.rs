@@ -0,0 +1,163 @@
+//! Read-only memory maps of input files, so large documents are parsed straight from the page
+//! cache instead of from a heap copy, and the OS pages them in and out as the parser goes.
+//! Small files, and platforms without `mmap`, are read into memory instead.
+
+use std::fs::File;
+use std::io::{self, BufRead, Read};
+use std::ops::Deref;
+use std::path::Path;
+
+/// Files smaller than this are read rather than mapped
+pub const MMAP_MIN_BYTES: u64 = 1 << 20;
+
+/// A [`MappedReader`] gives the pages behind it back to the OS in steps of this many bytes
+pub const RELEASE_STEP: usize = 16 << 20;
+
+/// The contents of a file, mapped or read
+///
+/// A mapped file must not be truncated while the map is alive: reading the missing pages
+/// kills the process with `SIGBUS`. Inputs are expected to be left alone while a schema is
+/// generated for them.
+#[derive(Debug)]
+pub struct MappedFile {
+    contents: Contents,
+}
+
+#[derive(Debug)]
+enum Contents {
+    #[cfg(unix)]
+    Mapped { ptr: *mut libc::c_void, len: usize },
+    Read(Vec<u8>),
+}
+
+// The map is read-only and owned, like a `Vec<u8>`
+unsafe impl Send for MappedFile {}
+unsafe impl Sync for MappedFile {}
+
+impl MappedFile {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        if len >= MMAP_MIN_BYTES {
+            #[cfg(unix)]
+            if let Ok(len) = usize::try_from(len) {
+                return Self::map(&file, len);
+            }
+        }
+
+        let mut contents = Vec::with_capacity(usize::try_from(len).unwrap_or(0));
+        file.read_to_end(&mut contents)?;
+        Ok(Self { contents: Contents::Read(contents) })
+    }
+
+    #[cfg(unix)]
+    fn map(file: &File, len: usize) -> io::Result<Self> {
+        use std::os::unix::io::AsRawFd;
+
+        // SAFETY: a private read-only map of an open file of `len > 0` bytes, unmapped on drop
+        let ptr = unsafe {
+            libc::mmap(std::ptr::null_mut(), len, libc::PROT_READ, libc::MAP_PRIVATE, file.as_raw_fd(), 0)
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        // Parsers read front to back, so pages behind them can be dropped early. Only a hint.
+        // SAFETY: `ptr` and `len` are the map just created
+        unsafe { libc::madvise(ptr, len, libc::MADV_SEQUENTIAL) };
+        Ok(Self { contents: Contents::Mapped { ptr, len } })
+    }
+
+    /// Whether the file is mapped rather than read into memory
+    pub fn is_mapped(&self) -> bool {
+        !matches!(self.contents, Contents::Read(_))
+    }
+
+    /// Reads the file front to back, releasing the pages already read from the process's
+    /// memory as it goes, so a streaming parser keeps a flat footprint over any file size
+    pub fn reader(&self) -> MappedReader<'_> {
+        MappedReader { file: self, position: 0, released: 0 }
+    }
+
+    // Drops the resident pages of `range`; they are read from the file again if touched
+    #[cfg_attr(not(unix), allow(unused_variables))]
+    fn release(&self, range: std::ops::Range<usize>) {
+        #[cfg(unix)]
+        if let Contents::Mapped { ptr, .. } = self.contents {
+            // SAFETY: `range` is page-aligned and within the map; the pages are private and
+            // never written, so dropping them loses nothing
+            unsafe { libc::madvise(ptr.cast::<u8>().add(range.start).cast(), range.len(), libc::MADV_DONTNEED) };
+        }
+    }
+}
+
+/// Sequential reader over a [`MappedFile`], see [`MappedFile::reader`]
+#[derive(Debug)]
+pub struct MappedReader<'a> {
+    file: &'a MappedFile,
+    position: usize,
+    /// Everything before this offset has been released
+    released: usize,
+}
+
+impl MappedReader<'_> {
+    fn advance(&mut self, amount: usize) {
+        self.position = (self.position + amount).min(self.file.len());
+        if self.file.is_mapped() && self.position - self.released >= RELEASE_STEP {
+            let end = self.position - self.position % page_size();
+            self.file.release(self.released..end);
+            self.released = end;
+        }
+    }
+}
+
+impl Read for MappedReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let amount = (&self.file[self.position..]).read(buf)?;
+        self.advance(amount);
+        Ok(amount)
+    }
+}
+
+impl BufRead for MappedReader<'_> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(&self.file[self.position..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.advance(amount);
+    }
+}
+
+fn page_size() -> usize {
+    #[cfg(unix)]
+    // SAFETY: sysconf has no preconditions
+    if let Ok(size) = usize::try_from(unsafe { libc::sysconf(libc::_SC_PAGESIZE) }) {
+        if size > 0 {
+            return size;
+        }
+    }
+    4096
+}
+
+impl Deref for MappedFile {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match &self.contents {
+            #[cfg(unix)]
+            // SAFETY: the map is `len` readable bytes for as long as `self` lives
+            Contents::Mapped { ptr, len } => unsafe { std::slice::from_raw_parts(*ptr as *const u8, *len) },
+            Contents::Read(contents) => contents,
+        }
+    }
+}
+
+impl Drop for MappedFile {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Contents::Mapped { ptr, len } = self.contents {
+            // SAFETY: unmapping the map created in `open`, which nothing borrows any more
+            unsafe { libc::munmap(ptr, len) };
+        }
+    }
+}
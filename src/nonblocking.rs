@@ -0,0 +1,111 @@
+//! Async variants of the I/O-bound operations, for callers already running on a tokio
+//! runtime. URLs are fetched with reqwest's async client; bundling and pipeline runs, which
+//! mostly read local files, run on tokio's blocking pool so they never stall the caller's
+//! worker threads.
+
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::bundle::{Bundler, Location};
+use crate::error::AppError;
+use crate::pipeline::{BatchInputs, BatchReport, Pipeline, ProcessReport, ProcessRequest, Silent};
+use crate::remote::{self, Fetched, FetchFailure, Lookup, RemoteOptions};
+
+/// Like [`remote::fetch_text_with`], with the same cache, retries and credentials
+pub async fn fetch_text(url: &str, options: &RemoteOptions) -> Result<String> {
+    let mut builder = reqwest::Client::builder();
+    if let Some(timeout) = options.timeout {
+        builder = builder.timeout(timeout);
+    }
+    if let Some(proxy) = remote::proxy(options)? {
+        builder = builder.proxy(proxy);
+    }
+    let client = builder
+        .build()
+        .map_err(|e| AppError::RemoteFetch(format!("{}: {}", url, e)))?;
+
+    let cached = match remote::cache_lookup(url, options) {
+        Lookup::Fresh(body) => return Ok(body),
+        Lookup::Stale(cached) => cached,
+    };
+    let etag = cached.as_ref().and_then(|entry| entry.etag.as_deref());
+
+    let mut attempt = 0;
+    let fetched = loop {
+        match fetch_once(&client, url, options, etag).await {
+            Ok(fetched) => break fetched,
+            Err(failure) => {
+                let delay = remote::retry_delay(failure, attempt, options)?;
+                attempt += 1;
+                tokio::time::sleep(delay).await;
+            }
+        }
+    };
+
+    remote::finish(url, options, fetched, cached)
+}
+
+/// Like [`remote::fetch_json_with`]
+pub async fn fetch_json(url: &str, options: &RemoteOptions) -> Result<Value> {
+    let body = fetch_text(url, options).await?;
+    let value = serde_json::from_str(&body).map_err(|e| AppError::invalid_json(&e))?;
+    Ok(value)
+}
+
+/// Inlines every `$ref` of `schema`, read from `location`, as [`Bundler::bundle`] does
+pub async fn bundle(schema: Value, location: Location, remote: RemoteOptions) -> Result<Value> {
+    blocking(move || Bundler::new(&schema, location).with_remote(remote).bundle()).await
+}
+
+/// Like [`Pipeline::process`]
+pub async fn process(pipeline: &Pipeline, request: ProcessRequest) -> Result<ProcessReport> {
+    let pipeline = pipeline.clone();
+    blocking(move || pipeline.process(&request)).await
+}
+
+/// Like [`Pipeline::process_batch`], without progress reporting
+pub async fn process_batch(pipeline: &Pipeline, inputs: BatchInputs) -> Result<BatchReport> {
+    let pipeline = pipeline.clone();
+    blocking(move || Ok(pipeline.process_batch(inputs, &mut Silent))).await
+}
+
+async fn blocking<T: Send + 'static>(task: impl FnOnce() -> Result<T> + Send + 'static) -> Result<T> {
+    tokio::task::spawn_blocking(task)
+        .await
+        .map_err(|e| AppError::Internal(format!("Background task failed: {}", e)))?
+}
+
+async fn fetch_once(
+    client: &reqwest::Client,
+    url: &str,
+    options: &RemoteOptions,
+    etag: Option<&str>,
+) -> std::result::Result<Fetched, FetchFailure> {
+    let mut request = client.get(url);
+    if let Some(etag) = etag {
+        request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+    }
+    for (name, value) in &options.headers {
+        request = request.header(name, value);
+    }
+    if let Some((user, password)) = &options.basic_auth {
+        request = request.basic_auth(user, password.as_ref());
+    }
+    if let Some(token) = &options.bearer_token {
+        request = request.bearer_auth(token);
+    }
+
+    let response = request.send().await.map_err(|e| FetchFailure::transport(url, e))?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_MODIFIED && etag.is_some() {
+        return Ok(Fetched::NotModified);
+    }
+    if !status.is_success() {
+        return Err(FetchFailure::status(url, status));
+    }
+
+    let etag = remote::response_etag(response.headers());
+    let body = response.text().await.map_err(|e| FetchFailure::transport(url, e))?;
+    Ok(Fetched::Body { body, etag })
+}
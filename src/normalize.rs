@@ -0,0 +1,204 @@
+use serde_json::{Map, Value};
+
+use crate::schema::keywords::for_each_subschema_mut;
+use crate::schema::walk::{for_each_schema_mut, for_each_schema_post_mut};
+
+const SINGLE_BRANCH_KEYWORDS: &[&str] = &["oneOf", "anyOf", "allOf"];
+
+pub fn normalize_schema(schema: &Value) -> Value {
+    let mut normalized = schema.clone();
+    normalize_node(&mut normalized);
+    sort_keys(&normalized)
+}
+
+pub fn canonical_string(value: &Value) -> String {
+    let mut out = serde_json::to_string_pretty(&sort_keys(value)).unwrap_or_default();
+    out.push('\n');
+    out
+}
+
+/// Orders the branches of every `oneOf` and `anyOf` by `type`, then canonically, which does not
+/// change what they accept
+pub fn sort_branches(schema: &mut Value) {
+    for_each_schema_post_mut(schema, &mut |obj| {
+        for keyword in ["oneOf", "anyOf"] {
+            if let Some(Value::Array(branches)) = obj.get_mut(keyword) {
+                branches.sort_by_cached_key(|branch| {
+                    let kind = branch.get("type").map(Value::to_string).unwrap_or_default();
+                    (kind, canonical_string(branch))
+                });
+            }
+        }
+    });
+}
+
+/// Sorts the keywords of every schema object, leaving the entries of `properties` and the
+/// other maps of named subschemas in the order they were inserted. Only makes a difference
+/// with the `preserve-order` feature; otherwise every map is sorted anyway.
+pub fn sort_keywords(schema: &mut Value) {
+    for_each_schema_mut(schema, &mut |obj| {
+        let mut entries: Vec<(String, Value)> = std::mem::take(obj).into_iter().collect();
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        obj.extend(entries);
+    });
+}
+
+/// Sorts the `required` list of every schema object
+pub fn sort_required(schema: &mut Value) {
+    for_each_schema_mut(schema, &mut |obj| {
+        if let Some(Value::Array(required)) = obj.get_mut("required") {
+            required.sort_by(|a, b| a.as_str().cmp(&b.as_str()));
+        }
+    });
+}
+
+fn normalize_node(node: &mut Value) {
+    let Value::Object(obj) = node else { return };
+
+    for_each_subschema_mut(obj, &mut normalize_node);
+
+    for keyword in SINGLE_BRANCH_KEYWORDS {
+        collapse_single_branch(obj, keyword);
+    }
+
+    normalize_type(obj);
+    remove_redundant(obj);
+
+    if let Some(Value::Array(required)) = obj.get_mut("required") {
+        sort_and_dedup(required);
+        if required.is_empty() {
+            obj.remove("required");
+        }
+    }
+
+    if let Some(Value::Array(values)) = obj.get_mut("enum") {
+        sort_and_dedup(values);
+    }
+}
+
+fn collapse_single_branch(obj: &mut Map<String, Value>, keyword: &str) {
+    let branch = match obj.get(keyword) {
+        Some(Value::Array(branches)) if branches.len() == 1 => branches[0].clone(),
+        _ => return,
+    };
+
+    // Pre-2019 drafts ignore keywords next to $ref, so a $ref branch only collapses when alone
+    let ref_conflict = |branch: &Map<String, Value>| branch.contains_key("$ref") && obj.len() > 1;
+
+    match branch {
+        Value::Object(branch)
+            if branch.keys().all(|k| !obj.contains_key(k)) && !ref_conflict(&branch) =>
+        {
+            obj.remove(keyword);
+            obj.extend(branch);
+        }
+        Value::Bool(true) => {
+            obj.remove(keyword);
+        }
+        _ => {}
+    }
+}
+
+fn normalize_type(obj: &mut Map<String, Value>) {
+    let Some(Value::Array(types)) = obj.get_mut("type") else { return };
+
+    sort_and_dedup(types);
+    if types.contains(&Value::String("number".to_string())) {
+        types.retain(|t| t != "integer");
+    }
+    if types.len() == 1 {
+        let single = types.remove(0);
+        obj.insert("type".to_string(), single);
+    }
+}
+
+fn remove_redundant(obj: &mut Map<String, Value>) {
+    for keyword in redundant_keywords(obj) {
+        obj.remove(keyword);
+    }
+}
+
+/// Keywords of `obj` that accept everything they could, e.g. `minLength: 0`, so removing them
+/// does not change what the schema accepts
+pub fn redundant_keywords(obj: &Map<String, Value>) -> Vec<&'static str> {
+    let mut redundant = Vec::new();
+    let zero = Value::from(0);
+    let defaults: [(&str, Value); 4] = [
+        ("minLength", zero.clone()),
+        ("minItems", zero.clone()),
+        ("minProperties", zero),
+        ("uniqueItems", Value::Bool(false)),
+    ];
+    for (keyword, default) in defaults {
+        if obj.get(keyword) == Some(&default) {
+            redundant.push(keyword);
+        }
+    }
+
+    for keyword in ["additionalProperties", "items", "unevaluatedProperties", "unevaluatedItems"] {
+        if obj.get(keyword).is_some_and(accepts_anything) {
+            redundant.push(keyword);
+        }
+    }
+
+    for keyword in ["properties", "patternProperties", "$defs", "definitions"] {
+        if obj.get(keyword).and_then(Value::as_object).is_some_and(Map::is_empty) {
+            redundant.push(keyword);
+        }
+    }
+
+    // Every integer is a multiple of one
+    if obj.get("type").and_then(Value::as_str) == Some("integer")
+        && obj.get("multipleOf").and_then(Value::as_f64) == Some(1.0)
+    {
+        redundant.push("multipleOf");
+    }
+    redundant
+}
+
+fn accepts_anything(schema: &Value) -> bool {
+    match schema {
+        Value::Bool(b) => *b,
+        Value::Object(obj) => obj.is_empty(),
+        _ => false,
+    }
+}
+
+fn sort_and_dedup(values: &mut Vec<Value>) {
+    values.sort_by_key(|v| v.to_string());
+    values.dedup();
+}
+
+/// Sorts the keys of every object in `value`, like [`sort_keys`] without copying
+pub fn sort_keys_in_place(value: &mut Value) {
+    let mut stack = vec![value];
+    while let Some(value) = stack.pop() {
+        match value {
+            Value::Object(obj) => {
+                let mut entries: Vec<(String, Value)> = std::mem::take(obj).into_iter().collect();
+                entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                obj.extend(entries);
+                stack.extend(obj.values_mut());
+            }
+            Value::Array(items) => stack.extend(items.iter_mut()),
+            _ => {}
+        }
+    }
+}
+
+/// A copy of `value` with the keys of every object sorted
+pub fn sort_keys(value: &Value) -> Value {
+    match value {
+        Value::Object(obj) => {
+            let mut keys: Vec<&String> = obj.keys().collect();
+            keys.sort();
+            let mut sorted = Map::new();
+            for key in keys {
+                sorted.insert(key.clone(), sort_keys(&obj[key.as_str()]));
+            }
+            Value::Object(sorted)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(sort_keys).collect()),
+        other => other.clone(),
+    }
+}
@@ -0,0 +1,111 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::samples::walk_with_samples;
+use crate::schema::{generate_schema_with, TierPolicy};
+
+/// How null samples are reflected in the generated schema
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum NullPolicy {
+    /// Nullable values get `"type": [..., "null"]`
+    Union,
+    /// Nullable values get `anyOf` with a `{"type": "null"}` branch
+    #[cfg_attr(feature = "cli", value(name = "anyOf", alias = "any-of"))]
+    #[serde(rename = "anyOf", alias = "any-of")]
+    AnyOf,
+    /// Nullable properties keep their type but are no longer required
+    OmitRequired,
+    /// Null samples are reflected literally, as `{"type": "null"}`
+    #[default]
+    AsIs,
+}
+
+/// Applies `policy` to every value that was null in at least one sample of `data`.
+///
+/// Unless the policy is `as-is`, a value that was null where the schema was generated but
+/// not in other samples, e.g. later elements of an array, gets its schema from those
+/// samples, and a value that was only ever null accepts any type.
+pub fn apply_null_policy(schema: &mut Value, data: &Value, policy: NullPolicy, tier: &dyn TierPolicy) -> Result<()> {
+    if policy == NullPolicy::AsIs {
+        return Ok(());
+    }
+
+    let mut result = Ok(());
+    walk_with_samples(schema, &[data], &mut |obj, samples| {
+        if result.is_err() {
+            return;
+        }
+        if policy == NullPolicy::OmitRequired {
+            relax_required(obj, samples);
+        }
+        if !samples.iter().any(|s| s.is_null()) {
+            return;
+        }
+
+        if is_null_schema(obj) {
+            *obj = match samples.iter().find(|s| !s.is_null()) {
+                Some(sample) => match generate_schema_with(sample, tier) {
+                    Ok(Value::Object(generated)) => generated,
+                    Ok(_) => Map::new(),
+                    Err(e) => {
+                        result = Err(e);
+                        return;
+                    }
+                },
+                None => {
+                    // Only ever null, so nothing is known about the type
+                    obj.remove("type");
+                    return;
+                }
+            };
+        }
+
+        match policy {
+            NullPolicy::Union => widen_type(obj),
+            NullPolicy::AnyOf => {
+                let inner = std::mem::take(obj);
+                obj.insert(
+                    "anyOf".to_string(),
+                    serde_json::json!([Value::Object(inner), { "type": "null" }]),
+                );
+            }
+            NullPolicy::OmitRequired | NullPolicy::AsIs => {}
+        }
+    });
+    result
+}
+
+fn is_null_schema(obj: &Map<String, Value>) -> bool {
+    obj.get("type").and_then(Value::as_str) == Some("null")
+}
+
+fn widen_type(obj: &mut Map<String, Value>) {
+    match obj.get_mut("type") {
+        Some(Value::String(t)) if t != "null" => {
+            let t = std::mem::take(t);
+            obj.insert("type".to_string(), serde_json::json!([t, "null"]));
+        }
+        Some(Value::Array(types)) if !types.iter().any(|t| t == "null") => {
+            types.push(Value::String("null".to_string()));
+        }
+        _ => {}
+    }
+}
+
+fn relax_required(obj: &mut Map<String, Value>, samples: &[&Value]) {
+    let Some(Value::Array(required)) = obj.get_mut("required") else { return };
+
+    required.retain(|name| {
+        let name = name.as_str().unwrap_or_default();
+        !samples
+            .iter()
+            .filter_map(|s| s.as_object())
+            .any(|o| o.get(name).is_some_and(Value::is_null))
+    });
+    if required.is_empty() {
+        obj.remove("required");
+    }
+}
@@ -0,0 +1,259 @@
+use anyhow::Result;
+use serde_json::{Map, Value};
+use std::path::Path;
+
+use crate::error::AppError;
+use crate::normalize::sort_keywords;
+use crate::pointer;
+use crate::schema::keywords::for_each_subschema_mut;
+use crate::spans::{line_indent, value_span};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecFormat {
+    Json,
+    Yaml,
+}
+
+impl SpecFormat {
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml") | Some("yml") => SpecFormat::Yaml,
+            _ => SpecFormat::Json,
+        }
+    }
+}
+
+pub fn update_component(text: &str, format: SpecFormat, component: &str, schema: &Value) -> Result<String> {
+    match format {
+        SpecFormat::Json => {
+            let spec: Value =
+                serde_json::from_str(text).map_err(|e| AppError::invalid_json(&e))?;
+            let schema = to_component_schema(schema, openapi_version(&spec).as_deref());
+            update_json(text, component, &schema)
+        }
+        SpecFormat::Yaml => {
+            let version = text.lines().find_map(|line| {
+                line.strip_prefix("openapi:")
+                    .map(|v| v.trim().trim_matches(['"', '\'']).to_string())
+            });
+            let schema = to_component_schema(schema, version.as_deref());
+            update_yaml(text, component, &schema)
+        }
+    }
+}
+
+fn openapi_version(spec: &Value) -> Option<String> {
+    spec.get("openapi").and_then(Value::as_str).map(str::to_string)
+}
+
+// OpenAPI 3.0 uses its own JSON Schema dialect: no $schema, `nullable` instead of
+// null in type arrays and a single `example` instead of `examples`
+pub fn to_component_schema(schema: &Value, version: Option<&str>) -> Value {
+    let mut schema = schema.clone();
+    if let Value::Object(obj) = &mut schema {
+        obj.remove("$schema");
+    }
+    if version.is_some_and(|v| v.starts_with("3.0")) {
+        downgrade_to_oas30(&mut schema);
+        // `nullable` and `example` take their sorted place, as generated keywords have
+        sort_keywords(&mut schema);
+    }
+    schema
+}
+
+fn downgrade_to_oas30(node: &mut Value) {
+    let Value::Object(obj) = node else { return };
+
+    for_each_subschema_mut(obj, &mut downgrade_to_oas30);
+
+    if let Some(Value::Array(types)) = obj.get("type").cloned() {
+        let non_null: Vec<Value> = types.into_iter().filter(|t| t != "null").collect();
+        if non_null.len() == 1 {
+            obj.insert("type".to_string(), non_null[0].clone());
+            obj.insert("nullable".to_string(), Value::Bool(true));
+        }
+    } else if obj.get("type").and_then(Value::as_str) == Some("null") {
+        obj.remove("type");
+        obj.insert("nullable".to_string(), Value::Bool(true));
+    }
+
+    if let Some(Value::Array(examples)) = obj.remove("examples") {
+        if let Some(first) = examples.into_iter().next() {
+            obj.insert("example".to_string(), first);
+        }
+    }
+}
+
+fn update_json(text: &str, component: &str, schema: &Value) -> Result<String> {
+    let component_pointer = pointer::join("/components/schemas", component);
+
+    if let Some(span) = value_span(text, &component_pointer) {
+        let indent = line_indent(text, span.start);
+        let rendered = indent_lines(&serde_json::to_string_pretty(schema)?, indent);
+        return Ok(format!("{}{}{}", &text[..span.start], rendered, &text[span.end..]));
+    }
+
+    let schemas = value_span(text, "/components/schemas").ok_or_else(|| {
+        AppError::InvalidSpec("Spec has no components.schemas section".to_string())
+    })?;
+    let body = &text[schemas.start + 1..schemas.end - 1];
+    let closing_indent = line_indent(text, schemas.end - 1).to_string();
+    let entry_indent = format!("{}  ", closing_indent);
+    let rendered = indent_lines(&serde_json::to_string_pretty(schema)?, &entry_indent);
+    let entry = format!("{}{}: {}", entry_indent, serde_json::to_string(component)?, rendered);
+
+    let trimmed = body.trim_end();
+    let separator = if trimmed.trim().is_empty() { "" } else { "," };
+    Ok(format!(
+        "{}{}{}\n{}\n{}{}",
+        &text[..schemas.start + 1],
+        trimmed,
+        separator,
+        entry,
+        closing_indent,
+        &text[schemas.end - 1..]
+    ))
+}
+
+fn indent_lines(rendered: &str, indent: &str) -> String {
+    rendered.replace('\n', &format!("\n{}", indent))
+}
+
+fn update_yaml(text: &str, component: &str, schema: &Value) -> Result<String> {
+    let lines: Vec<&str> = text.lines().collect();
+    let missing = || AppError::InvalidSpec("Spec has no components.schemas section".to_string());
+
+    let components = find_key(&lines, 0, lines.len(), 0, "components").ok_or_else(missing)?;
+    let components_end = block_end(&lines, components, 0);
+    let child_indent = first_child_indent(&lines, components, components_end).ok_or_else(missing)?;
+    let schemas = find_key(&lines, components + 1, components_end, child_indent, "schemas").ok_or_else(missing)?;
+    let schemas_end = block_end(&lines, schemas, child_indent);
+    let entry_indent = first_child_indent(&lines, schemas, schemas_end).unwrap_or(child_indent * 2);
+    let step = entry_indent - child_indent;
+
+    let mut rendered = format!("{}{}:", " ".repeat(entry_indent), yaml_key(component));
+    rendered.push_str(&render_yaml_value(schema, entry_indent + step, step));
+
+    let (start, end) = match find_key(&lines, schemas + 1, schemas_end, entry_indent, component) {
+        Some(start) => (start, block_end(&lines, start, entry_indent)),
+        None => (schemas_end, schemas_end),
+    };
+
+    let mut out: Vec<String> = lines[..start].iter().map(|l| l.to_string()).collect();
+    out.push(rendered);
+    out.extend(lines[end..].iter().map(|l| l.to_string()));
+
+    let mut result = out.join("\n");
+    if text.ends_with('\n') {
+        result.push('\n');
+    }
+    Ok(result)
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+fn is_content(line: &str) -> bool {
+    let trimmed = line.trim();
+    !trimmed.is_empty() && !trimmed.starts_with('#')
+}
+
+fn find_key(lines: &[&str], from: usize, to: usize, indent: usize, key: &str) -> Option<usize> {
+    (from..to).find(|&i| {
+        let line = lines[i];
+        if !is_content(line) || indent_of(line) != indent {
+            return false;
+        }
+        let Some((name, _)) = line.trim().split_once(':') else { return false };
+        name.trim().trim_matches(['"', '\'']) == key
+    })
+}
+
+// Index one past the last line belonging to the block opened at `start`, not counting
+// trailing blank lines so that spacing between sibling entries survives the splice
+fn block_end(lines: &[&str], start: usize, indent: usize) -> usize {
+    let mut end = start + 1;
+    let mut last_content = start + 1;
+    while end < lines.len() {
+        let line = lines[end];
+        if is_content(line) {
+            if indent_of(line) <= indent {
+                break;
+            }
+            last_content = end + 1;
+        }
+        end += 1;
+    }
+    last_content
+}
+
+fn first_child_indent(lines: &[&str], start: usize, end: usize) -> Option<usize> {
+    lines[start + 1..end]
+        .iter()
+        .find(|line| is_content(line))
+        .map(|line| indent_of(line))
+}
+
+fn yaml_key(key: &str) -> String {
+    let plain = key
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '$'));
+    if plain {
+        key.to_string()
+    } else {
+        serde_json::to_string(key).unwrap_or_default()
+    }
+}
+
+// Renders the value that follows `key:`; scalars stay on the key's line
+fn render_yaml_value(value: &Value, indent: usize, step: usize) -> String {
+    match value {
+        Value::Object(obj) if !obj.is_empty() => format!("\n{}", render_yaml_map(obj, indent, step)),
+        Value::Array(items) if !items.is_empty() => {
+            let pad = " ".repeat(indent);
+            let mut out = String::new();
+            for item in items {
+                out.push('\n');
+                out.push_str(&pad);
+                out.push('-');
+                match item {
+                    Value::Object(obj) if !obj.is_empty() => {
+                        let nested = render_yaml_map(obj, indent + 2, step);
+                        out.push(' ');
+                        out.push_str(nested.trim_start());
+                    }
+                    Value::Array(inner) if !inner.is_empty() => {
+                        out.push_str(&render_yaml_value(item, indent + 2, step));
+                    }
+                    scalar => {
+                        out.push(' ');
+                        out.push_str(&render_yaml_scalar(scalar));
+                    }
+                }
+            }
+            out
+        }
+        scalar => format!(" {}", render_yaml_scalar(scalar)),
+    }
+}
+
+fn render_yaml_map(obj: &Map<String, Value>, indent: usize, step: usize) -> String {
+    let pad = " ".repeat(indent);
+    obj.iter()
+        .map(|(key, value)| {
+            format!("{}{}:{}", pad, yaml_key(key), render_yaml_value(value, indent + step, step))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_yaml_scalar(value: &Value) -> String {
+    match value {
+        Value::Object(_) => "{}".to_string(),
+        Value::Array(_) => "[]".to_string(),
+        other => other.to_string(),
+    }
+}
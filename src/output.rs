@@ -0,0 +1,98 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::AppError;
+use crate::schema::{SchemaDraft, SchemaOutputTier};
+
+pub const TEMPLATE_VARIABLES: &[&str] = &["stem", "name", "ext", "dir", "tier", "draft"];
+
+/// Values substituted into an `--output-template`
+#[derive(Debug, Clone)]
+pub struct TemplateContext<'a> {
+    pub input: &'a Path,
+    /// Root definition name; defaults to the input's stem
+    pub name: Option<&'a str>,
+    /// Directory the schema would be written to without a template
+    pub dir: &'a Path,
+    pub tier: &'a SchemaOutputTier,
+    pub draft: SchemaDraft,
+}
+
+impl TemplateContext<'_> {
+    fn variable(&self, name: &str) -> Option<String> {
+        let lossy = |s: Option<&std::ffi::OsStr>| s.map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        match name {
+            "stem" => Some(lossy(self.input.file_stem())),
+            "name" => Some(self.name.map(str::to_string).unwrap_or_else(|| lossy(self.input.file_stem()))),
+            "ext" => Some(lossy(self.input.extension())),
+            "dir" if self.dir.as_os_str().is_empty() => Some(".".to_string()),
+            "dir" => Some(self.dir.to_string_lossy().to_string()),
+            "tier" => Some(format!("{:?}", self.tier).to_lowercase()),
+            "draft" => Some(self.draft.name().to_string()),
+            _ => None,
+        }
+    }
+}
+
+/// Expands `{variable}` placeholders, e.g. `{dir}/{stem}.{tier}.schema.json`.
+///
+/// Unknown variables and unbalanced braces are errors rather than being written literally.
+pub fn render_output_template(template: &str, context: &TemplateContext) -> Result<PathBuf> {
+    let mut rendered = String::new();
+    let mut rest = template;
+
+    while let Some(open) = rest.find('{') {
+        rendered.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let close = after.find('}').ok_or_else(|| {
+            AppError::InvalidOption(format!("Unclosed '{{' in output template: {}", template))
+        })?;
+        let name = &after[..close];
+        let value = context.variable(name).ok_or_else(|| {
+            AppError::InvalidOption(format!(
+                "Unknown output template variable {{{}}}; expected one of: {}",
+                name,
+                TEMPLATE_VARIABLES.join(", ")
+            ))
+        })?;
+        rendered.push_str(&value);
+        rest = &after[close + 1..];
+    }
+    rendered.push_str(rest);
+
+    Ok(PathBuf::from(rendered))
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WriteOptions {
+    /// Refuse to replace an existing file
+    pub no_clobber: bool,
+    /// Leave an existing file, and its mtime, alone when the new content is identical
+    pub skip_unchanged: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteOutcome {
+    Written,
+    Unchanged,
+}
+
+/// Writes `content` to `path`, creating parent directories as needed.
+pub fn write_output(path: &Path, content: &str, options: WriteOptions) -> Result<WriteOutcome> {
+    if path.exists() {
+        if options.skip_unchanged && fs::read(path).is_ok_and(|existing| existing == content.as_bytes()) {
+            return Ok(WriteOutcome::Unchanged);
+        }
+        if options.no_clobber {
+            return Err(AppError::OutputExists(path.display().to_string()).into());
+        }
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create output directory: {:?}", parent))?;
+    }
+    fs::write(path, content).with_context(|| format!("Failed to write schema to file: {:?}", path))?;
+    Ok(WriteOutcome::Written)
+}
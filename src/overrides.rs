@@ -0,0 +1,161 @@
+use anyhow::Result;
+use glob::Pattern;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+use crate::error::AppError;
+use crate::filter::PointerGlob;
+
+/// Changes made to a generated field, from the `[fields]` table of the config
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FieldOverride {
+    /// Replaces the inferred type and drops the constraints inferred for it
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Adds the field to, or removes it from, its parent's `required`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+/// Keywords kept when a field's type is overridden
+const ANNOTATIONS: &[&str] = &["title", "description", "$comment"];
+
+enum Selector {
+    /// A glob over the data path, with `*` standing for any array element
+    Path(PointerGlob),
+    /// A glob over property names, anywhere in the document
+    Name(Pattern),
+}
+
+impl Selector {
+    fn parse(key: &str) -> Result<Self> {
+        if key.starts_with('/') || key.starts_with("**") {
+            return Ok(Selector::Path(PointerGlob::parse(key)?));
+        }
+        Pattern::new(key)
+            .map(Selector::Name)
+            .map_err(|e| {
+                AppError::InvalidPattern { pattern: key.to_string(), message: format!("Invalid field pattern {}: {}", key, e) }
+                    .into()
+            })
+    }
+
+    fn matches(&self, path: &[String], is_property: bool) -> bool {
+        match self {
+            Selector::Path(glob) => glob.matches(path),
+            Selector::Name(pattern) => is_property && path.last().is_some_and(|name| pattern.matches(name)),
+        }
+    }
+}
+
+/// Applies the `[fields]` overrides to `schema`. Name patterns are applied before data
+/// paths, so an override for a specific path wins over one for a name.
+pub fn apply_field_overrides(schema: &mut Value, fields: &BTreeMap<String, FieldOverride>) -> Result<()> {
+    if fields.is_empty() {
+        return Ok(());
+    }
+
+    let mut rules = fields
+        .iter()
+        .map(|(key, field)| Ok((Selector::parse(key)?, field)))
+        .collect::<Result<Vec<_>>>()?;
+    rules.sort_by_key(|(selector, _)| matches!(selector, Selector::Path(_)));
+
+    visit(schema, &mut Vec::new(), Some(false), &rules);
+    Ok(())
+}
+
+/// `field` is `Some(is_property)` for the node describing the value at `path`, and `None`
+/// for `anyOf`/`oneOf` branches below it, which are only searched for nested fields
+fn visit(node: &mut Value, path: &mut Vec<String>, field: Option<bool>, rules: &[(Selector, &FieldOverride)]) {
+    let Value::Object(obj) = node else { return };
+
+    for branch in ["anyOf", "oneOf"] {
+        if let Some(Value::Array(branches)) = obj.get_mut(branch) {
+            for branch in branches {
+                visit(branch, path, None, rules);
+            }
+        }
+    }
+
+    if let Some(Value::Object(properties)) = obj.get_mut("properties") {
+        let mut required_changes = Vec::new();
+        for (name, child) in properties.iter_mut() {
+            path.push(name.clone());
+            visit(child, path, Some(true), rules);
+            for (selector, field) in rules {
+                if let Some(required) = field.required.filter(|_| selector.matches(path, true)) {
+                    required_changes.push((name.clone(), required));
+                }
+            }
+            path.pop();
+        }
+        for (name, required) in required_changes {
+            set_required(obj, &name, required);
+        }
+    }
+
+    match obj.get_mut("items") {
+        Some(Value::Array(items)) => {
+            path.push("*".to_string());
+            for item in items {
+                visit(item, path, Some(false), rules);
+            }
+            path.pop();
+        }
+        Some(items) => {
+            path.push("*".to_string());
+            visit(items, path, Some(false), rules);
+            path.pop();
+        }
+        None => {}
+    }
+
+    let Some(is_property) = field else { return };
+    for (selector, field) in rules {
+        if selector.matches(path, is_property) {
+            apply_override(obj, field);
+        }
+    }
+}
+
+fn apply_override(obj: &mut Map<String, Value>, field: &FieldOverride) {
+    if let Some(kind) = &field.kind {
+        if obj.get("type").and_then(Value::as_str) != Some(kind) {
+            obj.retain(|key, _| ANNOTATIONS.contains(&key.as_str()));
+            obj.insert("type".to_string(), Value::String(kind.clone()));
+        }
+    }
+    if let Some(format) = &field.format {
+        obj.insert("format".to_string(), Value::String(format.clone()));
+    }
+    if let Some(description) = &field.description {
+        obj.insert("description".to_string(), Value::String(description.clone()));
+    }
+    if let Some(title) = &field.title {
+        obj.insert("title".to_string(), Value::String(title.clone()));
+    }
+}
+
+fn set_required(obj: &mut Map<String, Value>, name: &str, required: bool) {
+    let mut names: Vec<Value> = match obj.remove("required") {
+        Some(Value::Array(names)) => names,
+        _ => Vec::new(),
+    };
+    if !required {
+        names.retain(|n| n != name);
+    } else if !names.iter().any(|n| n == name) {
+        names.push(Value::String(name.to_string()));
+    }
+    if !names.is_empty() {
+        obj.insert("required".to_string(), Value::Array(names));
+    }
+}
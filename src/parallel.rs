@@ -0,0 +1,80 @@
+//! Order-preserving parallel maps for the generators, on scoped threads. Work runs on the
+//! calling thread unless [`with_jobs`] allowed more than one, and always does inside a
+//! worker, so nested arrays and objects do not multiply the threads.
+//!
+//! Results come back in input order, so a schema generated with any number of jobs is
+//! identical to the sequential one.
+
+use std::cell::Cell;
+use std::num::NonZeroUsize;
+use std::panic;
+use std::thread;
+
+use crate::limits;
+
+/// Below this many items the work stays on the calling thread
+pub const MIN_PARALLEL_ITEMS: usize = 64;
+
+thread_local! {
+    static JOBS: Cell<usize> = const { Cell::new(1) };
+}
+
+/// Runs `f` with up to `jobs` threads for the parallel maps it makes; 0 means one per core
+pub fn with_jobs<R>(jobs: usize, f: impl FnOnce() -> R) -> R {
+    let jobs = if jobs == 0 { available_jobs() } else { jobs };
+    let previous = JOBS.with(|cell| cell.replace(jobs));
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(f));
+    JOBS.with(|cell| cell.set(previous));
+    result.unwrap_or_else(|payload| panic::resume_unwind(payload))
+}
+
+/// Threads the current parallel maps may use
+pub fn jobs() -> usize {
+    JOBS.with(Cell::get)
+}
+
+pub fn available_jobs() -> usize {
+    thread::available_parallelism().map_or(1, NonZeroUsize::get)
+}
+
+/// Whether a parallel map over `items` items starts threads rather than running on the
+/// calling thread
+pub fn runs_in_parallel(items: usize) -> bool {
+    threads_for(items) > 1
+}
+
+fn threads_for(items: usize) -> usize {
+    jobs().min(items / MIN_PARALLEL_ITEMS)
+}
+
+/// `f` over consecutive chunks of `items`, one chunk per thread, with the results in order.
+/// Without parallelism the whole slice is one chunk.
+pub fn par_chunks<T: Sync, R: Send>(items: &[T], f: impl Fn(&[T]) -> R + Sync) -> Vec<R> {
+    let jobs = threads_for(items.len());
+    if jobs <= 1 {
+        return vec![f(items)];
+    }
+
+    let size = items.len().div_ceil(jobs);
+    let f = &f;
+    // Workers stop at the caller's deadline too
+    let deadline = limits::deadline();
+    thread::scope(|scope| {
+        let workers: Vec<_> = items
+            .chunks(size)
+            .map(|chunk| scope.spawn(move || limits::with_deadline(deadline, || f(chunk))))
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().unwrap_or_else(|payload| panic::resume_unwind(payload)))
+            .collect()
+    })
+}
+
+/// `f` over every item, like `items.iter().map(f).collect()`
+pub fn par_map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    par_chunks(items, |chunk| chunk.iter().map(&f).collect::<Vec<R>>())
+        .into_iter()
+        .flatten()
+        .collect()
+}
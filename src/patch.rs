@@ -0,0 +1,117 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+use crate::pointer;
+
+/// How the changes to a regenerated schema are written next to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum PatchFormat {
+    /// RFC 6902 JSON Patch, in `<stem>.patch.json`
+    JsonPatch,
+    /// RFC 7386 JSON Merge Patch, in `<stem>.merge-patch.json`
+    MergePatch,
+}
+
+impl PatchFormat {
+    /// The patch from `from`, or from no document at all, to `to`
+    pub fn patch(self, from: Option<&Value>, to: &Value) -> Value {
+        match (self, from) {
+            (PatchFormat::JsonPatch, Some(from)) => serde_json::to_value(json_patch(from, to)).unwrap_or_default(),
+            (PatchFormat::JsonPatch, None) => {
+                let add = PatchOperation::Add { path: String::new(), value: to.clone() };
+                serde_json::to_value(vec![add]).unwrap_or_default()
+            }
+            (PatchFormat::MergePatch, Some(from)) => merge_patch(from, to),
+            (PatchFormat::MergePatch, None) => to.clone(),
+        }
+    }
+
+    /// Where the patch for the schema at `path` goes
+    pub fn path(self, path: &Path) -> PathBuf {
+        let suffix = match self {
+            PatchFormat::JsonPatch => "patch",
+            PatchFormat::MergePatch => "merge-patch",
+        };
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let patch = match file_name.strip_suffix(".json") {
+            Some(stem) => format!("{}.{}.json", stem, suffix),
+            None => format!("{}.{}", file_name, suffix),
+        };
+        path.with_file_name(patch)
+    }
+}
+
+/// One RFC 6902 operation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+}
+
+/// The operations that turn `from` into `to`, applied in order. Array elements are compared
+/// by index, so an insertion replaces every element after it.
+pub fn json_patch(from: &Value, to: &Value) -> Vec<PatchOperation> {
+    let mut operations = Vec::new();
+    diff(from, to, "", &mut operations);
+    operations
+}
+
+fn diff(from: &Value, to: &Value, at: &str, operations: &mut Vec<PatchOperation>) {
+    match (from, to) {
+        _ if from == to => {}
+        (Value::Object(from), Value::Object(to)) => {
+            for key in from.keys().filter(|key| !to.contains_key(*key)) {
+                operations.push(PatchOperation::Remove { path: pointer::join(at, key) });
+            }
+            for (key, value) in to {
+                match from.get(key) {
+                    Some(previous) => diff(previous, value, &pointer::join(at, key), operations),
+                    None => operations.push(PatchOperation::Add { path: pointer::join(at, key), value: value.clone() }),
+                }
+            }
+        }
+        (Value::Array(from), Value::Array(to)) => {
+            for (index, (previous, value)) in from.iter().zip(to).enumerate() {
+                diff(previous, value, &pointer::join(at, &index.to_string()), operations);
+            }
+            for (index, value) in to.iter().enumerate().skip(from.len()) {
+                operations.push(PatchOperation::Add { path: pointer::join(at, &index.to_string()), value: value.clone() });
+            }
+            // From the end, so the indices of the elements still to remove do not shift
+            for index in (to.len()..from.len()).rev() {
+                operations.push(PatchOperation::Remove { path: pointer::join(at, &index.to_string()) });
+            }
+        }
+        _ => operations.push(PatchOperation::Replace { path: at.to_string(), value: to.clone() }),
+    }
+}
+
+/// The merge patch that turns `from` into `to`. Removed members are `null`, so a member of `to`
+/// that changed to `null` is removed instead when the patch is applied.
+pub fn merge_patch(from: &Value, to: &Value) -> Value {
+    let (Value::Object(from), Value::Object(to)) = (from, to) else { return to.clone() };
+
+    let mut patch = Map::new();
+    for key in from.keys().filter(|key| !to.contains_key(*key)) {
+        patch.insert(key.clone(), Value::Null);
+    }
+    for (key, value) in to {
+        match from.get(key) {
+            Some(previous) if previous == value => {}
+            Some(previous) => {
+                patch.insert(key.clone(), merge_patch(previous, value));
+            }
+            None => {
+                patch.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    Value::Object(patch)
+}
@@ -0,0 +1,902 @@
+use anyhow::{Context, Result};
+use glob::glob;
+use serde_json::{json, Map, Value};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::{field, info, info_span, warn, Span};
+
+use crate::additional::AdditionalProperties;
+use crate::bench::peak_memory_kb;
+use crate::config::Config;
+use crate::diff::diff_schemas;
+use crate::editor::{inject_schema_reference, schema_reference};
+use crate::error::AppError;
+use crate::explain::{annotate_sources, explain_schema, ExplainReport, SourceAnnotation};
+use crate::filter::PathFilter;
+use crate::generator::{order_keys, ExamplePolicy, GeneratorOptions, NodeHook, SchemaBuilder, SchemaGenerator};
+use crate::ignore::IgnoreRules;
+use crate::infer::InferenceFamily;
+use crate::intern::InternStats;
+use crate::limits::ResourceLimits;
+use crate::metadata::SchemaMetadata;
+use crate::minify::{minified_path, minify_schema};
+use crate::mmap::MappedFile;
+use crate::nulls::NullPolicy;
+use crate::locale::Locale;
+use crate::logging;
+use crate::output::{render_output_template, write_output, TemplateContext, WriteOptions, WriteOutcome};
+use crate::patch::PatchFormat;
+use crate::provenance::{embed_provenance, sample_count, Provenance, Source};
+use crate::remote::{self, RemoteOptions};
+use crate::required::RequiredMode;
+use crate::sampling::sample_arrays;
+use crate::schema::{SchemaDraft, SchemaOutputTier};
+use crate::source_map::{source_map_path, SourceMap};
+use crate::streaming::{is_ndjson, stream_schema_file, verify_stream_file};
+use crate::tiers::TierChoice;
+use crate::update::update_schema;
+use crate::validation::{validate_json_against_schema, validate_schema_as, ValidationReport};
+
+/// Settings shared by every input of a run. Options left unset fall back to the config found
+/// for each input, as on the command line.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessOptions {
+    /// Config file for every input, instead of discovering one from each input's directory
+    pub config: Option<PathBuf>,
+    /// Config profile to apply
+    pub profile: Option<String>,
+    pub tier: Option<TierChoice>,
+    pub draft: Option<SchemaDraft>,
+    pub pretty: bool,
+    pub validate: bool,
+    /// Check the input against the schema generated from it, and fail when it does not match
+    pub verify: bool,
+    /// Merge into the schema already at the output path instead of replacing it, see
+    /// [`update_schema`]
+    pub update: bool,
+    /// Also write the changes from the schema already at the output path in these formats
+    pub patch: Vec<PatchFormat>,
+    /// Write only the `patch` files, leaving the schema at the output path as it is
+    pub patch_only: bool,
+    /// Golden file the generated schema must match, e.g. a committed snapshot in CI
+    pub expect: Option<PathBuf>,
+    /// Overwrite the `expect` golden file with the generated schema instead of comparing
+    pub update_golden: bool,
+    /// Point the `$schema` of each input at its generated schema, for validation and
+    /// completion in editors
+    pub inject_schema: bool,
+    /// Output path for a single input, instead of `<stem>.schema.json` next to it
+    pub output: Option<PathBuf>,
+    pub output_dir: Option<PathBuf>,
+    /// e.g. `{dir}/{stem}.{tier}.schema.json`, see [`render_output_template`]
+    pub output_template: Option<String>,
+    /// JSON Pointer of the subtree to generate the schema for
+    pub pointer: Option<String>,
+    /// Pointer globs of the input fields to keep
+    pub include: Vec<String>,
+    /// Pointer globs of the input fields to leave out, on top of the config's `exclude`
+    pub exclude: Vec<String>,
+    pub array_sample: Option<usize>,
+    pub sample_seed: Option<u64>,
+    pub null_policy: NullPolicy,
+    pub required_mode: Option<RequiredMode>,
+    pub additional_properties: Option<AdditionalProperties>,
+    pub max_depth: Option<usize>,
+    /// Caps on input size, values, depth and time, over the config's `[limits]`
+    pub limits: ResourceLimits,
+    pub infer: Vec<InferenceFamily>,
+    pub no_infer: Vec<InferenceFamily>,
+    pub no_examples: bool,
+    pub examples_limit: Option<usize>,
+    pub deterministic: bool,
+    /// Emit properties in the order of the input; needs the `preserve-order` feature
+    pub preserve_order: bool,
+    /// Threads for inferring large arrays and wide objects; 0 means one per core
+    pub jobs: usize,
+    /// Record on each subschema the data pointers and heuristics it came from
+    pub source_annotations: Option<SourceAnnotation>,
+    /// Embed an `x-generated-by` block describing how the schema was produced
+    pub provenance: bool,
+    pub metadata: SchemaMetadata,
+    /// Language of the generated titles and descriptions, e.g. `de`, instead of the config's
+    pub locale: Option<String>,
+    /// Directory of the `descriptions.<locale>.yaml` files, instead of the current directory
+    pub locale_dir: Option<PathBuf>,
+    pub hooks: Vec<NodeHook>,
+    /// Read NDJSON or a top-level array one record at a time instead of parsing the input
+    /// whole; always on for `.ndjson` and `.jsonl` files
+    pub stream: bool,
+    /// Also write a `.min.json` copy
+    pub minify: bool,
+    /// Also write a `.map.json` source map from schema pointers to data pointers
+    pub source_map: bool,
+    /// Explain which rule produced each keyword
+    pub explain: bool,
+    pub write: WriteOptions,
+    /// Generate and validate without writing anything
+    pub dry_run: bool,
+    /// Stop a batch at the first input that fails
+    pub fail_fast: bool,
+    pub remote: RemoteOptions,
+}
+
+/// One input to process
+#[derive(Debug, Clone)]
+pub struct ProcessRequest {
+    /// A file path or an `http(s)://` URL
+    pub input: PathBuf,
+    /// Directory the input's path is taken relative to below an output directory
+    pub base: PathBuf,
+}
+
+impl ProcessRequest {
+    pub fn new(input: impl Into<PathBuf>) -> Self {
+        let input = input.into();
+        let base = input.parent().map(Path::to_path_buf).unwrap_or_default();
+        Self { input, base }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileOutcome {
+    Written,
+    /// Identical content was already there and left alone
+    Unchanged,
+    /// Nothing was written because of `dry_run`
+    DryRun,
+    /// The file was left as it is because of `patch_only`
+    Kept,
+}
+
+/// A file the pipeline wrote, or would have written
+#[derive(Debug, Clone)]
+pub struct OutputFile {
+    pub path: PathBuf,
+    pub content: String,
+    pub outcome: FileOutcome,
+}
+
+/// What processing one input produced
+#[derive(Debug, Clone)]
+pub struct ProcessReport {
+    pub input: PathBuf,
+    pub schema: Value,
+    pub output: OutputFile,
+    /// The `.min.json` copy, with `minify`
+    pub minified: Option<OutputFile>,
+    /// The `.map.json` sidecar, with `source_map`
+    pub source_map: Option<OutputFile>,
+    /// One file per `patch` format
+    pub patches: Vec<OutputFile>,
+    /// The golden file, with `expect` and `update_golden`
+    pub golden: Option<OutputFile>,
+    /// The input with its `$schema` pointing at the schema, with `inject_schema`
+    pub injected: Option<OutputFile>,
+    /// The meta-schema check, always valid, when the config asks for validation
+    pub validation: Option<ValidationReport>,
+    /// The check of the input against its schema, always valid, with `verify`
+    pub verification: Option<ValidationReport>,
+    /// With `explain`
+    pub explanation: Option<ExplainReport>,
+    /// Strings shared between the records of a streamed input
+    pub interned: Option<InternStats>,
+}
+
+/// The inputs of a batch, before `.schemaignore` rules are applied
+#[derive(Debug, Clone)]
+pub struct BatchInputs {
+    /// Directory output paths are taken relative to
+    pub base: PathBuf,
+    /// Input paths, or errors for entries that could not be listed
+    pub entries: Vec<std::result::Result<PathBuf, String>>,
+    pub ignore: IgnoreRules,
+}
+
+/// Notified as a batch goes, e.g. to draw progress
+pub trait BatchObserver {
+    /// Called with the number of inputs left after ignore rules, before the first one
+    fn begin(&mut self, _total: usize) {}
+
+    fn started(&mut self, _input: &Path) {}
+
+    fn finished(&mut self, _input: &Path, _result: &Result<ProcessReport>, _elapsed: Duration) {}
+}
+
+/// A [`BatchObserver`] that ignores everything
+pub struct Silent;
+
+impl BatchObserver for Silent {}
+
+/// What processing a batch produced
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    pub reports: Vec<ProcessReport>,
+    /// One message per input that failed
+    pub errors: Vec<String>,
+    /// Inputs left after ignore rules
+    pub total: usize,
+    /// Inputs skipped because of ignore rules
+    pub ignored: usize,
+    pub elapsed: Duration,
+    /// Peak resident set size of the process by the end of the batch, where the platform
+    /// reports it
+    pub peak_memory_kb: Option<u64>,
+    /// Strings shared between records, over every streamed input
+    pub interned: InternStats,
+}
+
+impl BatchReport {
+    /// Inputs not processed because the batch stopped early
+    pub fn not_run(&self) -> usize {
+        self.total - self.reports.len() - self.errors.len()
+    }
+}
+
+/// What the options and the config found for an input come to
+struct Settings {
+    config: Config,
+    tier: SchemaOutputTier,
+    draft: SchemaDraft,
+    limits: ResourceLimits,
+    generator: SchemaGenerator,
+    filter: Option<PathFilter>,
+}
+
+impl Settings {
+    // Filters the fields of a document, then samples its arrays
+    fn prepare(&self, value: Value, options: &ProcessOptions) -> Value {
+        sample(self.filter_fields(value), options)
+    }
+
+    fn filter_fields(&self, value: Value) -> Value {
+        match &self.filter {
+            Some(filter) => filter.apply(&value),
+            None => value,
+        }
+    }
+}
+
+fn sample(value: Value, options: &ProcessOptions) -> Value {
+    match options.array_sample {
+        Some(limit) => sample_arrays(&value, limit, options.sample_seed),
+        None => value,
+    }
+}
+
+/// The read → generate → validate → write flow behind the command line
+#[derive(Debug, Clone, Default)]
+pub struct Pipeline {
+    options: ProcessOptions,
+}
+
+impl Pipeline {
+    pub fn new(options: ProcessOptions) -> Self {
+        Self { options }
+    }
+
+    pub fn options(&self) -> &ProcessOptions {
+        &self.options
+    }
+
+    /// The configured config file, or else the config files discovered from the input's directory up
+    pub fn config_for(&self, input: &Path) -> Result<Config> {
+        let target = input.to_str().filter(|location| remote::is_url(location)).is_none().then_some(input);
+        self.load_config(target.and_then(Path::parent).unwrap_or(Path::new("")), target)
+    }
+
+    pub fn load_config(&self, dir: &Path, target: Option<&Path>) -> Result<Config> {
+        match &self.options.config {
+            Some(path) => Config::load_with_profile(path, target, self.options.profile.as_deref()),
+            None => Config::discover(dir, target, self.options.profile.as_deref()),
+        }
+    }
+
+    pub fn process(&self, request: &ProcessRequest) -> Result<ProcessReport> {
+        let options = &self.options;
+        let input = &request.input;
+        let _file = info_span!("file", file = %input.display()).entered();
+        info!("Processing input file: {:?}", input);
+        let settings = self.settings(input)?;
+        let Settings { config, tier, limits, generator, .. } = &settings;
+        let draft = settings.draft;
+        let output_dir = options.output_dir.as_ref().or(config.output_directory.as_ref());
+        let url = input.to_str().filter(|location| remote::is_url(location));
+
+        // The input document, kept only when it was parsed whole, and for `verify` the document
+        // before arrays were sampled
+        // Files over the size limit are streamed, so only the records that fit are read
+        let oversized = url.is_none()
+            && limits.max_input_size.is_some_and(|max| input.metadata().is_ok_and(|meta| meta.len() > max));
+        let (mut schema, samples, source, json_value, unsampled, interned) = if url.is_none() && (options.stream || is_ndjson(input) || oversized) {
+            check_streamable(options).with_context(|| {
+                format!("{} is over the input size limit, so it can only be sampled by streaming", input.display())
+            })?;
+            let streamed = timed("stream", || stream_schema_file(input, generator, limits.max_input_size, |value| settings.prepare(value, options)))?;
+            info!("Streamed {} record(s) from {:?}", streamed.records, input);
+            (streamed.schema, streamed.records, None, None, None, Some(streamed.interned))
+        } else {
+            let json_value = timed("load", || match url {
+                Some(url) => remote::fetch_json_with(url, &options.remote),
+                None => load_json(input),
+            })?;
+            let source = options
+                .provenance
+                .then(|| Source::new(input.display().to_string(), &json_value));
+            let json_value = settings.filter_fields(self.select(input, json_value)?);
+            let unsampled = (options.verify && options.array_sample.is_some()).then(|| json_value.clone());
+            let json_value = sample(json_value, options);
+            let schema = timed("generate", || generator.generate(&json_value))?;
+            (schema, sample_count(&json_value), source, Some(json_value), unsampled, None)
+        };
+        let default_path = match (output_dir, url) {
+            // Schemas for URL inputs go to the current (or output) directory
+            (Some(dir), Some(_)) => dir.join(schema_file_name(input)),
+            (None, Some(_)) => PathBuf::from(schema_file_name(input)),
+            (Some(dir), None) => {
+                let relative = input.strip_prefix(&request.base).unwrap_or(input);
+                dir.join(relative).with_file_name(schema_file_name(input))
+            }
+            (None, None) => input.with_file_name(schema_file_name(input)),
+        };
+
+        let output_path = match (&options.output, &options.output_template) {
+            (Some(path), _) => path.clone(),
+            (None, Some(template)) => {
+                let context = TemplateContext {
+                    input,
+                    name: options.metadata.root_name.as_deref(),
+                    dir: default_path.parent().unwrap_or(Path::new("")),
+                    tier,
+                    draft,
+                };
+                render_output_template(template, &context)?
+            }
+            (None, None) => default_path,
+        };
+
+        let existing = if (options.update || !options.patch.is_empty()) && output_path.exists() {
+            Some(load_json(&output_path)?)
+        } else {
+            None
+        };
+        // The schema already at the output path keeps its manual edits and only widens to fit
+        // the new data
+        if let (true, Some(existing)) = (options.update, &existing) {
+            schema = timed("update", || Ok(update_schema(existing, &schema)))?;
+        }
+
+        if let (Some(annotation), Some(json_value)) = (options.source_annotations, &json_value) {
+            annotate_sources(&mut schema, json_value, annotation);
+        }
+        if options.provenance {
+            let provenance = Provenance::new(provenance_options(options, config, tier, draft), samples)
+                .with_source(source)
+                .with_generated_at((!options.deterministic).then(logging::timestamp));
+            embed_provenance(&mut schema, &provenance);
+        }
+        // Merging into the existing schema and the annotations above add keys after generation
+        order_keys(&mut schema, options.preserve_order);
+
+        let validation = if config.validate_schema {
+            let report = timed("validate", || {
+                let report = validate_schema_as(&schema, draft)?;
+                report.ensure_valid("Schema validation")?;
+                Ok(report)
+            })?;
+            Some(report)
+        } else {
+            None
+        };
+
+        let verification = if options.verify {
+            let report = timed("verify", || match unsampled.as_ref().or(json_value.as_ref()) {
+                Some(document) => validate_json_against_schema(document, &schema),
+                None => verify_stream_file(input, &schema, limits.max_input_size, |value| settings.filter_fields(value)),
+            })?;
+            if !report.is_valid() {
+                let mut report = report;
+                // Only documents read whole from a local file can be pointed into
+                if let (None, Some(_)) = (url, &json_value) {
+                    if let Ok(text) = std::fs::read_to_string(input) {
+                        report.locate(&text, options.pointer.as_deref().unwrap_or(""));
+                    }
+                }
+                return Err(AppError::ValidationFailed(format!(
+                    "{} does not match the schema generated from it:\n{}",
+                    input.display(),
+                    report.render_text().trim_end()
+                ))
+                .into());
+            }
+            Some(report)
+        } else {
+            None
+        };
+
+        let schema_json = if config.pretty_output {
+            serde_json::to_string_pretty(&schema)?
+        } else {
+            serde_json::to_string(&schema)?
+        };
+
+        // Compared before anything is written, so a mismatch leaves every output as it was
+        let golden = match &options.expect {
+            Some(path) if options.update_golden => Some(self.write(path.clone(), schema_json.clone())?),
+            Some(path) => {
+                let diff = diff_schemas(&load_json(path)?, &schema);
+                if !diff.is_empty() {
+                    return Err(AppError::GoldenMismatch {
+                        golden: path.display().to_string(),
+                        changes: diff.changes.len(),
+                        diff: diff.render_text().trim_end().to_string(),
+                    }
+                    .into());
+                }
+                None
+            }
+            None => None,
+        };
+        let output = if options.patch_only {
+            OutputFile { path: output_path, content: schema_json, outcome: FileOutcome::Kept }
+        } else {
+            self.write(output_path, schema_json)?
+        };
+        let patches = options
+            .patch
+            .iter()
+            .map(|format| {
+                let content = serde_json::to_string_pretty(&format.patch(existing.as_ref(), &schema))?;
+                self.write(format.path(&output.path), content)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let minified = if options.minify {
+            let content = serde_json::to_string(&minify_schema(&schema))?;
+            Some(self.write(minified_path(&output.path), content)?)
+        } else {
+            None
+        };
+        let source_map = match (options.source_map, &json_value) {
+            (true, Some(json_value)) => {
+                let schema_file = output.path.file_name().unwrap_or_default().to_string_lossy();
+                let map = SourceMap::new(&schema, json_value, schema_file, input.display().to_string());
+                let content = serde_json::to_string_pretty(&map)?;
+                Some(self.write(source_map_path(&output.path), content)?)
+            }
+            _ => None,
+        };
+
+        let injected = if options.inject_schema {
+            self.inject(input, url, json_value.is_some(), &output.path)?
+        } else {
+            None
+        };
+
+        let explanation = match (options.explain, json_value) {
+            (true, Some(json_value)) => Some(explain_schema(&schema, std::slice::from_ref(&json_value))),
+            _ => None,
+        };
+
+        Ok(ProcessReport {
+            input: input.clone(),
+            schema,
+            output,
+            minified,
+            source_map,
+            patches,
+            golden,
+            injected,
+            validation,
+            verification,
+            explanation,
+            interned,
+        })
+    }
+
+    /// The schema for every document of `inputs` merged, as `check` and `review` compare it
+    /// with a committed one. Documents go through the config, tier, filters and sampling
+    /// [`Pipeline::process`] would use for the first of them, so the result matches a plain run.
+    pub fn generate_merged(&self, inputs: &[PathBuf]) -> Result<Value> {
+        let first = inputs
+            .first()
+            .ok_or_else(|| AppError::InvalidOption("No input files to generate from".to_string()))?;
+        let settings = self.settings(first)?;
+        let mut builder = SchemaBuilder::from(settings.generator.clone());
+        for input in inputs {
+            let value = self.select(input, load_json(input)?)?;
+            builder.feed(&settings.prepare(value, &self.options))?;
+        }
+        Ok(builder.finalize().unwrap_or_default())
+    }
+
+    /// The schema for `value`, a document found inside `input` rather than `input` itself, e.g.
+    /// a request body of an API client export, with the config found for `input`
+    pub fn generate_value(&self, input: &Path, value: Value) -> Result<Value> {
+        let settings = self.settings(input)?;
+        settings.generator.generate(&settings.prepare(value, &self.options))
+    }
+
+    // The config for `input`, and what it and the options come to
+    fn settings(&self, input: &Path) -> Result<Settings> {
+        let options = &self.options;
+        let mut config = self.config_for(input)?;
+        let (tier, custom) = config.resolve_tier(options.tier.as_ref())?;
+        config.merge_with_args(None, options.pretty, options.validate);
+        let draft = options.draft.unwrap_or(config.draft);
+
+        let additional_properties = match &options.additional_properties {
+            Some(AdditionalProperties::Allow) => Some(Value::Bool(true)),
+            Some(AdditionalProperties::Deny) => Some(Value::Bool(false)),
+            Some(AdditionalProperties::Schema(path)) => Some(load_json(path)?),
+            None => custom.additional_properties.map(Value::Bool),
+        };
+        let infer = [&options.infer, &custom.infer, &config.infer]
+            .into_iter()
+            .find(|families| !families.is_empty())
+            .unwrap_or(&options.infer);
+        let no_infer = [&options.no_infer, &custom.no_infer, &config.no_infer]
+            .into_iter()
+            .find(|families| !families.is_empty())
+            .unwrap_or(&options.no_infer);
+        let examples = match options.examples_limit {
+            _ if options.no_examples || !custom.examples.unwrap_or(config.examples) => ExamplePolicy::Omit,
+            Some(limit) => ExamplePolicy::Limit(limit),
+            None => ExamplePolicy::Keep,
+        };
+        let locale = match options.locale.as_ref().or(config.locale.as_ref()) {
+            Some(name) => Some(Locale::load(options.locale_dir.as_deref().unwrap_or(Path::new(".")), name)?),
+            None => None,
+        };
+        let limits = options.limits.or(config.limits);
+        let generator = SchemaGenerator::new(GeneratorOptions {
+            tier: tier.clone(),
+            policy: None,
+            draft,
+            null_policy: options.null_policy,
+            required: options.required_mode.or(custom.required),
+            additional_properties,
+            infer: infer.clone(),
+            no_infer: no_infer.clone(),
+            examples,
+            max_depth: [options.max_depth, limits.max_depth].into_iter().flatten().min(),
+            max_nodes: limits.max_nodes,
+            time_limit: limits.time_limit(),
+            constraints: config.constraints.clone(),
+            fields: config.fields.clone(),
+            redact: config.redact.clone(),
+            metadata: options.metadata.clone(),
+            locale,
+            deterministic: options.deterministic,
+            preserve_order: options.preserve_order,
+            jobs: options.jobs,
+            hooks: options.hooks.clone(),
+        });
+        let filter = if !options.include.is_empty() || !options.exclude.is_empty() || !config.exclude.is_empty() {
+            let exclude = [options.exclude.as_slice(), config.exclude.as_slice()].concat();
+            Some(PathFilter::new(&options.include, &exclude)?)
+        } else {
+            None
+        };
+        Ok(Settings { config, tier, draft, limits, generator, filter })
+    }
+
+    // The part of the document read from `input` a schema is generated for
+    fn select(&self, input: &Path, mut value: Value) -> Result<Value> {
+        if let Some(pointer) = &self.options.pointer {
+            value = value.pointer_mut(pointer).map(Value::take).ok_or_else(|| AppError::PointerNotFound {
+                pointer: pointer.clone(),
+                file: input.display().to_string(),
+            })?;
+        }
+        // A `$schema` at the top of the input, e.g. from `inject_schema`, is there for
+        // editors and not part of the data
+        if let (None, Value::Object(obj)) = (&self.options.pointer, &mut value) {
+            if obj.get("$schema").is_some_and(Value::is_string) {
+                obj.retain(|key, _| key != "$schema");
+            }
+        }
+        Ok(value)
+    }
+
+    // Only local documents read whole, and not a subtree of them, describe the whole file
+    fn inject(&self, input: &Path, url: Option<&str>, parsed: bool, schema: &Path) -> Result<Option<OutputFile>> {
+        if url.is_some() || !parsed || self.options.pointer.is_some() {
+            warn!("Not adding $schema to {:?}: only whole local JSON documents can point at their schema", input);
+            return Ok(None);
+        }
+        let text = std::fs::read_to_string(input)
+            .map_err(|_| AppError::FileNotFound(input.display().to_string()))?;
+        let Some(content) = inject_schema_reference(&text, &schema_reference(input, schema)) else {
+            warn!("Not adding $schema to {:?}: the document is not an object", input);
+            return Ok(None);
+        };
+        // The input itself is rewritten, so refusing to replace existing files does not apply
+        let write = WriteOptions { no_clobber: false, skip_unchanged: true };
+        self.write_with(input.to_path_buf(), content, write).map(Some)
+    }
+
+    fn write(&self, path: PathBuf, content: String) -> Result<OutputFile> {
+        self.write_with(path, content, self.options.write)
+    }
+
+    fn write_with(&self, path: PathBuf, content: String, write: WriteOptions) -> Result<OutputFile> {
+        let outcome = if self.options.dry_run {
+            FileOutcome::DryRun
+        } else {
+            match timed("write", || write_output(&path, &content, write))? {
+                WriteOutcome::Written => FileOutcome::Written,
+                WriteOutcome::Unchanged => FileOutcome::Unchanged,
+            }
+        };
+        Ok(OutputFile { path, content, outcome })
+    }
+
+    /// The inputs matching a glob pattern, or every file below a directory with one of the
+    /// config's file extensions
+    pub fn glob_inputs(&self, input_pattern: &Path) -> Result<BatchInputs> {
+        if input_pattern.is_dir() {
+            return self.directory_inputs(input_pattern);
+        }
+
+        let pattern = input_pattern.to_string_lossy();
+        let base = glob_base(input_pattern);
+        let ignore = IgnoreRules::discover(&base);
+        let entries = glob(&pattern)
+            .map_err(|e| AppError::InvalidPattern { pattern: pattern.to_string(), message: format!("Invalid glob pattern: {}", e) })?
+            .map(|entry| entry.map_err(|e| format!("Glob error: {}", e)))
+            .collect();
+
+        Ok(BatchInputs { base, entries, ignore })
+    }
+
+    fn directory_inputs(&self, dir: &Path) -> Result<BatchInputs> {
+        let config = self.load_config(dir, None)?;
+        let pattern = format!("{}/**/*", glob::Pattern::escape(&dir.to_string_lossy()));
+        let entries = glob(&pattern)
+            .map_err(|e| AppError::InvalidPattern { pattern: pattern.to_string(), message: format!("Invalid glob pattern: {}", e) })?
+            .filter(|entry| match entry {
+                Ok(path) => {
+                    let name = path.file_name().unwrap_or_default().to_string_lossy();
+                    let extension = path.extension().unwrap_or_default().to_string_lossy();
+                    // Skip schemas written by earlier runs
+                    path.is_file()
+                        && !name.ends_with(".schema.json")
+                        && config.file_extensions.iter().any(|ext| ext.trim_start_matches('.') == extension)
+                }
+                Err(_) => true,
+            })
+            .map(|entry| entry.map_err(|e| format!("Glob error: {}", e)))
+            .collect();
+
+        Ok(BatchInputs {
+            base: dir.to_path_buf(),
+            entries,
+            ignore: IgnoreRules::discover(dir),
+        })
+    }
+
+    /// The input paths listed one per line in `list`, e.g. the output of `git diff --name-only`
+    pub fn listed_inputs(&self, list: &str) -> Result<BatchInputs> {
+        let entries = list
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(PathBuf::from)
+            .filter(|path| {
+                // Change lists include deleted files
+                let exists = path.exists();
+                if !exists {
+                    info!("Skipping missing file: {:?}", path);
+                }
+                exists
+            })
+            .map(Ok)
+            .collect();
+
+        let cwd = std::env::current_dir()?;
+        Ok(BatchInputs {
+            base: PathBuf::new(),
+            entries,
+            ignore: IgnoreRules::discover(&cwd),
+        })
+    }
+
+    /// Processes every input that is not ignored, stopping at the first failure with `fail_fast`
+    pub fn process_batch(&self, inputs: BatchInputs, observer: &mut dyn BatchObserver) -> BatchReport {
+        let started = Instant::now();
+        let mut report = BatchReport::default();
+        let cwd = std::env::current_dir().unwrap_or_default();
+        let entries: Vec<_> = inputs
+            .entries
+            .into_iter()
+            .filter(|entry| match entry {
+                Ok(path) if inputs.ignore.is_ignored(&cwd.join(path)) || inputs.ignore.is_ignored(path) => {
+                    info!("Skipping ignored file: {:?}", path);
+                    report.ignored += 1;
+                    false
+                }
+                _ => true,
+            })
+            .collect();
+        report.total = entries.len();
+        observer.begin(report.total);
+
+        for entry in entries {
+            match entry {
+                Ok(path) => {
+                    info!("Processing file: {:?}", path);
+                    observer.started(&path);
+                    let started = Instant::now();
+                    let request = ProcessRequest {
+                        input: path.clone(),
+                        base: inputs.base.clone(),
+                    };
+                    let result = self.process(&request);
+                    observer.finished(&path, &result, started.elapsed());
+                    match result {
+                        Ok(processed) => {
+                            report.interned.add(processed.interned.unwrap_or_default());
+                            report.reports.push(processed);
+                        }
+                        Err(e) => report.errors.push(format!("{:?}: {}", path, e)),
+                    }
+                }
+                Err(e) => report.errors.push(e),
+            }
+            if self.options.fail_fast && !report.errors.is_empty() {
+                break;
+            }
+        }
+
+        report.elapsed = started.elapsed();
+        report.peak_memory_kb = peak_memory_kb();
+        report
+    }
+}
+
+// Options that need the whole input document in memory
+fn check_streamable(options: &ProcessOptions) -> Result<()> {
+    let needs_document = [
+        ("--pointer", options.pointer.is_some()),
+        ("--source-map", options.source_map),
+        ("--source-annotations", options.source_annotations.is_some()),
+        ("--explain", options.explain),
+    ];
+    match needs_document.iter().find(|(_, set)| *set) {
+        Some((flag, _)) => Err(AppError::InvalidOption(format!(
+            "{} needs the whole input in memory and cannot be used with streamed input",
+            flag
+        ))
+        .into()),
+        None => Ok(()),
+    }
+}
+
+pub fn load_json(path: &Path) -> Result<Value> {
+    if !path.exists() {
+        return Err(AppError::FileNotFound(path.display().to_string()).into());
+    }
+
+    // Large files are parsed from a memory map rather than copied onto the heap first
+    let content = MappedFile::open(path).with_context(|| format!("Failed to read input file: {:?}", path))?;
+
+    let span = info_span!("phase", phase = "parse", file = %path.display(), bytes = content.len(), result = field::Empty);
+    let value = timed_with(span, || {
+        serde_json::from_slice(&content).map_err(|e| AppError::invalid_json_in(path.display().to_string(), &e).into())
+    })?;
+
+    Ok(value)
+}
+
+// The longest leading part of a glob pattern without wildcards, e.g. `data` for `data/**/*.json`
+fn glob_base(pattern: &Path) -> PathBuf {
+    pattern
+        .components()
+        .take_while(|c| !c.as_os_str().to_string_lossy().contains(['*', '?', '[']))
+        .collect::<PathBuf>()
+        .ancestors()
+        .find(|p| p.is_dir() || p.as_os_str().is_empty())
+        .map(Path::to_path_buf)
+        .unwrap_or_default()
+}
+
+/// The options of a run that were set, for the `x-generated-by` block
+fn provenance_options(
+    options: &ProcessOptions,
+    config: &Config,
+    tier: &SchemaOutputTier,
+    draft: SchemaDraft,
+) -> Map<String, Value> {
+    let mut set = Map::new();
+    // A custom tier is recorded by its own name rather than the built-in tier it is based on
+    let tier_name = match (&options.tier, &config.custom_default_tier) {
+        (Some(TierChoice::Custom(name)), _) | (None, Some(name)) => name.clone(),
+        _ => format!("{:?}", tier).to_lowercase(),
+    };
+    set.insert("tier".to_string(), json!(tier_name));
+    set.insert("draft".to_string(), json!(draft.name()));
+    set.insert("nullPolicy".to_string(), json!(options.null_policy));
+    if let Some(profile) = &options.profile {
+        set.insert("profile".to_string(), json!(profile));
+    }
+    if let Some(pointer) = &options.pointer {
+        set.insert("pointer".to_string(), json!(pointer));
+    }
+    if !options.include.is_empty() {
+        set.insert("include".to_string(), json!(options.include));
+    }
+    if !options.exclude.is_empty() {
+        set.insert("exclude".to_string(), json!(options.exclude));
+    }
+    if let Some(limit) = options.array_sample {
+        set.insert("arraySample".to_string(), json!(limit));
+    }
+    if let Some(seed) = options.sample_seed {
+        set.insert("sampleSeed".to_string(), json!(seed));
+    }
+    if let Some(mode) = options.required_mode {
+        set.insert("requiredMode".to_string(), json!(mode));
+    }
+    if let Some(additional) = &options.additional_properties {
+        let value = match additional {
+            AdditionalProperties::Allow => json!(true),
+            AdditionalProperties::Deny => json!(false),
+            AdditionalProperties::Schema(path) => json!(path.display().to_string()),
+        };
+        set.insert("additionalProperties".to_string(), value);
+    }
+    if let Some(depth) = options.max_depth {
+        set.insert("maxDepth".to_string(), json!(depth));
+    }
+    if !options.limits.is_empty() {
+        set.insert("limits".to_string(), json!(options.limits));
+    }
+    if !options.infer.is_empty() {
+        set.insert("infer".to_string(), json!(options.infer));
+    }
+    if !options.no_infer.is_empty() {
+        set.insert("noInfer".to_string(), json!(options.no_infer));
+    }
+    if options.no_examples {
+        set.insert("noExamples".to_string(), json!(true));
+    }
+    if let Some(limit) = options.examples_limit {
+        set.insert("examplesLimit".to_string(), json!(limit));
+    }
+    if options.deterministic {
+        set.insert("deterministic".to_string(), json!(true));
+    }
+    if options.preserve_order {
+        set.insert("preserveOrder".to_string(), json!(true));
+    }
+    if options.stream {
+        set.insert("stream".to_string(), json!(true));
+    }
+    if let Some(locale) = &options.locale {
+        set.insert("locale".to_string(), json!(locale));
+    }
+    if let Some(annotation) = options.source_annotations {
+        let name = match annotation {
+            SourceAnnotation::Comment => "comment",
+            SourceAnnotation::XSource => "x-source",
+        };
+        set.insert("sourceAnnotations".to_string(), json!(name));
+    }
+    set
+}
+
+fn schema_file_name(input: &Path) -> String {
+    let stem = input.file_stem().unwrap_or_default();
+    format!("{}.schema.json", stem.to_string_lossy())
+}
+
+/// Runs one processing phase of the current file in a `phase` span, which is logged with its
+/// duration and result when it closes
+fn timed<T>(phase: &'static str, f: impl FnOnce() -> Result<T>) -> Result<T> {
+    timed_with(info_span!("phase", phase, result = field::Empty), f)
+}
+
+fn timed_with<T>(span: Span, f: impl FnOnce() -> Result<T>) -> Result<T> {
+    let result = span.in_scope(f);
+    span.record("result", if result.is_ok() { "ok" } else { "error" });
+    result
+}
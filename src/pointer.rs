@@ -0,0 +1,19 @@
+pub fn escape_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+pub fn unescape_token(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+pub fn join(pointer: &str, token: &str) -> String {
+    format!("{}/{}", pointer, escape_token(token))
+}
+
+pub fn display(pointer: &str) -> &str {
+    if pointer.is_empty() {
+        "/"
+    } else {
+        pointer
+    }
+}
@@ -0,0 +1,102 @@
+use tracing::info;
+use std::fmt;
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::logging;
+
+const BAR_WIDTH: usize = 30;
+
+/// Batch progress: a redrawn bar on stderr when it is a terminal, per-file log lines otherwise.
+pub struct Progress {
+    total: usize,
+    done: usize,
+    started: Instant,
+    interactive: bool,
+}
+
+impl Progress {
+    pub fn new(total: usize) -> Self {
+        Self {
+            total,
+            done: 0,
+            started: Instant::now(),
+            interactive: io::stderr().is_terminal(),
+        }
+    }
+
+    /// A reporter that never draws a bar, for single-file runs
+    pub fn hidden() -> Self {
+        Self {
+            interactive: false,
+            ..Self::new(1)
+        }
+    }
+
+    /// Shows `current` as the file being processed
+    pub fn start(&self, current: &Path) {
+        if self.interactive {
+            self.draw(&current.display().to_string());
+        }
+    }
+
+    /// Prints a line on stdout above the bar
+    pub fn println(&self, message: &str) {
+        self.clear();
+        println!("{}", message);
+    }
+
+    /// Prints a status line above the bar, subject to `--quiet` and the log format
+    pub fn status(&self, args: fmt::Arguments) {
+        self.clear();
+        logging::print_status(args);
+    }
+
+    pub fn notice(&self, args: fmt::Arguments) {
+        self.clear();
+        logging::print_notice(args);
+    }
+
+    fn clear(&self) {
+        if self.interactive {
+            eprint!("\r\x1b[K");
+            let _ = io::stderr().flush();
+        }
+    }
+
+    pub fn finish_item(&mut self, path: &Path, elapsed: Duration, ok: bool) {
+        self.done += 1;
+        info!(
+            result = if ok { "ok" } else { "error" },
+            "[{}/{}] {:?} processed in {:.1?}", self.done, self.total, path, elapsed
+        );
+    }
+
+    pub fn finish(&self) {
+        self.clear();
+        info!("{} file(s) processed in {:.1?}", self.done, self.started.elapsed());
+    }
+
+    fn draw(&self, current: &str) {
+        let filled = BAR_WIDTH * self.done / self.total.max(1);
+        let bar = format!("{}{}", "=".repeat(filled), " ".repeat(BAR_WIDTH - filled));
+        eprint!(
+            "\r\x1b[K[{}] {}/{} ETA {} {}",
+            bar,
+            self.done,
+            self.total,
+            self.eta().map_or("--".to_string(), |eta| format!("{}s", eta.as_secs())),
+            current
+        );
+        let _ = io::stderr().flush();
+    }
+
+    fn eta(&self) -> Option<Duration> {
+        if self.done == 0 {
+            return None;
+        }
+        let per_file = self.started.elapsed() / self.done as u32;
+        Some(per_file * (self.total - self.done) as u32)
+    }
+}
@@ -0,0 +1,76 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::hash::{sha256, to_hex};
+
+pub const PROVENANCE_KEYWORD: &str = "x-generated-by";
+
+/// How a schema was produced, embedded under `x-generated-by` in its root
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Provenance {
+    pub tool: String,
+    pub version: String,
+    /// The options that were set, by their CLI flag names in camelCase
+    pub options: Map<String, Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<Source>,
+    /// Root array elements the schema was inferred from, or 1 for any other root
+    pub samples: usize,
+    /// Left out in deterministic mode, so the block only depends on the input and options
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generated_at: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Source {
+    /// Path or URL of the input
+    pub file: String,
+    /// `sha256:<hex>` of the input document as compact JSON with sorted keys, so it doesn't
+    /// change with formatting
+    pub sha256: String,
+}
+
+impl Provenance {
+    pub fn new(options: Map<String, Value>, samples: usize) -> Self {
+        Self {
+            tool: env!("CARGO_PKG_NAME").to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            options,
+            source: None,
+            samples,
+            generated_at: None,
+        }
+    }
+
+    pub fn with_source(mut self, source: Option<Source>) -> Self {
+        self.source = source;
+        self
+    }
+
+    pub fn with_generated_at(mut self, timestamp: Option<String>) -> Self {
+        self.generated_at = timestamp;
+        self
+    }
+}
+
+impl Source {
+    pub fn new(file: impl Into<String>, document: &Value) -> Self {
+        let digest = sha256(document.to_string().as_bytes());
+        Self { file: file.into(), sha256: format!("sha256:{}", to_hex(&digest)) }
+    }
+}
+
+/// Number of samples in `document`: the length of a root array, else 1
+pub fn sample_count(document: &Value) -> usize {
+    match document {
+        Value::Array(items) => items.len(),
+        _ => 1,
+    }
+}
+
+pub fn embed_provenance(schema: &mut Value, provenance: &Provenance) {
+    if let (Value::Object(obj), Ok(block)) = (schema, serde_json::to_value(provenance)) {
+        obj.insert(PROVENANCE_KEYWORD.to_string(), block);
+    }
+}
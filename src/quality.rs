@@ -0,0 +1,517 @@
+//! Validation of many data files against one schema, e.g. for nightly data-quality jobs.
+//! Files are validated on worker threads and summed up into which keywords and fields fail
+//! most, so thousands of files do not have to be read through one report at a time.
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Instant;
+
+use crate::bundle::RefResolver;
+use crate::parallel::available_jobs;
+use crate::pointer;
+use crate::pipeline::load_json;
+use crate::streaming::is_ndjson;
+use crate::validation::{Severity, ValidationError, Validator};
+
+/// How [`QualityReport`]s are written out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum QualityFormat {
+    Text,
+    Json,
+    /// JUnit XML, one test case per file, for CI test panels
+    Junit,
+    /// SARIF 2.1.0, one result per error, for code-scanning UIs
+    Sarif,
+}
+
+/// Errors listed under each failed file in the text report; the JSON report has them all
+pub const ERRORS_SHOWN_PER_FILE: usize = 3;
+
+/// How [`validate_files`] runs
+#[derive(Debug, Clone)]
+pub struct QualityOptions {
+    /// Files validated at once; 0 means one per core
+    pub jobs: usize,
+    /// Entries kept in the keyword and field rankings
+    pub top: usize,
+    /// Errors collected at most; the files after the one reaching it are not validated
+    pub max_errors: Option<usize>,
+    /// Keywords whose violations are warnings, e.g. `additionalProperties` while a migration
+    /// is under way
+    pub warn_keywords: Vec<String>,
+    /// How the schema's `$ref`s to other documents are loaded
+    pub refs: RefResolver,
+}
+
+impl Default for QualityOptions {
+    fn default() -> Self {
+        Self { jobs: 0, top: 10, max_errors: None, warn_keywords: Vec::new(), refs: RefResolver::default() }
+    }
+}
+
+/// How one data file fared
+#[derive(Debug, Clone, Serialize)]
+pub struct FileValidation {
+    pub path: PathBuf,
+    /// Errors and warnings. For NDJSON, instance pointers start with the index of the record.
+    pub errors: Vec<ValidationError>,
+    /// Why the file could not be read or parsed, which counts as a failure
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unreadable: Option<String>,
+}
+
+impl FileValidation {
+    /// Whether the file has no errors, though it may have warnings
+    pub fn passed(&self) -> bool {
+        self.error_count() == 0 && self.unreadable.is_none()
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.errors.iter().filter(|error| error.severity == Severity::Error).count()
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.errors.len() - self.error_count()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KeywordCount {
+    pub keyword: String,
+    pub errors: usize,
+    #[serde(skip_serializing_if = "is_zero")]
+    pub warnings: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldCount {
+    /// Instance pointer with array indices as `*`, e.g. `/items/*/sku`
+    pub field: String,
+    pub errors: usize,
+    #[serde(skip_serializing_if = "is_zero")]
+    pub warnings: usize,
+}
+
+fn is_zero(count: &usize) -> bool {
+    *count == 0
+}
+
+/// The summary of a run of [`validate_files`]
+#[derive(Debug, Clone, Serialize)]
+pub struct QualityReport {
+    pub files: usize,
+    /// Files validated without errors, though maybe with warnings
+    pub passed: usize,
+    pub failed: usize,
+    /// Files not validated because `max_errors` was reached
+    pub skipped: usize,
+    /// Errors over every file
+    pub errors: usize,
+    pub warnings: usize,
+    pub seconds: f64,
+    /// The keywords failing most, most errors and warnings first
+    pub top_keywords: Vec<KeywordCount>,
+    /// The fields failing most, most errors and warnings first
+    pub top_fields: Vec<FieldCount>,
+    /// The files with errors or warnings, or that could not be read, in input order
+    pub failures: Vec<FileValidation>,
+}
+
+impl QualityReport {
+    pub fn is_valid(&self) -> bool {
+        self.failed == 0
+    }
+
+    /// Whether anything of `level` or worse was found, e.g. for `--fail-at warning`
+    pub fn fails_at(&self, level: Severity) -> bool {
+        match level {
+            Severity::Error => !self.is_valid(),
+            Severity::Warning => !self.is_valid() || self.warnings > 0,
+        }
+    }
+
+    pub fn render_text(&self) -> String {
+        let mut out = format!(
+            "Validated {} file(s) in {:.2}s: {} passed, {} failed, {} error(s)",
+            self.files - self.skipped,
+            self.seconds,
+            self.passed,
+            self.failed,
+            self.errors
+        );
+        if self.warnings > 0 {
+            out.push_str(&format!(", {} warning(s)", self.warnings));
+        }
+        out.push('\n');
+        if self.skipped > 0 {
+            out.push_str(&format!("Stopped at the error limit; {} file(s) not validated\n", self.skipped));
+        }
+
+        if !self.top_keywords.is_empty() {
+            out.push_str("\nTop failing keywords:\n");
+            for keyword in &self.top_keywords {
+                out.push_str(&format!("  {:<20} {}\n", keyword.keyword, counts(keyword.errors, keyword.warnings)));
+            }
+        }
+        if !self.top_fields.is_empty() {
+            out.push_str("\nErrors per field:\n");
+            for field in &self.top_fields {
+                out.push_str(&format!("  {:<20} {}\n", pointer::display(&field.field), counts(field.errors, field.warnings)));
+            }
+        }
+
+        let (failed, warned): (Vec<&FileValidation>, Vec<&FileValidation>) =
+            self.failures.iter().partition(|file| !file.passed());
+        for (title, files) in [("Failed files", failed), ("Files with warnings", warned)] {
+            if files.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("\n{}:\n", title));
+            for file in files {
+                match &file.unreadable {
+                    Some(reason) => out.push_str(&format!("  {}: {}\n", file.path.display(), reason)),
+                    None => out.push_str(&format!(
+                        "  {}: {}\n",
+                        file.path.display(),
+                        counts(file.error_count(), file.warning_count())
+                    )),
+                }
+                for error in file.errors.iter().take(ERRORS_SHOWN_PER_FILE) {
+                    for line in error.render_text().lines() {
+                        out.push_str(&format!("    {}\n", line));
+                    }
+                }
+                if file.errors.len() > ERRORS_SHOWN_PER_FILE {
+                    out.push_str(&format!("    and {} more\n", file.errors.len() - ERRORS_SHOWN_PER_FILE));
+                }
+            }
+        }
+        out
+    }
+}
+
+// e.g. "3 error(s)" or "3 error(s), 1 warning(s)"
+fn counts(errors: usize, warnings: usize) -> String {
+    match warnings {
+        0 => format!("{} error(s)", errors),
+        _ => format!("{} error(s), {} warning(s)", errors, warnings),
+    }
+}
+
+impl QualityReport {
+    /// The report in `format`; `schema` names the JUnit test suite and SARIF run
+    pub fn render(&self, format: QualityFormat, schema: &Path) -> Result<String> {
+        Ok(match format {
+            QualityFormat::Text => self.render_text(),
+            QualityFormat::Json => format!("{}\n", serde_json::to_string_pretty(self)?),
+            QualityFormat::Junit => self.render_junit(schema),
+            QualityFormat::Sarif => format!("{}\n", serde_json::to_string_pretty(&self.to_sarif(schema))?),
+        })
+    }
+
+    /// Failed files are `<failure>`s listing their errors, unreadable ones `<error>`s, and the
+    /// warnings of a file go to its `<system-out>`. Only files with problems are kept in the
+    /// report, so the others are counted but not listed.
+    pub fn render_junit(&self, schema: &Path) -> String {
+        let unreadable = self.failures.iter().filter(|file| file.unreadable.is_some()).count();
+        let suite = xml_escape(&schema.display().to_string());
+        let counts = format!(
+            "tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.3}\"",
+            self.files,
+            self.failed - unreadable,
+            unreadable,
+            self.seconds
+        );
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str(&format!("<testsuites name=\"schema-jenerator validate\" {}>\n", counts));
+        out.push_str(&format!("  <testsuite name=\"{}\" {}>\n", suite, counts));
+        for file in &self.failures {
+            let name = xml_escape(&file.path.display().to_string());
+            out.push_str(&format!("    <testcase name=\"{}\" classname=\"{}\">\n", name, suite));
+            let (errors, warnings): (Vec<&ValidationError>, Vec<&ValidationError>) =
+                file.errors.iter().partition(|error| error.severity == Severity::Error);
+            match &file.unreadable {
+                Some(reason) => out.push_str(&format!(
+                    "      <error message=\"{}\" type=\"unreadable\"/>\n",
+                    xml_escape(reason)
+                )),
+                None if !errors.is_empty() => {
+                    let details: String = errors.iter().map(|error| error.render_text()).collect();
+                    out.push_str(&format!(
+                        "      <failure message=\"{} error(s)\" type=\"validation\">{}</failure>\n",
+                        errors.len(),
+                        xml_escape(&details)
+                    ));
+                }
+                None => {}
+            }
+            if !warnings.is_empty() {
+                let details: String = warnings.iter().map(|error| error.render_text()).collect();
+                out.push_str(&format!("      <system-out>{}</system-out>\n", xml_escape(&details)));
+            }
+            out.push_str("    </testcase>\n");
+        }
+        out.push_str("  </testsuite>\n</testsuites>\n");
+        out
+    }
+
+    /// A SARIF log with one rule per failing keyword and one result per error, located by file
+    /// and, as a logical location, by the instance pointer
+    pub fn to_sarif(&self, schema: &Path) -> Value {
+        let mut rules: Vec<&str> = self.failures.iter().flat_map(|file| &file.errors).map(|e| e.keyword.as_str()).collect();
+        if self.failures.iter().any(|file| file.unreadable.is_some()) {
+            rules.push(UNREADABLE_RULE);
+        }
+        rules.sort_unstable();
+        rules.dedup();
+
+        let mut results = Vec::new();
+        for file in &self.failures {
+            let artifact = json!({ "uri": file.path.display().to_string().replace('\\', "/") });
+            if let Some(reason) = &file.unreadable {
+                results.push(json!({
+                    "ruleId": UNREADABLE_RULE,
+                    "level": "error",
+                    "message": { "text": reason },
+                    "locations": [{ "physicalLocation": { "artifactLocation": artifact } }],
+                }));
+            }
+            for error in &file.errors {
+                let mut physical = json!({ "artifactLocation": artifact });
+                if let Some(location) = &error.location {
+                    physical["region"] = json!({
+                        "startLine": location.line,
+                        "startColumn": location.column,
+                        "snippet": { "text": location.snippet },
+                    });
+                }
+                results.push(json!({
+                    "ruleId": error.keyword,
+                    "level": error.severity,
+                    "message": { "text": error.message },
+                    "locations": [{
+                        "physicalLocation": physical,
+                        "logicalLocations": [{ "fullyQualifiedName": pointer::display(&error.instance_pointer), "kind": "member" }],
+                    }],
+                    "properties": { "schemaPointer": error.schema_pointer },
+                }));
+            }
+        }
+
+        json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "schema-jenerator",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rules.iter().map(|rule| json!({
+                            "id": rule,
+                            "shortDescription": { "text": rule_description(rule) },
+                        })).collect::<Vec<_>>(),
+                    }
+                },
+                "properties": { "schema": schema.display().to_string() },
+                "results": results,
+            }],
+        })
+    }
+}
+
+// SARIF rule of files that could not be read or parsed
+const UNREADABLE_RULE: &str = "unreadable";
+
+fn rule_description(rule: &str) -> String {
+    if rule == UNREADABLE_RULE {
+        "The data file could not be read or is not valid JSON".to_string()
+    } else {
+        format!("The data violates the schema's `{}` keyword", rule)
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Validates every file of `paths` against `schema` on parallel workers. `.ndjson` and
+/// `.jsonl` files are validated one record at a time. Fails only when `schema` cannot be
+/// compiled.
+pub fn validate_files(schema: &Value, paths: &[PathBuf], options: &QualityOptions) -> Result<QualityReport> {
+    let validator = Validator::with_refs(schema, options.refs.clone())?;
+    let started = Instant::now();
+    let jobs = if options.jobs == 0 { available_jobs() } else { options.jobs }.clamp(1, paths.len().max(1));
+
+    // Workers take the next file as they finish one, so a few large files do not hold up
+    // the rest, and take no more once the error limit is reached
+    let next = AtomicUsize::new(0);
+    let collected = AtomicUsize::new(0);
+    let limit = options.max_errors.unwrap_or(usize::MAX);
+    let mut results: Vec<(usize, FileValidation)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    while collected.load(Ordering::Relaxed) < limit {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(path) = paths.get(index) else { break };
+                        let file = validate_file(&validator, path, &options.warn_keywords);
+                        collected.fetch_add(file.error_count(), Ordering::Relaxed);
+                        done.push((index, file));
+                    }
+                    done
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_else(|payload| std::panic::resume_unwind(payload)))
+            .collect()
+    });
+    results.sort_by_key(|(index, _)| *index);
+
+    // Files are taken in order, so every file up to the one reaching the limit was validated;
+    // the files after it are dropped whichever of them the workers got to
+    let mut budget = limit;
+    let mut validated = Vec::new();
+    for (_, mut file) in results {
+        if budget == 0 {
+            break;
+        }
+        file.errors.retain(|error| {
+            let keep = error.severity != Severity::Error || budget > 0;
+            if keep && error.severity == Severity::Error {
+                budget -= 1;
+            }
+            keep
+        });
+        validated.push(file);
+    }
+
+    let mut keywords: HashMap<String, (usize, usize)> = HashMap::new();
+    let mut fields: HashMap<String, (usize, usize)> = HashMap::new();
+    for error in validated.iter().flat_map(|file| &file.errors) {
+        let keyword = keywords.entry(error.keyword.clone()).or_default();
+        let field = fields.entry(field_of(&error.instance_pointer)).or_default();
+        for (errors, warnings) in [keyword, field] {
+            match error.severity {
+                Severity::Error => *errors += 1,
+                Severity::Warning => *warnings += 1,
+            }
+        }
+    }
+
+    let passed = validated.iter().filter(|file| file.passed()).count();
+    let failed = validated.len() - passed;
+    let failures: Vec<FileValidation> =
+        validated.into_iter().filter(|file| !file.passed() || !file.errors.is_empty()).collect();
+    Ok(QualityReport {
+        files: paths.len(),
+        passed,
+        failed,
+        skipped: paths.len() - passed - failed,
+        errors: keywords.values().map(|(errors, _)| errors).sum(),
+        warnings: keywords.values().map(|(_, warnings)| warnings).sum(),
+        seconds: started.elapsed().as_secs_f64(),
+        top_keywords: ranked(keywords, options.top)
+            .into_iter()
+            .map(|(keyword, (errors, warnings))| KeywordCount { keyword, errors, warnings })
+            .collect(),
+        top_fields: ranked(fields, options.top)
+            .into_iter()
+            .map(|(field, (errors, warnings))| FieldCount { field, errors, warnings })
+            .collect(),
+        failures,
+    })
+}
+
+fn validate_file(validator: &Validator, path: &Path, warn_keywords: &[String]) -> FileValidation {
+    let mut file = FileValidation { path: path.to_path_buf(), errors: Vec::new(), unreadable: None };
+    if !is_ndjson(path) {
+        match load_json(path) {
+            Ok(document) => {
+                let mut report = validator.validate(&document);
+                report.downgrade(warn_keywords);
+                // The text is only read again for files that failed
+                if !report.is_valid() {
+                    if let Ok(text) = fs::read_to_string(path) {
+                        report.locate(&text, "");
+                    }
+                }
+                file.errors = report.errors;
+            }
+            Err(e) => file.unreadable = Some(e.to_string()),
+        }
+        return file;
+    }
+
+    let reader = match File::open(path) {
+        Ok(reader) => BufReader::new(reader),
+        Err(e) => {
+            file.unreadable = Some(format!("Failed to read input file: {}", e));
+            return file;
+        }
+    };
+    // One record per line; blank lines are not records
+    let mut index = 0;
+    for (number, line) in reader.lines().enumerate() {
+        let line = match line {
+            Ok(line) if line.trim().is_empty() => continue,
+            Ok(line) => line,
+            Err(e) => {
+                file.unreadable = Some(format!("Failed to read input file: {}", e));
+                break;
+            }
+        };
+        let record = match serde_json::from_str(&line) {
+            Ok(record) => record,
+            Err(e) => {
+                file.unreadable = Some(format!("Invalid JSON in record {} (line {}): {}", index, number + 1, e));
+                break;
+            }
+        };
+        let mut report = validator.validate(&record);
+        report.downgrade(warn_keywords);
+        report.locate(&line, "");
+        file.errors.extend(report.errors.into_iter().map(|mut error| {
+            error.instance_pointer = format!("/{}{}", index, error.instance_pointer);
+            if let Some(location) = &mut error.location {
+                location.line = number + 1;
+            }
+            error
+        }));
+        index += 1;
+    }
+    file
+}
+
+// Errors at different elements of one array count for the same field
+fn field_of(instance_pointer: &str) -> String {
+    instance_pointer
+        .split('/')
+        .map(|token| if !token.is_empty() && token.bytes().all(|b| b.is_ascii_digit()) { "*" } else { token })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// Most errors and warnings first, then by name, so reports are stable
+fn ranked(counts: HashMap<String, (usize, usize)>, top: usize) -> Vec<(String, (usize, usize))> {
+    let mut counts: Vec<(String, (usize, usize))> = counts.into_iter().collect();
+    counts.sort_by(|(a, (a_errors, a_warnings)), (b, (b_errors, b_warnings))| {
+        (b_errors + b_warnings).cmp(&(a_errors + a_warnings)).then_with(|| a.cmp(b))
+    });
+    counts.truncate(top);
+    counts
+}
@@ -0,0 +1,64 @@
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::filter::PointerGlob;
+use crate::schema::keywords::for_each_subschema_mut;
+
+/// Keywords that carry values copied from the input
+pub const VALUE_KEYWORDS: &[&str] = &["examples", "enum", "const", "default"];
+
+/// Strips the value keywords from the fields matching `patterns` and everything below them.
+/// Patterns are data paths with `*` standing for any array element, as in `[fields]`.
+pub fn redact_fields(schema: &mut Value, patterns: &[String]) -> Result<()> {
+    if patterns.is_empty() {
+        return Ok(());
+    }
+    let globs = patterns.iter().map(|p| PointerGlob::parse(p)).collect::<Result<Vec<_>>>()?;
+    visit(schema, &mut Vec::new(), &globs);
+    Ok(())
+}
+
+fn visit(node: &mut Value, path: &mut Vec<String>, globs: &[PointerGlob]) {
+    if globs.iter().any(|glob| glob.matches(path)) {
+        strip_values(node);
+        return;
+    }
+    let Value::Object(obj) = node else { return };
+
+    for branch in ["anyOf", "oneOf"] {
+        if let Some(Value::Array(branches)) = obj.get_mut(branch) {
+            for branch in branches {
+                visit(branch, path, globs);
+            }
+        }
+    }
+    if let Some(Value::Object(properties)) = obj.get_mut("properties") {
+        for (name, child) in properties.iter_mut() {
+            path.push(name.clone());
+            visit(child, path, globs);
+            path.pop();
+        }
+    }
+    match obj.get_mut("items") {
+        Some(Value::Array(items)) => {
+            path.push("*".to_string());
+            for item in items {
+                visit(item, path, globs);
+            }
+            path.pop();
+        }
+        Some(items) => {
+            path.push("*".to_string());
+            visit(items, path, globs);
+            path.pop();
+        }
+        None => {}
+    }
+}
+
+fn strip_values(schema: &mut Value) {
+    let Value::Object(obj) = schema else { return };
+
+    obj.retain(|key, _| !VALUE_KEYWORDS.contains(&key.as_str()));
+    for_each_subschema_mut(obj, &mut strip_values);
+}
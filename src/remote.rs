@@ -0,0 +1,262 @@
+use anyhow::Result;
+use tracing::{debug, warn};
+use reqwest::header::{HeaderMap, ETAG, IF_NONE_MATCH};
+use reqwest::{NoProxy, Proxy, StatusCode};
+use serde_json::Value;
+use std::error::Error as _;
+use std::thread;
+use std::time::Duration;
+
+use crate::cache::{CacheEntry, RemoteCache};
+use crate::error::AppError;
+
+/// How remote inputs and `$ref`s are fetched
+#[derive(Debug, Clone)]
+pub struct RemoteOptions {
+    /// Extra request headers, e.g. `("Authorization", "Bearer ...")`
+    pub headers: Vec<(String, String)>,
+    /// User name and optional password for HTTP basic authentication
+    pub basic_auth: Option<(String, Option<String>)>,
+    /// Sent as `Authorization: Bearer <token>`
+    pub bearer_token: Option<String>,
+    /// Further attempts after a connection error, timeout, HTTP 429 or 5xx response
+    pub retries: u32,
+    /// Wait before the first retry, doubled for each one after it
+    pub retry_delay: Duration,
+    /// Limit for each attempt, from connecting until the body has been read
+    pub timeout: Option<Duration>,
+    /// Proxy for every request, overriding `HTTP_PROXY`/`HTTPS_PROXY`; hosts in `NO_PROXY`
+    /// are still reached directly
+    pub proxy: Option<String>,
+    /// Where fetched bodies are kept between runs; `None` always fetches
+    pub cache: Option<RemoteCache>,
+}
+
+impl Default for RemoteOptions {
+    fn default() -> Self {
+        Self {
+            headers: Vec::new(),
+            basic_auth: None,
+            bearer_token: None,
+            retries: 2,
+            retry_delay: Duration::from_millis(500),
+            timeout: Some(Duration::from_secs(30)),
+            proxy: None,
+            cache: None,
+        }
+    }
+}
+
+impl RemoteOptions {
+    /// Parses a `Name: value` header
+    pub fn parse_header(header: &str) -> Result<(String, String)> {
+        let (name, value) = header.split_once(':').ok_or_else(|| {
+            AppError::RemoteFetch(format!("Invalid header {:?}; expected \"Name: value\"", header))
+        })?;
+        Ok((name.trim().to_string(), value.trim().to_string()))
+    }
+
+    /// Parses `user:pass`, or just `user`
+    pub fn parse_basic_auth(credentials: &str) -> (String, Option<String>) {
+        match credentials.split_once(':') {
+            Some((user, password)) => (user.to_string(), Some(password.to_string())),
+            None => (credentials.to_string(), None),
+        }
+    }
+
+    /// Reads the bearer token from the environment variable `name`
+    pub fn token_from_env(name: &str) -> Result<String> {
+        std::env::var(name)
+            .map_err(|_| AppError::RemoteFetch(format!("Environment variable {} is not set", name)).into())
+    }
+}
+
+pub fn is_url(location: &str) -> bool {
+    location.starts_with("http://") || location.starts_with("https://")
+}
+
+pub fn fetch_text(url: &str) -> Result<String> {
+    fetch_text_with(url, &RemoteOptions::default())
+}
+
+pub fn fetch_text_with(url: &str, options: &RemoteOptions) -> Result<String> {
+    let mut builder = reqwest::blocking::Client::builder();
+    if let Some(timeout) = options.timeout {
+        builder = builder.timeout(timeout);
+    }
+    if let Some(proxy) = proxy(options)? {
+        builder = builder.proxy(proxy);
+    }
+    let client = builder
+        .build()
+        .map_err(|e| AppError::RemoteFetch(format!("{}: {}", url, e)))?;
+
+    let cached = match cache_lookup(url, options) {
+        Lookup::Fresh(body) => return Ok(body),
+        Lookup::Stale(cached) => cached,
+    };
+    let etag = cached.as_ref().and_then(|entry| entry.etag.as_deref());
+
+    let mut attempt = 0;
+    let fetched = loop {
+        match fetch_once(&client, url, options, etag) {
+            Ok(fetched) => break fetched,
+            Err(failure) => {
+                let delay = retry_delay(failure, attempt, options)?;
+                attempt += 1;
+                thread::sleep(delay);
+            }
+        }
+    };
+
+    finish(url, options, fetched, cached)
+}
+
+// Without an explicit proxy, reqwest picks up HTTP_PROXY, HTTPS_PROXY and NO_PROXY itself
+pub(crate) fn proxy(options: &RemoteOptions) -> Result<Option<Proxy>> {
+    let Some(proxy) = &options.proxy else { return Ok(None) };
+    let proxy = Proxy::all(proxy)
+        .map_err(|e| AppError::RemoteFetch(format!("Invalid proxy {}: {}", proxy, e)))?
+        .no_proxy(NoProxy::from_env());
+    Ok(Some(proxy))
+}
+
+pub(crate) enum Lookup {
+    /// A cached body young enough to use without asking the server
+    Fresh(String),
+    /// The cached entry to revalidate, if any
+    Stale(Option<CacheEntry>),
+}
+
+pub(crate) fn cache_lookup(url: &str, options: &RemoteOptions) -> Lookup {
+    let cached = options.cache.as_ref().and_then(|cache| cache.lookup(url));
+    if let (Some(cache), Some(entry)) = (&options.cache, &cached) {
+        if cache.is_fresh(entry) {
+            debug!("Using cached {}", url);
+            return Lookup::Fresh(entry.body.clone());
+        }
+    }
+    Lookup::Stale(cached)
+}
+
+/// How long to wait before attempt `attempt + 1`, or the error to give up with
+pub(crate) fn retry_delay(failure: FetchFailure, attempt: u32, options: &RemoteOptions) -> Result<Duration> {
+    if !failure.retryable || attempt >= options.retries {
+        return Err(AppError::RemoteFetch(failure.message).into());
+    }
+    let delay = options.retry_delay.saturating_mul(2u32.saturating_pow(attempt));
+    warn!(
+        "{}; retrying in {:.1?} (attempt {} of {})",
+        failure.message, delay, attempt + 1, options.retries
+    );
+    Ok(delay)
+}
+
+/// The body to use after a successful request, which is also cached
+pub(crate) fn finish(url: &str, options: &RemoteOptions, fetched: Fetched, cached: Option<CacheEntry>) -> Result<String> {
+    let (body, etag) = match (fetched, cached) {
+        (Fetched::Body { body, etag }, _) => (body, etag),
+        (Fetched::NotModified, Some(entry)) => {
+            debug!("{} not modified since it was cached", url);
+            (entry.body, entry.etag)
+        }
+        (Fetched::NotModified, None) => {
+            return Err(AppError::RemoteFetch(format!("{}: HTTP 304 without a cached copy", url)).into())
+        }
+    };
+    if let Some(cache) = &options.cache {
+        if let Err(e) = cache.store(url, etag, &body) {
+            warn!("Could not cache {}: {:#}", url, e);
+        }
+    }
+    Ok(body)
+}
+
+pub(crate) enum Fetched {
+    Body { body: String, etag: Option<String> },
+    /// The server confirmed the cached copy with the given ETag is current
+    NotModified,
+}
+
+pub(crate) struct FetchFailure {
+    pub(crate) message: String,
+    pub(crate) retryable: bool,
+}
+
+impl FetchFailure {
+    pub(crate) fn transport(url: &str, e: reqwest::Error) -> Self {
+        // A request that could not be built or a URL the connector rejects, e.g. for its
+        // scheme, fails the same way every time; only failures of the network itself are retried
+        Self {
+            retryable: e.is_timeout() || (e.is_connect() && caused_by_io(&e)),
+            message: format!("{}: {}", url, e),
+        }
+    }
+
+    pub(crate) fn status(url: &str, status: StatusCode) -> Self {
+        Self {
+            message: format!("{}: HTTP {}", url, status),
+            retryable: status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
+}
+
+fn caused_by_io(e: &reqwest::Error) -> bool {
+    let mut source = e.source();
+    while let Some(cause) = source {
+        if cause.is::<std::io::Error>() {
+            return true;
+        }
+        source = cause.source();
+    }
+    false
+}
+
+fn fetch_once(
+    client: &reqwest::blocking::Client,
+    url: &str,
+    options: &RemoteOptions,
+    etag: Option<&str>,
+) -> std::result::Result<Fetched, FetchFailure> {
+    let mut request = client.get(url);
+    if let Some(etag) = etag {
+        request = request.header(IF_NONE_MATCH, etag);
+    }
+    for (name, value) in &options.headers {
+        request = request.header(name, value);
+    }
+    if let Some((user, password)) = &options.basic_auth {
+        request = request.basic_auth(user, password.as_ref());
+    }
+    if let Some(token) = &options.bearer_token {
+        request = request.bearer_auth(token);
+    }
+
+    let response = request.send().map_err(|e| FetchFailure::transport(url, e))?;
+
+    let status = response.status();
+    if status == StatusCode::NOT_MODIFIED && etag.is_some() {
+        return Ok(Fetched::NotModified);
+    }
+    if !status.is_success() {
+        return Err(FetchFailure::status(url, status));
+    }
+
+    let etag = response_etag(response.headers());
+    let body = response.text().map_err(|e| FetchFailure::transport(url, e))?;
+    Ok(Fetched::Body { body, etag })
+}
+
+pub(crate) fn response_etag(headers: &HeaderMap) -> Option<String> {
+    headers.get(ETAG).and_then(|value| value.to_str().ok()).map(str::to_string)
+}
+
+pub fn fetch_json(url: &str) -> Result<Value> {
+    fetch_json_with(url, &RemoteOptions::default())
+}
+
+pub fn fetch_json_with(url: &str, options: &RemoteOptions) -> Result<Value> {
+    let body = fetch_text_with(url, options)?;
+    let value = serde_json::from_str(&body).map_err(|e| AppError::invalid_json(&e))?;
+    Ok(value)
+}
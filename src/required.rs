@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::samples::walk_with_samples;
+
+/// Which properties of a generated object schema are listed in `required`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum RequiredMode {
+    /// No property is required
+    None,
+    /// Properties present with a non-null value in every sample
+    NonNull,
+    /// Every property
+    All,
+    /// Properties present in every sample, e.g. every element of an array of objects
+    Observed,
+}
+
+/// Rewrites `required` throughout `schema` according to `mode`, using `data` as the samples
+/// the schema was generated from
+pub fn apply_required_mode(schema: &mut Value, data: &Value, mode: RequiredMode) {
+    walk_with_samples(schema, &[data], &mut |obj, samples| {
+        let Some(Value::Object(properties)) = obj.get("properties") else { return };
+
+        let objects: Vec<&Map<String, Value>> = samples.iter().filter_map(|s| s.as_object()).collect();
+        let required: Vec<Value> = properties
+            .keys()
+            .filter(|name| match mode {
+                RequiredMode::None => false,
+                RequiredMode::All => true,
+                RequiredMode::Observed => objects.iter().all(|o| o.contains_key(name.as_str())),
+                RequiredMode::NonNull => objects
+                    .iter()
+                    .all(|o| o.get(name.as_str()).is_some_and(|v| !v.is_null())),
+            })
+            .map(|name| Value::String(name.clone()))
+            .collect();
+
+        if required.is_empty() {
+            obj.remove("required");
+        } else {
+            obj.insert("required".to_string(), Value::Array(required));
+        }
+    });
+}
@@ -0,0 +1,109 @@
+use serde_json::{Map, Value};
+
+use crate::pointer;
+use crate::schema::walk::entry_mut;
+
+/// Called with a schema object and the input values it describes
+pub type SampleVisitor<'a> = dyn FnMut(&mut Map<String, Value>, &[&Value]) + 'a;
+
+/// Like [`SampleVisitor`], also given the JSON Pointer of the schema object
+pub type PointerSampleVisitor<'a> = dyn FnMut(&str, &mut Map<String, Value>, &[&Value]) + 'a;
+
+/// Visits every object in a generated schema along with the input values it was generated
+/// from, so post-processing passes can make data-aware decisions.
+///
+/// Subschemas are visited before their parent.
+pub fn walk_with_samples(schema: &mut Value, samples: &[&Value], f: &mut SampleVisitor) {
+    walk_with_pointers(schema, samples, &mut |_, obj, samples| f(obj, samples));
+}
+
+/// Like [`walk_with_samples`], passing the JSON Pointer of each schema object
+pub fn walk_with_pointers(schema: &mut Value, samples: &[&Value], f: &mut PointerSampleVisitor) {
+    // Schemas nest as deep as the input, so the walk keeps its own stack. Each subschema is
+    // taken out of its parent while it is walked and put back when done, and the pointer of
+    // the schema on top is kept in one string that grows and shrinks with the stack.
+    let mut location = String::new();
+    let mut stack = vec![Frame::new(std::mem::take(schema), samples.to_vec(), 0)];
+    while let Some(top) = stack.last_mut() {
+        if let Some((path, samples)) = top.children.get_mut(top.next) {
+            top.next += 1;
+            let child = std::mem::take(entry_mut(&mut top.node, path));
+            let base = location.len();
+            for token in path.iter() {
+                location.push_str(&pointer::join("", token));
+            }
+            let samples = std::mem::take(samples);
+            stack.push(Frame::new(child, samples, base));
+            continue;
+        }
+
+        let mut done = stack.pop().unwrap_or_else(|| unreachable!());
+        if let Value::Object(obj) = &mut done.node {
+            f(&location, obj, &done.samples);
+        }
+        location.truncate(done.base);
+        match stack.last_mut() {
+            Some(parent) => *entry_mut(&mut parent.node, &parent.children[parent.next - 1].0) = done.node,
+            None => *schema = done.node,
+        }
+    }
+}
+
+// A schema object being walked, with the subschemas to walk and the samples of each
+struct Frame<'d> {
+    node: Value,
+    samples: Vec<&'d Value>,
+    children: Vec<(Vec<String>, Vec<&'d Value>)>,
+    next: usize,
+    /// Length of the pointer of the parent
+    base: usize,
+}
+
+impl<'d> Frame<'d> {
+    fn new(node: Value, samples: Vec<&'d Value>, base: usize) -> Self {
+        let children = match &node {
+            Value::Object(obj) => children(obj, &samples),
+            _ => Vec::new(),
+        };
+        Self { node, samples, children, next: 0, base }
+    }
+}
+
+// The schema objects under `obj` to walk, by keyword path, with the samples they describe
+fn children<'d>(obj: &Map<String, Value>, samples: &[&'d Value]) -> Vec<(Vec<String>, Vec<&'d Value>)> {
+    let mut children = Vec::new();
+    if let Some(Value::Object(properties)) = obj.get("properties") {
+        for (name, property) in properties {
+            let values: Vec<&Value> = samples
+                .iter()
+                .filter_map(|s| s.as_object())
+                .filter_map(|o| o.get(name))
+                .collect();
+            if property.is_object() {
+                children.push((vec!["properties".to_string(), name.clone()], values));
+            }
+        }
+    }
+
+    let elements: Vec<&Value> = samples
+        .iter()
+        .filter_map(|s| s.as_array())
+        .flatten()
+        .collect();
+    match obj.get("items") {
+        // Heterogeneous arrays get one `oneOf` branch per element of the first array
+        Some(Value::Object(items)) if items.contains_key("oneOf") => {
+            if let Some(Value::Array(branches)) = items.get("oneOf") {
+                for (index, (branch, element)) in branches.iter().zip(&elements).enumerate() {
+                    if branch.is_object() {
+                        let path = vec!["items".to_string(), "oneOf".to_string(), index.to_string()];
+                        children.push((path, vec![*element]));
+                    }
+                }
+            }
+        }
+        Some(Value::Object(_)) => children.push((vec!["items".to_string()], elements)),
+        _ => {}
+    }
+    children
+}
@@ -0,0 +1,39 @@
+use serde_json::Value;
+
+/// Seed used when none is given, so sampled schemas are reproducible
+pub const DEFAULT_SEED: u64 = 0;
+
+/// Copies `value`, replacing every array longer than `limit` with `limit` of its elements
+/// chosen by reservoir sampling. Sampled elements keep their original order.
+pub fn sample_arrays(value: &Value, limit: usize, seed: Option<u64>) -> Value {
+    let mut rng = fastrand::Rng::with_seed(seed.unwrap_or(DEFAULT_SEED));
+    sample(value, limit, &mut rng)
+}
+
+fn sample(value: &Value, limit: usize, rng: &mut fastrand::Rng) -> Value {
+    match value {
+        Value::Object(obj) => Value::Object(
+            obj.iter()
+                .map(|(key, child)| (key.clone(), sample(child, limit, rng)))
+                .collect(),
+        ),
+        Value::Array(items) => {
+            let mut indices = reservoir(items.len(), limit, rng);
+            indices.sort_unstable();
+            Value::Array(indices.into_iter().map(|i| sample(&items[i], limit, rng)).collect())
+        }
+        other => other.clone(),
+    }
+}
+
+// Algorithm R: every index ends up in the reservoir with probability limit / len
+fn reservoir(len: usize, limit: usize, rng: &mut fastrand::Rng) -> Vec<usize> {
+    let mut chosen: Vec<usize> = (0..len.min(limit)).collect();
+    for i in limit..len {
+        let j = rng.usize(0..=i);
+        if j < limit {
+            chosen[j] = i;
+        }
+    }
+    chosen
+}
@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum SchemaDraft {
+    #[cfg_attr(feature = "cli", value(name = "draft-04"))]
+    #[serde(rename = "draft-04")]
+    Draft04,
+    #[cfg_attr(feature = "cli", value(name = "draft-06"))]
+    #[serde(rename = "draft-06")]
+    Draft06,
+    #[cfg_attr(feature = "cli", value(name = "draft-07"))]
+    #[serde(rename = "draft-07")]
+    Draft07,
+    #[cfg_attr(feature = "cli", value(name = "2019-09"))]
+    #[serde(rename = "2019-09")]
+    Draft201909,
+    #[default]
+    #[cfg_attr(feature = "cli", value(name = "2020-12"))]
+    #[serde(rename = "2020-12")]
+    Draft202012,
+}
+
+impl SchemaDraft {
+    pub const ALL: [SchemaDraft; 5] = [
+        SchemaDraft::Draft04,
+        SchemaDraft::Draft06,
+        SchemaDraft::Draft07,
+        SchemaDraft::Draft201909,
+        SchemaDraft::Draft202012,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SchemaDraft::Draft04 => "draft-04",
+            SchemaDraft::Draft06 => "draft-06",
+            SchemaDraft::Draft07 => "draft-07",
+            SchemaDraft::Draft201909 => "2019-09",
+            SchemaDraft::Draft202012 => "2020-12",
+        }
+    }
+
+    pub fn uri(&self) -> &'static str {
+        match self {
+            SchemaDraft::Draft04 => "http://json-schema.org/draft-04/schema#",
+            SchemaDraft::Draft06 => "http://json-schema.org/draft-06/schema#",
+            SchemaDraft::Draft07 => "http://json-schema.org/draft-07/schema#",
+            SchemaDraft::Draft201909 => "https://json-schema.org/draft/2019-09/schema",
+            SchemaDraft::Draft202012 => "https://json-schema.org/draft/2020-12/schema",
+        }
+    }
+
+    pub fn from_uri(uri: &str) -> Option<Self> {
+        let normalized = uri
+            .trim_end_matches('#')
+            .trim_start_matches("https://")
+            .trim_start_matches("http://");
+        Self::ALL.into_iter().find(|draft| {
+            draft
+                .uri()
+                .trim_end_matches('#')
+                .trim_start_matches("https://")
+                .trim_start_matches("http://")
+                == normalized
+        })
+    }
+
+    pub fn detect(schema: &Value) -> Option<Self> {
+        schema
+            .get("$schema")
+            .and_then(Value::as_str)
+            .and_then(Self::from_uri)
+    }
+
+    pub fn uses_defs(&self) -> bool {
+        *self >= SchemaDraft::Draft201909
+    }
+}
+
+impl std::fmt::Display for SchemaDraft {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
@@ -0,0 +1,216 @@
+use serde_json::{Number, Value};
+
+use crate::pointer;
+use crate::schema::model::{InstanceType, Items, Schema, SchemaOrBool, TypeSet};
+
+impl Schema {
+    /// Editor for the subschema at the JSON Pointer `location`, e.g.
+    /// `schema.at("/properties/age").set_minimum(0).require()`
+    pub fn at(&mut self, location: &str) -> SchemaEdit<'_> {
+        let tokens = match location.strip_prefix('/') {
+            Some(rest) => rest.split('/').map(pointer::unescape_token).collect(),
+            None => Vec::new(),
+        };
+        SchemaEdit { root: self, tokens }
+    }
+}
+
+/// Chained edits to one subschema of a [`Schema`], for applying organization rules after
+/// generation without hand-editing JSON.
+///
+/// Edits through a pointer that does not resolve are skipped, so the same rules can run on
+/// schemas that lack some of the fields; [`SchemaEdit::exists`] tells whether it resolves.
+#[derive(Debug)]
+pub struct SchemaEdit<'a> {
+    root: &'a mut Schema,
+    tokens: Vec<String>,
+}
+
+impl SchemaEdit<'_> {
+    pub fn exists(&mut self) -> bool {
+        resolve(self.root, &self.tokens).is_some()
+    }
+
+    /// Applies `f` to the subschema, for the keywords without a setter
+    pub fn edit(&mut self, f: impl FnOnce(&mut Schema)) -> &mut Self {
+        if let Some(schema) = resolve(self.root, &self.tokens) {
+            f(schema);
+        }
+        self
+    }
+
+    pub fn set_type(&mut self, kind: InstanceType) -> &mut Self {
+        self.edit(|schema| schema.kind = Some(TypeSet::Single(kind)))
+    }
+
+    /// Also accepts `null`, keeping the current type
+    pub fn nullable(&mut self) -> &mut Self {
+        self.edit(|schema| {
+            let mut kinds = match schema.kind.take() {
+                Some(TypeSet::Single(kind)) => vec![kind],
+                Some(TypeSet::Multiple(kinds)) => kinds,
+                None => return,
+            };
+            if !kinds.contains(&InstanceType::Null) {
+                kinds.push(InstanceType::Null);
+            }
+            schema.kind = Some(TypeSet::Multiple(kinds));
+        })
+    }
+
+    pub fn set_title(&mut self, title: impl Into<String>) -> &mut Self {
+        let title = title.into();
+        self.edit(|schema| schema.title = Some(title))
+    }
+
+    pub fn set_description(&mut self, description: impl Into<String>) -> &mut Self {
+        let description = description.into();
+        self.edit(|schema| schema.description = Some(description))
+    }
+
+    pub fn set_format(&mut self, format: impl Into<String>) -> &mut Self {
+        let format = format.into();
+        self.edit(|schema| schema.format = Some(format))
+    }
+
+    pub fn set_pattern(&mut self, pattern: impl Into<String>) -> &mut Self {
+        let pattern = pattern.into();
+        self.edit(|schema| schema.pattern = Some(pattern))
+    }
+
+    pub fn set_minimum(&mut self, minimum: impl Into<Number>) -> &mut Self {
+        let minimum = minimum.into();
+        self.edit(|schema| schema.minimum = Some(minimum))
+    }
+
+    pub fn set_maximum(&mut self, maximum: impl Into<Number>) -> &mut Self {
+        let maximum = maximum.into();
+        self.edit(|schema| schema.maximum = Some(maximum))
+    }
+
+    pub fn set_min_length(&mut self, length: u64) -> &mut Self {
+        self.edit(|schema| schema.min_length = Some(length))
+    }
+
+    pub fn set_max_length(&mut self, length: u64) -> &mut Self {
+        self.edit(|schema| schema.max_length = Some(length))
+    }
+
+    pub fn set_min_items(&mut self, count: u64) -> &mut Self {
+        self.edit(|schema| schema.min_items = Some(count))
+    }
+
+    pub fn set_max_items(&mut self, count: u64) -> &mut Self {
+        self.edit(|schema| schema.max_items = Some(count))
+    }
+
+    pub fn set_enum(&mut self, values: impl IntoIterator<Item = Value>) -> &mut Self {
+        let values = values.into_iter().collect();
+        self.edit(|schema| schema.enumeration = Some(values))
+    }
+
+    pub fn set_default(&mut self, value: impl Into<Value>) -> &mut Self {
+        let value = value.into();
+        self.edit(|schema| schema.default = Some(value))
+    }
+
+    pub fn set_additional_properties(&mut self, allowed: bool) -> &mut Self {
+        self.edit(|schema| schema.additional_properties = Some(SchemaOrBool::Bool(allowed)))
+    }
+
+    /// Sets a keyword the model has no field for, e.g. an `x-` extension
+    pub fn set_extension(&mut self, keyword: impl Into<String>, value: impl Into<Value>) -> &mut Self {
+        let (keyword, value) = (keyword.into(), value.into());
+        self.edit(|schema| {
+            schema.extra.insert(keyword, value);
+        })
+    }
+
+    /// Removes `keyword`, typed or not
+    pub fn remove(&mut self, keyword: &str) -> &mut Self {
+        self.edit(|schema| {
+            let mut value = schema.to_value();
+            if let Some(obj) = value.as_object_mut() {
+                obj.remove(keyword);
+            }
+            if let Ok(edited) = Schema::try_from(value) {
+                *schema = edited;
+            }
+        })
+    }
+
+    /// Adds the property to its parent's `required`. Only for a pointer ending in
+    /// `/properties/<name>`; anything else is left alone.
+    pub fn require(&mut self) -> &mut Self {
+        self.edit_parent(|parent, name| {
+            let required = parent.required.get_or_insert_with(Vec::new);
+            if !required.iter().any(|r| r == name) {
+                required.push(name.to_string());
+            }
+        })
+    }
+
+    /// Removes the property from its parent's `required`, like [`SchemaEdit::require`]
+    pub fn optional(&mut self) -> &mut Self {
+        self.edit_parent(|parent, name| {
+            if let Some(required) = &mut parent.required {
+                required.retain(|r| r != name);
+                if required.is_empty() {
+                    parent.required = None;
+                }
+            }
+        })
+    }
+
+    /// Removes the property from its parent, along with its `required` entry
+    pub fn remove_property(&mut self) -> &mut Self {
+        self.optional().edit_parent(|parent, name| {
+            if let Some(properties) = &mut parent.properties {
+                #[cfg(feature = "preserve-order")]
+                properties.shift_remove(name);
+                #[cfg(not(feature = "preserve-order"))]
+                properties.remove(name);
+            }
+        })
+    }
+
+    fn edit_parent(&mut self, f: impl FnOnce(&mut Schema, &str)) -> &mut Self {
+        let [parent @ .., keyword, name] = self.tokens.as_slice() else { return self };
+        if keyword != "properties" {
+            return self;
+        }
+        if let Some(parent) = resolve(self.root, parent) {
+            if parent.properties.as_ref().is_some_and(|p| p.contains_key(name)) {
+                f(parent, name);
+            }
+        }
+        self
+    }
+}
+
+fn resolve<'s>(schema: &'s mut Schema, tokens: &[String]) -> Option<&'s mut Schema> {
+    let Some((token, rest)) = tokens.split_first() else { return Some(schema) };
+    let index = |rest: &[String]| rest.first().and_then(|t| t.parse::<usize>().ok());
+
+    let (child, rest) = match token.as_str() {
+        "properties" => (schema.properties.as_mut()?.get_mut(rest.first()?)?, &rest[1..]),
+        "$defs" => (schema.defs.as_mut()?.get_mut(rest.first()?)?, &rest[1..]),
+        "anyOf" => (schema.any_of.as_mut()?.get_mut(index(rest)?)?, &rest[1..]),
+        "oneOf" => (schema.one_of.as_mut()?.get_mut(index(rest)?)?, &rest[1..]),
+        "allOf" => (schema.all_of.as_mut()?.get_mut(index(rest)?)?, &rest[1..]),
+        "items" => match schema.items.as_mut()? {
+            Items::Single(SchemaOrBool::Schema(items)) => (items.as_mut(), rest),
+            Items::Tuple(items) => match items.get_mut(index(rest)?)? {
+                SchemaOrBool::Schema(item) => (item.as_mut(), &rest[1..]),
+                SchemaOrBool::Bool(_) => return None,
+            },
+            Items::Single(SchemaOrBool::Bool(_)) => return None,
+        },
+        "additionalProperties" => match schema.additional_properties.as_mut()? {
+            SchemaOrBool::Schema(additional) => (additional.as_mut(), rest),
+            SchemaOrBool::Bool(_) => return None,
+        },
+        _ => return None,
+    };
+    resolve(child, rest)
+}
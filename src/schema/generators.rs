@@ -1,225 +1,143 @@
 use serde_json::{Value, Map};
 use anyhow::Result;
 
-use crate::schema::{SchemaOutputTier, types::*};
+use crate::parallel;
+use crate::shape;
+use crate::schema::{TierPolicy, types::*};
+use crate::schema::model::{InstanceType, Items, Schema, SchemaMap, SchemaOrBool};
 
 pub fn generate_object_schema(
     obj: &Map<String, Value>,
-    tier: &SchemaOutputTier
-) -> Result<Value> {
-    let mut schema = serde_json::json!({
-        "type": "object",
-        "properties": {}
-    });
-
-    if matches!(tier, SchemaOutputTier::Comprehensive | SchemaOutputTier::Expert) {
-        schema["$schema"] = Value::String("https://json-schema.org/draft/2020-12/schema".to_string());
+    policy: &dyn TierPolicy
+) -> Result<Schema> {
+    let values: Vec<&Value> = obj.values().collect();
+    Ok(finish_object(obj, build_all(&values, policy)?, policy))
+}
+
+pub fn generate_array_schema(
+    arr: &[Value],
+    policy: &dyn TierPolicy
+) -> Result<Schema> {
+    let items = array_children(arr);
+    Ok(finish_array(arr, build_all(&items, policy)?, policy))
+}
+
+/// The values a schema for `value` is built from: every property value of an object, the
+/// first element of a homogeneous array, or each distinct element otherwise
+pub(crate) fn child_values(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Object(obj) => obj.values().collect(),
+        Value::Array(arr) => array_children(arr),
+        _ => Vec::new(),
+    }
+}
+
+fn array_children(arr: &[Value]) -> Vec<&Value> {
+    if arr.is_empty() {
+        Vec::new()
+    } else if is_homogeneous_array(arr) {
+        vec![&arr[0]]
+    } else {
+        // Identical elements get identical branches, so each is built once
+        shape::distinct(arr).0
+    }
+}
+
+/// The schema for `value` given the schemas of its [`child_values`], in order
+pub(crate) fn finish_schema(value: &Value, children: Vec<Schema>, policy: &dyn TierPolicy) -> Result<Schema> {
+    match value {
+        Value::Object(obj) => Ok(finish_object(obj, children, policy)),
+        Value::Array(arr) => Ok(finish_array(arr, children, policy)),
+        Value::String(_) => generate_string_schema(value, policy),
+        Value::Number(n) => generate_number_schema(n, policy),
+        Value::Bool(_) => generate_boolean_schema(value, policy),
+        Value::Null => generate_null_schema(),
     }
+}
 
+/// Schemas for `values`, in parallel when allowed
+pub(crate) fn build_all(values: &[&Value], policy: &dyn TierPolicy) -> Result<Vec<Schema>> {
+    parallel::par_map(values, |value| crate::schema::build_schema(value, policy))
+        .into_iter()
+        .collect()
+}
+
+fn finish_object(obj: &Map<String, Value>, children: Vec<Schema>, policy: &dyn TierPolicy) -> Schema {
     let mut required_props = Vec::new();
-    let mut properties_map = Map::new();
-    
-    for (key, value) in obj {
-        let property_schema = crate::schema::generate_schema(value, tier)?;
+    let mut properties_map = SchemaMap::new();
+
+    for ((key, value), property_schema) in obj.iter().zip(children) {
         properties_map.insert(key.clone(), property_schema);
 
-        match tier {
-            SchemaOutputTier::Basic => {},
-            SchemaOutputTier::Standard => {
-                if !value.is_null() {
-                    required_props.push(Value::String(key.clone()));
-                }
-            }
-            SchemaOutputTier::Comprehensive | SchemaOutputTier::Expert => {
-                required_props.push(Value::String(key.clone()));
-            }
+        if policy.is_required(key, value) {
+            required_props.push(key.clone());
         }
     }
 
-    schema["properties"] = Value::Object(properties_map);
+    let mut schema = Schema::of_type(InstanceType::Object);
+    schema.properties = Some(properties_map);
 
     if !required_props.is_empty() {
-        schema["required"] = Value::Array(required_props);
+        schema.required = Some(required_props);
     }
 
-    match tier {
-        SchemaOutputTier::Basic => {},
-        SchemaOutputTier::Standard => {
-            schema["additionalProperties"] = Value::Bool(true);
-        }
-        SchemaOutputTier::Comprehensive => {
-            schema["additionalProperties"] = Value::Bool(false);
-            schema["minProperties"] = Value::Number(1.into());
-        }
-        SchemaOutputTier::Expert => {
-            schema["additionalProperties"] = Value::Bool(false);
-            schema["minProperties"] = Value::Number(1.into());
-            schema["title"] = Value::String("Generated Object Schema".to_string());
-            schema["description"] = Value::String("Auto-generated schema from JSON data".to_string());
-        }
-    }
-
-    Ok(schema)
+    policy.object(&mut schema, obj);
+    schema
 }
 
-pub fn generate_array_schema(
-    arr: &[Value],
-    tier: &SchemaOutputTier
-) -> Result<Value> {
+fn finish_array(arr: &[Value], mut children: Vec<Schema>, policy: &dyn TierPolicy) -> Schema {
+    let mut schema = Schema::of_type(InstanceType::Array);
+
     if arr.is_empty() {
-        return Ok(serde_json::json!({
-            "type": "array",
-            "items": {}
-        }));
+        schema.items = Some(Items::Single(SchemaOrBool::Schema(Box::default())));
+        return schema;
     }
 
-    let mut schema = serde_json::json!({
-        "type": "array"
-    });
-
-    if is_homogeneous_array(arr) {
-        let item_schema = crate::schema::generate_schema(&arr[0], tier)?;
-        schema["items"] = item_schema;
+    let item_schema = if is_homogeneous_array(arr) {
+        children.swap_remove(0)
     } else {
-        let mut item_schemas = Vec::new();
-        for item in arr {
-            let item_schema = crate::schema::generate_schema(item, tier)?;
-            item_schemas.push(item_schema);
-        }
-        schema["items"] = serde_json::json!({ "oneOf": item_schemas });
-    }
-
-    match tier {
-        SchemaOutputTier::Basic => {},
-        SchemaOutputTier::Standard => {
-            if !arr.is_empty() {
-                schema["minItems"] = Value::Number(0.into());
-            }
-        }
-        SchemaOutputTier::Comprehensive => {
-            if !arr.is_empty() {
-                schema["minItems"] = Value::Number(1.into());
-                schema["maxItems"] = Value::Number((arr.len() * 2).into());
-            }
-        }
-        SchemaOutputTier::Expert => {
-            if !arr.is_empty() {
-                schema["minItems"] = Value::Number(1.into());
-                schema["maxItems"] = Value::Number((arr.len() * 2).into());
-                schema["uniqueItems"] = Value::Bool(true);
-                schema["title"] = Value::String("Generated Array Schema".to_string());
-                schema["description"] = Value::String("Auto-generated array schema from JSON data".to_string());
-            }
-        }
-    }
-
-    Ok(schema)
+        // One branch per element, from the schemas of the distinct elements
+        let (_, indices) = shape::distinct(arr);
+        let branches = indices.into_iter().map(|index| children[index].clone()).collect();
+        Schema { one_of: Some(branches), ..Schema::default() }
+    };
+    schema.items = Some(Items::Single(SchemaOrBool::Schema(Box::new(item_schema))));
+
+    policy.array(&mut schema, arr);
+    schema
 }
 
-pub fn generate_string_schema(value: &Value, tier: &SchemaOutputTier) -> Result<Value> {
-    let mut schema = serde_json::json!({ "type": "string" });
+pub fn generate_string_schema(value: &Value, policy: &dyn TierPolicy) -> Result<Schema> {
+    let mut schema = Schema::of_type(InstanceType::String);
 
     if let Value::String(s) = value {
-        match tier {
-            SchemaOutputTier::Basic => {},
-            SchemaOutputTier::Standard => {
-                schema["minLength"] = Value::Number(0.into());
-            }
-            SchemaOutputTier::Comprehensive => {
-                schema["minLength"] = Value::Number(0.into());
-                schema["maxLength"] = Value::Number((s.len() * 2).into());
-                if !s.is_empty() {
-                    schema["examples"] = serde_json::json!([s]);
-                }
-            }
-            SchemaOutputTier::Expert => {
-                schema["minLength"] = Value::Number(0.into());
-                schema["maxLength"] = Value::Number((s.len() * 2).into());
-                if !s.is_empty() {
-                    schema["examples"] = serde_json::json!([s]);
-                    
-                    if let Some(format) = detect_string_format(s) {
-                        schema["format"] = Value::String(format.to_string());
-                    } else if let Some(pattern) = detect_string_pattern(s) {
-                        schema["pattern"] = Value::String(pattern.to_string());
-                    }
-                }
-                schema["title"] = Value::String("Generated String Schema".to_string());
-            }
-        }
+        policy.string(&mut schema, s);
     }
 
     Ok(schema)
 }
 
-pub fn generate_number_schema(n: &serde_json::Number, tier: &SchemaOutputTier) -> Result<Value> {
-    let mut schema = serde_json::json!({});
-
-    if n.is_i64() || n.is_u64() {
-        schema["type"] = Value::String("integer".into());
+pub fn generate_number_schema(n: &serde_json::Number, policy: &dyn TierPolicy) -> Result<Schema> {
+    let mut schema = if n.is_i64() || n.is_u64() {
+        Schema::of_type(InstanceType::Integer)
     } else {
-        schema["type"] = Value::String("number".into());
-    }
-
-    match tier {
-        SchemaOutputTier::Basic => {},
-        SchemaOutputTier::Standard => {
-            if let Some(n_val) = n.as_i64() {
-                schema["minimum"] = serde_json::json!(n_val);
-            } else if let Some(n_val) = n.as_f64() {
-                schema["minimum"] = serde_json::json!(n_val);
-            }
-        }
-        SchemaOutputTier::Comprehensive => {
-            if let Some(n_val) = n.as_i64() {
-                schema["examples"] = serde_json::json!([n_val]);
-                schema["minimum"] = serde_json::json!(n_val - 1000);
-                schema["maximum"] = serde_json::json!(n_val + 1000);
-            } else if let Some(n_val) = n.as_f64() {
-                schema["examples"] = serde_json::json!([n_val]);
-                schema["minimum"] = serde_json::json!(n_val - 1000.0);
-                schema["maximum"] = serde_json::json!(n_val + 1000.0);
-            }
-        }
-        SchemaOutputTier::Expert => {
-            if let Some(n_val) = n.as_i64() {
-                schema["examples"] = serde_json::json!([n_val]);
-                schema["minimum"] = serde_json::json!(n_val - 1000);
-                schema["maximum"] = serde_json::json!(n_val + 1000);
-                schema["multipleOf"] = serde_json::json!(1);
-                schema["title"] = Value::String("Generated Integer Schema".to_string());
-            } else if let Some(n_val) = n.as_f64() {
-                schema["examples"] = serde_json::json!([n_val]);
-                schema["minimum"] = serde_json::json!(n_val - 1000.0);
-                schema["maximum"] = serde_json::json!(n_val + 1000.0);
-                schema["title"] = Value::String("Generated Number Schema".to_string());
-            }
-        }
-    }
+        Schema::of_type(InstanceType::Number)
+    };
 
+    policy.number(&mut schema, n);
     Ok(schema)
 }
 
-pub fn generate_boolean_schema(value: &Value, tier: &SchemaOutputTier) -> Result<Value> {
-    let mut schema = serde_json::json!({ "type": "boolean" });
+pub fn generate_boolean_schema(value: &Value, policy: &dyn TierPolicy) -> Result<Schema> {
+    let mut schema = Schema::of_type(InstanceType::Boolean);
 
     if let Value::Bool(b) = value {
-        match tier {
-            SchemaOutputTier::Basic | SchemaOutputTier::Standard => {},
-            SchemaOutputTier::Comprehensive => {
-                schema["examples"] = serde_json::json!([b]);
-            }
-            SchemaOutputTier::Expert => {
-                schema["examples"] = serde_json::json!([b]);
-                schema["title"] = Value::String("Generated Boolean Schema".to_string());
-                schema["description"] = Value::String("Boolean value from JSON data".to_string());
-            }
-        }
+        policy.boolean(&mut schema, *b);
     }
 
     Ok(schema)
 }
 
-pub fn generate_null_schema() -> Result<Value> {
-    Ok(serde_json::json!({ "type": "null" }))
-}
\ No newline at end of file
+pub fn generate_null_schema() -> Result<Schema> {
+    Ok(Schema::of_type(InstanceType::Null))
+}
@@ -0,0 +1,117 @@
+use serde_json::{Map, Value};
+
+pub const ANNOTATION_KEYWORDS: &[&str] = &[
+    "title",
+    "description",
+    "examples",
+    "default",
+    "$comment",
+    "deprecated",
+    "readOnly",
+    "writeOnly",
+];
+
+pub const SUBSCHEMA_KEYWORDS: &[&str] = &[
+    "items",
+    "additionalItems",
+    "additionalProperties",
+    "contains",
+    "propertyNames",
+    "not",
+    "if",
+    "then",
+    "else",
+    "unevaluatedItems",
+    "unevaluatedProperties",
+    "contentSchema",
+];
+
+pub const SUBSCHEMA_MAP_KEYWORDS: &[&str] = &[
+    "properties",
+    "patternProperties",
+    "$defs",
+    "definitions",
+    "dependentSchemas",
+    "dependencies",
+];
+
+pub const SUBSCHEMA_ARRAY_KEYWORDS: &[&str] = &["allOf", "anyOf", "oneOf", "prefixItems", "items"];
+
+pub fn for_each_subschema<'a>(obj: &'a Map<String, Value>, f: &mut dyn FnMut(Vec<String>, &'a Value)) {
+    for (key, value) in obj {
+        match value {
+            Value::Object(map) if SUBSCHEMA_MAP_KEYWORDS.contains(&key.as_str()) => {
+                for (name, schema) in map {
+                    if is_schema(schema) {
+                        f(vec![key.clone(), name.clone()], schema);
+                    }
+                }
+            }
+            Value::Array(items) if SUBSCHEMA_ARRAY_KEYWORDS.contains(&key.as_str()) => {
+                for (index, schema) in items.iter().enumerate() {
+                    if is_schema(schema) {
+                        f(vec![key.clone(), index.to_string()], schema);
+                    }
+                }
+            }
+            _ if SUBSCHEMA_KEYWORDS.contains(&key.as_str()) && is_schema(value) => {
+                f(vec![key.clone()], value)
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn for_each_subschema_mut<'a>(obj: &'a mut Map<String, Value>, f: &mut dyn FnMut(&'a mut Value)) {
+    // Checked before matching, since subschemas are lent to `f` for as long as `obj`
+    for (key, value) in obj.iter_mut() {
+        let key = key.as_str();
+        if SUBSCHEMA_MAP_KEYWORDS.contains(&key) && value.is_object() {
+            for schema in value.as_object_mut().into_iter().flat_map(|map| map.values_mut()) {
+                if is_schema(schema) {
+                    f(schema);
+                }
+            }
+        } else if SUBSCHEMA_ARRAY_KEYWORDS.contains(&key) && value.is_array() {
+            for schema in value.as_array_mut().into_iter().flatten() {
+                if is_schema(schema) {
+                    f(schema);
+                }
+            }
+        } else if SUBSCHEMA_KEYWORDS.contains(&key) && is_schema(value) {
+            f(value);
+        }
+    }
+}
+
+/// Like [`for_each_subschema_mut`], also passing the keyword path of each subschema
+pub fn for_each_subschema_entry_mut<'a>(
+    obj: &'a mut Map<String, Value>,
+    f: &mut dyn FnMut(Vec<String>, &'a mut Value),
+) {
+    for (key, value) in obj.iter_mut() {
+        if SUBSCHEMA_MAP_KEYWORDS.contains(&key.as_str()) && value.is_object() {
+            for (name, schema) in value.as_object_mut().into_iter().flatten() {
+                if is_schema(schema) {
+                    f(vec![key.clone(), name.clone()], schema);
+                }
+            }
+        } else if SUBSCHEMA_ARRAY_KEYWORDS.contains(&key.as_str()) && value.is_array() {
+            for (index, schema) in value.as_array_mut().into_iter().flatten().enumerate() {
+                if is_schema(schema) {
+                    f(vec![key.clone(), index.to_string()], schema);
+                }
+            }
+        } else if SUBSCHEMA_KEYWORDS.contains(&key.as_str()) && is_schema(value) {
+            f(vec![key.clone()], value);
+        }
+    }
+}
+
+pub fn is_annotation(keyword: &str) -> bool {
+    ANNOTATION_KEYWORDS.contains(&keyword) || keyword.starts_with("x-")
+}
+
+pub fn is_schema(value: &Value) -> bool {
+    value.is_object() || value.is_boolean()
+}
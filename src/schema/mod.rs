@@ -1,28 +1,90 @@
 use serde_json::Value;
-use clap::ValueEnum;
 use anyhow::Result;
 
+use crate::limits;
+use crate::parallel;
+
+pub mod draft;
+pub mod edit;
 pub mod generators;
+pub mod keywords;
+pub mod model;
+pub mod policy;
+pub mod stream;
 pub mod types;
+pub mod walk;
 
+pub use draft::SchemaDraft;
+pub use edit::SchemaEdit;
 pub use generators::*;
+pub use model::Schema;
+pub use policy::TierPolicy;
+pub use stream::{stream_schema, stream_schema_from_reader};
 pub use types::*;
+pub use walk::SchemaWalk;
 
-#[derive(Debug, Clone, ValueEnum, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
 pub enum SchemaOutputTier {
+    #[serde(alias = "basic")]
     Basic,
+    #[serde(alias = "standard")]
     Standard,
+    #[serde(alias = "comprehensive")]
     Comprehensive,
+    #[serde(alias = "expert")]
     Expert,
 }
 
 pub fn generate_schema(value: &Value, tier: &SchemaOutputTier) -> Result<Value> {
-    match value {
-        Value::Object(obj) => generate_object_schema(obj, tier),
-        Value::Array(arr) => generate_array_schema(arr, tier),
-        Value::String(_) => generate_string_schema(value, tier),
-        Value::Number(n) => generate_number_schema(n, tier),
-        Value::Bool(_) => generate_boolean_schema(value, tier),
-        Value::Null => generate_null_schema(),
+    generate_schema_with(value, tier)
+}
+
+/// Generates the schema for `value`, with `policy` deciding the keywords a tier would
+pub fn generate_schema_with(value: &Value, policy: &dyn TierPolicy) -> Result<Value> {
+    build_schema(value, policy).map(Value::from)
+}
+
+/// Like [`generate_schema_with`], as a typed [`Schema`]
+///
+/// Walks `value` with an explicit stack rather than recursion, so input nested any number of
+/// levels deep cannot overflow the call stack.
+pub fn build_schema(value: &Value, policy: &dyn TierPolicy) -> Result<Schema> {
+    enum Children {
+        /// Built by the nodes that follow, this many
+        Pending(usize),
+        /// Built up front, in parallel
+        Built(Vec<Schema>),
+    }
+
+    // Every node in depth-first order, each followed by the nodes below it
+    let mut nodes: Vec<(&Value, Children)> = Vec::new();
+    let mut stack = vec![value];
+    while let Some(value) = stack.pop() {
+        let children = generators::child_values(value);
+        // Only when threads are started: built on this thread, the children would recurse
+        if parallel::runs_in_parallel(children.len()) {
+            nodes.push((value, Children::Built(generators::build_all(&children, policy)?)));
+        } else {
+            nodes.push((value, Children::Pending(children.len())));
+            stack.extend(children.into_iter().rev());
+        }
+    }
+
+    // Backwards, so the schemas of a node's children are on top of `built`, last child first
+    let mut built: Vec<Schema> = Vec::new();
+    let mut checked = 0;
+    for (value, children) in nodes.into_iter().rev() {
+        limits::check_deadline_every(&mut checked)?;
+        let children = match children {
+            Children::Pending(count) => {
+                let mut children = built.split_off(built.len() - count);
+                children.reverse();
+                children
+            }
+            Children::Built(children) => children,
+        };
+        built.push(generators::finish_schema(value, children, policy)?);
     }
+    Ok(built.pop().expect("the root is always built"))
 }
\ No newline at end of file
@@ -0,0 +1,241 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Number, Value};
+
+/// Named subschemas, as in `properties` and `$defs`: sorted by name, or in insertion order
+/// with the `preserve-order` feature
+#[cfg(not(feature = "preserve-order"))]
+pub type SchemaMap = std::collections::BTreeMap<String, Schema>;
+#[cfg(feature = "preserve-order")]
+pub type SchemaMap = indexmap::IndexMap<String, Schema>;
+
+/// A JSON Schema with the keywords the generators emit as typed fields. Every other keyword,
+/// e.g. `$comment` or `x-*` extensions, is kept in `extra`, so any schema object converts to
+/// a `Schema` and back without loss.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Schema {
+    #[serde(rename = "$schema", skip_serializing_if = "Option::is_none")]
+    pub schema_uri: Option<String>,
+    #[serde(rename = "$ref", skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub kind: Option<TypeSet>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub properties: Option<SchemaMap>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub additional_properties: Option<SchemaOrBool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_properties: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_properties: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Items>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_items: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_items: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unique_items: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<Number>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<Number>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multiple_of: Option<Number>,
+
+    #[serde(rename = "enum", skip_serializing_if = "Option::is_none")]
+    pub enumeration: Option<Vec<Value>>,
+    /// `Some(Value::Null)` is `"const": null`
+    #[serde(rename = "const", default, deserialize_with = "present", skip_serializing_if = "Option::is_none")]
+    pub constant: Option<Value>,
+    #[serde(default, deserialize_with = "present", skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub examples: Option<Vec<Value>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub any_of: Option<Vec<Schema>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub one_of: Option<Vec<Schema>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub all_of: Option<Vec<Schema>>,
+    #[serde(rename = "$defs", skip_serializing_if = "Option::is_none")]
+    pub defs: Option<SchemaMap>,
+
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InstanceType {
+    Null,
+    Boolean,
+    Object,
+    Array,
+    Number,
+    Integer,
+    String,
+}
+
+/// `"type": "string"` or `"type": ["string", "null"]`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TypeSet {
+    Single(InstanceType),
+    Multiple(Vec<InstanceType>),
+}
+
+/// A subschema that may also be `true` (anything) or `false` (nothing)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SchemaOrBool {
+    Bool(bool),
+    Schema(Box<Schema>),
+}
+
+/// `items`: one schema for every element, or one per position in drafts before 2020-12
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Items {
+    Single(SchemaOrBool),
+    Tuple(Vec<SchemaOrBool>),
+}
+
+impl Schema {
+    pub fn of_type(kind: InstanceType) -> Self {
+        Self {
+            kind: Some(TypeSet::Single(kind)),
+            ..Self::default()
+        }
+    }
+
+    /// The type, or the first type other than `null` of a type list
+    pub fn instance_type(&self) -> Option<InstanceType> {
+        match self.kind.as_ref()? {
+            TypeSet::Single(kind) => Some(*kind),
+            TypeSet::Multiple(kinds) => kinds.iter().copied().find(|k| *k != InstanceType::Null),
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        serde_json::to_value(self).expect("a schema always serializes to JSON")
+    }
+}
+
+impl From<Schema> for Value {
+    /// Converts with an explicit stack rather than recursion, so schemas nested any number of
+    /// levels deep convert without overflowing the call stack
+    fn from(schema: Schema) -> Self {
+        enum Step {
+            Enter(Box<Schema>),
+            /// A converted schema whose subschemas are placeholders, waiting for their values
+            Exit(Value, Vec<Slot>),
+        }
+
+        let mut steps = vec![Step::Enter(Box::new(schema))];
+        let mut converted: Vec<Value> = Vec::new();
+        while let Some(step) = steps.pop() {
+            match step {
+                Step::Enter(mut schema) => {
+                    let (slots, children): (Vec<Slot>, Vec<Schema>) = schema.detach_subschemas().into_iter().unzip();
+                    steps.push(Step::Exit(schema.to_value(), slots));
+                    steps.extend(children.into_iter().rev().map(|child| Step::Enter(Box::new(child))));
+                }
+                Step::Exit(mut value, slots) => {
+                    let children = converted.split_off(converted.len() - slots.len());
+                    for (slot, child) in slots.into_iter().zip(children) {
+                        slot.place(&mut value, child);
+                    }
+                    converted.push(value);
+                }
+            }
+        }
+        converted.pop().expect("the root is always converted")
+    }
+}
+
+/// Where a subschema goes in its parent's JSON
+enum Slot {
+    Named(&'static str, String),
+    Indexed(&'static str, usize),
+    Whole(&'static str),
+}
+
+impl Slot {
+    fn place(self, parent: &mut Value, child: Value) {
+        let target = match &self {
+            Slot::Named(keyword, name) => parent.get_mut(*keyword).and_then(|map| map.get_mut(name)),
+            Slot::Indexed(keyword, index) => parent.get_mut(*keyword).and_then(|list| list.get_mut(*index)),
+            Slot::Whole(keyword) => parent.get_mut(*keyword),
+        };
+        if let Some(target) = target {
+            *target = child;
+        }
+    }
+}
+
+impl Schema {
+    // Moves every subschema out, leaving empty schemas in their place
+    fn detach_subschemas(&mut self) -> Vec<(Slot, Schema)> {
+        let mut detached = Vec::new();
+        for (keyword, map) in [("properties", &mut self.properties), ("$defs", &mut self.defs)] {
+            for (name, schema) in map.iter_mut().flatten() {
+                detached.push((Slot::Named(keyword, name.clone()), std::mem::take(schema)));
+            }
+        }
+        if let Some(SchemaOrBool::Schema(schema)) = &mut self.additional_properties {
+            detached.push((Slot::Whole("additionalProperties"), std::mem::take(schema.as_mut())));
+        }
+        match &mut self.items {
+            Some(Items::Single(SchemaOrBool::Schema(schema))) => {
+                detached.push((Slot::Whole("items"), std::mem::take(schema.as_mut())));
+            }
+            Some(Items::Tuple(items)) => {
+                for (index, item) in items.iter_mut().enumerate() {
+                    if let SchemaOrBool::Schema(schema) = item {
+                        detached.push((Slot::Indexed("items", index), std::mem::take(schema.as_mut())));
+                    }
+                }
+            }
+            _ => {}
+        }
+        for (keyword, branches) in [("anyOf", &mut self.any_of), ("oneOf", &mut self.one_of), ("allOf", &mut self.all_of)] {
+            for (index, schema) in branches.iter_mut().flatten().enumerate() {
+                detached.push((Slot::Indexed(keyword, index), std::mem::take(schema)));
+            }
+        }
+        detached
+    }
+}
+
+impl TryFrom<Value> for Schema {
+    type Error = serde_json::Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        serde_json::from_value(value)
+    }
+}
+
+// Distinguishes a keyword set to `null` from one that is missing
+fn present<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Value>, D::Error> {
+    Value::deserialize(deserializer).map(Some)
+}
@@ -0,0 +1,159 @@
+use serde_json::{Map, Number, Value};
+use std::fmt::Debug;
+
+use crate::locale;
+use crate::schema::model::{Schema, SchemaOrBool};
+use crate::schema::types::{detect_string_format, detect_string_pattern};
+use crate::schema::SchemaOutputTier;
+
+/// The decisions a tier makes while a schema is generated. Each hook gets the schema built so
+/// far for one value (its `type`, and `properties`/`required` or `items`) and adds keywords.
+///
+/// Every hook defaults to adding nothing and no property is required by default, which is
+/// the basic tier. The built-in tiers are implemented by [`SchemaOutputTier`].
+pub trait TierPolicy: Debug + Send + Sync {
+    /// Whether property `name`, sampled as `value`, is listed in its object's `required`
+    fn is_required(&self, _name: &str, _value: &Value) -> bool {
+        false
+    }
+
+    fn object(&self, _schema: &mut Schema, _obj: &Map<String, Value>) {}
+
+    /// Only called for non-empty arrays; an empty array always gets `"items": {}`
+    fn array(&self, _schema: &mut Schema, _arr: &[Value]) {}
+
+    /// Like [`TierPolicy::array`], for generators that do not keep every element, e.g. the
+    /// streaming one: `len` elements were seen, and `samples` holds the first element of each
+    /// type with objects and arrays left empty
+    fn streamed_array(&self, schema: &mut Schema, _len: usize, samples: &[Value]) {
+        self.array(schema, samples);
+    }
+
+    fn string(&self, _schema: &mut Schema, _s: &str) {}
+
+    /// `schema` already has `"type": "integer"` or `"type": "number"`
+    fn number(&self, _schema: &mut Schema, _n: &Number) {}
+
+    fn boolean(&self, _schema: &mut Schema, _b: bool) {}
+}
+
+impl TierPolicy for SchemaOutputTier {
+    fn is_required(&self, _name: &str, value: &Value) -> bool {
+        match self {
+            SchemaOutputTier::Basic => false,
+            SchemaOutputTier::Standard => !value.is_null(),
+            SchemaOutputTier::Comprehensive | SchemaOutputTier::Expert => true,
+        }
+    }
+
+    fn object(&self, schema: &mut Schema, _obj: &Map<String, Value>) {
+        match self {
+            SchemaOutputTier::Basic => {}
+            SchemaOutputTier::Standard => {
+                schema.additional_properties = Some(SchemaOrBool::Bool(true));
+            }
+            SchemaOutputTier::Comprehensive | SchemaOutputTier::Expert => {
+                schema.schema_uri = Some("https://json-schema.org/draft/2020-12/schema".to_string());
+                schema.additional_properties = Some(SchemaOrBool::Bool(false));
+                schema.min_properties = Some(1);
+            }
+        }
+        if let SchemaOutputTier::Expert = self {
+            schema.title = Some(locale::OBJECT_TITLE.to_string());
+            schema.description = Some(locale::OBJECT_DESCRIPTION.to_string());
+        }
+    }
+
+    fn array(&self, schema: &mut Schema, arr: &[Value]) {
+        self.streamed_array(schema, arr.len(), arr);
+    }
+
+    fn streamed_array(&self, schema: &mut Schema, len: usize, _samples: &[Value]) {
+        match self {
+            SchemaOutputTier::Basic => {}
+            SchemaOutputTier::Standard => {
+                schema.min_items = Some(0);
+            }
+            SchemaOutputTier::Comprehensive | SchemaOutputTier::Expert => {
+                schema.min_items = Some(1);
+                schema.max_items = Some(len as u64 * 2);
+            }
+        }
+        if let SchemaOutputTier::Expert = self {
+            schema.unique_items = Some(true);
+            schema.title = Some(locale::ARRAY_TITLE.to_string());
+            schema.description = Some(locale::ARRAY_DESCRIPTION.to_string());
+        }
+    }
+
+    fn string(&self, schema: &mut Schema, s: &str) {
+        if let SchemaOutputTier::Basic = self {
+            return;
+        }
+        schema.min_length = Some(0);
+        if let SchemaOutputTier::Standard = self {
+            return;
+        }
+
+        schema.max_length = Some(s.len() as u64 * 2);
+        if !s.is_empty() {
+            schema.examples = Some(vec![Value::String(s.to_string())]);
+        }
+        if let SchemaOutputTier::Expert = self {
+            if !s.is_empty() {
+                if let Some(format) = detect_string_format(s) {
+                    schema.format = Some(format.to_string());
+                } else if let Some(pattern) = detect_string_pattern(s) {
+                    schema.pattern = Some(pattern.to_string());
+                }
+            }
+            schema.title = Some(locale::STRING_TITLE.to_string());
+        }
+    }
+
+    fn number(&self, schema: &mut Schema, n: &Number) {
+        match self {
+            SchemaOutputTier::Basic => {}
+            SchemaOutputTier::Standard => {
+                if let Some(n_val) = n.as_i64() {
+                    schema.minimum = Some(n_val.into());
+                } else {
+                    schema.minimum = n.as_f64().and_then(Number::from_f64);
+                }
+            }
+            SchemaOutputTier::Comprehensive | SchemaOutputTier::Expert => {
+                let expert = matches!(self, SchemaOutputTier::Expert);
+                if let Some(n_val) = n.as_i64() {
+                    schema.examples = Some(vec![n_val.into()]);
+                    schema.minimum = Some((n_val - 1000).into());
+                    schema.maximum = Some((n_val + 1000).into());
+                    if expert {
+                        schema.multiple_of = Some(1.into());
+                        schema.title = Some(locale::INTEGER_TITLE.to_string());
+                    }
+                } else if let Some(n_val) = n.as_f64() {
+                    schema.examples = Some(vec![n_val.into()]);
+                    schema.minimum = Number::from_f64(n_val - 1000.0);
+                    schema.maximum = Number::from_f64(n_val + 1000.0);
+                    if expert {
+                        schema.title = Some(locale::NUMBER_TITLE.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    fn boolean(&self, schema: &mut Schema, b: bool) {
+        match self {
+            SchemaOutputTier::Basic | SchemaOutputTier::Standard => {}
+            SchemaOutputTier::Comprehensive => {
+                schema.examples = Some(vec![Value::Bool(b)]);
+            }
+            SchemaOutputTier::Expert => {
+                schema.examples = Some(vec![Value::Bool(b)]);
+                schema.title = Some(locale::BOOLEAN_TITLE.to_string());
+                schema.description = Some(locale::BOOLEAN_DESCRIPTION.to_string());
+            }
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use anyhow::Result;
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::{Map, Number, Value};
+use std::fmt;
+use std::io::Read;
+
+use crate::error::AppError;
+use crate::schema::model::{InstanceType, Items, Schema, SchemaMap, SchemaOrBool};
+use crate::schema::types::json_type_name;
+use crate::schema::{build_schema, TierPolicy};
+
+/// Generates the schema for the document `deserializer` yields while it is being parsed,
+/// without building a `serde_json::Value` for it.
+///
+/// Hooks of `policy` get shallow stand-ins for the values they are called with: scalars as
+/// they are, objects and arrays empty. Only the first element of each type is kept for an
+/// array, so a heterogeneous array gets one `oneOf` branch per element type rather than per
+/// element as with [`generate_schema_with`](crate::schema::generate_schema_with).
+pub fn stream_schema<'de, D: Deserializer<'de>>(deserializer: D, policy: &dyn TierPolicy) -> Result<Schema, D::Error> {
+    SchemaSeed { policy }.deserialize(deserializer).map(|streamed| streamed.schema)
+}
+
+/// [`stream_schema`] over JSON read from `reader`
+pub fn stream_schema_from_reader(reader: impl Read, policy: &dyn TierPolicy) -> Result<Schema> {
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let schema = stream_schema(&mut deserializer, policy).map_err(|e| AppError::invalid_json(&e))?;
+    deserializer.end().map_err(|e| AppError::invalid_json(&e))?;
+    Ok(schema)
+}
+
+struct Streamed {
+    schema: Schema,
+    /// The value itself for scalars, an empty object or array otherwise
+    sample: Value,
+}
+
+#[derive(Clone, Copy)]
+struct SchemaSeed<'p> {
+    policy: &'p dyn TierPolicy,
+}
+
+impl SchemaSeed<'_> {
+    fn scalar<E: de::Error>(self, value: Value) -> Result<Streamed, E> {
+        let schema = build_schema(&value, self.policy).map_err(E::custom)?;
+        Ok(Streamed { schema, sample: value })
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for SchemaSeed<'_> {
+    type Value = Streamed;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Streamed, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for SchemaSeed<'_> {
+    type Value = Streamed;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_bool<E: de::Error>(self, b: bool) -> Result<Streamed, E> {
+        self.scalar(Value::Bool(b))
+    }
+
+    fn visit_i64<E: de::Error>(self, n: i64) -> Result<Streamed, E> {
+        self.scalar(Value::Number(n.into()))
+    }
+
+    fn visit_u64<E: de::Error>(self, n: u64) -> Result<Streamed, E> {
+        self.scalar(Value::Number(n.into()))
+    }
+
+    fn visit_f64<E: de::Error>(self, n: f64) -> Result<Streamed, E> {
+        let n = Number::from_f64(n).ok_or_else(|| E::custom(format!("{} is not a JSON number", n)))?;
+        self.scalar(Value::Number(n))
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Streamed, E> {
+        self.scalar(Value::String(s.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, s: String) -> Result<Streamed, E> {
+        self.scalar(Value::String(s))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Streamed, E> {
+        self.scalar(Value::Null)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Streamed, E> {
+        self.scalar(Value::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Streamed, D::Error> {
+        self.deserialize(deserializer)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Streamed, A::Error> {
+        let mut properties = SchemaMap::new();
+        let mut samples = Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            let Streamed { schema, sample } = map.next_value_seed(self)?;
+            properties.insert(key.clone(), schema);
+            samples.insert(key, sample);
+        }
+
+        let required: Vec<String> = samples
+            .iter()
+            .filter(|(key, sample)| self.policy.is_required(key, sample))
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        let mut schema = Schema::of_type(InstanceType::Object);
+        schema.properties = Some(properties);
+        if !required.is_empty() {
+            schema.required = Some(required);
+        }
+        self.policy.object(&mut schema, &samples);
+        Ok(Streamed { schema, sample: Value::Object(Map::new()) })
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Streamed, A::Error> {
+        let mut len = 0;
+        // The first element of each type, in the order the types first appear
+        let mut firsts: Vec<(&str, Streamed)> = Vec::new();
+        while let Some(element) = seq.next_element_seed(self)? {
+            len += 1;
+            let kind = json_type_name(&element.sample);
+            if !firsts.iter().any(|(seen, _)| *seen == kind) {
+                firsts.push((kind, element));
+            }
+        }
+
+        let mut schema = Schema::of_type(InstanceType::Array);
+        if len == 0 {
+            schema.items = Some(Items::Single(SchemaOrBool::Schema(Box::default())));
+            return Ok(Streamed { schema, sample: Value::Array(Vec::new()) });
+        }
+
+        let (schemas, samples): (Vec<Schema>, Vec<Value>) =
+            firsts.into_iter().map(|(_, element)| (element.schema, element.sample)).unzip();
+        let item_schema = match <[Schema; 1]>::try_from(schemas) {
+            Ok([single]) => single,
+            Err(schemas) => Schema { one_of: Some(schemas), ..Schema::default() },
+        };
+        schema.items = Some(Items::Single(SchemaOrBool::Schema(Box::new(item_schema))));
+        self.policy.streamed_array(&mut schema, len, &samples);
+        Ok(Streamed { schema, sample: Value::Array(Vec::new()) })
+    }
+}
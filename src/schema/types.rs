@@ -6,8 +6,6 @@ pub fn detect_string_format(s: &str) -> Option<&'static str> {
         Some("email")
     } else if s.starts_with("http") {
         Some("uri")
-    } else if s.chars().all(|c| c.is_ascii_digit() || c == '-' || c == ' ') {
-        None
     } else {
         None
     }
@@ -21,17 +19,19 @@ pub fn detect_string_pattern(s: &str) -> Option<&'static str> {
     }
 }
 
+pub fn json_type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Object(_) => "object",
+        Value::Array(_) => "array",
+        Value::String(_) => "string",
+        Value::Number(_) => "number",
+        Value::Bool(_) => "boolean",
+        Value::Null => "null",
+    }
+}
+
 pub fn get_array_item_types(arr: &[Value]) -> HashSet<&'static str> {
-    arr.iter()
-        .map(|v| match v {
-            Value::Object(_) => "object",
-            Value::Array(_) => "array",
-            Value::String(_) => "string",
-            Value::Number(_) => "number",
-            Value::Bool(_) => "boolean",
-            Value::Null => "null",
-        })
-        .collect()
+    arr.iter().map(json_type_name).collect()
 }
 
 pub fn is_homogeneous_array(arr: &[Value]) -> bool {
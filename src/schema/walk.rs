@@ -0,0 +1,110 @@
+use serde_json::{Map, Value};
+
+use crate::pointer;
+use crate::schema::keywords::{for_each_subschema, for_each_subschema_entry_mut, for_each_subschema_mut};
+
+/// Visiting every schema in a generated document, e.g. to rename titles, strip keywords or
+/// add annotations, without a hand-written walker over `properties`, `items`, `$defs` and
+/// the other keywords that hold subschemas
+pub trait SchemaWalk {
+    /// Calls `f` with the JSON Pointer and value of every schema, parents before children.
+    /// Boolean schemas are visited too.
+    fn walk(&self, f: impl FnMut(&str, &Value));
+
+    /// Like [`SchemaWalk::walk`], with each schema mutable. A schema is visited before its
+    /// subschemas, so the ones `f` adds are visited and the ones it removes are not.
+    fn transform(&mut self, f: impl FnMut(&str, &mut Value));
+}
+
+// Schemas nest as deep as the input, so none of the walkers below recurse: each keeps its
+// own stack, and children are pushed in reverse to be visited in document order
+impl SchemaWalk for Value {
+    fn walk(&self, mut f: impl FnMut(&str, &Value)) {
+        let mut stack = vec![(String::new(), self)];
+        while let Some((location, node)) = stack.pop() {
+            f(&location, node);
+            if let Value::Object(obj) = node {
+                let mut children = Vec::new();
+                for_each_subschema(obj, &mut |path, child| children.push((child_pointer(&location, &path), child)));
+                stack.extend(children.into_iter().rev());
+            }
+        }
+    }
+
+    fn transform(&mut self, mut f: impl FnMut(&str, &mut Value)) {
+        let mut stack = vec![(String::new(), self)];
+        while let Some((location, node)) = stack.pop() {
+            f(&location, node);
+            if let Value::Object(obj) = node {
+                let mut children = Vec::new();
+                for_each_subschema_entry_mut(obj, &mut |path, child| {
+                    children.push((child_pointer(&location, &path), child))
+                });
+                stack.extend(children.into_iter().rev());
+            }
+        }
+    }
+}
+
+/// Calls `f` with every schema object in `schema`, parents before children. Like
+/// [`SchemaWalk::transform`] without the pointers, for passes over every schema.
+pub fn for_each_schema_mut(schema: &mut Value, f: &mut dyn FnMut(&mut Map<String, Value>)) {
+    let mut stack = vec![schema];
+    while let Some(node) = stack.pop() {
+        if let Value::Object(obj) = node {
+            f(obj);
+            let mut children = Vec::new();
+            for_each_subschema_mut(obj, &mut |child| children.push(child));
+            stack.extend(children.into_iter().rev());
+        }
+    }
+}
+
+/// Like [`for_each_schema_mut`], with subschemas before their parent, for passes that look at
+/// subschemas they have already handled
+pub fn for_each_schema_post_mut(schema: &mut Value, f: &mut dyn FnMut(&mut Map<String, Value>)) {
+    // Each subschema is taken out of its parent while it is walked and put back when done,
+    // since a parent cannot be borrowed alongside its children
+    struct Frame {
+        node: Value,
+        paths: Vec<Vec<String>>,
+        next: usize,
+    }
+    let frame = |mut node: Value| {
+        let mut paths = Vec::new();
+        if let Value::Object(obj) = &mut node {
+            for_each_subschema_entry_mut(obj, &mut |path, _| paths.push(path));
+        }
+        Frame { node, paths, next: 0 }
+    };
+
+    let mut stack = vec![frame(std::mem::take(schema))];
+    while let Some(top) = stack.last_mut() {
+        if let Some(path) = top.paths.get(top.next) {
+            top.next += 1;
+            let child = std::mem::take(entry_mut(&mut top.node, path));
+            stack.push(frame(child));
+            continue;
+        }
+        let mut done = stack.pop().unwrap_or_else(|| unreachable!());
+        if let Value::Object(obj) = &mut done.node {
+            f(obj);
+        }
+        match stack.last_mut() {
+            Some(parent) => *entry_mut(&mut parent.node, &parent.paths[parent.next - 1]) = done.node,
+            None => *schema = done.node,
+        }
+    }
+}
+
+// The subschema at a keyword path of `node`, as passed by `for_each_subschema_entry_mut`
+pub(crate) fn entry_mut<'a>(node: &'a mut Value, path: &[String]) -> &'a mut Value {
+    path.iter().fold(node, |node, token| match node {
+        Value::Array(items) => &mut items[token.parse::<usize>().unwrap_or_default()],
+        node => &mut node[token.as_str()],
+    })
+}
+
+fn child_pointer(location: &str, path: &[String]) -> String {
+    path.iter().fold(location.to_string(), |acc, token| pointer::join(&acc, token))
+}
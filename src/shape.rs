@@ -0,0 +1,106 @@
+//! Hashes of JSON values. A [`shape_hash`] covers types, object keys and the shapes of array
+//! elements, without the scalar values; a [`content_hash`] covers everything. Hashes are only
+//! a first test: values with the same hash are compared in full before they are treated as
+//! the same, so crafted collisions cannot merge different input.
+
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+pub type Shape = u64;
+
+/// The shape of `value`. Arrays hash the distinct shapes of their elements in order of first
+/// appearance, so their length does not matter.
+pub fn shape_hash(value: &Value) -> Shape {
+    let mut hasher = DefaultHasher::new();
+    hash_shape(value, &mut hasher);
+    hasher.finish()
+}
+
+fn hash_shape(value: &Value, hasher: &mut DefaultHasher) {
+    match value {
+        Value::Null => 0u8.hash(hasher),
+        Value::Bool(_) => 1u8.hash(hasher),
+        Value::Number(n) if n.is_f64() => 2u8.hash(hasher),
+        Value::Number(_) => 3u8.hash(hasher),
+        Value::String(_) => 4u8.hash(hasher),
+        Value::Array(items) => {
+            5u8.hash(hasher);
+            let mut seen = HashSet::new();
+            for shape in items.iter().map(shape_hash) {
+                if seen.insert(shape) {
+                    shape.hash(hasher);
+                }
+            }
+        }
+        Value::Object(obj) => {
+            6u8.hash(hasher);
+            obj.len().hash(hasher);
+            for (key, child) in obj {
+                key.hash(hasher);
+                hash_shape(child, hasher);
+            }
+        }
+    }
+}
+
+/// A hash of everything in `value`, properties in order
+pub fn content_hash(value: &Value) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hash_content(value, &mut hasher);
+    hasher.finish()
+}
+
+fn hash_content(value: &Value, hasher: &mut DefaultHasher) {
+    match value {
+        Value::Null => 0u8.hash(hasher),
+        Value::Bool(b) => (1u8, b).hash(hasher),
+        Value::Number(n) => (2u8, n.to_string()).hash(hasher),
+        Value::String(s) => (3u8, s).hash(hasher),
+        Value::Array(items) => {
+            (4u8, items.len()).hash(hasher);
+            items.iter().for_each(|item| hash_content(item, hasher));
+        }
+        Value::Object(obj) => {
+            (5u8, obj.len()).hash(hasher);
+            for (key, child) in obj {
+                key.hash(hasher);
+                hash_content(child, hasher);
+            }
+        }
+    }
+}
+
+/// Whether `a` and `b` are the same, properties in the same order. Unlike `==`, which ignores
+/// the order of properties, this tells apart values whose schemas list properties differently
+/// with `preserve_order`.
+pub fn identical(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Array(a), Value::Array(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| identical(a, b)),
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|((ka, va), (kb, vb))| ka == kb && identical(va, vb))
+        }
+        _ => a == b,
+    }
+}
+
+/// The distinct values of `values`, in order of first appearance, and for each value the index
+/// of its distinct value, so work done once per distinct value can be handed to every copy
+pub fn distinct<'a>(values: impl IntoIterator<Item = &'a Value>) -> (Vec<&'a Value>, Vec<usize>) {
+    let mut firsts: Vec<&'a Value> = Vec::new();
+    let mut indices = Vec::new();
+    let mut by_hash: HashMap<u64, Vec<usize>> = HashMap::new();
+    for value in values {
+        let candidates = by_hash.entry(content_hash(value)).or_default();
+        let index = match candidates.iter().find(|&&index| identical(firsts[index], value)) {
+            Some(&index) => index,
+            None => {
+                firsts.push(value);
+                candidates.push(firsts.len() - 1);
+                firsts.len() - 1
+            }
+        };
+        indices.push(index);
+    }
+    (firsts, indices)
+}
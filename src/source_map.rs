@@ -0,0 +1,47 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::explain::trace_sources;
+
+/// Sidecar linking each subschema to the values in the input it was derived from, e.g. for
+/// review tools that show example occurrences next to each property
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SourceMap {
+    pub version: u32,
+    /// File name of the schema the pointers refer to
+    pub schema: String,
+    /// Path or URL of the input
+    pub source: String,
+    /// Schema JSON Pointer to the data JSON Pointers in `source`
+    pub mappings: BTreeMap<String, Vec<String>>,
+}
+
+impl SourceMap {
+    pub fn new(schema: &Value, document: &Value, schema_file: impl Into<String>, source: impl Into<String>) -> Self {
+        let mappings = trace_sources(schema, document)
+            .into_iter()
+            .map(|trace| (trace.pointer, trace.sources))
+            .collect();
+        Self {
+            version: 1,
+            schema: schema_file.into(),
+            source: source.into(),
+            mappings,
+        }
+    }
+}
+
+/// `out.schema.json` -> `out.schema.map.json`
+pub fn source_map_path(path: &Path) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let map = match file_name.strip_suffix(".json") {
+        Some(stem) => format!("{}.map.json", stem),
+        None => format!("{}.map", file_name),
+    };
+    path.with_file_name(map)
+}
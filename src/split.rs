@@ -0,0 +1,154 @@
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+use crate::pointer;
+use crate::schema::keywords::for_each_subschema_mut;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum NameCase {
+    #[default]
+    Original,
+    Kebab,
+    Snake,
+}
+
+#[derive(Debug, Clone)]
+pub struct SplitOptions {
+    pub root_file: String,
+    pub file_template: String,
+    pub case: NameCase,
+}
+
+impl Default for SplitOptions {
+    fn default() -> Self {
+        Self {
+            root_file: "root.schema.json".to_string(),
+            file_template: "{name}.schema.json".to_string(),
+            case: NameCase::Original,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SplitOutput {
+    pub root: Value,
+    pub files: BTreeMap<String, Value>,
+    pub index: Value,
+}
+
+pub fn split_schema(schema: &Value, options: &SplitOptions) -> SplitOutput {
+    let mut root = schema.clone();
+    let defs_keyword = if root.get("$defs").is_some() { "$defs" } else { "definitions" };
+    let defs = match root.as_object_mut().and_then(|obj| obj.remove(defs_keyword)) {
+        Some(Value::Object(defs)) => defs,
+        _ => Map::new(),
+    };
+
+    let mut file_names = BTreeMap::new();
+    for name in defs.keys() {
+        let mut file_name = def_file_name(name, options);
+        let mut suffix = 2;
+        while file_names.values().any(|existing| existing == &file_name) || file_name == options.root_file {
+            file_name = def_file_name(&format!("{}{}", name, suffix), options);
+            suffix += 1;
+        }
+        file_names.insert(name.clone(), file_name);
+    }
+
+    let draft = root.get("$schema").cloned();
+    let prefix = format!("#/{}/", defs_keyword);
+
+    rewrite_refs(&mut root, &|reference| {
+        rewrite_reference(reference, &prefix, &file_names, None, &options.root_file)
+    });
+
+    let mut files = BTreeMap::new();
+    for (name, mut def) in defs {
+        rewrite_refs(&mut def, &|reference| {
+            rewrite_reference(reference, &prefix, &file_names, Some(name.as_str()), &options.root_file)
+        });
+        if let (Value::Object(obj), Some(draft)) = (&mut def, &draft) {
+            obj.entry("$schema").or_insert_with(|| draft.clone());
+        }
+        files.insert(file_names[&name].clone(), def);
+    }
+
+    let index = serde_json::json!({
+        "root": options.root_file,
+        "definitions": file_names,
+    });
+
+    SplitOutput { root, files, index }
+}
+
+fn def_file_name(name: &str, options: &SplitOptions) -> String {
+    let cased = match options.case {
+        NameCase::Original => name.to_string(),
+        NameCase::Kebab => to_separated_case(name, '-'),
+        NameCase::Snake => to_separated_case(name, '_'),
+    };
+    options.file_template.replace("{name}", &cased)
+}
+
+fn to_separated_case(name: &str, separator: char) -> String {
+    let mut out = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if c.is_uppercase() {
+            if prev_lower {
+                out.push(separator);
+            }
+            out.extend(c.to_lowercase());
+            prev_lower = false;
+        } else if c == '-' || c == '_' || c == ' ' {
+            out.push(separator);
+            prev_lower = false;
+        } else {
+            out.push(c);
+            prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        }
+    }
+    out
+}
+
+fn rewrite_reference(
+    reference: &str,
+    prefix: &str,
+    file_names: &BTreeMap<String, String>,
+    current: Option<&str>,
+    root_file: &str,
+) -> Option<String> {
+    if let Some(rest) = reference.strip_prefix(prefix) {
+        let (name, tail) = match rest.split_once('/') {
+            Some((name, tail)) => (pointer::unescape_token(name), format!("/{}", tail)),
+            None => (pointer::unescape_token(rest), String::new()),
+        };
+        let file = file_names.get(&name)?;
+        return Some(match (current == Some(name.as_str()), tail.is_empty()) {
+            (true, true) => "#".to_string(),
+            (true, false) => format!("#{}", tail),
+            (false, true) => file.clone(),
+            (false, false) => format!("{}#{}", file, tail),
+        });
+    }
+
+    // Fragments pointing elsewhere in the root document must be anchored to the root file
+    if current.is_some() && reference.starts_with('#') {
+        return Some(format!("{}{}", root_file, reference));
+    }
+
+    None
+}
+
+fn rewrite_refs(node: &mut Value, rewrite: &dyn Fn(&str) -> Option<String>) {
+    let Value::Object(obj) = node else { return };
+
+    if let Some(Value::String(reference)) = obj.get_mut("$ref") {
+        if let Some(updated) = rewrite(reference) {
+            *reference = updated;
+        }
+    }
+
+    for_each_subschema_mut(obj, &mut |child| rewrite_refs(child, rewrite));
+}
@@ -0,0 +1,237 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::pointer;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ArrayStats {
+    pub count: usize,
+    pub min_length: usize,
+    pub max_length: usize,
+    pub average_length: f64,
+    pub empty: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PathNullRate {
+    pub path: String,
+    pub occurrences: usize,
+    pub nulls: usize,
+    pub rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Subtree {
+    pub pointer: String,
+    pub nodes: usize,
+    pub bytes: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct JsonStats {
+    pub max_depth: usize,
+    pub total_nodes: usize,
+    pub type_counts: BTreeMap<String, usize>,
+    pub object_count: usize,
+    pub total_keys: usize,
+    pub unique_keys: usize,
+    pub max_keys_per_object: usize,
+    pub arrays: ArrayStats,
+    pub null_rate: f64,
+    pub null_paths: Vec<PathNullRate>,
+    pub largest_subtrees: Vec<Subtree>,
+}
+
+#[derive(Default)]
+struct Collector {
+    stats: JsonStats,
+    keys: BTreeSet<String>,
+    array_total: usize,
+    paths: BTreeMap<String, (usize, usize)>,
+    subtrees: Vec<Subtree>,
+}
+
+pub fn collect_stats(value: &Value, top: usize) -> JsonStats {
+    let mut collector = Collector::default();
+    collector.visit(value, "", "", 0);
+
+    let mut stats = collector.stats;
+    stats.unique_keys = collector.keys.len();
+
+    if stats.arrays.count > 0 {
+        stats.arrays.average_length = collector.array_total as f64 / stats.arrays.count as f64;
+    }
+
+    let nulls = stats.type_counts.get("null").copied().unwrap_or(0);
+    if stats.total_nodes > 0 {
+        stats.null_rate = nulls as f64 / stats.total_nodes as f64;
+    }
+
+    let mut null_paths: Vec<PathNullRate> = collector
+        .paths
+        .into_iter()
+        .filter(|(_, (_, nulls))| *nulls > 0)
+        .map(|(path, (occurrences, nulls))| PathNullRate {
+            path,
+            occurrences,
+            nulls,
+            rate: nulls as f64 / occurrences as f64,
+        })
+        .collect();
+    null_paths.sort_by(|a, b| b.rate.total_cmp(&a.rate).then(b.nulls.cmp(&a.nulls)));
+    null_paths.truncate(top);
+    stats.null_paths = null_paths;
+
+    let mut subtrees = collector.subtrees;
+    subtrees.sort_by(|a, b| b.nodes.cmp(&a.nodes).then(a.pointer.cmp(&b.pointer)));
+    subtrees.truncate(top);
+    for subtree in &mut subtrees {
+        subtree.bytes = value
+            .pointer(&subtree.pointer)
+            .and_then(|node| serde_json::to_string(node).ok())
+            .map(|s| s.len())
+            .unwrap_or(0);
+    }
+    stats.largest_subtrees = subtrees;
+
+    stats
+}
+
+impl Collector {
+    // Returns the number of nodes in the subtree rooted at `value`
+    fn visit(&mut self, value: &Value, pointer: &str, path: &str, depth: usize) -> usize {
+        self.stats.total_nodes += 1;
+        self.stats.max_depth = self.stats.max_depth.max(depth);
+        *self
+            .stats
+            .type_counts
+            .entry(type_name(value).to_string())
+            .or_insert(0) += 1;
+
+        if !path.is_empty() {
+            let entry = self.paths.entry(path.to_string()).or_insert((0, 0));
+            entry.0 += 1;
+            if value.is_null() {
+                entry.1 += 1;
+            }
+        }
+
+        let nodes = match value {
+            Value::Object(obj) => {
+                self.stats.object_count += 1;
+                self.stats.total_keys += obj.len();
+                self.stats.max_keys_per_object = self.stats.max_keys_per_object.max(obj.len());
+                let mut nodes = 1;
+                for (key, child) in obj {
+                    self.keys.insert(key.clone());
+                    nodes += self.visit(
+                        child,
+                        &pointer::join(pointer, key),
+                        &pointer::join(path, key),
+                        depth + 1,
+                    );
+                }
+                nodes
+            }
+            Value::Array(items) => {
+                let arrays = &mut self.stats.arrays;
+                if arrays.count == 0 || items.len() < arrays.min_length {
+                    arrays.min_length = items.len();
+                }
+                arrays.max_length = arrays.max_length.max(items.len());
+                arrays.count += 1;
+                if items.is_empty() {
+                    arrays.empty += 1;
+                }
+                self.array_total += items.len();
+
+                let item_path = format!("{}/*", path);
+                let mut nodes = 1;
+                for (index, child) in items.iter().enumerate() {
+                    nodes += self.visit(
+                        child,
+                        &pointer::join(pointer, &index.to_string()),
+                        &item_path,
+                        depth + 1,
+                    );
+                }
+                nodes
+            }
+            _ => 1,
+        };
+
+        if !pointer.is_empty() && (value.is_object() || value.is_array()) {
+            self.subtrees.push(Subtree {
+                pointer: pointer.to_string(),
+                nodes,
+                bytes: 0,
+            });
+        }
+
+        nodes
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Object(_) => "object",
+        Value::Array(_) => "array",
+        Value::String(_) => "string",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::Bool(_) => "boolean",
+        Value::Null => "null",
+    }
+}
+
+impl JsonStats {
+    pub fn render_text(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&format!("Total nodes:        {}\n", self.total_nodes));
+        out.push_str(&format!("Max depth:          {}\n", self.max_depth));
+        out.push_str(&format!(
+            "Objects:            {} ({} keys, {} unique, max {} per object)\n",
+            self.object_count, self.total_keys, self.unique_keys, self.max_keys_per_object
+        ));
+        out.push_str(&format!(
+            "Arrays:             {} (length min {}, max {}, avg {:.1}, {} empty)\n",
+            self.arrays.count,
+            self.arrays.min_length,
+            self.arrays.max_length,
+            self.arrays.average_length,
+            self.arrays.empty
+        ));
+        out.push_str(&format!("Null rate:          {:.1}%\n", self.null_rate * 100.0));
+
+        out.push_str("\nType distribution:\n");
+        for (name, count) in &self.type_counts {
+            out.push_str(&format!("  {:<10} {}\n", name, count));
+        }
+
+        if !self.null_paths.is_empty() {
+            out.push_str("\nNullable paths:\n");
+            for path in &self.null_paths {
+                out.push_str(&format!(
+                    "  {} {}/{} ({:.0}%)\n",
+                    path.path,
+                    path.nulls,
+                    path.occurrences,
+                    path.rate * 100.0
+                ));
+            }
+        }
+
+        if !self.largest_subtrees.is_empty() {
+            out.push_str("\nLargest subtrees:\n");
+            for subtree in &self.largest_subtrees {
+                out.push_str(&format!(
+                    "  {} {} nodes, {} bytes\n",
+                    subtree.pointer, subtree.nodes, subtree.bytes
+                ));
+            }
+        }
+
+        out
+    }
+}
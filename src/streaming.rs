@@ -0,0 +1,338 @@
+//! Schemas for inputs too large to hold in memory. NDJSON files and top-level JSON arrays are
+//! read one record at a time, and each record's schema is merged into the accumulated one, so
+//! memory is bounded by the largest record and the schema, not by the input.
+
+use anyhow::{Context, Result};
+use serde::de::{self, DeserializeSeed, Deserializer, SeqAccess, Visitor};
+use serde_json::Value;
+use std::fmt;
+use std::io::{BufRead, Read};
+use std::path::Path;
+use std::time::Instant;
+use tracing::warn;
+
+use crate::error::AppError;
+use crate::generator::{ExamplePolicy, GeneratorOptions, SchemaBuilder, SchemaGenerator};
+use crate::intern::{InternStats, InternedValue, Interner};
+use crate::limits;
+use crate::mmap::MappedFile;
+use crate::schema::model::InstanceType;
+use crate::schema::Schema;
+use crate::validation::{ValidationReport, Validator};
+
+/// Errors [`verify_stream_file`] collects at most before it stops reading
+pub const VERIFY_ERRORS_LIMIT: usize = 100;
+
+/// Examples kept per keyword when streaming with [`ExamplePolicy::Keep`], since merging every
+/// record's examples would grow without bound
+pub const STREAM_EXAMPLES_LIMIT: usize = 5;
+
+/// How the records of a streamed input are laid out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamShape {
+    /// One JSON value per line, or any whitespace-separated sequence of values
+    Ndjson,
+    /// The elements of a top-level JSON array
+    Array,
+}
+
+/// The schema of a streamed input
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamedSchema {
+    pub schema: Value,
+    pub shape: StreamShape,
+    pub records: usize,
+    /// Property names and short strings the records shared
+    pub interned: InternStats,
+}
+
+/// `.ndjson` and `.jsonl` files, which are always streamed
+pub fn is_ndjson(path: &Path) -> bool {
+    matches!(path.extension().and_then(|ext| ext.to_str()), Some("ndjson" | "jsonl"))
+}
+
+/// [`stream_schema`] over the file at `path`, which is memory-mapped when large, so the pages
+/// already parsed can be dropped again by the OS. Past `max_input_size` bytes, no more records
+/// are read and the schema is marked as [sampled](crate::limits::SAMPLED_KEYWORD); an input
+/// whose first record does not fit is rejected.
+pub fn stream_schema_file(
+    path: &Path,
+    generator: &SchemaGenerator,
+    max_input_size: Option<u64>,
+    prepare: impl FnMut(Value) -> Value,
+) -> Result<StreamedSchema> {
+    if !path.exists() {
+        return Err(AppError::FileNotFound(path.display().to_string()).into());
+    }
+    let contents = MappedFile::open(path).with_context(|| format!("Failed to read input file: {:?}", path))?;
+    let limit = max_input_size.filter(|&max| contents.len() as u64 > max);
+    let streamed = match limit {
+        Some(max) => {
+            warn!("{} is larger than {} bytes; inferring from the records that fit", path.display(), max);
+            stream(contents.reader().take(max), generator, true, prepare)
+        }
+        None => stream(contents.reader(), generator, false, prepare),
+    };
+    streamed.map_err(|e| match (e.downcast::<serde_json::Error>(), limit) {
+        (Ok(json), Some(max)) if json.is_eof() => AppError::LimitExceeded {
+            limit: limits::MAX_INPUT_SIZE,
+            message: format!(
+                "{} is larger than the input size limit of {} bytes and is not a JSON array or NDJSON whose first records fit",
+                path.display(),
+                max
+            ),
+        }
+        .into(),
+        (Ok(json), _) => AppError::invalid_json_in(path.display().to_string(), &json).into(),
+        (Err(e), _) => e,
+    })
+}
+
+/// Generates the schema for NDJSON or a top-level array read from `reader` one record at a
+/// time. `prepare` is applied to each record first, e.g. to filter fields.
+///
+/// NDJSON gets the schema of one record. A top-level array gets the schema for the whole
+/// array, as [`SchemaGenerator::generate`] would give it, except that `examples` are capped at
+/// [`STREAM_EXAMPLES_LIMIT`] unless the options set a limit of their own. Any other document
+/// is read as NDJSON of one record, which takes as much memory as parsing it whole.
+///
+/// The options' `time_limit` applies to the whole stream: once it is up, the records read so
+/// far make up the schema, which is marked as [sampled](crate::limits::SAMPLED_KEYWORD).
+pub fn stream_schema(
+    reader: impl BufRead,
+    generator: &SchemaGenerator,
+    prepare: impl FnMut(Value) -> Value,
+) -> Result<StreamedSchema> {
+    stream(reader, generator, false, prepare)
+}
+
+// `truncated` readers stop at a size limit, so their last record may be cut off
+fn stream(
+    reader: impl BufRead,
+    generator: &SchemaGenerator,
+    truncated: bool,
+    mut prepare: impl FnMut(Value) -> Value,
+) -> Result<StreamedSchema> {
+    let options = generator.options();
+    let examples = match options.examples {
+        ExamplePolicy::Keep => ExamplePolicy::Limit(STREAM_EXAMPLES_LIMIT),
+        policy => policy,
+    };
+    let mut builder =
+        SchemaBuilder::new(GeneratorOptions { examples, time_limit: None, ..options.clone() }).with_value_cache();
+
+    let deadline = options.time_limit.map(|limit| Instant::now() + limit);
+    let mut sampled = truncated.then_some(limits::MAX_INPUT_SIZE);
+    let mut interner = Interner::default();
+    let mut read = 0;
+    let (shape, records) = limits::with_deadline(deadline, || {
+        for_each_record(reader, truncated, &mut interner, |record| {
+            read += 1;
+            let fed = match record {
+                // Array records are fed as one-element arrays, so data paths in the options
+                // still match and the root gets the tier's array keywords
+                Record::Element(element) => builder.feed(&prepare(Value::Array(vec![element.into_value()]))),
+                Record::Line(line) => builder.feed(&prepare(line.into_value())),
+            };
+            match fed.and_then(|()| Ok(limits::check_deadline()?)) {
+                Err(e) if limits::is_time_limit(&e) && builder.observations() > 0 => {
+                    warn!("Streaming ran out of time after {} record(s)", read);
+                    sampled = Some(limits::TIME_LIMIT);
+                    Ok(false)
+                }
+                fed => fed.map(|()| true),
+            }
+        })
+    })?;
+
+    let mut schema = match (builder.finalize(), shape) {
+        (Some(mut schema), StreamShape::Array) => {
+            fix_array_bounds(&mut schema, generator, records);
+            schema
+        }
+        (Some(schema), StreamShape::Ndjson) => schema,
+        (None, StreamShape::Array) => generator.generate(&prepare(Value::Array(Vec::new())))?,
+        (None, StreamShape::Ndjson) => {
+            return Err(AppError::InvalidOption("The input has no JSON records to stream".to_string()).into())
+        }
+    };
+    if let Some(limit) = sampled {
+        limits::mark_sampled(&mut schema, limit);
+    }
+    Ok(StreamedSchema { schema, shape, records, interned: interner.stats() })
+}
+
+/// Checks the records of the file at `path` against `schema`, the schema streamed from it, one
+/// record at a time, with `prepare` applied to each first. Each NDJSON record is checked against
+/// `schema`, and each element of a top-level array as a one-element array, so keywords about
+/// the array as a whole such as `uniqueItems` are not checked. Error pointers start with the
+/// index of the record. Past `max_input_size` bytes, or [`VERIFY_ERRORS_LIMIT`] errors, no more
+/// records are read.
+pub fn verify_stream_file(
+    path: &Path,
+    schema: &Value,
+    max_input_size: Option<u64>,
+    mut prepare: impl FnMut(Value) -> Value,
+) -> Result<ValidationReport> {
+    let validator = Validator::new(schema)?;
+    let contents = MappedFile::open(path).with_context(|| format!("Failed to read input file: {:?}", path))?;
+    let limit = max_input_size.filter(|&max| contents.len() as u64 > max);
+    let reader: Box<dyn BufRead> = match limit {
+        Some(max) => Box::new(contents.reader().take(max)),
+        None => Box::new(contents.reader()),
+    };
+
+    let mut report = ValidationReport::default();
+    let mut index = 0;
+    for_each_record(reader, limit.is_some(), &mut Interner::default(), |record| {
+        let errors = match record {
+            Record::Element(element) => {
+                let element = validator.validate(&prepare(Value::Array(vec![element.into_value()])));
+                element.errors.into_iter().map(|mut error| {
+                    if let Some(rest) = error.instance_pointer.strip_prefix("/0") {
+                        error.instance_pointer = format!("/{}{}", index, rest);
+                    }
+                    error
+                }).collect::<Vec<_>>()
+            }
+            Record::Line(line) => {
+                let line = validator.validate(&prepare(line.into_value()));
+                line.errors.into_iter().map(|mut error| {
+                    error.instance_pointer = format!("/{}{}", index, error.instance_pointer);
+                    error
+                }).collect()
+            }
+        };
+        report.errors.extend(errors);
+        index += 1;
+        Ok(report.errors.len() < VERIFY_ERRORS_LIMIT)
+    })
+    .with_context(|| format!("Failed to read {} again to verify it", path.display()))?;
+    report.errors.truncate(VERIFY_ERRORS_LIMIT);
+    Ok(report)
+}
+
+enum Record {
+    Element(InternedValue),
+    Line(InternedValue),
+}
+
+// Calls `f` with every record until it returns false, returning the shape and the number of
+// records read. A `truncated` input ends at the record cut off by the truncation.
+fn for_each_record(
+    mut reader: impl BufRead,
+    truncated: bool,
+    interner: &mut Interner,
+    mut f: impl FnMut(Record) -> Result<bool>,
+) -> Result<(StreamShape, usize)> {
+    // Whether a parse error is the end of a truncated input rather than invalid JSON
+    let cut_off = |e: &serde_json::Error, records| truncated && e.is_eof() && records > 0;
+    let mut records = 0;
+    if first_byte(&mut reader)? == Some(b'[') {
+        let mut failed = None;
+        let mut stopped = false;
+        let mut each = |element| {
+            records += 1;
+            f(Record::Element(element))
+        };
+        let mut deserializer = serde_json::Deserializer::from_reader(reader);
+        let seed = ElementSeed { interner, each: &mut each, failed: &mut failed, stopped: &mut stopped };
+        let streamed = seed.deserialize(&mut deserializer);
+        if let Some(error) = failed {
+            return Err(error);
+        }
+        if !stopped {
+            match streamed.and_then(|()| deserializer.end()) {
+                Err(e) if cut_off(&e, records) => {}
+                streamed => streamed?,
+            }
+        }
+        return Ok((StreamShape::Array, records));
+    }
+
+    while first_byte(&mut reader)?.is_some() {
+        let line = match interner.seed().deserialize(&mut serde_json::Deserializer::from_reader(&mut reader)) {
+            Err(e) if cut_off(&e, records) => break,
+            line => line?,
+        };
+        records += 1;
+        if !f(Record::Line(line))? {
+            break;
+        }
+    }
+    Ok((StreamShape::Ndjson, records))
+}
+
+// The first byte that is not whitespace or a byte order mark, left unread
+fn first_byte(reader: &mut impl BufRead) -> Result<Option<u8>> {
+    loop {
+        let buffer = reader.fill_buf()?;
+        let Some(&byte) = buffer.first() else { return Ok(None) };
+        if buffer.starts_with(b"\xEF\xBB\xBF") {
+            reader.consume(3);
+        } else if byte.is_ascii_whitespace() {
+            reader.consume(1);
+        } else {
+            return Ok(Some(byte));
+        }
+    }
+}
+
+// Hands each element of a top-level array to `each` as soon as it is parsed
+struct ElementSeed<'a> {
+    interner: &'a mut Interner,
+    /// Returns false to stop
+    each: &'a mut dyn FnMut(InternedValue) -> Result<bool>,
+    /// The error `each` failed with, which serde can only carry as a message
+    failed: &'a mut Option<anyhow::Error>,
+    stopped: &'a mut bool,
+}
+
+impl<'de> DeserializeSeed<'de> for ElementSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ElementSeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a JSON array")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(element) = seq.next_element_seed(self.interner.seed())? {
+            match (self.each)(element) {
+                Ok(true) => {}
+                // The rest of the array is left unread
+                Ok(false) => {
+                    *self.stopped = true;
+                    return Err(de::Error::custom("stopped"));
+                }
+                Err(error) => {
+                    let message = error.to_string();
+                    *self.failed = Some(error);
+                    return Err(de::Error::custom(message));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Records were fed one at a time, so count-based array bounds at the root reflect a
+// one-element array; the tier's bounds for the real length replace them
+fn fix_array_bounds(schema: &mut Value, generator: &SchemaGenerator, records: usize) {
+    let options = generator.options();
+    let policy = options.policy.as_deref().unwrap_or(&options.tier);
+    let mut bounds = Schema::of_type(InstanceType::Array);
+    policy.streamed_array(&mut bounds, records, &[]);
+    if let (Some(max_items), Some(obj)) = (bounds.max_items, schema.as_object_mut()) {
+        if obj.contains_key("maxItems") {
+            obj.insert("maxItems".to_string(), max_items.into());
+        }
+    }
+}
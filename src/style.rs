@@ -0,0 +1,115 @@
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static STDOUT: AtomicBool = AtomicBool::new(false);
+static STDERR: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+}
+
+impl Color {
+    fn code(self) -> u8 {
+        match self {
+            Color::Red => 31,
+            Color::Green => 32,
+            Color::Yellow => 33,
+            Color::Blue => 34,
+            Color::Magenta => 35,
+            Color::Cyan => 36,
+        }
+    }
+}
+
+/// Enables color for each stream that is a terminal, unless `--no-color` is given or
+/// `NO_COLOR` is set to a non-empty value (https://no-color.org)
+pub fn init(no_color: bool) {
+    let allowed = !no_color && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty());
+    STDOUT.store(allowed && io::stdout().is_terminal(), Ordering::Relaxed);
+    STDERR.store(allowed && io::stderr().is_terminal(), Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    STDOUT.load(Ordering::Relaxed)
+}
+
+pub fn stderr_enabled() -> bool {
+    STDERR.load(Ordering::Relaxed)
+}
+
+/// Colors text bound for stdout
+pub fn paint(text: &str, color: Color) -> String {
+    paint_if(enabled(), text, color)
+}
+
+/// Colors text bound for stderr
+pub fn paint_stderr(text: &str, color: Color) -> String {
+    paint_if(stderr_enabled(), text, color)
+}
+
+pub(crate) fn paint_if(enabled: bool, text: &str, color: Color) -> String {
+    if enabled {
+        format!("\x1b[{}m{}\x1b[0m", color.code(), text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Highlights serialized JSON for stdout: keys, strings, numbers and literals each get a color
+pub fn highlight_json(json: &str) -> String {
+    if !enabled() {
+        return json.to_string();
+    }
+
+    let mut out = String::with_capacity(json.len() * 2);
+    let mut chars = json.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '"' => {
+                let mut end = start + 1;
+                let mut escaped = false;
+                for (i, c) in chars.by_ref() {
+                    end = i + c.len_utf8();
+                    match c {
+                        '\\' if !escaped => escaped = true,
+                        '"' if !escaped => break,
+                        _ => escaped = false,
+                    }
+                }
+                let rest = json[end..].trim_start();
+                let color = if rest.starts_with(':') { Color::Cyan } else { Color::Green };
+                out.push_str(&paint(&json[start..end], color));
+            }
+            '-' | '0'..='9' => {
+                let mut end = start + 1;
+                while let Some(&(i, c)) = chars.peek() {
+                    if !matches!(c, '0'..='9' | '.' | 'e' | 'E' | '+' | '-') {
+                        break;
+                    }
+                    end = i + 1;
+                    chars.next();
+                }
+                out.push_str(&paint(&json[start..end], Color::Yellow));
+            }
+            't' | 'f' | 'n' => {
+                let mut end = start + 1;
+                while let Some(&(i, c)) = chars.peek() {
+                    if !c.is_ascii_alphabetic() {
+                        break;
+                    }
+                    end = i + 1;
+                    chars.next();
+                }
+                out.push_str(&paint(&json[start..end], Color::Magenta));
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::str::FromStr;
+
+use crate::infer::InferenceFamily;
+use crate::required::RequiredMode;
+use crate::schema::SchemaOutputTier;
+
+/// A `--tier` value: a built-in tier, or the name of one defined under `[tiers]` in the config
+#[derive(Debug, Clone)]
+pub enum TierChoice {
+    Builtin(SchemaOutputTier),
+    Custom(String),
+}
+
+impl FromStr for TierChoice {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "basic" => TierChoice::Builtin(SchemaOutputTier::Basic),
+            "standard" => TierChoice::Builtin(SchemaOutputTier::Standard),
+            "comprehensive" => TierChoice::Builtin(SchemaOutputTier::Comprehensive),
+            "expert" => TierChoice::Builtin(SchemaOutputTier::Expert),
+            _ => TierChoice::Custom(s.to_string()),
+        })
+    }
+}
+
+/// A named bundle of generation options on top of a built-in tier, from `[tiers.<name>]`.
+/// Command-line flags still take precedence over it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CustomTier {
+    /// Built-in tier the schema is generated with before the options below are applied
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base: Option<SchemaOutputTier>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required: Option<RequiredMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub additional_properties: Option<bool>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub infer: Vec<InferenceFamily>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub no_infer: Vec<InferenceFamily>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub examples: Option<bool>,
+}
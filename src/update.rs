@@ -0,0 +1,70 @@
+use serde_json::{Map, Value};
+
+use crate::merge::merge_schemas;
+use crate::schema::keywords::{is_annotation, SUBSCHEMA_KEYWORDS, SUBSCHEMA_MAP_KEYWORDS};
+
+/// Marks a subschema as maintained by hand, e.g. `"x-manual": true`, so that updates leave it
+/// exactly as it is
+pub const MANUAL_KEYWORD: &str = "x-manual";
+
+/// Merges `regenerated`, inferred from new data, into `existing`, e.g. the committed schema.
+/// Types widen and new properties are added as in [`merge_schemas`], but nothing is deleted:
+/// properties the new data lacks stay, annotations such as `title` and `description` are kept,
+/// and subschemas marked [`MANUAL_KEYWORD`] are not touched at all.
+pub fn update_schema(existing: &Value, regenerated: &Value) -> Value {
+    let mut updated = merge_schemas(existing, regenerated);
+    preserve(existing, &mut updated);
+    updated
+}
+
+pub fn is_manual(schema: &Value) -> bool {
+    schema.get(MANUAL_KEYWORD) == Some(&Value::Bool(true))
+}
+
+fn contains_manual(schema: &Value) -> bool {
+    match schema {
+        Value::Object(obj) => is_manual(schema) || obj.values().any(contains_manual),
+        Value::Array(items) => items.iter().any(contains_manual),
+        _ => false,
+    }
+}
+
+// Merging keeps the annotations of `existing` only while both sides are plain schemas of
+// some type, and subschemas only while both have them, so this puts back what it dropped
+fn preserve(existing: &Value, updated: &mut Value) {
+    if is_manual(existing) {
+        *updated = existing.clone();
+        return;
+    }
+    let (Value::Object(old), Value::Object(new)) = (existing, updated) else { return };
+
+    // Examples come from the data, so they are left to the merge
+    for (key, value) in old {
+        if is_annotation(key) && key != "examples" && !new.contains_key(key) {
+            new.insert(key.clone(), value.clone());
+        }
+    }
+
+    for keyword in SUBSCHEMA_MAP_KEYWORDS {
+        let Some(Value::Object(old_map)) = old.get(*keyword) else { continue };
+        for (name, old_child) in old_map {
+            let new_child = new.get_mut(*keyword).and_then(Value::as_object_mut).and_then(|map| map.get_mut(name));
+            match new_child {
+                Some(new_child) => preserve(old_child, new_child),
+                None if contains_manual(old_child) => {
+                    let map = new.entry(keyword.to_string()).or_insert_with(|| Value::Object(Map::new()));
+                    if let Value::Object(map) = map {
+                        map.insert(name.clone(), old_child.clone());
+                    }
+                }
+                None => {}
+            }
+        }
+    }
+
+    for keyword in SUBSCHEMA_KEYWORDS {
+        if let (Some(old_child), Some(new_child)) = (old.get(*keyword), new.get_mut(*keyword)) {
+            preserve(old_child, new_child);
+        }
+    }
+}
@@ -1,53 +1,356 @@
-use jsonschema::JSONSchema;
-use serde_json::Value;
+use jsonschema::error::ValidationErrorKind;
+use jsonschema::{Draft, JSONSchema};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::sync::OnceLock;
 use anyhow::Result;
+use tracing::warn;
 
+use crate::bundle::RefResolver;
 use crate::error::AppError;
+use crate::pointer;
+use crate::schema::SchemaDraft;
+use crate::spans;
 
-pub fn validate_schema(schema: &Value) -> Result<()> {
-    let meta_schema = serde_json::json!({
-        "$schema": "https://json-schema.org/draft/2020-12/schema"
-    });
-    
-    match JSONSchema::compile(&meta_schema) {
-        Ok(compiled) => {
-            if let Err(errors) = compiled.validate(schema) {
-                let error_messages: Vec<String> = errors
-                    .map(|e| e.to_string())
-                    .collect();
-                return Err(AppError::SchemaGeneration(
-                    format!("Schema validation failed: {}", error_messages.join(", "))
-                ).into());
+/// Characters of the offending line kept around the value in a [`SourceLocation`]'s snippet
+pub const SNIPPET_WIDTH: usize = 80;
+
+/// One way a document fails a schema
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidationError {
+    /// JSON Pointer of the offending value in the document
+    pub instance_pointer: String,
+    /// JSON Pointer of the failing keyword in the schema
+    pub schema_pointer: String,
+    /// e.g. `type` or `required`
+    pub keyword: String,
+    pub message: String,
+    /// Errors unless the keyword was [downgraded](ValidationReport::downgrade)
+    pub severity: Severity,
+    /// Where the offending value is in the document's text, once
+    /// [located](ValidationReport::locate)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<SourceLocation>,
+}
+
+/// How much a validation error matters, least first
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Severity {
+    Warning,
+    #[default]
+    Error,
+}
+
+/// Where a value is in the text of a document. A value missing from the document, e.g. a
+/// required property, is located at its nearest enclosing value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SourceLocation {
+    /// 1-based
+    pub line: usize,
+    /// 1-based, in characters
+    pub column: usize,
+    /// The line the value starts on, cut to [`SNIPPET_WIDTH`] characters around it
+    pub snippet: String,
+    /// 0-based character offset of the value in `snippet`
+    pub snippet_column: usize,
+}
+
+impl ValidationError {
+    /// The error, then the schema location and, once located, the line and column with the
+    /// offending line marked under the value
+    pub fn render_text(&self) -> String {
+        let keyword = match self.severity {
+            Severity::Error => self.keyword.clone(),
+            Severity::Warning => format!("{}, warning", self.keyword),
+        };
+        let mut out = format!("{}: {} ({})\n", pointer::display(&self.instance_pointer), self.message, keyword);
+        let schema = pointer::display(&self.schema_pointer);
+        match &self.location {
+            Some(location) => {
+                out.push_str(&format!(
+                    "    at line {}, column {}; schema location {}\n",
+                    location.line, location.column, schema
+                ));
+                out.push_str(&format!("    | {}\n", location.snippet));
+                out.push_str(&format!("    | {}^\n", " ".repeat(location.snippet_column)));
             }
-            println!("Schema validation passed");
-        }
-        Err(e) => {
-            return Err(AppError::SchemaGeneration(
-                format!("Failed to compile meta-schema: {}", e)
-            ).into());
+            None => out.push_str(&format!("    schema location {}\n", schema)),
         }
+        out
     }
-    
-    Ok(())
 }
 
-pub fn validate_json_against_schema(json: &Value, schema: &Value) -> Result<()> {
-    match JSONSchema::compile(schema) {
-        Ok(compiled) => {
-            if let Err(errors) = compiled.validate(json) {
-                let error_messages: Vec<String> = errors
-                    .map(|e| e.to_string())
-                    .collect();
-                return Err(AppError::SchemaGeneration(
-                    format!("JSON validation failed: {}", error_messages.join(", "))
-                ).into());
+/// Everything wrong with a document; empty when it is valid
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ValidationReport {
+    pub errors: Vec<ValidationError>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Makes the errors of every keyword in `keywords`, e.g. `additionalProperties`, warnings
+    pub fn downgrade(&mut self, keywords: &[String]) {
+        for error in &mut self.errors {
+            if keywords.contains(&error.keyword) {
+                error.severity = Severity::Warning;
             }
-            Ok(())
         }
-        Err(e) => {
-            Err(AppError::SchemaGeneration(
-                format!("Failed to compile schema for validation: {}", e)
-            ).into())
+    }
+
+    /// Fills in where each error is in `text`, the document that was validated. `base` is the
+    /// pointer of the validated value in `text`, when only part of it was validated.
+    pub fn locate(&mut self, text: &str, base: &str) {
+        for error in &mut self.errors {
+            error.location = locate(text, &format!("{}{}", base, error.instance_pointer));
+        }
+    }
+
+    pub fn render_text(&self) -> String {
+        self.errors.iter().map(ValidationError::render_text).collect()
+    }
+
+    /// `Err` with every error when there are, as `ValidationFailed` with `what` (e.g. "Schema
+    /// validation") leading the message
+    pub fn ensure_valid(&self, what: &str) -> Result<()> {
+        if self.is_valid() {
+            return Ok(());
+        }
+        Err(AppError::ValidationFailed(format!(
+            "{} failed: {} error(s)\n{}",
+            what,
+            self.errors.len(),
+            self.render_text().trim_end()
+        ))
+        .into())
+    }
+}
+
+fn locate(text: &str, pointer: &str) -> Option<SourceLocation> {
+    let span = spans::enclosing_value_span(text, pointer)?;
+    let (line, column) = spans::line_col(text, span.start);
+    let chars: Vec<char> = text.lines().nth(line - 1)?.chars().collect();
+    let at = column - 1;
+
+    // Indentation is left out, and long lines keep some context before the value
+    let indent = chars.iter().take_while(|c| c.is_whitespace()).count().min(at);
+    let start = at.saturating_sub(SNIPPET_WIDTH / 4).max(indent);
+    let end = chars.len().min(start + SNIPPET_WIDTH);
+    let snippet: String = chars[start..end].iter().collect();
+    Some(SourceLocation { line, column, snippet: snippet.trim_end().to_string(), snippet_column: at - start })
+}
+
+const DRAFT_04: &[(&str, &str)] = &[("http://json-schema.org/draft-04/schema", include_str!("../meta_schemas/draft4.json"))];
+const DRAFT_06: &[(&str, &str)] = &[("http://json-schema.org/draft-06/schema", include_str!("../meta_schemas/draft6.json"))];
+const DRAFT_07: &[(&str, &str)] = &[("http://json-schema.org/draft-07/schema", include_str!("../meta_schemas/draft7.json"))];
+
+// From 2019-09 on, the meta-schema is split into one document per vocabulary, which refer to
+// each other by these ids. The root comes first.
+const DRAFT_2019_09: &[(&str, &str)] = &[
+    ("https://json-schema.org/draft/2019-09/schema", include_str!("../meta_schemas/draft2019-09/schema.json")),
+    ("https://json-schema.org/draft/2019-09/meta/core", include_str!("../meta_schemas/draft2019-09/meta/core.json")),
+    (
+        "https://json-schema.org/draft/2019-09/meta/applicator",
+        include_str!("../meta_schemas/draft2019-09/meta/applicator.json"),
+    ),
+    (
+        "https://json-schema.org/draft/2019-09/meta/validation",
+        include_str!("../meta_schemas/draft2019-09/meta/validation.json"),
+    ),
+    (
+        "https://json-schema.org/draft/2019-09/meta/meta-data",
+        include_str!("../meta_schemas/draft2019-09/meta/meta-data.json"),
+    ),
+    ("https://json-schema.org/draft/2019-09/meta/format", include_str!("../meta_schemas/draft2019-09/meta/format.json")),
+    (
+        "https://json-schema.org/draft/2019-09/meta/content",
+        include_str!("../meta_schemas/draft2019-09/meta/content.json"),
+    ),
+];
+
+const DRAFT_2020_12: &[(&str, &str)] = &[
+    ("https://json-schema.org/draft/2020-12/schema", include_str!("../meta_schemas/draft2020-12/schema.json")),
+    ("https://json-schema.org/draft/2020-12/meta/core", include_str!("../meta_schemas/draft2020-12/meta/core.json")),
+    (
+        "https://json-schema.org/draft/2020-12/meta/applicator",
+        include_str!("../meta_schemas/draft2020-12/meta/applicator.json"),
+    ),
+    (
+        "https://json-schema.org/draft/2020-12/meta/unevaluated",
+        include_str!("../meta_schemas/draft2020-12/meta/unevaluated.json"),
+    ),
+    (
+        "https://json-schema.org/draft/2020-12/meta/validation",
+        include_str!("../meta_schemas/draft2020-12/meta/validation.json"),
+    ),
+    (
+        "https://json-schema.org/draft/2020-12/meta/meta-data",
+        include_str!("../meta_schemas/draft2020-12/meta/meta-data.json"),
+    ),
+    (
+        "https://json-schema.org/draft/2020-12/meta/format-annotation",
+        include_str!("../meta_schemas/draft2020-12/meta/format-annotation.json"),
+    ),
+    (
+        "https://json-schema.org/draft/2020-12/meta/content",
+        include_str!("../meta_schemas/draft2020-12/meta/content.json"),
+    ),
+];
+
+/// The bundled meta-schema documents of `draft`, as `($id, JSON)` pairs with the root first
+pub fn meta_schema_documents(draft: SchemaDraft) -> &'static [(&'static str, &'static str)] {
+    match draft {
+        SchemaDraft::Draft04 => DRAFT_04,
+        SchemaDraft::Draft06 => DRAFT_06,
+        SchemaDraft::Draft07 => DRAFT_07,
+        SchemaDraft::Draft201909 => DRAFT_2019_09,
+        SchemaDraft::Draft202012 => DRAFT_2020_12,
+    }
+}
+
+/// Checks `schema` against the bundled meta-schema of the draft its `$schema` names, or of
+/// 2020-12 when it has none. A `$schema` of no supported draft is not checked.
+pub fn validate_schema(schema: &Value) -> Result<ValidationReport> {
+    match schema.get("$schema").and_then(Value::as_str) {
+        Some(uri) => match SchemaDraft::from_uri(uri) {
+            Some(draft) => validate_schema_as(schema, draft),
+            None => {
+                warn!("No bundled meta-schema for {}; the schema is not validated", uri);
+                Ok(ValidationReport::default())
+            }
+        },
+        None => validate_schema_as(schema, SchemaDraft::default()),
+    }
+}
+
+/// Checks `schema` against the bundled meta-schema of `draft`, whatever its `$schema` says.
+/// Fails only when the meta-schema cannot be compiled; an invalid schema is a report with
+/// errors. Nothing is fetched over the network.
+pub fn validate_schema_as(schema: &Value, draft: SchemaDraft) -> Result<ValidationReport> {
+    Ok(report(meta_schema(draft)?, schema))
+}
+
+// Each meta-schema is compiled once per process, as every generated schema is checked
+// against it
+fn meta_schema(draft: SchemaDraft) -> Result<&'static JSONSchema> {
+    static COMPILED: [OnceLock<Result<JSONSchema, String>>; SchemaDraft::ALL.len()] =
+        [const { OnceLock::new() }; SchemaDraft::ALL.len()];
+    COMPILED[draft as usize]
+        .get_or_init(|| compile_meta_schema(draft))
+        .as_ref()
+        .map_err(|e| AppError::InvalidSchema(format!("Failed to compile the {} meta-schema: {}", draft, e)).into())
+}
+
+fn compile_meta_schema(draft: SchemaDraft) -> Result<JSONSchema, String> {
+    let documents = meta_schema_documents(draft);
+    let root_id = documents[0].0;
+    let parse = |text: &str| {
+        let document: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        Ok::<_, String>(if draft.uses_defs() { static_refs(document, root_id) } else { document })
+    };
+
+    // Drafts past 7 are compiled as draft 7 after `static_refs`, which is all their
+    // meta-schemas need
+    let mut options = JSONSchema::options();
+    options.with_draft(match draft {
+        SchemaDraft::Draft04 => Draft::Draft4,
+        SchemaDraft::Draft06 => Draft::Draft6,
+        _ => Draft::Draft7,
+    });
+    for (id, text) in &documents[1..] {
+        options.with_document(id.to_string(), parse(text)?);
+    }
+    let root = parse(documents[0].1)?;
+    options.compile(&root).map_err(|e| e.to_string())
+}
+
+// The validator predates `$recursiveRef` and `$dynamicRef`, and ignores the keywords next to
+// a `$ref`. The meta-schemas' recursive and dynamic references all point back at the root, so
+// they become plain `$ref`s to `root_id`, and a `$ref` with siblings moves into an `allOf` so
+// both apply.
+fn static_refs(value: Value, root_id: &str) -> Value {
+    match value {
+        Value::Object(obj) => {
+            let mut obj: Map<String, Value> =
+                obj.into_iter().map(|(key, value)| (key, static_refs(value, root_id))).collect();
+            for keyword in ["$recursiveRef", "$dynamicRef"] {
+                if let Some(Value::String(_)) = obj.get(keyword) {
+                    obj.remove(keyword);
+                    obj.insert("$ref".to_string(), Value::String(root_id.to_string()));
+                }
+            }
+            if matches!(obj.get("$ref"), Some(Value::String(_))) && obj.len() > 1 {
+                let reference = obj.remove("$ref").unwrap_or_default();
+                let mut wrapped = Map::new();
+                wrapped.insert("$ref".to_string(), reference);
+                obj.insert("allOf".to_string(), Value::Array(vec![Value::Object(wrapped)]));
+            }
+            Value::Object(obj)
         }
+        Value::Array(items) => Value::Array(items.into_iter().map(|item| static_refs(item, root_id)).collect()),
+        other => other,
     }
-}
\ No newline at end of file
+}
+
+/// Checks `json` against `schema`. Fails only when `schema` cannot be compiled.
+pub fn validate_json_against_schema(json: &Value, schema: &Value) -> Result<ValidationReport> {
+    Ok(Validator::new(schema)?.validate(json))
+}
+
+/// A schema compiled once, to check many documents against, e.g. the records of a stream
+pub struct Validator {
+    compiled: JSONSchema,
+}
+
+impl Validator {
+    /// Fails when `schema` cannot be compiled. `$ref`s to other documents resolve only when
+    /// absolute or relative to an `$id`; see [`Validator::with_refs`].
+    pub fn new(schema: &Value) -> Result<Self> {
+        Self::with_refs(schema, RefResolver::default())
+    }
+
+    /// Resolves `$ref`s to other documents with `refs`, e.g. against the schema's file and
+    /// through the remote cache. They are loaded the first time a document reaches them, and
+    /// one that cannot be loaded fails validation with a `$ref` error.
+    pub fn with_refs(schema: &Value, refs: RefResolver) -> Result<Self> {
+        let compiled = JSONSchema::options()
+            .with_resolver(refs)
+            .compile(schema)
+            .map_err(|e| AppError::InvalidSchema(format!("Failed to compile schema for validation: {}", e)))?;
+        Ok(Self { compiled })
+    }
+
+    pub fn validate(&self, document: &Value) -> ValidationReport {
+        report(&self.compiled, document)
+    }
+}
+
+fn report(compiled: &JSONSchema, instance: &Value) -> ValidationReport {
+    let Err(errors) = compiled.validate(instance) else { return ValidationReport::default() };
+
+    let errors = errors
+        .map(|error| {
+            let (keyword, message) = match &error.kind {
+                // A `$ref` to another document that could not be loaded, which is reported
+                // without the keyword's path
+                ValidationErrorKind::Resolver { url, error } => {
+                    ("$ref".to_string(), format!("Failed to resolve {}: {:#}", url, error))
+                }
+                _ => (error.schema_path.clone().into_vec().pop().unwrap_or_default(), error.to_string()),
+            };
+            ValidationError {
+                instance_pointer: error.instance_path.to_string(),
+                schema_pointer: error.schema_path.to_string(),
+                keyword,
+                message,
+                severity: Severity::Error,
+                location: None,
+            }
+        })
+        .collect();
+    ValidationReport { errors }
+}
@@ -0,0 +1,16 @@
+//! The generator for JavaScript, e.g. a browser playground. Built for wasm32 with the `wasm`
+//! feature, this module exports `generateSchema(json, options)`, a wrapper around
+//! [`generate_schema_json`].
+
+pub use crate::bindings::generate_schema_json;
+
+#[cfg(target_arch = "wasm32")]
+mod exports {
+    use wasm_bindgen::prelude::*;
+
+    /// `generateSchema(json: string, options?: string): string`, throwing on invalid input
+    #[wasm_bindgen(js_name = generateSchema)]
+    pub fn generate_schema(json: &str, options: Option<String>) -> Result<String, JsError> {
+        super::generate_schema_json(json, options.as_deref()).map_err(|e| JsError::new(&format!("{:#}", e)))
+    }
+}
@@ -80,6 +80,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::expect_fun_call)]
     fn test_schema_validation_with_sample_data() {
         let test_samples = [
             ("user_profile.json", include_str!("../test_samples/user_profile.json")),
@@ -156,4 +157,3874 @@ mod tests {
             assert!(schema_path.exists(), "Schema file should exist for {}", filename);
         }
     }
+
+    #[test]
+    fn test_diff_subcommand() {
+        let dir = tempdir().unwrap();
+        let old_path = dir.path().join("old.schema.json");
+        let new_path = dir.path().join("new.schema.json");
+
+        fs::write(&old_path, r#"{"type": "object", "properties": {"name": {"type": "string"}, "age": {"type": "integer"}}, "required": ["name"]}"#).unwrap();
+        fs::write(&new_path, r#"{"type": "object", "properties": {"name": {"type": "string", "maxLength": 10}, "age": {"type": "number"}, "email": {"type": "string"}}, "required": ["name", "email"]}"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("diff")
+            .arg(&old_path)
+            .arg(&new_path)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("+ /properties/email: property added"))
+            .stdout(predicate::str::contains("~ /properties/age: type changed from \"integer\" to \"number\""))
+            .stdout(predicate::str::contains("+ /properties/name: maxLength added (10)"))
+            .stdout(predicate::str::contains("+ /properties/email: now required"));
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        let output = cmd.arg("diff")
+            .arg(&old_path)
+            .arg(&new_path)
+            .arg("--format")
+            .arg("json")
+            .output()
+            .unwrap();
+
+        let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(report["changes"].as_array().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_compat_subcommand() {
+        let dir = tempdir().unwrap();
+        let old_path = dir.path().join("old.schema.json");
+        let widened_path = dir.path().join("widened.schema.json");
+        let narrowed_path = dir.path().join("narrowed.schema.json");
+
+        fs::write(&old_path, r#"{"type": "object", "properties": {"id": {"type": "integer", "maximum": 10}, "tag": {"type": "string"}}, "required": ["id"]}"#).unwrap();
+        fs::write(&widened_path, r#"{"type": "object", "properties": {"id": {"type": "number", "maximum": 20}, "tag": {"type": "string"}, "note": {"type": "string"}}, "title": "Widened"}"#).unwrap();
+        fs::write(&narrowed_path, r#"{"type": "object", "properties": {"id": {"type": "integer", "maximum": 5}, "name": {"type": "string"}}, "required": ["id", "name"]}"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("compat")
+            .arg(&old_path)
+            .arg(&widened_path)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("0 breaking"));
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("compat")
+            .arg(&old_path)
+            .arg(&narrowed_path)
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("[BREAKING] - /properties/tag: property removed"))
+            .stdout(predicate::str::contains("upper bound tightened"))
+            .stdout(predicate::str::contains("new required field"))
+            .stderr(predicate::str::contains("Breaking schema changes detected"));
+
+        // A schema without `type` accepts every type
+        let untyped_path = dir.path().join("untyped.schema.json");
+        let typed_path = dir.path().join("typed.schema.json");
+        fs::write(&untyped_path, r#"{"type": "object", "properties": {"a": {}}}"#).unwrap();
+        fs::write(&typed_path, r#"{"type": "object", "properties": {"a": {"type": "string"}}}"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("compat")
+            .arg(&untyped_path)
+            .arg(&typed_path)
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("[BREAKING]"))
+            .stdout(predicate::str::contains("type narrowed"));
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("compat")
+            .arg(&typed_path)
+            .arg(&untyped_path)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("[compatible]"))
+            .stdout(predicate::str::contains("type widened"))
+            .stdout(predicate::str::contains("0 breaking"));
+    }
+
+    #[test]
+    fn test_merge_subcommand() {
+        let dir = tempdir().unwrap();
+        let a_path = dir.path().join("a.schema.json");
+        let b_path = dir.path().join("b.schema.json");
+        let output_path = dir.path().join("combined.schema.json");
+
+        fs::write(&a_path, r#"{"type": "object", "properties": {"id": {"type": "integer", "maximum": 10}, "name": {"type": "string"}}, "required": ["id", "name"]}"#).unwrap();
+        fs::write(&b_path, r#"{"type": "object", "properties": {"id": {"type": "number", "maximum": 50}, "email": {"type": "string"}}, "required": ["id", "email"]}"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("merge")
+            .arg(&a_path)
+            .arg(&b_path)
+            .arg("-o")
+            .arg(&output_path)
+            .assert()
+            .success();
+
+        let merged: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output_path).unwrap()).unwrap();
+        assert_eq!(merged["properties"]["id"]["type"], "number");
+        assert_eq!(merged["properties"]["id"]["maximum"], 50);
+        assert!(merged["properties"]["name"].is_object());
+        assert!(merged["properties"]["email"].is_object());
+        assert_eq!(merged["required"], serde_json::json!(["id"]));
+    }
+
+    #[test]
+    fn test_convert_subcommand_round_trip() {
+        let dir = tempdir().unwrap();
+        let input_path = dir.path().join("legacy.schema.json");
+        let modern_path = dir.path().join("modern.schema.json");
+        let legacy_path = dir.path().join("legacy-again.schema.json");
+
+        let original = r##"{
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "properties": {
+                "point": {"type": "array", "items": [{"type": "number"}, {"type": "number"}], "additionalItems": false},
+                "owner": {"$ref": "#/definitions/user"}
+            },
+            "dependencies": {"owner": ["point"]},
+            "definitions": {"user": {"type": "string"}}
+        }"##;
+        fs::write(&input_path, original).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("convert")
+            .arg(&input_path)
+            .arg("--to")
+            .arg("2020-12")
+            .arg("-o")
+            .arg(&modern_path)
+            .assert()
+            .success();
+
+        let modern: serde_json::Value = serde_json::from_str(&fs::read_to_string(&modern_path).unwrap()).unwrap();
+        assert_eq!(modern["$schema"], "https://json-schema.org/draft/2020-12/schema");
+        assert!(modern["$defs"]["user"].is_object());
+        assert_eq!(modern["properties"]["owner"]["$ref"], "#/$defs/user");
+        assert!(modern["properties"]["point"]["prefixItems"].is_array());
+        assert_eq!(modern["properties"]["point"]["items"], false);
+        assert_eq!(modern["dependentRequired"]["owner"], serde_json::json!(["point"]));
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("convert")
+            .arg(&modern_path)
+            .arg("--from")
+            .arg("2020-12")
+            .arg("--to")
+            .arg("draft-07")
+            .arg("-o")
+            .arg(&legacy_path)
+            .assert()
+            .success();
+
+        let legacy: serde_json::Value = serde_json::from_str(&fs::read_to_string(&legacy_path).unwrap()).unwrap();
+        let original: serde_json::Value = serde_json::from_str(original).unwrap();
+        assert_eq!(legacy, original);
+    }
+
+    #[test]
+    fn test_bundle_subcommand_inlines_refs() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("common")).unwrap();
+        let root_path = dir.path().join("order.schema.json");
+        let output_path = dir.path().join("bundled.schema.json");
+
+        fs::write(dir.path().join("common/address.schema.json"), r#"{"type": "object", "properties": {"city": {"type": "string"}}}"#).unwrap();
+        fs::write(&root_path, r##"{
+            "type": "object",
+            "properties": {
+                "shipping": {"$ref": "common/address.schema.json", "description": "Where to ship"},
+                "tree": {"$ref": "#/$defs/node"}
+            },
+            "$defs": {
+                "node": {"type": "object", "properties": {"children": {"type": "array", "items": {"$ref": "#/$defs/node"}}}}
+            }
+        }"##).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("bundle")
+            .arg(&root_path)
+            .arg("-o")
+            .arg(&output_path)
+            .assert()
+            .success();
+
+        let bundled: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output_path).unwrap()).unwrap();
+        assert_eq!(bundled["properties"]["shipping"]["properties"]["city"]["type"], "string");
+        assert_eq!(bundled["properties"]["shipping"]["description"], "Where to ship");
+        assert_eq!(bundled["properties"]["tree"]["$ref"], "#/$defs/node");
+        assert_eq!(bundled["$defs"]["node"]["properties"]["children"]["items"]["$ref"], "#/$defs/node");
+    }
+
+    #[test]
+    fn test_split_subcommand() {
+        let dir = tempdir().unwrap();
+        let input_path = dir.path().join("order.schema.json");
+        let out_dir = dir.path().join("split");
+
+        fs::write(&input_path, r##"{
+            "type": "object",
+            "properties": {"customer": {"$ref": "#/$defs/CustomerInfo"}},
+            "$defs": {
+                "CustomerInfo": {"type": "object", "properties": {"address": {"$ref": "#/$defs/PostalAddress"}}},
+                "PostalAddress": {"type": "object", "properties": {"city": {"type": "string"}}}
+            }
+        }"##).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("split")
+            .arg(&input_path)
+            .arg("--out-dir")
+            .arg(&out_dir)
+            .arg("--case")
+            .arg("kebab")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Split 2 definition(s)"));
+
+        let read = |name: &str| -> serde_json::Value {
+            serde_json::from_str(&fs::read_to_string(out_dir.join(name)).unwrap()).unwrap()
+        };
+
+        let root = read("order.schema.json");
+        assert!(root.get("$defs").is_none());
+        assert_eq!(root["properties"]["customer"]["$ref"], "customer-info.schema.json");
+
+        let customer = read("customer-info.schema.json");
+        assert_eq!(customer["properties"]["address"]["$ref"], "postal-address.schema.json");
+
+        let index = read("index.json");
+        assert_eq!(index["root"], "order.schema.json");
+        assert_eq!(index["definitions"]["PostalAddress"], "postal-address.schema.json");
+    }
+
+    #[test]
+    fn test_fake_subcommand_satisfies_schema() {
+        let dir = tempdir().unwrap();
+        let schema_path = dir.path().join("user.schema.json");
+        let schema = r#"{
+            "type": "object",
+            "properties": {
+                "id": {"type": "integer", "minimum": 1, "maximum": 99},
+                "email": {"type": "string", "format": "email"},
+                "role": {"enum": ["admin", "member"]},
+                "phone": {"type": "string", "pattern": "^[\\d\\-\\s]+$"},
+                "tags": {"type": "array", "items": {"type": "string", "maxLength": 8}, "minItems": 1, "uniqueItems": true}
+            },
+            "required": ["id", "email", "role", "phone", "tags"],
+            "additionalProperties": false
+        }"#;
+        fs::write(&schema_path, schema).unwrap();
+
+        let run = || {
+            let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+            cmd.arg("fake")
+                .arg("--schema")
+                .arg(&schema_path)
+                .arg("--count")
+                .arg("20")
+                .arg("--seed")
+                .arg("42")
+                .output()
+                .unwrap()
+        };
+
+        let first = run();
+        assert!(first.status.success());
+        assert_eq!(first.stdout, run().stdout, "same seed should reproduce the same documents");
+
+        let documents: serde_json::Value = serde_json::from_slice(&first.stdout).unwrap();
+        let documents = documents.as_array().unwrap();
+        assert_eq!(documents.len(), 20);
+
+        let schema: serde_json::Value = serde_json::from_str(schema).unwrap();
+        for document in documents {
+            let report = schema_jenerator::validation::validate_json_against_schema(document, &schema).unwrap();
+            assert!(report.is_valid(), "{}", report.render_text());
+        }
+    }
+
+    #[test]
+    fn test_stats_subcommand() {
+        let dir = tempdir().unwrap();
+        let input_path = dir.path().join("data.json");
+        fs::write(&input_path, r#"{"users": [{"name": "a", "email": null}, {"name": "b", "email": "b@example.com"}], "total": 2}"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("stats")
+            .arg(&input_path)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Max depth:          3"))
+            .stdout(predicate::str::contains("/users/*/email 1/2 (50%)"));
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        let output = cmd.arg("stats")
+            .arg(&input_path)
+            .arg("--format")
+            .arg("json")
+            .output()
+            .unwrap();
+
+        let stats: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(stats["total_nodes"], 9);
+        assert_eq!(stats["type_counts"]["null"], 1);
+        assert_eq!(stats["arrays"]["max_length"], 2);
+        assert_eq!(stats["largest_subtrees"][0]["pointer"], "/users");
+        assert!(!input_path.with_file_name("data.schema.json").exists());
+    }
+
+    #[test]
+    fn test_init_writes_commented_config() {
+        let dir = tempdir().unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.current_dir(dir.path())
+            .arg("init")
+            .assert()
+            .success();
+
+        let config_path = dir.path().join(".schema-jenerator.toml");
+        let content = fs::read_to_string(&config_path).unwrap();
+        assert!(content.contains("# Schema tier used when --tier is not given"));
+        assert!(content.contains("default_tier = \"Standard\""));
+        assert!(content.contains("# output_directory = \"schemas\""));
+
+        let config = schema_jenerator::config::Config::load_from_file(&config_path).unwrap();
+        assert!(matches!(config.default_tier, schema_jenerator::SchemaOutputTier::Standard));
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.current_dir(dir.path())
+            .arg("init")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Refusing to overwrite existing file .schema-jenerator.toml"));
+    }
+
+    #[test]
+    fn test_init_interactive_json() {
+        let dir = tempdir().unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.current_dir(dir.path())
+            .arg("init")
+            .arg("--json")
+            .arg("--interactive")
+            .write_stdin("expert\ndraft-07\nschemas\n")
+            .assert()
+            .success();
+
+        let config: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(dir.path().join("schema-jenerator.json")).unwrap()
+        ).unwrap();
+        assert_eq!(config["default_tier"], "Expert");
+        assert_eq!(config["draft"], "draft-07");
+        assert_eq!(config["output_directory"], "schemas");
+    }
+
+    #[test]
+    fn test_check_detects_schema_drift() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("data")).unwrap();
+        let data_path = dir.path().join("data/user.json");
+        let schema_path = dir.path().join("user.schema.json");
+
+        fs::write(&data_path, r#"{"name": "test", "value": 42}"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&data_path)
+            .arg("-o")
+            .arg(&schema_path)
+            .assert()
+            .success();
+
+        let pattern = dir.path().join("data/*.json");
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("check")
+            .arg("--schema")
+            .arg(&schema_path)
+            .arg(&pattern)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("No differences found"));
+
+        fs::write(&data_path, r#"{"name": "test", "value": 42, "email": "a@example.com"}"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("check")
+            .arg("--schema")
+            .arg(&schema_path)
+            .arg(&pattern)
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("+ /properties/email: property added"))
+            .stderr(predicate::str::contains("Schema drift detected"));
+    }
+
+    #[test]
+    fn test_normalize_subcommand() {
+        let dir = tempdir().unwrap();
+        let input_path = dir.path().join("messy.schema.json");
+        let output_path = dir.path().join("clean.schema.json");
+
+        fs::write(&input_path, r#"{
+            "type": "object",
+            "required": ["name", "id", "name"],
+            "additionalProperties": true,
+            "properties": {
+                "name": {"type": "string", "minLength": 0},
+                "id": {"oneOf": [{"type": ["integer", "integer"], "multipleOf": 1}]},
+                "tags": {"type": "array", "items": {}, "minItems": 0, "uniqueItems": false}
+            }
+        }"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("normalize")
+            .arg(&input_path)
+            .arg("-o")
+            .arg(&output_path)
+            .assert()
+            .success();
+
+        let normalized: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output_path).unwrap()).unwrap();
+        assert_eq!(normalized, serde_json::json!({
+            "type": "object",
+            "required": ["id", "name"],
+            "properties": {
+                "name": {"type": "string"},
+                "id": {"type": "integer"},
+                "tags": {"type": "array"}
+            }
+        }));
+
+        // Normalizing canonical output again must be byte-identical
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        let output = cmd.arg("normalize").arg(&output_path).output().unwrap();
+        assert_eq!(output.stdout, fs::read(&output_path).unwrap());
+    }
+
+    #[test]
+    fn test_minify_alongside_generation() {
+        let dir = tempdir().unwrap();
+        let input_path = dir.path().join("user.json");
+        fs::write(&input_path, r#"{"title": "Dr", "email": "a@example.com"}"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input_path)
+            .arg("--tier")
+            .arg("expert")
+            .arg("--minify")
+            .assert()
+            .success();
+
+        let annotated: serde_json::Value = serde_json::from_str(&fs::read_to_string(dir.path().join("user.schema.json")).unwrap()).unwrap();
+        let minified: serde_json::Value = serde_json::from_str(&fs::read_to_string(dir.path().join("user.schema.min.json")).unwrap()).unwrap();
+
+        assert!(annotated["properties"]["email"]["examples"].is_array());
+        assert!(minified.get("title").is_none());
+        assert!(minified.get("description").is_none());
+        assert!(minified["properties"]["email"].get("examples").is_none());
+        assert_eq!(minified["properties"]["email"]["format"], "email");
+        // A property that happens to be called "title" is data, not an annotation
+        assert!(minified["properties"]["title"].is_object());
+    }
+
+    #[test]
+    fn test_openapi_update_preserves_rest_of_spec() {
+        let dir = tempdir().unwrap();
+        let sample_path = dir.path().join("user1.json");
+        fs::write(&sample_path, r#"{"id": 1, "email": null}"#).unwrap();
+
+        let yaml_path = dir.path().join("spec.yaml");
+        fs::write(&yaml_path, "openapi: 3.0.3\n# keep this comment\ninfo:\n  title: Users\npaths: {}\ncomponents:\n  schemas:\n    User:\n      type: object\n\n    Error:\n      type: string\n").unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("openapi")
+            .arg("update")
+            .arg(&yaml_path)
+            .arg("--component")
+            .arg("User")
+            .arg("--from")
+            .arg(dir.path().join("user*.json").to_str().unwrap())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Component User updated"));
+
+        let yaml = fs::read_to_string(&yaml_path).unwrap();
+        assert!(yaml.starts_with("openapi: 3.0.3\n# keep this comment\ninfo:\n  title: Users\n"));
+        assert!(yaml.contains("    User:\n"));
+        assert!(yaml.contains("      type: \"object\"\n"));
+        assert!(yaml.contains("          type: \"integer\"\n"));
+        assert!(!yaml.contains("      type: object\n"));
+        // OpenAPI 3.0 has no null type
+        assert!(yaml.contains("nullable: true"));
+        assert!(yaml.contains("\n\n    Error:\n      type: string\n"));
+        assert!(!yaml.contains("$schema"));
+
+        let json_path = dir.path().join("spec.json");
+        fs::write(&json_path, r#"{
+  "openapi": "3.1.0",
+  "components": {
+    "schemas": {
+      "Error": {"type": "string"}
+    }
+  }
+}"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("openapi")
+            .arg("update")
+            .arg(&json_path)
+            .arg("--component")
+            .arg("User")
+            .arg("--from")
+            .arg(&sample_path)
+            .assert()
+            .success();
+
+        let json = fs::read_to_string(&json_path).unwrap();
+        assert!(json.contains(r#""Error": {"type": "string"},"#));
+        let spec: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(spec["components"]["schemas"]["User"]["properties"]["id"]["type"], "integer");
+        assert!(spec["components"]["schemas"]["User"].get("$schema").is_none());
+    }
+
+    #[test]
+    fn test_explain_attributes_keywords_to_rules() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("samples")).unwrap();
+        let first = dir.path().join("samples/a.json");
+        let second = dir.path().join("samples/b.json");
+        fs::write(&first, r#"{"email": "a@example.com", "age": 30}"#).unwrap();
+        fs::write(&second, r#"{"email": "b@example.com"}"#).unwrap();
+        let schema_path = dir.path().join("user.schema.json");
+        fs::write(&schema_path, r#"{
+            "type": "object",
+            "properties": {
+                "email": {"type": "string", "format": "email", "maxLength": 26},
+                "age": {"type": "integer", "minimum": 30}
+            },
+            "required": ["email"]
+        }"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("explain")
+            .arg(&schema_path)
+            .arg("--data")
+            .arg(dir.path().join("samples/*.json").to_str().unwrap())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("/properties/email\n"))
+            .stdout(predicate::str::contains("format: \"email\" [heuristic] format detector"))
+            .stdout(predicate::str::contains("minimum: 30 [observed] smallest value seen in samples (1/2 samples)"))
+            .stdout(predicate::str::contains("keyword(s) explained, 2 heuristic"));
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        let output = cmd.arg("explain").arg(&schema_path).arg("--format").arg("json").output().unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let maxlength = report["explanations"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|e| e["keyword"] == "maxLength")
+            .unwrap();
+        assert_eq!(maxlength["kind"], "heuristic");
+        assert_eq!(maxlength["pointer"], "/properties/email");
+    }
+
+    #[test]
+    fn test_review_asks_before_overwriting() {
+        let dir = tempdir().unwrap();
+        let data_path = dir.path().join("user.json");
+        let schema_path = dir.path().join("user.schema.json");
+        fs::write(&data_path, r#"{"id": 1, "email": "a@example.com"}"#).unwrap();
+        let hand_tuned = r#"{"type": "object", "properties": {"id": {"type": "integer"}}}"#;
+        fs::write(&schema_path, hand_tuned).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("review")
+            .arg(&data_path)
+            .arg("--against")
+            .arg(&schema_path)
+            .write_stdin("n\n")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("+ /properties/email: property added"))
+            .stdout(predicate::str::contains("Overwrite"))
+            .stdout(predicate::str::contains("left unchanged"));
+        assert_eq!(fs::read_to_string(&schema_path).unwrap(), hand_tuned);
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("review")
+            .arg(&data_path)
+            .arg("--against")
+            .arg(&schema_path)
+            .arg("--yes")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Overwrite").not());
+        let written: serde_json::Value = serde_json::from_str(&fs::read_to_string(&schema_path).unwrap()).unwrap();
+        assert_eq!(written["properties"]["email"]["type"], "string");
+    }
+
+    #[test]
+    fn test_doctor_reports_config_and_pattern_problems() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("user.json"), "{}").unwrap();
+        fs::write(dir.path().join(".schema-jenerator.toml"), "default_tier = \"Expert\"\ncolour = true\n").unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.current_dir(dir.path())
+            .arg("doctor")
+            .arg("*.json")
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("[error] config: .schema-jenerator.toml: unknown key `colour`"))
+            .stdout(predicate::str::contains("[ok] output directory: . is writable"))
+            .stdout(predicate::str::contains("[ok] pattern: *.json matches 1 file(s)"));
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.current_dir(dir.path())
+            .arg("doctor")
+            .arg("missing/*.json")
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("[error] pattern: missing/*.json matches no files"))
+            .stderr(predicate::str::contains("Doctor found 2 problem(s)"));
+    }
+
+    #[test]
+    fn test_bench_reports_throughput_per_tier() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("corpus/nested")).unwrap();
+        fs::write(dir.path().join("corpus/a.json"), r#"{"id": 1}"#).unwrap();
+        fs::write(dir.path().join("corpus/nested/b.json"), r#"[{"name": "x"}]"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        let output = cmd
+            .arg("bench")
+            .arg(dir.path().join("corpus/**/*.json").to_str().unwrap())
+            .arg("--tier")
+            .arg("basic")
+            .arg("--tier")
+            .arg("expert")
+            .arg("--format")
+            .arg("json")
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+
+        let reports: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let reports = reports.as_array().unwrap();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0]["tier"], "Basic");
+        assert_eq!(reports[1]["tier"], "Expert");
+        assert_eq!(reports[0]["files"], 2);
+        assert_eq!(reports[0]["bytes"], 24);
+        let phases: Vec<&str> = reports[0]["phases"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["phase"].as_str().unwrap())
+            .collect();
+        assert_eq!(phases, ["read", "parse", "generate", "serialize"]);
+    }
+
+    #[test]
+    fn test_hash_is_stable_across_equivalent_schemas() {
+        let dir = tempdir().unwrap();
+        let first = dir.path().join("a.schema.json");
+        let second = dir.path().join("b.schema.json");
+        let embedded = dir.path().join("embedded.schema.json");
+        fs::write(&first, r#"{"type": "string", "minLength": 0}"#).unwrap();
+        fs::write(&second, "{\n  \"type\": \"string\"\n}").unwrap();
+
+        let hash_of = |path: &std::path::Path| {
+            let output = Command::cargo_bin("schema-jenerator").unwrap().arg("hash").arg(path).output().unwrap();
+            assert!(output.status.success());
+            String::from_utf8(output.stdout).unwrap().trim().to_string()
+        };
+
+        // sha256 of the canonical form `{\n  "type": "string"\n}\n`
+        let expected = "sha256:bf0b727f7b1c6d07c7312c6c7760fd34302744385611a86804210c77d80b0cc7";
+        assert_eq!(hash_of(&first), expected);
+        assert_eq!(hash_of(&second), expected);
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("hash")
+            .arg(&first)
+            .arg("--embed")
+            .arg("-o")
+            .arg(&embedded)
+            .assert()
+            .success();
+        let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(&embedded).unwrap()).unwrap();
+        assert_eq!(schema["x-schema-hash"], expected);
+        // An embedded hash does not change the fingerprint
+        assert_eq!(hash_of(&embedded), expected);
+    }
+
+    #[test]
+    fn test_dry_run_batch_writes_nothing() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.json"), r#"{"id": 1}"#).unwrap();
+        fs::write(dir.path().join("b.json"), r#"{"name": "x"}"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(dir.path().join("*.json"))
+            .arg("--batch")
+            .arg("--validate")
+            .arg("--dry-run")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Would write").count(2))
+            .stdout(predicate::str::contains("a.schema.json"))
+            .stdout(predicate::str::contains(r#""name":{"minLength":0,"type":"string"}"#))
+            .stdout(predicate::str::contains("Processed 2 files successfully"));
+
+        let written: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| name.contains(".schema."))
+            .collect();
+        assert!(written.is_empty(), "dry run wrote {:?}", written);
+    }
+
+    #[test]
+    fn test_output_dir_mirrors_input_structure() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("data/users/active")).unwrap();
+        fs::write(dir.path().join("data/top.json"), r#"{"id": 1}"#).unwrap();
+        fs::write(dir.path().join("data/users/active/alice.json"), r#"{"name": "alice"}"#).unwrap();
+        let out_dir = dir.path().join("schemas");
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(dir.path().join("data/**/*.json"))
+            .arg("--batch")
+            .arg("--output-dir")
+            .arg(&out_dir)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Processed 2 files successfully"));
+
+        assert!(out_dir.join("top.schema.json").exists());
+        assert!(out_dir.join("users/active/alice.schema.json").exists());
+        // Data directories are left alone
+        assert!(!dir.path().join("data/top.schema.json").exists());
+        assert!(!dir.path().join("data/users/active/alice.schema.json").exists());
+    }
+
+    #[test_case("{dir}/{stem}.{tier}.schema.json", "orders.comprehensive.schema.json" ; "tier suffix")]
+    #[test_case("{dir}/{ext}/{stem}-{draft}.json", "json/orders-draft-07.json" ; "extension and draft")]
+    fn test_output_template(template: &str, expected: &str) {
+        let dir = tempdir().unwrap();
+        let input_path = dir.path().join("orders.json");
+        fs::write(&input_path, r#"{"id": 1}"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input_path)
+            .arg("--tier")
+            .arg("comprehensive")
+            .arg("--draft")
+            .arg("draft-07")
+            .arg("--output-template")
+            .arg(template)
+            .assert()
+            .success();
+
+        let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(dir.path().join(expected)).unwrap()).unwrap();
+        assert_eq!(schema["$schema"], "http://json-schema.org/draft-07/schema#");
+    }
+
+    #[test]
+    fn test_output_template_rejects_unknown_variable() {
+        let dir = tempdir().unwrap();
+        let input_path = dir.path().join("orders.json");
+        fs::write(&input_path, r#"{"id": 1}"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input_path)
+            .arg("--output-template")
+            .arg("{dir}/{basename}.json")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Unknown output template variable {basename}"));
+    }
+
+    #[test]
+    fn test_no_clobber_and_skip_unchanged() {
+        let dir = tempdir().unwrap();
+        let input_path = dir.path().join("user.json");
+        let output_path = dir.path().join("user.schema.json");
+        fs::write(&input_path, r#"{"id": 1}"#).unwrap();
+        fs::write(&output_path, "hand-tuned").unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input_path)
+            .arg("--no-clobber")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Refusing to overwrite existing file"));
+        assert_eq!(fs::read_to_string(&output_path).unwrap(), "hand-tuned");
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input_path)
+            .arg("--no-clobber")
+            .arg("--force")
+            .assert()
+            .success();
+        let modified = fs::metadata(&output_path).unwrap().modified().unwrap();
+
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input_path)
+            .arg("--no-clobber")
+            .arg("--skip-unchanged")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Schema unchanged"));
+        assert_eq!(fs::metadata(&output_path).unwrap().modified().unwrap(), modified);
+    }
+
+    #[test]
+    fn test_batch_logs_per_file_timing_without_tty() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.json"), r#"{"id": 1}"#).unwrap();
+        fs::write(dir.path().join("b.json"), r#"{"id": 2}"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.env("RUST_LOG", "info")
+            .arg(dir.path().join("*.json"))
+            .arg("--batch")
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("[1/2]"))
+            .stderr(predicate::str::contains("[2/2]"))
+            .stderr(predicate::str::contains("processed in"))
+            // No bar escape codes when stderr is not a terminal
+            .stderr(predicate::str::contains("\x1b[K").not());
+    }
+
+    #[test_case(&[], true, 2 ; "fails by default")]
+    #[test_case(&["--keep-going"], false, 2 ; "keep going")]
+    #[test_case(&["--fail-fast"], true, 1 ; "fail fast")]
+    fn test_batch_exit_status(flags: &[&str], fails: bool, reported_errors: usize) {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.json"), "{ broken").unwrap();
+        fs::write(dir.path().join("b.json"), "also broken").unwrap();
+        fs::write(dir.path().join("c.json"), r#"{"id": 1}"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        let assert = cmd.arg(dir.path().join("*.json")).arg("--batch").args(flags).assert();
+        let assert = if fails { assert.failure() } else { assert.success() };
+        assert.stdout(predicate::str::contains("Invalid JSON").count(reported_errors));
+
+        // Files after the first failure are only processed without --fail-fast
+        assert_eq!(dir.path().join("c.schema.json").exists(), reported_errors == 2);
+    }
+
+    #[test]
+    fn test_batch_honors_schemaignore() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("data/node_modules/pkg")).unwrap();
+        fs::create_dir_all(dir.path().join("data/fixtures")).unwrap();
+        fs::write(dir.path().join("data/user.json"), r#"{"id": 1}"#).unwrap();
+        fs::write(dir.path().join("data/old.schema.json"), r#"{"type": "object"}"#).unwrap();
+        fs::write(dir.path().join("data/node_modules/pkg/package.json"), r#"{"name": "pkg"}"#).unwrap();
+        fs::write(dir.path().join("data/fixtures/broken.json"), "{ nope").unwrap();
+        fs::write(dir.path().join("data/fixtures/keep.json"), r#"{"ok": true}"#).unwrap();
+        fs::write(
+            dir.path().join("data/.schemaignore"),
+            "# generated outputs\n*.schema.json\nnode_modules/\n/fixtures/*.json\n!/fixtures/keep.json\n",
+        )
+        .unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(dir.path().join("data/**/*.json"))
+            .arg("--batch")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Processed 2 files successfully"));
+
+        assert!(dir.path().join("data/user.schema.json").exists());
+        assert!(dir.path().join("data/fixtures/keep.schema.json").exists());
+        assert!(!dir.path().join("data/old.schema.schema.json").exists());
+        assert!(!dir.path().join("data/node_modules/pkg/package.schema.json").exists());
+    }
+
+    #[test]
+    fn test_quiet_and_json_logging() {
+        let dir = tempdir().unwrap();
+        let input_path = dir.path().join("user.json");
+        fs::write(&input_path, r#"{"id": 1}"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input_path)
+            .arg("--validate")
+            .arg("-q")
+            .assert()
+            .success()
+            .stdout(predicate::str::is_empty())
+            .stderr(predicate::str::is_empty());
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        let output = cmd.arg(&input_path).arg("--log-format").arg("json").output().unwrap();
+        assert!(output.status.success());
+        assert!(output.stdout.is_empty());
+
+        let lines: Vec<serde_json::Value> = String::from_utf8(output.stderr)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let generate = lines.iter().find(|line| line["phase"] == "generate").unwrap();
+        assert_eq!(generate["result"], "ok");
+        assert!(generate["duration_ms"].is_number());
+        assert!(generate["file"].as_str().unwrap().ends_with("user.json"));
+        assert!(lines.iter().any(|line| line["message"].as_str().unwrap().contains("Schema generated successfully")));
+    }
+
+    #[test]
+    fn test_batch_summary_footer() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.json"), r#"{"id": 1}"#).unwrap();
+        fs::write(dir.path().join("b.json"), "{ broken").unwrap();
+        fs::write(dir.path().join("c.json"), r#"{"id": 2}"#).unwrap();
+        fs::write(dir.path().join(".schemaignore"), "c.json\n").unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.env_remove("NO_COLOR")
+            .arg(dir.path().join("*.json"))
+            .arg("--batch")
+            .arg("--keep-going")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Summary: 1 succeeded, 1 failed, 1 ignored in"))
+            // Output is not a terminal, so no escape codes
+            .stdout(predicate::str::contains("\x1b[").not());
+    }
+
+    #[test]
+    fn test_annotate_github_reports_invalid_json_location() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(temp_dir.path().join("good.json"), r#"{"name": "John"}"#).unwrap();
+        fs::write(temp_dir.path().join("bad.json"), "{\"name\": 1,\n  \"age\": }\n").unwrap();
+        let pattern = temp_dir.path().join("*.json");
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(pattern.to_str().unwrap())
+            .arg("--batch")
+            .arg("--annotate")
+            .arg("github")
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("::error file="))
+            .stdout(predicate::str::contains("bad.json,line=2,col=10::Invalid JSON"));
+    }
+
+    #[test_case("missing.json", None, 2 ; "input not found")]
+    #[test_case("bad.json", Some("{\"name\": }"), 3 ; "invalid json")]
+    fn test_exit_codes(file: &str, content: Option<&str>, code: i32) {
+        let temp_dir = tempdir().unwrap();
+        let input = temp_dir.path().join(file);
+        if let Some(content) = content {
+            fs::write(&input, content).unwrap();
+        }
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input).assert().code(code);
+    }
+
+    #[test]
+    fn test_invalid_arguments_exit_code() {
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("data.json").arg("--no-such-flag").assert().code(64).stderr(predicate::str::contains("--no-such-flag"));
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("--help").assert().code(0);
+    }
+
+    #[test]
+    fn test_partial_batch_failure_exit_code() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(temp_dir.path().join("good.json"), r#"{"name": "John"}"#).unwrap();
+        fs::write(temp_dir.path().join("bad.json"), "{").unwrap();
+        let pattern = temp_dir.path().join("*.json");
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(pattern.to_str().unwrap()).arg("--batch").assert().code(5);
+    }
+
+    #[test]
+    fn test_root_name_and_metadata_flags() {
+        let temp_dir = tempdir().unwrap();
+        let input = temp_dir.path().join("user.json");
+        fs::write(&input, r#"{"name": "John"}"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input)
+            .arg("--tier")
+            .arg("expert")
+            .arg("--root-name")
+            .arg("User")
+            .arg("--description")
+            .arg("User payload")
+            .arg("--output-template")
+            .arg("{dir}/{name}.schema.json")
+            .assert()
+            .success();
+
+        let schema: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(temp_dir.path().join("User.schema.json")).unwrap()).unwrap();
+        assert_eq!(schema["title"], "User");
+        assert_eq!(schema["description"], "User payload");
+        assert_eq!(schema["properties"]["name"]["title"], "Generated String Schema");
+    }
+
+    #[test_case("none", None, None ; "none")]
+    #[test_case("non-null", Some(r#"["id","items"]"#), Some(r#"["a"]"#) ; "non-null")]
+    #[test_case("all", Some(r#"["id","items","note"]"#), Some(r#"["a","b"]"#) ; "all")]
+    #[test_case("observed", Some(r#"["id","items","note"]"#), Some(r#"["a"]"#) ; "observed")]
+    fn test_required_mode(mode: &str, root: Option<&str>, items: Option<&str>) {
+        let temp_dir = tempdir().unwrap();
+        let input = temp_dir.path().join("data.json");
+        let output = temp_dir.path().join("data.schema.json");
+        fs::write(&input, r#"{"id": 1, "note": null, "items": [{"a": 1, "b": 2}, {"a": 3}]}"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input)
+            .arg("--tier")
+            .arg("basic")
+            .arg("--required-mode")
+            .arg(mode)
+            .assert()
+            .success();
+
+        let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        let parse = |s: Option<&str>| s.map(|s| serde_json::from_str::<serde_json::Value>(s).unwrap());
+        assert_eq!(schema.get("required").cloned(), parse(root));
+        assert_eq!(schema["properties"]["items"]["items"].get("required").cloned(), parse(items));
+    }
+
+    #[test]
+    fn test_additional_properties_flag() {
+        let temp_dir = tempdir().unwrap();
+        let input = temp_dir.path().join("event.json");
+        let output = temp_dir.path().join("event.schema.json");
+        let fragment = temp_dir.path().join("extension.json");
+        fs::write(&input, r#"{"id": 1, "meta": {"source": "api"}}"#).unwrap();
+        fs::write(&fragment, r#"{"type": "string"}"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input)
+            .arg("--tier")
+            .arg("comprehensive")
+            .arg("--additional-properties")
+            .arg("true")
+            .assert()
+            .success();
+        let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(schema["additionalProperties"], true);
+        assert_eq!(schema["properties"]["meta"]["additionalProperties"], true);
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input)
+            .arg("--tier")
+            .arg("basic")
+            .arg("--additional-properties")
+            .arg(&fragment)
+            .assert()
+            .success();
+        let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(schema["properties"]["meta"]["additionalProperties"], serde_json::json!({"type": "string"}));
+    }
+
+    #[test]
+    fn test_no_examples_flag_and_config() {
+        let temp_dir = tempdir().unwrap();
+        let input = temp_dir.path().join("secret.json");
+        let output = temp_dir.path().join("secret.schema.json");
+        let config = temp_dir.path().join("config.toml");
+        fs::write(&input, r#"{"token": "s3cr3t-value", "count": 7}"#).unwrap();
+        fs::write(&config, "examples = false\n").unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input).arg("--tier").arg("expert").arg("--no-examples").assert().success();
+        let content = fs::read_to_string(&output).unwrap();
+        assert!(!content.contains("examples"));
+        assert!(!content.contains("s3cr3t-value"));
+        assert!(content.contains("maxLength"));
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input).arg("--tier").arg("expert").arg("--config").arg(&config).assert().success();
+        assert!(!fs::read_to_string(&output).unwrap().contains("s3cr3t-value"));
+    }
+
+    #[test]
+    fn test_long_examples_are_truncated_and_limited() {
+        let temp_dir = tempdir().unwrap();
+        let input = temp_dir.path().join("blob.json");
+        let output = temp_dir.path().join("blob.schema.json");
+        let long_text = "x".repeat(500);
+        fs::write(&input, format!(r#"{{"body": "{}", "id": 5}}"#, long_text)).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input).arg("--tier").arg("comprehensive").assert().success();
+        let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        let example = schema["properties"]["body"]["examples"][0].as_str().unwrap();
+        assert_eq!(example, format!("{}…", "x".repeat(100)));
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input)
+            .arg("--tier")
+            .arg("comprehensive")
+            .arg("--examples-limit")
+            .arg("0")
+            .assert()
+            .success();
+        let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        assert!(schema["properties"]["id"].get("examples").is_none());
+    }
+
+    #[test]
+    fn test_infer_and_no_infer_toggles() {
+        let temp_dir = tempdir().unwrap();
+        let input = temp_dir.path().join("tickets.json");
+        let output = temp_dir.path().join("tickets.schema.json");
+        fs::write(&input, r#"{"status": ["open", "closed", "open"], "contact": "ops@example.com", "sizes": [3, 9, 4]}"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input)
+            .arg("--tier")
+            .arg("basic")
+            .arg("--infer")
+            .arg("formats,enums,bounds")
+            .assert()
+            .success();
+        let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(schema["properties"]["contact"]["format"], "email");
+        assert_eq!(schema["properties"]["status"]["items"]["enum"], serde_json::json!(["closed", "open"]));
+        assert_eq!(schema["properties"]["sizes"]["items"]["minimum"], 3);
+        assert_eq!(schema["properties"]["sizes"]["items"]["maximum"], 9);
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input)
+            .arg("--tier")
+            .arg("expert")
+            .arg("--no-infer")
+            .arg("formats,bounds")
+            .assert()
+            .success();
+        let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        assert!(schema["properties"]["contact"].get("format").is_none());
+        assert!(schema["properties"]["contact"].get("maxLength").is_none());
+        assert!(schema["properties"]["contact"].get("examples").is_some());
+    }
+
+    #[test]
+    fn test_max_depth_truncates_nested_schemas() {
+        let temp_dir = tempdir().unwrap();
+        let input = temp_dir.path().join("nested.json");
+        let output = temp_dir.path().join("nested.schema.json");
+        fs::write(&input, r#"{"a": {"b": {"c": {"d": 1}}, "list": [[1, 2]]}, "x": 1}"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input).arg("--max-depth").arg("2").assert().success();
+
+        let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        let a = &schema["properties"]["a"]["properties"];
+        assert_eq!(a["b"], serde_json::json!({"type": "object", "x-truncated": true}));
+        assert_eq!(a["list"], serde_json::json!({"type": "array", "x-truncated": true}));
+        assert_eq!(schema["properties"]["x"]["type"], "integer");
+    }
+
+    #[test]
+    fn test_array_sample_limits_inferred_elements() {
+        let temp_dir = tempdir().unwrap();
+        let input = temp_dir.path().join("events.json");
+        let output = temp_dir.path().join("events.schema.json");
+        let events: Vec<serde_json::Value> = (0..1000)
+            .map(|i| if i % 2 == 0 { serde_json::json!(i) } else { serde_json::json!(format!("event-{}", i)) })
+            .collect();
+        fs::write(&input, serde_json::json!({ "events": events }).to_string()).unwrap();
+
+        let run = || {
+            let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+            cmd.arg(&input).arg("--array-sample").arg("10").assert().success();
+            fs::read_to_string(&output).unwrap()
+        };
+        let first = run();
+        let schema: serde_json::Value = serde_json::from_str(&first).unwrap();
+        assert_eq!(schema["properties"]["events"]["items"]["oneOf"].as_array().unwrap().len(), 10);
+        assert_eq!(run(), first);
+    }
+
+    #[test]
+    fn test_pointer_selects_subtree() {
+        let temp_dir = tempdir().unwrap();
+        let input = temp_dir.path().join("envelope.json");
+        let output = temp_dir.path().join("envelope.schema.json");
+        fs::write(&input, r#"{"status": "ok", "data": {"items": [{"sku": "A1", "qty": 2}]}}"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input).arg("--pointer").arg("/data/items/0").assert().success();
+        let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        let properties: Vec<&String> = schema["properties"].as_object().unwrap().keys().collect();
+        assert_eq!(properties, ["qty", "sku"]);
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input)
+            .arg("--pointer")
+            .arg("/data/missing")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("JSON pointer \"/data/missing\" does not exist"));
+    }
+
+    #[test]
+    fn test_include_and_exclude_path_filters() {
+        let temp_dir = tempdir().unwrap();
+        let input = temp_dir.path().join("order.json");
+        let output = temp_dir.path().join("order.schema.json");
+        fs::write(&input, r#"{"metadata": {"rev": 1}, "data": {"total": 5, "items": [{"id": 1, "internal": 2}]}, "status": "ok"}"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input)
+            .arg("--exclude")
+            .arg("/metadata/**")
+            .arg("--exclude")
+            .arg("/data/items/*/internal")
+            .assert()
+            .success();
+        let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        assert!(schema["properties"].get("metadata").is_none());
+        assert!(schema["properties"]["status"].is_object());
+        let item = &schema["properties"]["data"]["properties"]["items"]["items"]["properties"];
+        assert!(item.get("internal").is_none());
+        assert!(item["id"].is_object());
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input).arg("--include").arg("/data/**").assert().success();
+        let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        let properties: Vec<&String> = schema["properties"].as_object().unwrap().keys().collect();
+        assert_eq!(properties, ["data"]);
+    }
+
+    #[test_case("as-is", serde_json::json!({"type": "null"}) ; "as is")]
+    #[test_case("union", serde_json::json!({"type": ["integer", "null"]}) ; "union")]
+    #[test_case("anyOf", serde_json::json!({"anyOf": [{"type": "integer"}, {"type": "null"}]}) ; "any of")]
+    #[test_case("omit-required", serde_json::json!({"type": "integer"}) ; "omit required")]
+    fn test_null_policy(policy: &str, expected: serde_json::Value) {
+        let temp_dir = tempdir().unwrap();
+        let input = temp_dir.path().join("rows.json");
+        let output = temp_dir.path().join("rows.schema.json");
+        fs::write(&input, r#"{"rows": [{"v": null}, {"v": 3}], "note": null}"#).unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input)
+            .arg("--tier")
+            .arg("basic")
+            .arg("--null-policy")
+            .arg(policy)
+            .assert()
+            .success();
+
+        let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(schema["properties"]["rows"]["items"]["properties"]["v"], expected);
+        if policy != "as-is" {
+            assert_eq!(schema["properties"]["note"], serde_json::json!({}));
+        }
+    }
+
+    #[test]
+    fn test_files_from_stdin() {
+        let temp_dir = tempdir().unwrap();
+        let first = temp_dir.path().join("first.json");
+        let second = temp_dir.path().join("second.json");
+        let unlisted = temp_dir.path().join("unlisted.json");
+        for path in [&first, &second, &unlisted] {
+            fs::write(path, r#"{"id": 1}"#).unwrap();
+        }
+        let list = format!(
+            "{}\n{}\n\n{}\n",
+            first.display(),
+            temp_dir.path().join("deleted.json").display(),
+            second.display()
+        );
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("--files-from")
+            .arg("-")
+            .write_stdin(list)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Processed 2 files successfully"));
+
+        assert!(temp_dir.path().join("first.schema.json").exists());
+        assert!(temp_dir.path().join("second.schema.json").exists());
+        assert!(!temp_dir.path().join("unlisted.schema.json").exists());
+    }
+
+    // Answers one request per (status, body) pair and hands back the raw request texts
+    fn serve(responses: &'static [(u16, &'static str)]) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        use std::io::{Read, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/users", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let response = format!(
+                    "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nETag: \"v1\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+                requests.push(String::from_utf8_lossy(&request).to_lowercase());
+            }
+            requests
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn test_url_input_with_auth_headers() {
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir.path().join("users.schema.json");
+        let (url, server) = serve(&[(200, r#"{"id": 1, "name": "Ada"}"#)]);
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&url)
+            .arg("--no-cache")
+            .arg("-o")
+            .arg(&output)
+            .arg("--header")
+            .arg("X-Api-Key: k123")
+            .arg("--token-env")
+            .arg("SCHEMA_TEST_TOKEN")
+            .env("SCHEMA_TEST_TOKEN", "secret-token")
+            .assert()
+            .success();
+
+        let request = &server.join().unwrap()[0];
+        assert!(request.contains("x-api-key: k123"));
+        assert!(request.contains("authorization: bearer secret-token"));
+        let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(schema["properties"]["name"]["type"], "string");
+    }
+
+    #[test]
+    fn test_https_url_input_is_fetched_over_tls() {
+        // Nothing speaks TLS here, so the fetch fails in the handshake rather than on the scheme
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("https://{}/users", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || drop(listener.accept().unwrap()));
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&url)
+            .arg("--no-cache")
+            .arg("--retries")
+            .arg("0")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Remote fetch failed"))
+            .stderr(predicate::str::contains("scheme is not http").not());
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_url_input_retries_transient_failures() {
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir.path().join("users.schema.json");
+        let (url, server) = serve(&[(503, "busy"), (429, "slow down"), (200, r#"{"id": 1}"#)]);
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&url)
+            .arg("--no-cache")
+            .arg("-o")
+            .arg(&output)
+            .arg("--retries")
+            .arg("2")
+            .arg("--retry-delay")
+            .arg("10")
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("HTTP 503 Service Unavailable; retrying"));
+
+        assert_eq!(server.join().unwrap().len(), 3);
+        assert!(output.exists());
+
+        let (url, server) = serve(&[(503, "busy"), (503, "still busy")]);
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&url)
+            .arg("--no-cache")
+            .arg("-o")
+            .arg(&output)
+            .arg("--retries")
+            .arg("1")
+            .arg("--retry-delay")
+            .arg("10")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("HTTP 503"));
+        server.join().unwrap();
+
+        // A request that cannot be built fails at once
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("http://exa mple.com/users")
+            .arg("--no-cache")
+            .arg("--retries")
+            .arg("2")
+            .arg("--retry-delay")
+            .arg("10")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("builder error"))
+            .stderr(predicate::str::contains("retrying").not());
+    }
+
+    #[test]
+    fn test_url_input_through_proxy() {
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir.path().join("users.schema.json");
+
+        let (proxy, server) = serve(&[(200, r#"{"id": 1}"#)]);
+        let proxy = proxy.trim_end_matches("/users").to_string();
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("http://api.example.invalid/users")
+            .arg("--no-cache")
+            .arg("-o")
+            .arg(&output)
+            .arg("--proxy")
+            .arg(&proxy)
+            .env_remove("NO_PROXY")
+            .assert()
+            .success();
+        assert!(server.join().unwrap()[0].starts_with("get http://api.example.invalid/users"));
+
+        let (proxy, server) = serve(&[(200, r#"{"id": 1}"#)]);
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg("http://api.example.invalid/users")
+            .arg("--no-cache")
+            .arg("-o")
+            .arg(&output)
+            .env("HTTP_PROXY", proxy.trim_end_matches("/users"))
+            .env_remove("NO_PROXY")
+            .assert()
+            .success();
+        assert!(server.join().unwrap()[0].starts_with("get http://api.example.invalid/users"));
+    }
+
+    #[test]
+    fn test_url_input_cache_and_revalidation() {
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir.path().join("users.schema.json");
+        let cache_dir = temp_dir.path().join("cache");
+        let (url, server) = serve(&[(200, r#"{"id": 1}"#), (304, ""), (200, r#"{"name": "Ada"}"#)]);
+
+        let run = |extra: &[&str]| {
+            let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+            cmd.arg(&url)
+                .arg("-o")
+                .arg(&output)
+                .arg("--cache-dir")
+                .arg(&cache_dir)
+                .args(extra)
+                .assert()
+                .success();
+            let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+            schema["properties"].as_object().unwrap().keys().cloned().collect::<Vec<_>>()
+        };
+
+        assert_eq!(run(&[]), ["id"]);
+        // Fresh entries are served without a request
+        assert_eq!(run(&[]), ["id"]);
+        // Expired entries are revalidated with their ETag
+        assert_eq!(run(&["--cache-ttl", "0"]), ["id"]);
+        assert_eq!(run(&["--refresh"]), ["name"]);
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert!(!requests[0].contains("if-none-match"));
+        assert!(requests[1].contains("if-none-match: \"v1\""));
+        assert!(!requests[2].contains("if-none-match"));
+    }
+
+    #[test]
+    fn test_config_discovered_from_input_directory_upwards() {
+        let temp_dir = tempdir().unwrap();
+        let outer = temp_dir.path().join("a");
+        let inner = outer.join("b");
+        fs::create_dir_all(&inner).unwrap();
+        fs::write(temp_dir.path().join(".schema-jenerator.toml"), "examples = false\n").unwrap();
+        fs::write(inner.join("schema-jenerator.json"), r#"{"examples": true}"#).unwrap();
+        fs::write(outer.join("data.json"), r#"{"token": "outer-value"}"#).unwrap();
+        fs::write(inner.join("data.json"), r#"{"token": "inner-value"}"#).unwrap();
+
+        for dir in [&outer, &inner] {
+            let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+            cmd.arg(dir.join("data.json")).arg("--tier").arg("expert").assert().success();
+        }
+
+        assert!(!fs::read_to_string(outer.join("data.schema.json")).unwrap().contains("outer-value"));
+        assert!(fs::read_to_string(inner.join("data.schema.json")).unwrap().contains("inner-value"));
+    }
+
+    #[test]
+    fn test_config_field_overrides() {
+        let temp_dir = tempdir().unwrap();
+        let input = temp_dir.path().join("data.json");
+        let output = temp_dir.path().join("data.schema.json");
+        fs::write(
+            &input,
+            r#"{"user": {"id": 5, "created_at": "2020-01-01"}, "items": [{"sku": "a", "updated_at": "x"}]}"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join(".schema-jenerator.toml"),
+            r#"
+[fields."/user/id"]
+type = "string"
+format = "uuid"
+description = "Account id"
+
+[fields."*_at"]
+format = "date-time"
+required = false
+
+[fields."/items/*/sku"]
+title = "StockKeepingUnit"
+"#,
+        )
+        .unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input).assert().success();
+        let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+
+        let user = &schema["properties"]["user"];
+        assert_eq!(
+            user["properties"]["id"],
+            serde_json::json!({"type": "string", "format": "uuid", "description": "Account id"})
+        );
+        assert_eq!(user["properties"]["created_at"]["format"], "date-time");
+        assert_eq!(user["required"], serde_json::json!(["id"]));
+
+        let item = &schema["properties"]["items"]["items"];
+        assert_eq!(item["properties"]["sku"]["title"], "StockKeepingUnit");
+        assert_eq!(item["properties"]["updated_at"]["format"], "date-time");
+        assert_eq!(item["required"], serde_json::json!(["sku"]));
+    }
+
+    #[test]
+    fn test_config_exclude_and_redact_rules() {
+        let temp_dir = tempdir().unwrap();
+        let input = temp_dir.path().join("data.json");
+        let output = temp_dir.path().join("data.schema.json");
+        fs::write(
+            &input,
+            r#"{"debug": {"trace": 1}, "user": {"ssn": "123-45-6789", "internal_id": 9, "role": "admin"}}"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("schema-jenerator.json"),
+            r#"{"exclude": ["/debug", "**/internal_*"], "redact": ["/user/ssn"]}"#,
+        )
+        .unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input).arg("--tier").arg("expert").arg("--infer").arg("enums").assert().success();
+        let content = fs::read_to_string(&output).unwrap();
+        let schema: serde_json::Value = serde_json::from_str(&content).unwrap();
+
+        assert!(schema["properties"].get("debug").is_none());
+        let user = &schema["properties"]["user"]["properties"];
+        assert!(user.get("internal_id").is_none());
+        assert_eq!(user["ssn"]["type"], "string");
+        assert!(!content.contains("123-45-6789"));
+        assert!(content.contains("admin"));
+    }
+
+    #[test]
+    fn test_config_profiles() {
+        let temp_dir = tempdir().unwrap();
+        let input = temp_dir.path().join("data.json");
+        let output = temp_dir.path().join("data.schema.json");
+        fs::write(&input, r#"{"name": "Ada"}"#).unwrap();
+        fs::write(
+            temp_dir.path().join(".schema-jenerator.toml"),
+            r#"
+default_tier = "Basic"
+
+[profile.docs]
+default_tier = "Expert"
+draft = "draft-07"
+no_infer = ["examples"]
+"#,
+        )
+        .unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input).assert().success();
+        let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        assert!(schema.get("title").is_none());
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input).arg("--profile").arg("docs").assert().success();
+        let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(schema["$schema"], "http://json-schema.org/draft-07/schema#");
+        assert!(schema.get("title").is_some());
+        assert!(schema["properties"]["name"].get("examples").is_none());
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input)
+            .arg("--profile")
+            .arg("ci")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Profile \"ci\" is not defined"));
+
+        // A config named on the command line must exist rather than fall back to defaults
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input)
+            .arg("--config")
+            .arg(temp_dir.path().join("missing.toml"))
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Config file"))
+            .stderr(predicate::str::contains("missing.toml"));
+    }
+
+    #[test]
+    fn test_config_rules_scoped_to_path_globs() {
+        let temp_dir = tempdir().unwrap();
+        let events = temp_dir.path().join("events").join("ui");
+        fs::create_dir_all(&events).unwrap();
+        fs::write(temp_dir.path().join("users.json"), r#"{"name": "Ada"}"#).unwrap();
+        fs::write(events.join("click.json"), r#"{"x": 1}"#).unwrap();
+        fs::write(
+            temp_dir.path().join(".schema-jenerator.toml"),
+            r#"
+default_tier = "Basic"
+
+[[rules]]
+pattern = "events/**"
+tier = "expert"
+draft = "draft-07"
+"#,
+        )
+        .unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(format!("{}/**/*.json", temp_dir.path().display())).arg("--batch").assert().success();
+
+        let users: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(temp_dir.path().join("users.schema.json")).unwrap()).unwrap();
+        assert!(users.get("$schema").is_none());
+        assert!(users.get("title").is_none());
+
+        let click: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(events.join("click.schema.json")).unwrap()).unwrap();
+        assert_eq!(click["$schema"], "http://json-schema.org/draft-07/schema#");
+        assert!(click.get("title").is_some());
+    }
+
+    #[test]
+    fn test_custom_tier_from_config() {
+        let temp_dir = tempdir().unwrap();
+        let input = temp_dir.path().join("data.json");
+        let output = temp_dir.path().join("data.schema.json");
+        fs::write(&input, r#"[{"id": 1, "tags": ["a", "b"]}, {"id": 2}]"#).unwrap();
+        fs::write(
+            temp_dir.path().join(".schema-jenerator.toml"),
+            r#"
+[tiers.api]
+base = "comprehensive"
+required = "observed"
+additional_properties = false
+no_infer = ["bounds"]
+examples = false
+"#,
+        )
+        .unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input).arg("--tier").arg("api").assert().success();
+        let content = fs::read_to_string(&output).unwrap();
+        let schema: serde_json::Value = serde_json::from_str(&content).unwrap();
+
+        let item = &schema["items"];
+        assert_eq!(item["required"], serde_json::json!(["id"]));
+        assert_eq!(item["additionalProperties"], false);
+        assert!(!content.contains("maxItems"));
+        assert!(!content.contains("examples"));
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input).args(["--tier", "api", "--provenance"]).assert().success();
+        let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(schema["x-generated-by"]["options"]["tier"], "api");
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input)
+            .arg("--tier")
+            .arg("internal")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Unknown tier \"internal\""));
+
+        // The config can select its custom tier as the default
+        let config = temp_dir.path().join(".schema-jenerator.toml");
+        let tiers = fs::read_to_string(&config).unwrap();
+        fs::write(&config, format!("default_tier = \"api\"\n{}", tiers)).unwrap();
+        fs::remove_file(&output).unwrap();
+        Command::cargo_bin("schema-jenerator").unwrap().arg(&input).assert().success();
+        assert_eq!(fs::read_to_string(&output).unwrap(), content);
+        Command::cargo_bin("schema-jenerator").unwrap().arg(&input).arg("--provenance").assert().success();
+        let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(schema["x-generated-by"]["options"]["tier"], "api");
+
+        fs::write(&config, format!("default_tier = \"internal\"\n{}", tiers)).unwrap();
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .arg(&input)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("default_tier: \"internal\" is not one of basic, standard, comprehensive, expert or a config tier: [\"api\"]"));
+    }
+
+    #[test]
+    fn test_config_schema_and_validation() {
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        let output = cmd.arg("config").arg("schema").assert().success().get_output().stdout.clone();
+        let schema: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(schema["additionalProperties"], false);
+        assert!(schema["properties"]["default_tier"]["description"].is_string());
+
+        let temp_dir = tempdir().unwrap();
+        let input = temp_dir.path().join("data.json");
+        let config = temp_dir.path().join(".schema-jenerator.toml");
+        fs::write(&input, r#"{"a": 1}"#).unwrap();
+
+        fs::write(&config, "colour = true\n").unwrap();
+        let error = schema_jenerator::config::Config::load_from_file(&config).unwrap_err();
+        assert!(error.to_string().contains("unknown key `colour`"));
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("unknown key `colour`"))
+            .stderr(predicate::str::contains("did you mean").not());
+
+        fs::write(&config, "defualt_tier = \"expert\"\n").unwrap();
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("unknown key `defualt_tier`, did you mean `default_tier`?"));
+
+        fs::write(&config, "examples = \"no\"\ndefault_tier = \"Hyper\"\n[tiers.api]\nbse = \"basic\"\n").unwrap();
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("examples: \"no\" is not of type \"boolean\""))
+            .stderr(predicate::str::contains("default_tier: \"Hyper\" is not one of"))
+            .stderr(predicate::str::contains("tiers.api: unknown key `bse`"));
+    }
+
+    #[test]
+    fn test_config_settings_precedence() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(temp_dir.path().join("data.json"), r#"{"name": "Ada"}"#).unwrap();
+        fs::write(
+            temp_dir.path().join(".schema-jenerator.toml"),
+            "default_tier = \"Basic\"\npretty_output = true\nvalidate_schema = true\noutput_directory = \"out\"\n",
+        )
+        .unwrap();
+        let output = temp_dir.path().join("out").join("data.schema.json");
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.current_dir(temp_dir.path())
+            .arg("data.json")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Schema validation passed"));
+        let content = fs::read_to_string(&output).unwrap();
+        assert!(content.contains('\n'));
+        assert!(!content.contains("title"));
+
+        // Environment variables override the config file
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.current_dir(temp_dir.path())
+            .arg("data.json")
+            .env("SCHEMA_JENERATOR_DEFAULT_TIER", "expert")
+            .assert()
+            .success();
+        assert!(fs::read_to_string(&output).unwrap().contains("title"));
+
+        // Flags override both
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.current_dir(temp_dir.path())
+            .arg("data.json")
+            .arg("--tier")
+            .arg("basic")
+            .arg("--output-dir")
+            .arg("flag")
+            .env("SCHEMA_JENERATOR_DEFAULT_TIER", "expert")
+            .assert()
+            .success();
+        let content = fs::read_to_string(temp_dir.path().join("flag").join("data.schema.json")).unwrap();
+        assert!(!content.contains("title"));
+    }
+
+    #[test]
+    fn test_batch_directory_uses_config_file_extensions() {
+        let temp_dir = tempdir().unwrap();
+        let data = temp_dir.path().join("data");
+        fs::create_dir_all(data.join("nested")).unwrap();
+        fs::write(data.join("a.json"), r#"{"a": 1}"#).unwrap();
+        fs::write(data.join("nested").join("b.sample"), r#"{"b": 2}"#).unwrap();
+        fs::write(data.join("notes.txt"), "not json").unwrap();
+        fs::write(
+            temp_dir.path().join("schema-jenerator.json"),
+            r#"{"file_extensions": ["json", "sample"]}"#,
+        )
+        .unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&data).arg("--batch").assert().success();
+        assert!(data.join("a.schema.json").exists());
+        assert!(data.join("nested").join("b.schema.json").exists());
+        assert!(!data.join("notes.schema.json").exists());
+
+        // Schemas from the first run are not picked up as inputs
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&data).arg("--batch").assert().success();
+        assert!(!data.join("a.schema.schema.json").exists());
+    }
+
+    #[test]
+    fn test_config_constraint_policies() {
+        let temp_dir = tempdir().unwrap();
+        let input = temp_dir.path().join("data.json");
+        let output = temp_dir.path().join("data.schema.json");
+        let config = temp_dir.path().join(".schema-jenerator.toml");
+        fs::write(&input, r#"{"names": ["Ada", "Grace Hopper"], "age": 36}"#).unwrap();
+        fs::write(
+            &config,
+            r#"
+[constraints.string]
+maxLength = { padding = 20 }
+
+[constraints.integer]
+minimum = "omit"
+maximum = "omit"
+multipleOf = "omit"
+
+[constraints.array]
+minItems = "observed"
+maxItems = "omit"
+"#,
+        )
+        .unwrap();
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input).arg("--tier").arg("expert").assert().success();
+        let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+
+        let names = &schema["properties"]["names"];
+        assert_eq!(names["items"]["maxLength"], 15);
+        assert_eq!(names["minItems"], 2);
+        assert!(names.get("maxItems").is_none());
+        let age = &schema["properties"]["age"];
+        assert!(age.get("minimum").is_none() && age.get("maximum").is_none() && age.get("multipleOf").is_none());
+
+        fs::write(&config, "[constraints.string]\nmaxItems = \"omit\"\n").unwrap();
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("constraints.string: unknown key `maxItems`"));
+    }
+
+    #[test]
+    fn test_schema_generator_builder() {
+        use schema_jenerator::generator::ExamplePolicy;
+        use schema_jenerator::required::RequiredMode;
+        use schema_jenerator::schema::SchemaDraft;
+        use schema_jenerator::{SchemaGenerator, SchemaOutputTier};
+
+        let generator = SchemaGenerator::builder()
+            .tier(SchemaOutputTier::Expert)
+            .draft(SchemaDraft::Draft07)
+            .required(RequiredMode::NonNull)
+            .additional_properties(serde_json::Value::Bool(true))
+            .examples(ExamplePolicy::Omit)
+            .build();
+        let schema = generator
+            .generate(&serde_json::json!({ "name": "Ada", "nickname": null }))
+            .unwrap();
+
+        assert_eq!(schema["$schema"], "http://json-schema.org/draft-07/schema#");
+        assert_eq!(schema["required"], serde_json::json!(["name"]));
+        assert_eq!(schema["additionalProperties"], true);
+        assert!(schema["properties"]["name"].get("examples").is_none());
+        assert_eq!(schema["properties"]["name"]["maxLength"], 6);
+    }
+
+    #[test]
+    fn test_custom_tier_policy() {
+        use schema_jenerator::schema::{Schema, TierPolicy};
+        use schema_jenerator::SchemaGenerator;
+        use serde_json::Value;
+
+        /// Requires every property and bounds strings by their observed length
+        #[derive(Debug)]
+        struct ExactStrings;
+
+        impl TierPolicy for ExactStrings {
+            fn is_required(&self, _name: &str, _value: &Value) -> bool {
+                true
+            }
+
+            fn string(&self, schema: &mut Schema, s: &str) {
+                schema.min_length = Some(s.chars().count() as u64);
+                schema.max_length = Some(s.chars().count() as u64);
+            }
+        }
+
+        let generator = SchemaGenerator::builder().policy(ExactStrings).build();
+        let schema = generator
+            .generate(&serde_json::json!({ "code": "AB12", "tags": ["x"], "count": 3 }))
+            .unwrap();
+
+        assert_eq!(schema["required"], serde_json::json!(["code", "count", "tags"]));
+        assert_eq!(schema["properties"]["code"]["minLength"], 4);
+        assert_eq!(schema["properties"]["code"]["maxLength"], 4);
+        assert_eq!(schema["properties"]["tags"]["items"]["maxLength"], 1);
+        assert_eq!(schema["properties"]["count"], serde_json::json!({ "type": "integer" }));
+        assert!(schema.get("additionalProperties").is_none());
+    }
+
+    #[test]
+    fn test_typed_schema_model() {
+        use schema_jenerator::schema::model::{InstanceType, Items, SchemaOrBool, TypeSet};
+        use schema_jenerator::schema::Schema;
+        use schema_jenerator::{SchemaGenerator, SchemaOutputTier};
+
+        let generator = SchemaGenerator::builder().tier(SchemaOutputTier::Expert).build();
+        let schema = generator
+            .generate_typed(&serde_json::json!({ "email": "ada@example.com", "scores": [3, 4] }))
+            .unwrap();
+
+        let properties = schema.properties.as_ref().unwrap();
+        assert_eq!(properties["email"].format.as_deref(), Some("email"));
+        assert_eq!(properties["email"].max_length, Some(30));
+        let scores = &properties["scores"];
+        assert_eq!(scores.max_items, Some(4));
+        let Some(Items::Single(SchemaOrBool::Schema(items))) = &scores.items else { panic!("{:?}", scores.items) };
+        assert_eq!(items.kind, Some(TypeSet::Single(InstanceType::Integer)));
+        assert_eq!(schema.required, Some(vec!["email".to_string(), "scores".to_string()]));
+
+        let tuple = Schema::try_from(serde_json::json!({ "items": [{ "type": "integer" }, true] })).unwrap();
+        assert!(matches!(&tuple.items, Some(Items::Tuple(items)) if items.len() == 2));
+
+        let raw = serde_json::json!({
+            "type": ["string", "null"],
+            "const": null,
+            "$comment": "kept",
+            "x-origin": { "file": "a.json" },
+            "additionalProperties": { "type": "number", "minimum": 1.5 }
+        });
+        let parsed = Schema::try_from(raw.clone()).unwrap();
+        assert_eq!(parsed.instance_type(), Some(InstanceType::String));
+        assert_eq!(parsed.constant, Some(serde_json::Value::Null));
+        assert_eq!(parsed.extra["x-origin"]["file"], "a.json");
+        assert_eq!(serde_json::Value::from(parsed), raw);
+    }
+
+    #[test]
+    fn test_schema_walk_and_transform() {
+        use schema_jenerator::schema::SchemaWalk;
+        use schema_jenerator::{SchemaGenerator, SchemaOutputTier};
+
+        let generator = SchemaGenerator::builder().tier(SchemaOutputTier::Expert).build();
+        let mut schema = generator
+            .generate(&serde_json::json!({ "user": { "name": "Ada" }, "tags": ["a/b"] }))
+            .unwrap();
+
+        let mut pointers = Vec::new();
+        schema.walk(|pointer, _| pointers.push(pointer.to_string()));
+        assert_eq!(
+            pointers,
+            [
+                "",
+                "/additionalProperties",
+                "/properties/tags",
+                "/properties/tags/items",
+                "/properties/user",
+                "/properties/user/additionalProperties",
+                "/properties/user/properties/name",
+            ]
+        );
+
+        schema.transform(|pointer, node| {
+            let Some(obj) = node.as_object_mut() else { return };
+            obj.remove("title");
+            if pointer.ends_with("/name") {
+                obj.insert("x-pii".to_string(), serde_json::Value::Bool(true));
+            }
+        });
+
+        let mut titles = 0;
+        schema.walk(|_, node| titles += node.get("title").is_some() as usize);
+        assert_eq!(titles, 0);
+        assert_eq!(schema["properties"]["user"]["properties"]["name"]["x-pii"], true);
+    }
+
+    #[test]
+    fn test_generator_node_hooks() {
+        use schema_jenerator::schema::SchemaDraft;
+        use schema_jenerator::{SchemaGenerator, SchemaOutputTier};
+
+        let generator = SchemaGenerator::builder()
+            .tier(SchemaOutputTier::Standard)
+            .draft(SchemaDraft::Draft07)
+            .hook(|context, node| {
+                if context.pointer.ends_with("/properties/email") {
+                    node.insert("x-classification".to_string(), "pii".into());
+                }
+            })
+            .hook(|context, node| {
+                let distinct: std::collections::BTreeSet<String> =
+                    context.samples.iter().map(|s| s.to_string()).collect();
+                if node.get("type") == Some(&"string".into()) && distinct.len() == 1 {
+                    node.insert("x-single-value".to_string(), true.into());
+                }
+            })
+            .build();
+        let schema = generator
+            .generate(&serde_json::json!({
+                "users": [
+                    { "email": "a@example.com", "role": "admin" },
+                    { "email": "b@example.com", "role": "admin" }
+                ]
+            }))
+            .unwrap();
+
+        let user = &schema["properties"]["users"]["items"]["properties"];
+        assert_eq!(user["email"]["x-classification"], "pii");
+        assert!(user["email"].get("x-single-value").is_none());
+        assert_eq!(user["role"]["x-single-value"], true);
+        assert!(user["role"].get("x-classification").is_none());
+    }
+
+    #[test]
+    fn test_pipeline_library_api() {
+        use schema_jenerator::pipeline::{FileOutcome, Pipeline, ProcessOptions, ProcessRequest, Silent};
+        use schema_jenerator::tiers::TierChoice;
+        use schema_jenerator::SchemaOutputTier;
+
+        let dir = tempdir().unwrap();
+        let data = dir.path().join("data");
+        fs::create_dir_all(data.join("nested")).unwrap();
+        fs::write(data.join("user.json"), r#"{"id": 1}"#).unwrap();
+        fs::write(data.join("nested/order.json"), r#"{"total": 9.5}"#).unwrap();
+        fs::write(data.join("broken.json"), "{").unwrap();
+
+        let pipeline = Pipeline::new(ProcessOptions {
+            tier: Some(TierChoice::Builtin(SchemaOutputTier::Basic)),
+            output_dir: Some(dir.path().join("schemas")),
+            dry_run: true,
+            ..ProcessOptions::default()
+        });
+
+        let report = pipeline.process(&ProcessRequest::new(data.join("user.json"))).unwrap();
+        assert_eq!(report.schema["properties"]["id"], serde_json::json!({ "type": "integer" }));
+        assert_eq!(report.output.outcome, FileOutcome::DryRun);
+        assert_eq!(report.output.path, dir.path().join("schemas/user.schema.json"));
+
+        let inputs = pipeline.glob_inputs(&data).unwrap();
+        let batch = pipeline.process_batch(inputs, &mut Silent);
+        assert_eq!(batch.total, 3);
+        assert_eq!(batch.errors.len(), 1);
+        assert!(batch.errors[0].contains("broken.json"));
+        let mut outputs: Vec<_> = batch.reports.iter().map(|r| r.output.path.clone()).collect();
+        outputs.sort();
+        assert_eq!(
+            outputs,
+            [dir.path().join("schemas/nested/order.schema.json"), dir.path().join("schemas/user.schema.json")]
+        );
+        assert!(!dir.path().join("schemas").exists());
+    }
+
+    #[test]
+    fn test_streaming_generation_matches_generate() {
+        use schema_jenerator::schema::{generate_schema, stream_schema_from_reader};
+        use schema_jenerator::SchemaOutputTier;
+
+        let document = serde_json::json!({
+            "users": [
+                { "id": 1, "email": "ada@example.com", "tags": ["a", "b"], "score": 9.5, "note": null },
+                { "id": 2, "email": "bob@example.com", "tags": [], "score": 7, "active": true }
+            ],
+            "total": -2,
+            "big": 18446744073709551615u64
+        });
+        let text = serde_json::to_string(&document).unwrap();
+
+        for tier in [
+            SchemaOutputTier::Basic,
+            SchemaOutputTier::Standard,
+            SchemaOutputTier::Comprehensive,
+            SchemaOutputTier::Expert,
+        ] {
+            let streamed = stream_schema_from_reader(text.as_bytes(), &tier).unwrap();
+            assert_eq!(serde_json::Value::from(streamed), generate_schema(&document, &tier).unwrap(), "{:?}", tier);
+        }
+
+        let mixed = stream_schema_from_reader(r#"[1, "a", 2, {"k": 1}, "b"]"#.as_bytes(), &SchemaOutputTier::Expert)
+            .unwrap();
+        let mixed = serde_json::Value::from(mixed);
+        let branches = mixed["items"]["oneOf"].as_array().unwrap();
+        let types: Vec<_> = branches.iter().map(|b| b["type"].as_str().unwrap()).collect();
+        assert_eq!(types, ["integer", "string", "object"]);
+        assert_eq!(mixed["maxItems"], 10);
+
+        let error = stream_schema_from_reader("[1, 2".as_bytes(), &SchemaOutputTier::Basic).unwrap_err();
+        assert!(error.to_string().contains("Invalid JSON"), "{}", error);
+    }
+
+    #[test]
+    fn test_incremental_schema_builder() {
+        use schema_jenerator::generator::ExamplePolicy;
+        use schema_jenerator::schema::SchemaDraft;
+        use schema_jenerator::{SchemaBuilder, SchemaGenerator, SchemaOutputTier};
+
+        let generator = SchemaGenerator::builder()
+            .tier(SchemaOutputTier::Comprehensive)
+            .draft(SchemaDraft::Draft07)
+            .examples(ExamplePolicy::Limit(2))
+            .build();
+        let mut builder = SchemaBuilder::from(generator);
+        assert!(builder.finalize().is_none());
+
+        builder.feed(&serde_json::json!({ "id": 1, "name": "Ada" })).unwrap();
+        let first = builder.finalize().unwrap();
+        assert_eq!(first["required"], serde_json::json!(["id", "name"]));
+
+        builder.feed(&serde_json::json!({ "id": 5000, "email": "bob@example.com" })).unwrap();
+        builder.feed(&serde_json::json!({ "id": 7, "name": "Cy", "email": null })).unwrap();
+        assert_eq!(builder.observations(), 3);
+
+        let schema = builder.finalize().unwrap();
+        assert_eq!(schema["$schema"], "http://json-schema.org/draft-07/schema#");
+        assert_eq!(schema["required"], serde_json::json!(["id"]));
+        let id = &schema["properties"]["id"];
+        assert_eq!(id["minimum"], -999);
+        assert_eq!(id["maximum"], 6000);
+        assert_eq!(id["examples"], serde_json::json!([1, 5000]));
+        assert_eq!(schema["properties"]["email"]["type"], serde_json::json!(["null", "string"]));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_async_pipeline_and_bundle() {
+        use schema_jenerator::bundle::Location;
+        use schema_jenerator::nonblocking;
+        use schema_jenerator::pipeline::{Pipeline, ProcessOptions, ProcessRequest};
+        use schema_jenerator::remote::RemoteOptions;
+
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("user.json"), r#"{"id": 1}"#).unwrap();
+        fs::write(dir.path().join("broken.json"), "{").unwrap();
+        fs::write(dir.path().join("common.json"), r#"{"$defs": {"id": {"type": "integer"}}}"#).unwrap();
+        let root = serde_json::json!({ "properties": { "id": { "$ref": "common.json#/$defs/id" } } });
+
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        runtime.block_on(async {
+            let pipeline = Pipeline::new(ProcessOptions { dry_run: true, ..ProcessOptions::default() });
+            let report = nonblocking::process(&pipeline, ProcessRequest::new(dir.path().join("user.json")))
+                .await
+                .unwrap();
+            assert_eq!(report.schema["properties"]["id"]["type"], "integer");
+
+            let inputs = pipeline.glob_inputs(dir.path()).unwrap();
+            let batch = nonblocking::process_batch(&pipeline, inputs).await.unwrap();
+            assert_eq!(batch.total, 3);
+            assert_eq!(batch.errors.len(), 1);
+
+            let location = Location::from_path(&dir.path().join("root.json"));
+            let bundled = nonblocking::bundle(root, location, RemoteOptions::default()).await.unwrap();
+            assert_eq!(bundled["properties"]["id"], serde_json::json!({ "type": "integer" }));
+        });
+    }
+
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn test_wasm_generate_schema_json() {
+        use schema_jenerator::wasm::generate_schema_json;
+
+        let options = r#"{"tier": "basic", "draft": "draft-07", "requiredMode": "all", "nullPolicy": "union"}"#;
+        let text = generate_schema_json(r#"{"id": 1, "name": null}"#, Some(options)).unwrap();
+        let schema: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(schema["required"], serde_json::json!(["id", "name"]));
+        assert_eq!(schema["properties"]["id"], serde_json::json!({ "type": "integer" }));
+
+        let error = generate_schema_json("{}", Some(r#"{"tire": "basic"}"#)).unwrap_err();
+        assert!(error.to_string().contains("Invalid options"));
+        assert!(generate_schema_json("{", None).is_err());
+    }
+
+    #[cfg(feature = "ffi")]
+    #[test]
+    fn test_ffi_generate_and_last_error() {
+        use schema_jenerator::ffi::{sj_free_string, sj_generate_schema, sj_last_error};
+        use std::ffi::{CStr, CString};
+
+        let json = CString::new(r#"{"id": 1}"#).unwrap();
+        let options = CString::new(r#"{"tier": "basic"}"#).unwrap();
+        unsafe {
+            let schema = sj_generate_schema(json.as_ptr(), options.as_ptr());
+            assert!(!schema.is_null());
+            assert!(sj_last_error().is_null());
+            let text = CStr::from_ptr(schema).to_str().unwrap().to_string();
+            sj_free_string(schema);
+            assert_eq!(
+                serde_json::from_str::<serde_json::Value>(&text).unwrap(),
+                serde_json::json!({ "type": "object", "properties": { "id": { "type": "integer" } } })
+            );
+
+            let broken = CString::new("{").unwrap();
+            assert!(sj_generate_schema(broken.as_ptr(), std::ptr::null()).is_null());
+            let error = CStr::from_ptr(sj_last_error()).to_str().unwrap();
+            assert!(error.contains("Invalid JSON"), "{}", error);
+            assert!(sj_generate_schema(std::ptr::null(), std::ptr::null()).is_null());
+        }
+    }
+
+    #[test]
+    fn test_structured_errors() {
+        use schema_jenerator::error::ErrorReport;
+        use schema_jenerator::pipeline::{Pipeline, ProcessOptions, ProcessRequest};
+        use schema_jenerator::AppError;
+
+        let dir = tempdir().unwrap();
+        let input_path = dir.path().join("broken.json");
+        fs::write(&input_path, "{\n  \"id\": 1,\n}").unwrap();
+
+        let output = Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .arg(&input_path)
+            .args(["--log-format", "json"])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(3));
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        let line: serde_json::Value = serde_json::from_str(stderr.lines().last().unwrap()).unwrap();
+        assert_eq!(line["level"], "error");
+        assert_eq!(line["error"]["code"], "invalid-json");
+        assert_eq!(line["error"]["file"], input_path.display().to_string());
+        assert_eq!((line["error"]["line"].as_u64(), line["error"]["column"].as_u64()), (Some(3), Some(1)));
+
+        fs::write(dir.path().join("data.json"), r#"{"id": 1}"#).unwrap();
+        let pipeline = Pipeline::new(ProcessOptions { pointer: Some("/missing".to_string()), ..ProcessOptions::default() });
+        let error = pipeline.process(&ProcessRequest::new(dir.path().join("data.json"))).unwrap_err();
+        match error.downcast_ref::<AppError>() {
+            Some(AppError::PointerNotFound { pointer, .. }) => assert_eq!(pointer, "/missing"),
+            other => panic!("unexpected error: {:?}", other),
+        }
+        let report = ErrorReport::from(&error);
+        assert_eq!(report.code, "pointer-not-found");
+        assert_eq!(report.pointer.as_deref(), Some("/missing"));
+    }
+
+    #[test]
+    fn test_tracing_phase_spans() {
+        let dir = tempdir().unwrap();
+        let input_path = dir.path().join("user.json");
+        fs::write(&input_path, r#"{"id": 1, "address": {"city": "Oslo"}}"#).unwrap();
+
+        let output = Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .arg(&input_path)
+            .args(["--log-format", "json", "--validate", "--dry-run"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let lines: Vec<serde_json::Value> = String::from_utf8(output.stderr)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        // Span records are the ones with a duration; events inside a span also carry its phase
+        let phase = |name: &str| {
+            lines
+                .iter()
+                .find(|line| line["phase"] == name && line["duration_ms"].is_number())
+                .unwrap_or_else(|| panic!("no {} record", name))
+        };
+
+        assert_eq!(phase("parse")["bytes"], 38);
+        assert_eq!(phase("infer")["properties"], 3);
+        assert_eq!(phase("validate")["result"], "ok");
+        for name in ["load", "parse", "infer", "generate", "validate", "file"] {
+            assert!(phase(name)["file"].as_str().unwrap().ends_with("user.json"));
+        }
+
+        let output = Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .arg(&input_path)
+            .arg("--dry-run")
+            .env("RUST_LOG", "warn,schema_jenerator::generator=info")
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("infer in"), "{}", stderr);
+        assert!(!stderr.contains("generate ok"), "{}", stderr);
+    }
+
+    #[test]
+    fn test_deterministic_output() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.json"), r#"{"values": ["x", 1, {"id": 2}], "when": "2024-01-01T00:00:00Z"}"#).unwrap();
+        fs::write(dir.path().join("b.json"), r#"{"when": "2024-01-01T00:00:00Z", "values": [{"id": 2}, 1, "x"]}"#).unwrap();
+
+        let generate = |name: &str| {
+            let output = dir.path().join(format!("{}.out.json", name));
+            Command::cargo_bin("schema-jenerator")
+                .unwrap()
+                .arg(dir.path().join(name))
+                .args(["--deterministic", "--pretty", "--tier", "expert", "-o"])
+                .arg(&output)
+                .assert()
+                .success();
+            fs::read(output).unwrap()
+        };
+        let first = generate("a.json");
+        assert_eq!(first, generate("a.json"));
+        assert_eq!(first, generate("b.json"));
+
+        let schema: serde_json::Value = serde_json::from_slice(&first).unwrap();
+        let branches: Vec<&str> = schema["properties"]["values"]["items"]["oneOf"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|branch| branch["type"].as_str().unwrap())
+            .collect();
+        assert_eq!(branches, ["integer", "object", "string"]);
+    }
+
+    #[test]
+    fn test_provenance_block() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("events.json");
+        fs::write(&input, r#"[{"id": 1}, {"id": 2}, {"id": 3}]"#).unwrap();
+
+        let generate = |extra: &[&str]| {
+            let output = dir.path().join("events.schema.json");
+            Command::cargo_bin("schema-jenerator")
+                .unwrap()
+                .arg(&input)
+                .args(["--provenance", "--max-depth", "4", "-o"])
+                .arg(&output)
+                .args(extra)
+                .assert()
+                .success();
+            fs::read_to_string(output).unwrap()
+        };
+
+        let schema: serde_json::Value = serde_json::from_str(&generate(&[])).unwrap();
+        let block = &schema["x-generated-by"];
+        assert_eq!(block["tool"], "schema-jenerator");
+        assert_eq!(block["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(block["options"]["maxDepth"], 4);
+        assert!(block["options"].get("sampleSeed").is_none());
+        assert_eq!(block["samples"], 3);
+        assert!(block["source"]["sha256"].as_str().unwrap().starts_with("sha256:"));
+        assert!(block["generatedAt"].is_string());
+
+        let first = generate(&["--deterministic"]);
+        assert!(!first.contains("generatedAt"));
+        assert_eq!(first, generate(&["--deterministic"]));
+    }
+
+    #[test]
+    fn test_source_annotations() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("users.json");
+        fs::write(&input, r#"{"users": [{"email": "a@b.co"}, {"email": "c@d.io"}]}"#).unwrap();
+
+        let generate = |style: &str| {
+            let output = dir.path().join(format!("{}.schema.json", style));
+            Command::cargo_bin("schema-jenerator")
+                .unwrap()
+                .arg(&input)
+                .args(["--tier", "expert", "--source-annotations", style, "-o"])
+                .arg(&output)
+                .assert()
+                .success();
+            serde_json::from_str::<serde_json::Value>(&fs::read_to_string(output).unwrap()).unwrap()
+        };
+
+        let schema = generate("comment");
+        let email = &schema["properties"]["users"]["items"]["properties"]["email"];
+        let comment = email["$comment"].as_str().unwrap();
+        assert!(comment.starts_with("Inferred from /users/0/email, /users/1/email"));
+        assert!(comment.contains("format: format detector"));
+        assert_eq!(schema["$comment"], "Inferred from /");
+
+        let schema = generate("x-source");
+        let source = &schema["properties"]["users"]["items"]["properties"]["email"]["x-source"];
+        assert_eq!(source["pointers"], serde_json::json!(["/users/0/email", "/users/1/email"]));
+        assert_eq!(source["occurrences"], 2);
+        assert!(source["heuristics"].as_array().unwrap().iter().any(|h| h.as_str().unwrap().starts_with("maxLength")));
+    }
+
+    #[test]
+    fn test_source_map_sidecar() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("users.json");
+        fs::write(&input, r#"{"users": [{"name": "Al"}, {"name": "Bo"}]}"#).unwrap();
+
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .arg(&input)
+            .arg("--source-map")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Source map generated successfully"));
+
+        let map: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(dir.path().join("users.schema.map.json")).unwrap()).unwrap();
+        assert_eq!(map["version"], 1);
+        assert_eq!(map["schema"], "users.schema.json");
+        let mappings = &map["mappings"];
+        assert_eq!(
+            mappings["/properties/users/items/properties/name"],
+            serde_json::json!(["/users/0/name", "/users/1/name"])
+        );
+        assert_eq!(mappings[""], serde_json::json!([""]));
+    }
+
+    #[test]
+    fn test_preserve_order() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("ordered.json");
+        fs::write(&input, r#"{"zeta": 1, "alpha": {"y": "a", "b": true}, "mid": null}"#).unwrap();
+        let output = dir.path().join("ordered.schema.json");
+
+        let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+        cmd.arg(&input).arg("--preserve-order").arg("-o").arg(&output);
+        if !cfg!(feature = "preserve-order") {
+            cmd.assert().failure().stderr(predicate::str::contains("preserve-order feature"));
+            return;
+        }
+        cmd.assert().success();
+
+        let text = fs::read_to_string(&output).unwrap();
+        let position = |needle: &str| text.find(needle).unwrap();
+        assert!(position(r#""zeta""#) < position(r#""alpha""#));
+        assert!(position(r#""alpha""#) < position(r#""mid""#));
+        assert!(position(r#""y""#) < position(r#""b""#));
+        assert!(position(r#""additionalProperties""#) < position(r#""properties""#));
+
+        Command::cargo_bin("schema-jenerator").unwrap().arg(&input).arg("-o").arg(&output).assert().success();
+        let text = fs::read_to_string(&output).unwrap();
+        assert!(text.find(r#""alpha""#).unwrap() < text.find(r#""zeta""#).unwrap());
+        let sorted = text;
+
+        // Merging into a schema in input order still writes sorted keys without the flag
+        let ordered = dir.path().join("ordered.schema.json");
+        let in_input_order = || {
+            Command::cargo_bin("schema-jenerator").unwrap().arg(&input).arg("--preserve-order").arg("-o").arg(&ordered).assert().success();
+        };
+        in_input_order();
+        Command::cargo_bin("schema-jenerator").unwrap().arg(&input).arg("--update").arg("-o").arg(&ordered).assert().success();
+        assert_eq!(fs::read_to_string(&ordered).unwrap(), sorted);
+
+        in_input_order();
+        let grown = dir.path().join("grown.json");
+        fs::write(&grown, r#"{"zeta": 1, "alpha": {"y": "a", "b": true}, "mid": null, "new": 2}"#).unwrap();
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .args(["review", "--yes", "--update", "--against"])
+            .arg(&ordered)
+            .arg(&grown)
+            .assert()
+            .success();
+        let text = fs::read_to_string(&ordered).unwrap();
+        let position = |needle: &str| text.find(needle).unwrap();
+        assert!(position(r#""mid""#) < position(r#""new""#));
+        assert!(position(r#""new""#) < position(r#""zeta""#));
+        assert!(position(r#""b""#) < position(r#""y""#));
+    }
+
+    #[test]
+    fn test_schema_edit_dsl() {
+        use schema_jenerator::schema::model::{InstanceType, TypeSet};
+        use schema_jenerator::SchemaGenerator;
+
+        let generator = SchemaGenerator::builder().build();
+        let mut schema = generator
+            .generate_typed(&serde_json::json!({ "age": 3, "user": { "name": "Ada", "ssn": "123" } }))
+            .unwrap();
+        schema.required = None;
+
+        schema.at("/properties/age").set_minimum(0).set_maximum(150).require();
+        schema
+            .at("/properties/user/properties/name")
+            .set_max_length(64)
+            .set_extension("x-pii", true)
+            .nullable();
+        schema.at("/properties/user/properties/ssn").remove_property();
+        schema.at("/properties/user").remove("additionalProperties");
+        assert!(!schema.at("/properties/missing").set_minimum(1).require().exists());
+
+        let age = &schema.properties.as_ref().unwrap()["age"];
+        assert_eq!(age.minimum, Some(0.into()));
+        assert_eq!(age.maximum, Some(150.into()));
+        assert_eq!(schema.required, Some(vec!["age".to_string()]));
+
+        let user = &schema.properties.as_ref().unwrap()["user"];
+        let name = &user.properties.as_ref().unwrap()["name"];
+        assert_eq!(name.max_length, Some(64));
+        assert_eq!(name.extra["x-pii"], true);
+        assert_eq!(name.kind, Some(TypeSet::Multiple(vec![InstanceType::String, InstanceType::Null])));
+        assert!(!user.properties.as_ref().unwrap().contains_key("ssn"));
+        assert_eq!(user.required, Some(vec!["name".to_string()]));
+        assert!(user.additional_properties.is_none());
+    }
+
+    #[test]
+    fn test_generate_schema_for_rust_types() {
+        use schema_jenerator::diff::ChangeKind;
+        use schema_jenerator::from_sample::{cross_check, generate_schema_for};
+
+        #[derive(serde::Serialize)]
+        struct Address {
+            city: String,
+        }
+
+        #[derive(serde::Serialize)]
+        struct User {
+            id: u64,
+            address: Address,
+            nickname: Option<String>,
+        }
+
+        let user = User { id: 7, address: Address { city: "Oslo".to_string() }, nickname: None };
+        let inferred = generate_schema_for(&user, Default::default()).unwrap();
+        assert_eq!(inferred["properties"]["id"]["type"], "integer");
+        assert_eq!(inferred["properties"]["address"]["properties"]["city"]["type"], "string");
+
+        // As schemars derives it
+        let derived = serde_json::json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "title": "User",
+            "type": "object",
+            "required": ["address", "id", "nickname"],
+            "properties": {
+                "id": { "type": "integer", "format": "uint64", "minimum": 0.0 },
+                "address": { "allOf": [{ "$ref": "#/definitions/Address" }] },
+                "nickname": { "type": ["string", "null"] },
+                "email": { "type": "string" }
+            },
+            "definitions": {
+                "Address": { "type": "object", "required": ["city"], "properties": { "city": { "type": "string" } } }
+            }
+        });
+        let diff = cross_check(&inferred, &derived);
+        let kinds: Vec<(&str, ChangeKind)> =
+            diff.changes.iter().map(|change| (change.pointer.as_str(), change.kind)).collect();
+        assert_eq!(
+            kinds,
+            [
+                ("/properties/email", ChangeKind::PropertyRemoved),
+                ("/properties/nickname", ChangeKind::TypeChanged),
+                ("/properties/nickname", ChangeKind::RequiredRemoved),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validation_report() {
+        use schema_jenerator::validation::{validate_json_against_schema, validate_schema};
+
+        let schema = serde_json::json!({
+            "type": "object",
+            "required": ["id"],
+            "properties": { "tags": { "type": "array", "items": { "type": "string" } } }
+        });
+        let report = validate_json_against_schema(&serde_json::json!({ "tags": ["a", 2] }), &schema).unwrap();
+        assert!(!report.is_valid());
+
+        let mut found: Vec<(&str, &str, &str)> = report
+            .errors
+            .iter()
+            .map(|e| (e.instance_pointer.as_str(), e.schema_pointer.as_str(), e.keyword.as_str()))
+            .collect();
+        found.sort();
+        assert_eq!(
+            found,
+            [("", "/required", "required"), ("/tags/1", "/properties/tags/items/type", "type")]
+        );
+        assert!(report.render_text().contains("/tags/1: 2 is not of type \"string\" (type)"));
+        let error = report.ensure_valid("Document validation").unwrap_err();
+        assert!(error.to_string().starts_with("Document validation failed: "));
+
+        assert!(validate_json_against_schema(&serde_json::json!({ "id": 1 }), &schema).unwrap().is_valid());
+        assert!(validate_schema(&schema).unwrap().is_valid());
+    }
+
+    #[test]
+    fn test_localized_descriptions() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("data.json");
+        fs::write(&input, r#"{"tags": ["a"]}"#).unwrap();
+        fs::write(
+            dir.path().join("descriptions.de.yaml"),
+            "# German\nobject:\n  title: Generiertes Objektschema  # root\n  description: 'Automatisch erzeugtes Schema'\n",
+        )
+        .unwrap();
+
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .arg(&input)
+            .args(["--tier", "expert", "--locale", "de-AT", "--locale-dir"])
+            .arg(dir.path())
+            .assert()
+            .success();
+
+        let schema: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(dir.path().join("data.schema.json")).unwrap()).unwrap();
+        assert_eq!(schema["title"], "Generiertes Objektschema");
+        assert_eq!(schema["description"], "Automatisch erzeugtes Schema");
+        // Messages the file leaves out fall back to English
+        assert_eq!(schema["properties"]["tags"]["title"], "Generated Array Schema");
+
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .arg(&input)
+            .args(["--locale", "fr", "--locale-dir"])
+            .arg(dir.path())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("descriptions.fr.yaml"));
+    }
+
+    #[test]
+    fn test_streaming_large_inputs() {
+        let dir = tempdir().unwrap();
+        let ndjson = dir.path().join("events.ndjson");
+        fs::write(&ndjson, "{\"id\": 1, \"name\": \"a\"}\n{\"id\": 2, \"tags\": [\"x\"]}\n\n").unwrap();
+
+        Command::cargo_bin("schema-jenerator").unwrap().arg(&ndjson).assert().success();
+        let schema: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(dir.path().join("events.schema.json")).unwrap()).unwrap();
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["required"], serde_json::json!(["id"]));
+        assert!(schema["properties"]["tags"].is_object());
+
+        let array = dir.path().join("export.json");
+        let records: Vec<_> = (0..50).map(|i| serde_json::json!({ "id": i, "score": i % 3 })).collect();
+        fs::write(&array, serde_json::to_string(&records).unwrap()).unwrap();
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .arg(&array)
+            .args(["--stream", "--tier", "comprehensive", "--exclude", "/*/score"])
+            .assert()
+            .success();
+        let schema: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(dir.path().join("export.schema.json")).unwrap()).unwrap();
+        assert_eq!(schema["type"], "array");
+        assert_eq!(schema["maxItems"], 100);
+        assert_eq!(schema["items"]["properties"]["id"]["examples"].as_array().unwrap().len(), 5);
+        assert!(schema["items"]["properties"].get("score").is_none());
+
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .arg(&array)
+            .args(["--stream", "--explain"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("--explain needs the whole input"));
+    }
+
+    #[test]
+    fn test_parallel_inference_matches_sequential() {
+        use schema_jenerator::infer::InferenceFamily;
+        use schema_jenerator::parallel::{jobs, par_map, with_jobs};
+        use schema_jenerator::{SchemaGenerator, SchemaOutputTier};
+
+        // Heterogeneous elements, a wide object and strings for the format detectors
+        let elements: Vec<serde_json::Value> = (0..1000)
+            .map(|i| match i % 3 {
+                0 => serde_json::Value::Null,
+                1 => serde_json::json!({ "id": i, "mail": format!("u{}@example.com", i) }),
+                _ => serde_json::json!(format!("2024-01-{:02}", i % 28 + 1)),
+            })
+            .collect();
+        let wide: serde_json::Map<String, serde_json::Value> =
+            (0..200).map(|i| (format!("field{:03}", i), serde_json::json!([i, "x"]))).collect();
+        let emails: Vec<String> = (0..500).map(|i| format!("user{}@example.com", i)).collect();
+        let input = serde_json::json!({ "elements": elements, "wide": wide, "emails": emails });
+
+        let generate = |jobs| {
+            SchemaGenerator::builder()
+                .tier(SchemaOutputTier::Expert)
+                .infer(vec![InferenceFamily::Formats, InferenceFamily::Bounds])
+                .jobs(jobs)
+                .build()
+                .generate(&input)
+                .unwrap()
+        };
+        let sequential = generate(1);
+        assert_eq!(generate(4), sequential);
+        assert_eq!(generate(0), sequential);
+        assert_eq!(sequential["properties"]["emails"]["items"]["format"], "email");
+
+        let squares = with_jobs(3, || {
+            assert_eq!(jobs(), 3);
+            par_map(&(0..1000).collect::<Vec<u64>>(), |n| n * n)
+        });
+        assert_eq!(squares, (0..1000).map(|n| n * n).collect::<Vec<u64>>());
+        assert_eq!(jobs(), 1);
+    }
+
+    #[test]
+    fn test_memory_mapped_input() {
+        use schema_jenerator::mmap::{MappedFile, MMAP_MIN_BYTES, RELEASE_STEP};
+        use std::io::Read;
+
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("large.json");
+        // Past the release step, so the reader drops pages behind it
+        let records: Vec<_> = (0..9_000)
+            .map(|i| serde_json::json!({ "id": i, "name": format!("user-{:08}", i), "active": i % 2 == 0, "note": "x".repeat(2_000) }))
+            .collect();
+        let text = serde_json::to_string(&records).unwrap();
+        assert!(text.len() > RELEASE_STEP && text.len() as u64 > MMAP_MIN_BYTES);
+        fs::write(&input, &text).unwrap();
+
+        let mapped = MappedFile::open(&input).unwrap();
+        assert_eq!(mapped.is_mapped(), cfg!(unix));
+        assert_eq!(&mapped[..], text.as_bytes());
+        let mut read = Vec::new();
+        mapped.reader().read_to_end(&mut read).unwrap();
+        assert_eq!(read, text.as_bytes());
+        // Released pages are read from the file again when touched
+        assert_eq!(&mapped[..16], &text.as_bytes()[..16]);
+
+        let small = dir.path().join("small.json");
+        fs::write(&small, "{}").unwrap();
+        assert!(!MappedFile::open(&small).unwrap().is_mapped());
+
+        for stream in [false, true] {
+            let mut command = Command::cargo_bin("schema-jenerator").unwrap();
+            command.arg(&input).args(["--tier", "basic"]);
+            if stream {
+                command.arg("--stream");
+            }
+            command.assert().success();
+            let schema: serde_json::Value =
+                serde_json::from_str(&fs::read_to_string(dir.path().join("large.schema.json")).unwrap()).unwrap();
+            assert_eq!(schema["items"]["properties"]["name"]["type"], "string");
+        }
+    }
+
+    #[test]
+    fn test_identical_values_are_inferred_once() {
+        use schema_jenerator::shape::distinct;
+        use schema_jenerator::{GeneratorOptions, SchemaBuilder, SchemaGenerator, SchemaOutputTier};
+
+        // Values are only the same when they are identical
+        let values = [
+            serde_json::json!({ "a": 1, "b": [2] }),
+            serde_json::json!({ "a": 1, "b": [3] }),
+            serde_json::json!({ "a": 1, "b": [2] }),
+            serde_json::json!(1),
+            serde_json::json!(1.0),
+        ];
+        let (firsts, indices) = distinct(&values);
+        assert_eq!(firsts.len(), 4);
+        assert_eq!(indices, vec![0, 1, 0, 2, 3]);
+
+        // Every element keeps its branch, whatever its shape
+        let generator = SchemaGenerator::new(GeneratorOptions { tier: SchemaOutputTier::Comprehensive, ..Default::default() });
+        let schema = generator.generate(&serde_json::json!([1, 5000, "a"])).unwrap();
+        let branches = schema["items"]["oneOf"].as_array().unwrap();
+        assert_eq!(branches.len(), 3);
+        assert_eq!(branches[1]["maximum"], 6000);
+        let schema = generator.generate(&serde_json::json!([{ "id": 1 }, { "id": 1 }, null])).unwrap();
+        let branches = schema["items"]["oneOf"].as_array().unwrap();
+        assert_eq!(branches.len(), 3);
+        assert_eq!(branches[0], branches[1]);
+
+        // A cached builder widens over every value, as one without the cache does
+        let records = [
+            serde_json::json!({ "n": 1, "s": "a" }),
+            serde_json::json!({ "n": 5000, "s": "abcdefghijkl" }),
+            serde_json::json!({ "n": 1, "s": "a" }),
+        ];
+        let options = GeneratorOptions { tier: SchemaOutputTier::Comprehensive, ..Default::default() };
+        let mut cached = SchemaBuilder::new(options.clone()).with_value_cache();
+        let mut uncached = SchemaBuilder::new(options);
+        for record in &records {
+            cached.feed(record).unwrap();
+            uncached.feed(record).unwrap();
+        }
+        assert_eq!(cached.observations(), 3);
+        let schema = cached.finalize().unwrap();
+        assert_eq!(schema, uncached.finalize().unwrap());
+        assert_eq!(schema["properties"]["n"]["maximum"], 6000);
+        assert_eq!(schema["properties"]["s"]["maxLength"], 24);
+    }
+
+    #[test]
+    fn test_deeply_nested_input_does_not_overflow_stack() {
+        use schema_jenerator::schema::generate_schema;
+        use schema_jenerator::SchemaOutputTier;
+
+        // Dropping a deep Value recurses, so both ends are taken apart level by level
+        fn dismantle(value: serde_json::Value) {
+            let mut stack = vec![value];
+            while let Some(value) = stack.pop() {
+                match value {
+                    serde_json::Value::Object(obj) => stack.extend(obj.into_iter().map(|(_, child)| child)),
+                    serde_json::Value::Array(items) => stack.extend(items),
+                    _ => {}
+                }
+            }
+        }
+
+        const DEPTH: usize = 20_000;
+        let worker = std::thread::Builder::new().stack_size(256 * 1024).spawn(|| {
+            let mut value = serde_json::json!("leaf");
+            for level in 0..DEPTH {
+                // Built by hand: `json!` would serialize `value`, recursively
+                value = if level % 2 == 0 {
+                    let mut obj = serde_json::Map::new();
+                    obj.insert("next".to_string(), value);
+                    obj.insert("n".to_string(), level.into());
+                    serde_json::Value::Object(obj)
+                } else {
+                    serde_json::Value::Array(vec![value])
+                };
+            }
+            let schema = generate_schema(&value, &SchemaOutputTier::Comprehensive).unwrap();
+
+            let mut node = &schema;
+            let mut levels = 0;
+            while let Some(next) = node.get("properties").and_then(|p| p.get("next")).or_else(|| node.get("items")) {
+                node = next;
+                levels += 1;
+            }
+            let leaf_type = node["type"].clone();
+            dismantle(schema);
+            dismantle(value);
+            (levels, leaf_type)
+        });
+        let (levels, leaf_type) = worker.unwrap().join().unwrap();
+        assert_eq!(levels, DEPTH);
+        assert_eq!(leaf_type, "string");
+
+        // The generator's passes after inference walk the schema without recursing too, on the
+        // stack of an ordinary thread
+        let worker = std::thread::Builder::new().stack_size(8 * 1024 * 1024).spawn(|| {
+            use schema_jenerator::constraints::{KeywordPolicy, PolicyMode};
+            use schema_jenerator::schema::SchemaDraft;
+            use schema_jenerator::{GeneratorOptions, SchemaGenerator};
+
+            let mut value = serde_json::json!("leaf");
+            for _ in 0..DEPTH {
+                value = serde_json::Value::Array(vec![value]);
+            }
+            let observed = KeywordPolicy::Mode(PolicyMode::Observed);
+            let generator = SchemaGenerator::new(GeneratorOptions {
+                tier: SchemaOutputTier::Comprehensive,
+                draft: SchemaDraft::Draft07,
+                constraints: [("array".to_string(), [("maxItems".to_string(), observed)].into())].into(),
+                additional_properties: Some(serde_json::json!(false)),
+                required: Some(schema_jenerator::required::RequiredMode::All),
+                deterministic: true,
+                preserve_order: cfg!(feature = "preserve-order"),
+                ..Default::default()
+            });
+            let schema = generator.generate(&value).unwrap();
+
+            let mut node = &schema;
+            let mut levels = 0;
+            while let Some(next) = node.get("items") {
+                node = next;
+                levels += 1;
+            }
+            let leaf_type = node["type"].clone();
+            dismantle(schema);
+            dismantle(value);
+            (levels, leaf_type)
+        });
+        let (levels, leaf_type) = worker.unwrap().join().unwrap();
+        assert_eq!(levels, DEPTH);
+        assert_eq!(leaf_type, "string");
+
+        // Objects too narrow to be split across threads are walked on the explicit stack too
+        // when more than one job is allowed, as on the command line
+        let worker = std::thread::Builder::new().stack_size(256 * 1024).spawn(|| {
+            const WIDTH: usize = 70;
+            let mut value = serde_json::json!("leaf");
+            for _ in 0..DEPTH / 10 {
+                let mut obj = serde_json::Map::new();
+                obj.insert("next".to_string(), value);
+                for key in 1..WIDTH {
+                    obj.insert(format!("k{}", key), serde_json::Value::Null);
+                }
+                value = serde_json::Value::Object(obj);
+            }
+            let schema = schema_jenerator::parallel::with_jobs(4, || {
+                generate_schema(&value, &SchemaOutputTier::Comprehensive).unwrap()
+            });
+
+            let mut node = &schema;
+            let mut levels = 0;
+            while let Some(next) = node.get("properties").and_then(|p| p.get("next")) {
+                assert_eq!(node["properties"].as_object().unwrap().len(), WIDTH);
+                node = next;
+                levels += 1;
+            }
+            let leaf_type = node["type"].clone();
+            dismantle(schema);
+            dismantle(value);
+            (levels, leaf_type)
+        });
+        let (levels, leaf_type) = worker.unwrap().join().unwrap();
+        assert_eq!(levels, DEPTH / 10);
+        assert_eq!(leaf_type, "string");
+
+        // The command line stops at the parser's nesting limit with an error, not a crash
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("deep.json");
+        fs::write(&input, format!("{}{}", "[".repeat(10_000), "]".repeat(10_000))).unwrap();
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .arg(&input)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("recursion limit exceeded"));
+    }
+
+    #[test]
+    fn test_resource_limits_degrade_to_samples() {
+        let dir = tempdir().unwrap();
+        let read_schema = |name: &str| -> serde_json::Value {
+            serde_json::from_str(&fs::read_to_string(dir.path().join(name)).unwrap()).unwrap()
+        };
+        let array = dir.path().join("uploads.json");
+        let records: Vec<_> = (0..2000).map(|i| serde_json::json!({ "id": i, "tags": ["a", "b", "c"] })).collect();
+        fs::write(&array, serde_json::to_string(&records).unwrap()).unwrap();
+
+        // Too many values: arrays are sampled down until the rest fits
+        Command::cargo_bin("schema-jenerator").unwrap().arg(&array).args(["--max-nodes", "100"]).assert().success();
+        let schema = read_schema("uploads.schema.json");
+        assert_eq!(schema["x-sampled"], "max_nodes");
+        assert_eq!(schema["items"]["required"], serde_json::json!(["id", "tags"]));
+
+        // Too large: only the records in the first bytes are read
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .arg(&array)
+            .args(["--max-input-size", "1000", "--force"])
+            .assert()
+            .success();
+        let schema = read_schema("uploads.schema.json");
+        assert_eq!(schema["x-sampled"], "max_input_size");
+        assert_eq!(schema["items"]["properties"]["tags"]["items"]["type"], "string");
+
+        // Out of time: inference starts over on a sample of each array
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .arg(&array)
+            .args(["--time-limit", "0", "--force"])
+            .assert()
+            .success();
+        assert_eq!(read_schema("uploads.schema.json")["x-sampled"], "time_limit");
+
+        // Caps from the config; a document that is not a stream of records cannot be sampled
+        let document = dir.path().join("document.json");
+        fs::write(&document, r#"{"a": {"b": {"c": {"d": 1}}}, "padding": "xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx"}"#).unwrap();
+        fs::write(dir.path().join(".schema-jenerator.toml"), "[limits]\nmax_depth = 1\n").unwrap();
+        Command::cargo_bin("schema-jenerator").unwrap().arg(&document).assert().success();
+        let schema = read_schema("document.schema.json");
+        assert_eq!(schema["properties"]["a"]["x-truncated"], true);
+        assert!(schema.get("x-sampled").is_none());
+
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .arg(&document)
+            .args(["--max-input-size", "40", "--force", "--log-format", "json"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("\"code\":\"limit-exceeded\""));
+    }
+
+    #[test]
+    fn test_interned_records_share_strings() {
+        use schema_jenerator::intern::Interner;
+        use serde::de::DeserializeSeed;
+
+        let mut interner = Interner::default();
+        let mut parse = |json: &str| {
+            interner.seed().deserialize(&mut serde_json::Deserializer::from_str(json)).unwrap()
+        };
+        let first = parse(r#"{"timestamp": "2024-01-01", "user_id": "u1", "note": "a note well over thirty-two bytes long"}"#);
+        let second = parse(r#"{"timestamp": "2024-01-02", "user_id": "u1", "note": "another note well over thirty-two bytes"}"#);
+        assert_eq!(first.shape(), second.shape());
+        assert_eq!(second.clone().into_value()["user_id"], "u1");
+
+        let stats = interner.stats();
+        // Both keys and short values are looked up; the long notes are not
+        assert_eq!(stats.lookups, 10);
+        // "timestamp", "user_id", "note" and "u1" the second time around
+        assert_eq!(stats.bytes_saved, ("timestamp".len() + "user_id".len() + "note".len() + "u1".len()) as u64);
+
+        let dir = tempdir().unwrap();
+        let lines: Vec<String> = (0..500)
+            .map(|i| serde_json::json!({ "timestamp": format!("2024-01-{:02}", i % 28 + 1), "user_id": format!("u{}", i % 10) }).to_string())
+            .collect();
+        fs::write(dir.path().join("events.ndjson"), lines.join("\n")).unwrap();
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .arg(dir.path().join("*.ndjson"))
+            .arg("--batch")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Memory: ").and(predicate::str::contains("distinct strings shared by 2000 lookups")));
+        let schema: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(dir.path().join("events.schema.json")).unwrap()).unwrap();
+        assert_eq!(schema["required"], serde_json::json!(["timestamp", "user_id"]));
+    }
+
+    #[test]
+    fn test_meta_schema_rejects_malformed_schemas() {
+        use schema_jenerator::validation::validate_schema;
+        use schema_jenerator::{SchemaGenerator, SchemaOutputTier};
+
+        let document = serde_json::json!({
+            "id": 7,
+            "email": "ada@example.com",
+            "tags": ["a", "b"],
+            "address": { "city": "Berlin", "zip": null },
+            "scores": [1.5, 2]
+        });
+        for tier in [SchemaOutputTier::Basic, SchemaOutputTier::Standard, SchemaOutputTier::Comprehensive, SchemaOutputTier::Expert] {
+            let schema = SchemaGenerator::builder().tier(tier.clone()).build().generate(&document).unwrap();
+            let report = validate_schema(&schema).unwrap();
+            assert!(report.is_valid(), "{:?}: {}", tier, report.render_text());
+        }
+
+        let report = validate_schema(&serde_json::json!({ "type": "strnig" })).unwrap();
+        assert!(!report.is_valid());
+        // Subschemas are checked against the whole meta-schema too
+        let report = validate_schema(&serde_json::json!({
+            "type": "object",
+            "properties": { "age": { "minimum": "zero", "required": "age" } }
+        }))
+        .unwrap();
+        let pointers: Vec<&str> = report.errors.iter().map(|e| e.instance_pointer.as_str()).collect();
+        assert!(pointers.contains(&"/properties/age/minimum"), "{:?}", pointers);
+        assert!(pointers.contains(&"/properties/age/required"), "{:?}", pointers);
+    }
+
+    #[test]
+    fn test_bundled_meta_schemas_for_every_draft() {
+        use schema_jenerator::schema::SchemaDraft;
+        use schema_jenerator::validation::{validate_schema, validate_schema_as};
+        use schema_jenerator::{SchemaGenerator, SchemaOutputTier};
+
+        let document = serde_json::json!({ "id": 7, "tags": ["a"], "address": { "city": "Berlin" }, "score": 1.5 });
+        for draft in SchemaDraft::ALL {
+            let generator = SchemaGenerator::builder().tier(SchemaOutputTier::Expert).draft(draft).build();
+            let schema = generator.generate(&document).unwrap();
+            let report = validate_schema(&schema).unwrap();
+            assert!(report.is_valid(), "{}: {}", draft, report.render_text());
+        }
+
+        // Draft-04 bounds are exclusive through booleans, later drafts through numbers
+        let draft4 = serde_json::json!({ "minimum": 0, "exclusiveMinimum": true });
+        assert!(validate_schema_as(&draft4, SchemaDraft::Draft04).unwrap().is_valid());
+        assert!(!validate_schema_as(&draft4, SchemaDraft::Draft07).unwrap().is_valid());
+        let mut declared = draft4.clone();
+        declared["$schema"] = "http://json-schema.org/draft-04/schema#".into();
+        assert!(validate_schema(&declared).unwrap().is_valid());
+        declared["$schema"] = "https://json-schema.org/draft/2019-09/schema".into();
+        assert!(!validate_schema(&declared).unwrap().is_valid());
+
+        let nested = serde_json::json!({ "properties": { "a": { "items": { "type": "strnig" } } } });
+        for draft in SchemaDraft::ALL {
+            assert!(!validate_schema_as(&nested, draft).unwrap().is_valid(), "{}", draft);
+        }
+    }
+
+    #[test]
+    fn test_verify_input_against_generated_schema() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("data.json");
+        fs::write(&input, r#"{"tags": ["a", "a"], "meta": {}}"#).unwrap();
+
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .arg(&input)
+            .args(["--tier", "expert", "--verify"])
+            .assert()
+            .failure()
+            .code(4)
+            .stderr(predicate::str::contains("does not match the schema generated from it"))
+            .stderr(predicate::str::contains("/tags: [\"a\",\"a\"] has non-unique elements (uniqueItems)"))
+            .stderr(predicate::str::contains("/meta: {} has less than 1 property (minProperties)"));
+
+        // Sampled arrays are verified whole
+        fs::write(&input, r#"{"items": [1, 2, 3, 4, 5, 6, 7, 8, 9, "ten"]}"#).unwrap();
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .arg(&input)
+            .args(["--tier", "standard", "--array-sample", "2", "--verify"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("/items/0: 1 is less than the minimum"));
+        fs::write(&input, r#"{"items": [1, "ten", true]}"#).unwrap();
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .arg(&input)
+            .args(["--tier", "standard", "--verify"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Input matches its schema"));
+
+        // Streamed records are verified one at a time
+        let records = dir.path().join("events.ndjson");
+        fs::write(&records, "{\"ids\": [1]}\n{\"ids\": [2, 2]}\n").unwrap();
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .arg(&records)
+            .args(["--tier", "expert", "--verify"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("/1/ids: [2,2] has non-unique elements (uniqueItems)"));
+    }
+
+    #[test]
+    fn test_validate_many_files_with_summary() {
+        let dir = tempdir().unwrap();
+        let schema = dir.path().join("order.schema.json");
+        fs::write(
+            &schema,
+            r#"{"type": "object", "required": ["id"], "properties": {"id": {"type": "integer"}, "items": {"type": "array", "items": {"type": "object", "properties": {"sku": {"type": "string"}}}}}}"#,
+        )
+        .unwrap();
+        let data = dir.path().join("data");
+        fs::create_dir(&data).unwrap();
+        for i in 0..20 {
+            fs::write(data.join(format!("ok-{:02}.json", i)), format!(r#"{{"id": {}, "items": [{{"sku": "a"}}]}}"#, i)).unwrap();
+        }
+        fs::write(data.join("bad.json"), r#"{"items": [{"sku": 1}, {"sku": 2}]}"#).unwrap();
+        fs::write(data.join("events.ndjson"), "{\"id\": 1}\n{\"id\": \"two\"}\n").unwrap();
+        fs::write(data.join("broken.json"), "{").unwrap();
+        let pattern = data.join("*.json").display().to_string();
+        let ndjson = data.join("*.ndjson").display().to_string();
+
+        let output = Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .args(["validate", "--schema"])
+            .arg(&schema)
+            .args([&pattern, &ndjson, "--jobs", "4", "--format", "json"])
+            .assert()
+            .failure()
+            .code(4)
+            .get_output()
+            .stdout
+            .clone();
+        let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(report["files"], 23);
+        assert_eq!(report["passed"], 20);
+        assert_eq!(report["failed"], 3);
+        assert_eq!(report["errors"], 4);
+        assert_eq!(report["top_keywords"][0], serde_json::json!({ "keyword": "type", "errors": 3 }));
+        assert_eq!(report["top_fields"][0], serde_json::json!({ "field": "/items/*/sku", "errors": 2 }));
+        let failed: Vec<&str> = report["failures"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|file| file["path"].as_str().unwrap().rsplit('/').next().unwrap())
+            .collect();
+        assert_eq!(failed, ["bad.json", "broken.json", "events.ndjson"]);
+        assert_eq!(report["failures"][2]["errors"][0]["instance_pointer"], "/1/id");
+
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .args(["validate", "--schema"])
+            .arg(&schema)
+            .arg(data.join("ok-*.json"))
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Validated 20 file(s)"))
+            .stdout(predicate::str::contains("20 passed, 0 failed, 0 error(s)"));
+    }
+
+    #[test]
+    fn test_validation_report_formats() {
+        let dir = tempdir().unwrap();
+        let schema = dir.path().join("user.schema.json");
+        fs::write(&schema, r#"{"type": "object", "required": ["id"], "properties": {"id": {"type": "integer"}}}"#).unwrap();
+        fs::write(dir.path().join("a.json"), r#"{"id": 1}"#).unwrap();
+        fs::write(dir.path().join("b.json"), r#"{"id": "<two>"}"#).unwrap();
+        fs::write(dir.path().join("c.json"), "[").unwrap();
+        let pattern = dir.path().join("[abc].json").display().to_string();
+
+        let junit = dir.path().join("results.xml");
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .args(["validate", "--schema"])
+            .arg(&schema)
+            .args([&pattern, "--report-format", "junit", "--output"])
+            .arg(&junit)
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("Validated 3 file(s)"));
+        let xml = fs::read_to_string(&junit).unwrap();
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
+        assert!(xml.contains(r#"<testsuites name="schema-jenerator validate" tests="3" failures="1" errors="1""#));
+        assert!(xml.contains(r#"<failure message="1 error(s)" type="validation">/id: &quot;&lt;two&gt;&quot; is not of type &quot;integer&quot; (type)"#));
+        assert!(xml.contains(r#"<error message="Invalid JSON in "#));
+
+        let output = Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .args(["validate", "--schema"])
+            .arg(&schema)
+            .args([&pattern, "--report-format", "sarif"])
+            .assert()
+            .failure()
+            .get_output()
+            .stdout
+            .clone();
+        let sarif: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(sarif["version"], "2.1.0");
+        let run = &sarif["runs"][0];
+        let rules: Vec<&str> = run["tool"]["driver"]["rules"].as_array().unwrap().iter().map(|r| r["id"].as_str().unwrap()).collect();
+        assert_eq!(rules, ["type", "unreadable"]);
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0]["ruleId"], "type");
+        assert_eq!(results[0]["locations"][0]["logicalLocations"][0]["fullyQualifiedName"], "/id");
+        assert_eq!(results[0]["properties"]["schemaPointer"], "/properties/id/type");
+        assert!(results[0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"].as_str().unwrap().ends_with("b.json"));
+    }
+
+    #[test]
+    fn test_validation_errors_point_into_the_source() {
+        use schema_jenerator::validation::validate_json_against_schema;
+
+        let schema = serde_json::json!({
+            "type": "object",
+            "required": ["id"],
+            "properties": { "tags": { "type": "array", "items": { "type": "string" } } }
+        });
+        let text = "{\n  \"tags\": [\"a\", 2]\n}\n";
+        let mut report = validate_json_against_schema(&serde_json::from_str(text).unwrap(), &schema).unwrap();
+        report.locate(text, "");
+        report.errors.sort_by(|a, b| a.instance_pointer.cmp(&b.instance_pointer));
+
+        // A missing property is located at the object that lacks it
+        let root = report.errors[0].location.as_ref().unwrap();
+        assert_eq!((root.line, root.column), (1, 1));
+        let item = report.errors[1].location.as_ref().unwrap();
+        assert_eq!((item.line, item.column), (2, 17));
+        assert_eq!(item.snippet, "\"tags\": [\"a\", 2]");
+        assert_eq!(item.snippet_column, 14);
+        assert!(report.render_text().contains(
+            "/tags/1: 2 is not of type \"string\" (type)\n    at line 2, column 17; schema location /properties/tags/items/type\n    | \"tags\": [\"a\", 2]\n    |               ^\n"
+        ));
+        let error = report.ensure_valid("Document validation").unwrap_err().to_string();
+        assert!(error.starts_with("Document validation failed: 2 error(s)\n/: \"id\" is a required property (required)\n"));
+
+        // --verify points into the input file, through --pointer
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("data.json");
+        fs::write(&input, "{\n  \"body\": {\n    \"tags\": [\"a\", \"a\"]\n  }\n}\n").unwrap();
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .arg(&input)
+            .args(["--tier", "expert", "--pointer", "/body", "--verify"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("/tags: [\"a\",\"a\"] has non-unique elements (uniqueItems)\n    at line 3, column 13"));
+    }
+
+    #[test]
+    fn test_validation_thresholds_and_warnings() {
+        let dir = tempdir().unwrap();
+        let schema = dir.path().join("user.schema.json");
+        fs::write(
+            &schema,
+            r#"{"type": "object", "properties": {"age": {"type": "integer"}}, "additionalProperties": false}"#,
+        )
+        .unwrap();
+        let data = dir.path().join("data");
+        fs::create_dir(&data).unwrap();
+        fs::write(data.join("a.json"), r#"{"age": 1, "nickname": "al"}"#).unwrap();
+        fs::write(data.join("b.json"), r#"{"age": "two"}"#).unwrap();
+        fs::write(data.join("c.json"), r#"{"age": "three"}"#).unwrap();
+        let pattern = data.join("*.json").display().to_string();
+
+        // Extra properties only warn, so a file with nothing else passes
+        let output = Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .args(["validate", "--schema"])
+            .arg(&schema)
+            .args([&pattern, "--warn-keyword", "additionalProperties", "--format", "json"])
+            .assert()
+            .failure()
+            .code(4)
+            .get_output()
+            .stdout
+            .clone();
+        let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(report["passed"], 1);
+        assert_eq!(report["failed"], 2);
+        assert_eq!(report["errors"], 2);
+        assert_eq!(report["warnings"], 1);
+        assert_eq!(report["failures"][0]["errors"][0]["severity"], "warning");
+        assert_eq!(report["failures"][1]["errors"][0]["severity"], "error");
+
+        // Collection stops at the limit, and the files after it are skipped
+        let output = Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .args(["validate", "--schema"])
+            .arg(&schema)
+            .args([&pattern, "--max-errors", "1", "--format", "json"])
+            .assert()
+            .failure()
+            .get_output()
+            .stdout
+            .clone();
+        let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(report["errors"], 1);
+        assert_eq!(report["failed"], 1);
+        assert_eq!(report["skipped"], 2);
+
+        // Warnings pass by default and fail with --fail-at warning
+        let ok = data.join("a.json");
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .args(["validate", "--schema"])
+            .arg(&schema)
+            .arg(&ok)
+            .args(["--warn-keyword", "additionalProperties"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("1 passed, 0 failed, 0 error(s), 1 warning(s)"))
+            .stdout(predicate::str::contains("Files with warnings:"))
+            .stdout(predicate::str::contains("(additionalProperties, warning)"));
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .args(["validate", "--schema"])
+            .arg(&schema)
+            .arg(&ok)
+            .args(["--warn-keyword", "additionalProperties", "--fail-at", "warning"])
+            .assert()
+            .failure()
+            .code(4)
+            .stderr(predicate::str::contains("1 warning(s) with --fail-at warning"));
+    }
+
+    #[test]
+    fn test_validate_resolves_file_and_remote_refs() {
+        let dir = tempdir().unwrap();
+        let (url, server) = serve(&[(200, r#"{"$defs": {"id": {"type": "integer", "minimum": 1}}}"#)]);
+        fs::create_dir(dir.path().join("shared")).unwrap();
+        fs::write(dir.path().join("shared/address.json"), r#"{"type": "object", "required": ["city"]}"#).unwrap();
+        let schema = dir.path().join("order.schema.json");
+        fs::write(
+            &schema,
+            format!(
+                r#"{{"type": "object", "properties": {{"id": {{"$ref": "{}#/$defs/id"}}, "address": {{"$ref": "shared/address.json"}}}}}}"#,
+                url
+            ),
+        )
+        .unwrap();
+        let data = dir.path().join("order.json");
+        fs::write(&data, r#"{"id": 0, "address": {}}"#).unwrap();
+        let cache = dir.path().join("cache");
+
+        for _ in 0..2 {
+            let output = Command::cargo_bin("schema-jenerator")
+                .unwrap()
+                .args(["validate", "--schema"])
+                .arg(&schema)
+                .arg(&data)
+                .arg("--cache-dir")
+                .arg(&cache)
+                .args(["--format", "json"])
+                .assert()
+                .failure()
+                .code(4)
+                .get_output()
+                .stdout
+                .clone();
+            let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+            let mut keywords: Vec<&str> = report["failures"][0]["errors"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|error| error["keyword"].as_str().unwrap())
+                .collect();
+            keywords.sort_unstable();
+            assert_eq!(keywords, ["minimum", "required"]);
+        }
+        // The second run used the cached copy
+        assert_eq!(server.join().unwrap().len(), 1);
+
+        fs::remove_file(dir.path().join("shared/address.json")).unwrap();
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .args(["validate", "--schema"])
+            .arg(&schema)
+            .arg(&data)
+            .arg("--cache-dir")
+            .arg(&cache)
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("Failed to read referenced schema"))
+            .stdout(predicate::str::contains("($ref)"));
+    }
+
+    #[test]
+    fn test_lint_fix() {
+        let dir = tempdir().unwrap();
+        let schema = dir.path().join("user.schema.json");
+        fs::write(
+            &schema,
+            r#"{"type": "object", "required": ["name", "id", "id"], "properties": {"id": {"type": "integer", "minimum": 10, "maximum": 5}, "name": {"minLength": 0, "maxLength": 20}, "role": {"enum": ["admin", "user", "admin"]}}}"#,
+        )
+        .unwrap();
+
+        // Without --fix nothing is written, and fixable issues still fail
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .args(["lint"])
+            .arg(&schema)
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("/properties/name: minLength 0 is redundant (redundant-keyword, fixable)"))
+            .stdout(predicate::str::contains("6 issue(s), 5 fixable with --fix"));
+
+        let fixed = dir.path().join("fixed.schema.json");
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .args(["lint", "--fix", "-o"])
+            .arg(&fixed)
+            .arg(&schema)
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("minimum 10 is greater than maximum 5 (contradictory-bounds)"))
+            .stdout(predicate::str::contains("6 issue(s), 5 fixed"))
+            .stderr(predicate::str::contains("Lint found 1 issue(s)"));
+        let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(&fixed).unwrap()).unwrap();
+        assert_eq!(schema["required"], serde_json::json!(["id", "name"]));
+        assert_eq!(schema["properties"]["name"], serde_json::json!({ "type": "string", "maxLength": 20 }));
+        assert_eq!(schema["properties"]["role"]["enum"], serde_json::json!(["admin", "user"]));
+
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .args(["lint", "--format", "json"])
+            .arg(&fixed)
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains(r#""rule": "contradictory-bounds""#));
+        fs::write(&fixed, r#"{"type": "object", "required": ["id"], "properties": {"id": {"type": "integer"}}}"#).unwrap();
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .args(["lint"])
+            .arg(&fixed)
+            .assert()
+            .success()
+            .stdout("No issues found\n");
+    }
+
+    #[test]
+    fn test_update_preserves_manual_edits() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("users.json");
+        let output = dir.path().join("users.schema.json");
+        fs::write(&input, r#"{"id": 1, "name": "Ada", "legacy": true, "address": {"city": "Paris"}}"#).unwrap();
+        Command::cargo_bin("schema-jenerator").unwrap().arg(&input).arg("-o").arg(&output).assert().success();
+
+        let mut schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        schema["title"] = serde_json::json!("User");
+        schema["properties"]["name"]["description"] = serde_json::json!("Display name");
+        schema["properties"]["address"] = serde_json::json!({
+            "x-manual": true,
+            "type": "object",
+            "properties": { "city": { "type": "string", "enum": ["Paris", "Rome"] } }
+        });
+        fs::write(&output, schema.to_string()).unwrap();
+
+        fs::write(&input, r#"{"id": "u-2", "name": "Bob", "email": "bob@example.com", "address": {"city": "Oslo", "zip": "0150"}}"#).unwrap();
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .arg(&input)
+            .arg("-o")
+            .arg(&output)
+            .arg("--update")
+            .assert()
+            .success();
+
+        let updated: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(updated["title"], "User");
+        assert_eq!(updated["properties"]["name"]["description"], "Display name");
+        assert_eq!(updated["properties"]["id"]["type"], serde_json::json!(["integer", "string"]));
+        assert_eq!(updated["properties"]["email"]["type"], "string");
+        assert_eq!(updated["properties"]["legacy"]["type"], "boolean");
+        assert_eq!(updated["properties"]["address"], schema["properties"]["address"]);
+
+        // Without --update the schema is replaced
+        Command::cargo_bin("schema-jenerator").unwrap().arg(&input).arg("-o").arg(&output).assert().success();
+        let replaced: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        assert!(replaced["properties"]["legacy"].is_null());
+        assert!(replaced.get("title").is_none());
+    }
+
+    // RFC 7386, to check that a merge patch turns the old schema into the new one
+    fn apply_merge_patch(target: &mut serde_json::Value, patch: &serde_json::Value) {
+        let serde_json::Value::Object(patch) = patch else {
+            *target = patch.clone();
+            return;
+        };
+        if !target.is_object() {
+            *target = serde_json::json!({});
+        }
+        let target = target.as_object_mut().unwrap();
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(key);
+            } else {
+                apply_merge_patch(target.entry(key.clone()).or_insert(serde_json::Value::Null), value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_patch_output_for_schema_changes() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("users.json");
+        let output = dir.path().join("users.schema.json");
+        let patch = dir.path().join("users.schema.patch.json");
+        let merge_patch = dir.path().join("users.schema.merge-patch.json");
+        fs::write(&input, r#"{"id": 1, "name": "Ada", "legacy": true}"#).unwrap();
+
+        // Without an existing schema the patch adds the whole document
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .arg(&input)
+            .arg("-o")
+            .arg(&output)
+            .args(["--patch", "json-patch"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Patch generated successfully"));
+        let old: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        let operations: serde_json::Value = serde_json::from_str(&fs::read_to_string(&patch).unwrap()).unwrap();
+        assert_eq!(operations, serde_json::json!([{ "op": "add", "path": "", "value": old }]));
+
+        fs::write(&input, r#"{"id": "u-2", "name": "Bob", "email": "bob@example.com"}"#).unwrap();
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .arg(&input)
+            .arg("-o")
+            .arg(&output)
+            .args(["--patch", "json-patch", "--patch", "merge-patch", "--patch-only"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Schema left as it is"));
+        let kept: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(kept, old);
+
+        let operations: Vec<serde_json::Value> = serde_json::from_str(&fs::read_to_string(&patch).unwrap()).unwrap();
+        assert!(operations.contains(&serde_json::json!({ "op": "remove", "path": "/properties/legacy" })));
+        assert!(operations.contains(&serde_json::json!({ "op": "replace", "path": "/properties/id/type", "value": "string" })));
+        assert!(operations.iter().any(|op| op["op"] == "add" && op["path"] == "/properties/email"));
+
+        let patch: serde_json::Value = serde_json::from_str(&fs::read_to_string(&merge_patch).unwrap()).unwrap();
+        assert!(patch["properties"]["legacy"].is_null());
+        let mut patched = old;
+        apply_merge_patch(&mut patched, &patch);
+        Command::cargo_bin("schema-jenerator").unwrap().arg(&input).arg("-o").arg(&output).assert().success();
+        let new: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(patched, new);
+    }
+
+    #[test]
+    fn test_expect_golden_schema() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("users.json");
+        let output = dir.path().join("users.schema.json");
+        let golden = dir.path().join("users.golden.json");
+        fs::write(&input, r#"{"id": 1, "name": "Ada"}"#).unwrap();
+
+        let run = |extra: &[&str]| {
+            let mut cmd = Command::cargo_bin("schema-jenerator").unwrap();
+            cmd.arg(&input).arg("-o").arg(&output).arg("--expect").arg(&golden).args(extra);
+            cmd.assert()
+        };
+        run(&["--update-golden"]).success().stdout(predicate::str::contains("Golden file generated successfully"));
+        assert_eq!(fs::read_to_string(&golden).unwrap(), fs::read_to_string(&output).unwrap());
+        run(&[]).success();
+
+        fs::write(&input, r#"{"id": "u-1", "name": "Ada", "email": "ada@example.com"}"#).unwrap();
+        let before = fs::read_to_string(&output).unwrap();
+        run(&[])
+            .failure()
+            .code(1)
+            .stderr(predicate::str::contains("Generated schema differs from"))
+            .stderr(predicate::str::contains("+ /properties/email: property added"))
+            .stderr(predicate::str::contains("~ /properties/id: type changed from \"integer\" to \"string\""));
+        // Nothing is written on a mismatch
+        assert_eq!(fs::read_to_string(&output).unwrap(), before);
+
+        run(&["--update-golden"]).success();
+        run(&[]).success();
+        let updated: serde_json::Value = serde_json::from_str(&fs::read_to_string(&golden).unwrap()).unwrap();
+        assert_eq!(updated["properties"]["email"]["type"], "string");
+    }
+
+    #[test]
+    fn test_complexity_metrics_and_thresholds() {
+        let dir = tempdir().unwrap();
+        let schema = dir.path().join("event.schema.json");
+        fs::write(
+            &schema,
+            r##"{"type": "object", "properties": {"kind": {"enum": ["a", "b", "c"]}, "payload": {"oneOf": [{"type": "string"}, {"type": "integer"}, {"$ref": "#/$defs/item"}]}}, "$defs": {"item": {"type": "object", "properties": {"tags": {"type": "array", "items": {"type": "string"}}}}}}"##,
+        )
+        .unwrap();
+
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .args(["complexity"])
+            .arg(&schema)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Nodes:              9"))
+            .stdout(predicate::str::contains("Max depth:          3 (/$defs/item/properties/tags/items)"))
+            .stdout(predicate::str::contains("3 branch(es), at most 3 (/properties/payload/oneOf)"))
+            .stdout(predicate::str::contains("Complexity score:   17"));
+
+        let output = Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .args(["complexity", "-f", "json"])
+            .arg(&schema)
+            .output()
+            .unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(report["refs"], 1);
+        assert_eq!(report["max_enum"], 3);
+        assert_eq!(report["largest_enum"], "/properties/kind/enum");
+
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .args(["complexity", "--max-branches", "2", "--max-depth", "4", "--max-score", "10"])
+            .arg(&schema)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("largest oneOf/anyOf 3 is over --max-branches 2"))
+            .stderr(predicate::str::contains("score 17 is over --max-score 10"))
+            .stderr(predicate::str::contains("Schema exceeds 2 complexity threshold(s)"));
+    }
+
+    #[test]
+    fn test_coverage_report() {
+        let dir = tempdir().unwrap();
+        let schema = dir.path().join("order.schema.json");
+        fs::write(
+            &schema,
+            r##"{"type": "object", "required": ["id"], "properties": {"id": {"type": "integer"}, "note": {"type": "string"}, "payment": {"oneOf": [{"$ref": "#/$defs/card"}, {"type": "object", "required": ["iban"], "properties": {"iban": {"type": "string"}}}]}, "items": {"type": "array", "items": {"type": "object", "properties": {"sku": {"type": "string"}}}}}, "$defs": {"card": {"type": "object", "required": ["number"], "properties": {"number": {"type": "string"}, "expiry": {"type": "string"}}, "additionalProperties": false}}}"##,
+        )
+        .unwrap();
+        let samples = dir.path().join("samples");
+        fs::create_dir(&samples).unwrap();
+        fs::write(samples.join("a.json"), r#"{"id": 1, "payment": {"number": "4111"}, "items": [{"sku": "x", "qty": 2}]}"#).unwrap();
+        fs::write(samples.join("b.json"), r#"{"id": 2, "payment": {"number": "5500"}, "items": [{"sku": "y", "qty": 1}]}"#).unwrap();
+        let data = samples.join("*.json").display().to_string();
+
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .args(["coverage", "--schema"])
+            .arg(&schema)
+            .arg(&data)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Documents:          2"))
+            .stdout(predicate::str::contains("Unused branches:\n  /properties/payment/oneOf/1\n"))
+            .stdout(predicate::str::contains("  /$defs/card/properties/expiry\n  /properties/note\n"))
+            .stdout(predicate::str::contains("/items/*/qty in 2 occurrence(s), at /properties/items/items"));
+
+        let output = Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .args(["coverage", "--format", "json", "--schema"])
+            .arg(&schema)
+            .arg(&data)
+            .output()
+            .unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(report["uncovered_fields"][0]["path"], "/items/*/qty");
+        assert_eq!(report["uncovered_fields"][0]["rejected"], false);
+    }
+
+    #[test]
+    fn test_editor_integration_artifacts() {
+        let dir = tempdir().unwrap();
+        let data_dir = dir.path().join("data");
+        fs::create_dir(&data_dir).unwrap();
+        let input = data_dir.join("user.json");
+        fs::write(&input, "{\n    \"name\": \"Ada\",\n    \"age\": 36\n}\n").unwrap();
+        let vscode = dir.path().join(".vscode");
+        fs::create_dir(&vscode).unwrap();
+        let settings = vscode.join("settings.json");
+        fs::write(&settings, r#"{"editor.tabSize": 2}"#).unwrap();
+
+        for _ in 0..2 {
+            Command::cargo_bin("schema-jenerator")
+                .unwrap()
+                .arg(&input)
+                .args(["--inject-schema", "--vscode-settings"])
+                .arg(&settings)
+                .assert()
+                .success();
+        }
+
+        // The reference is added once, keeping the layout, and does not leak into the schema
+        assert_eq!(
+            fs::read_to_string(&input).unwrap(),
+            "{\n    \"$schema\": \"./user.schema.json\",\n    \"name\": \"Ada\",\n    \"age\": 36\n}\n"
+        );
+        let schema: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(data_dir.join("user.schema.json")).unwrap()).unwrap();
+        assert!(schema["properties"].get("$schema").is_none());
+        assert!(schema["properties"].get("name").is_some());
+
+        let settings: serde_json::Value = serde_json::from_str(&fs::read_to_string(&settings).unwrap()).unwrap();
+        assert_eq!(settings["editor.tabSize"], 2);
+        assert_eq!(
+            settings["json.schemas"],
+            serde_json::json!([{"fileMatch": ["/data/user.json"], "url": "./data/user.schema.json"}])
+        );
+    }
+
+    #[test]
+    fn test_insomnia_export_input() {
+        let dir = tempdir().unwrap();
+        let export = dir.path().join("insomnia.json");
+        fs::write(
+            &export,
+            serde_json::json!({
+                "_type": "export",
+                "__export_format": 4,
+                "resources": [
+                    {"_id": "wrk_1", "_type": "workspace", "name": "Shop"},
+                    {"_id": "fld_1", "_type": "request_group", "parentId": "wrk_1", "name": "Users"},
+                    {"_id": "req_1", "_type": "request", "parentId": "fld_1", "name": "Create user", "method": "POST",
+                     "url": "{{ base }}/users", "body": {"mimeType": "application/json", "text": "{\"name\": \"Ada\", \"age\": 36}"}},
+                    {"_id": "req_2", "_type": "request", "parentId": "wrk_1", "name": "Create user", "method": "PUT",
+                     "url": "{{ base }}/users/1", "body": {"mimeType": "application/json", "text": "{\"name\": \"Ada\"}"}},
+                    {"_id": "req_3", "_type": "request", "parentId": "wrk_1", "name": "List users", "method": "GET",
+                     "url": "{{ base }}/users", "body": {}},
+                    {"_id": "req_4", "_type": "request", "parentId": "wrk_1", "name": "Templated", "method": "POST",
+                     "url": "{{ base }}/orders", "body": {"mimeType": "application/json", "text": "{\"id\": {{ _.id }}}"}}
+                ]
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let out_dir = dir.path().join("schemas");
+
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .args(["insomnia", "--out-dir"])
+            .arg(&out_dir)
+            .arg(&export)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("create-user.request.schema.json"))
+            .stdout(predicate::str::contains("create-user-2.request.schema.json"))
+            .stderr(predicate::str::contains("Skipping Templated: body is not valid JSON"));
+
+        let schema: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(out_dir.join("create-user.request.schema.json")).unwrap()).unwrap();
+        assert_eq!(schema["title"], "Create user");
+        assert_eq!(schema["description"], "Request body of POST {{ base }}/users");
+        assert_eq!(schema["properties"]["age"]["type"], "integer");
+        assert!(out_dir.join("create-user-2.request.schema.json").exists());
+        assert_eq!(fs::read_dir(&out_dir).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_regenerating_subcommands_follow_config_and_options() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join(".schema-jenerator.toml"),
+            "default_tier = \"comprehensive\"\nexclude = [\"/token\"]\n\n[tiers.strict]\nbase = \"expert\"\n",
+        )
+        .unwrap();
+        let data = dir.path().join("d.json");
+        fs::write(&data, r#"{"id": 5, "name": "alice", "tags": ["a", "b"], "token": "secret"}"#).unwrap();
+        let schema = dir.path().join("d.schema.json");
+
+        Command::cargo_bin("schema-jenerator").unwrap().arg(&data).assert().success();
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .arg("check")
+            .arg("--schema")
+            .arg(&schema)
+            .arg(&data)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("No differences found"));
+
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .arg(&data)
+            .args(["--tier", "strict", "--draft", "draft-07", "--no-examples"])
+            .assert()
+            .success();
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .args(["--draft", "draft-07", "--no-examples", "review", "--tier", "strict", "--against"])
+            .arg(&schema)
+            .arg(&data)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("No differences found"));
+        Command::cargo_bin("schema-jenerator")
+            .unwrap()
+            .arg("check")
+            .arg("--schema")
+            .arg(&schema)
+            .arg(&data)
+            .assert()
+            .failure();
+    }
 }